@@ -151,6 +151,7 @@ const initializeSwcTransformer = async (
       config,
       artifact,
       sourceMap: true,
+      outputProfile: "hermes",
     });
     lastArtifact = artifact;
     setSharedSwcTransformer(stateKey, transformer);
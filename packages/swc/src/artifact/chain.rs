@@ -0,0 +1,94 @@
+//! Layered artifact lookup.
+//!
+//! An ordered list of artifacts (e.g., app-local overrides, then a shared base)
+//! is searched front to back. The first layer that contains a canonical ID owns
+//! it, even if its element fails to load, so a broken override never silently
+//! falls back to a stale base entry.
+
+use std::collections::HashSet;
+
+use crate::types::{BuilderArtifactElement, CanonicalId};
+
+use super::hash::content_hash;
+use super::{ArtifactSource, EntryIndex};
+
+/// A named layer in the chain.
+pub struct ArtifactLayer {
+    /// Label used in diagnostics (file path or position).
+    pub label: String,
+    pub artifact: Box<dyn ArtifactSource>,
+}
+
+/// Artifact that resolves canonical IDs through an ordered list of layers.
+pub struct ArtifactChain {
+    layers: Vec<ArtifactLayer>,
+    /// Hash over the layer hashes in order.
+    content_hash: Option<String>,
+    /// Index over the resolved entries of all layers.
+    index: EntryIndex,
+}
+
+impl ArtifactChain {
+    pub fn new(layers: Vec<ArtifactLayer>) -> Result<Self, String> {
+        if layers.is_empty() {
+            return Err("Artifact chain must contain at least one artifact".to_string());
+        }
+
+        let layer_hashes: Option<Vec<&str>> = layers
+            .iter()
+            .map(|layer| layer.artifact.content_hash())
+            .collect();
+        let content_hash = layer_hashes.map(|hashes| content_hash(hashes.join("\n").as_bytes()));
+
+        Ok(Self {
+            layers,
+            content_hash,
+            index: EntryIndex::default(),
+        })
+    }
+
+    /// Find the first layer that contains `id`.
+    fn owner(&self, id: &CanonicalId) -> Option<(usize, &ArtifactLayer)> {
+        self.layers
+            .iter()
+            .enumerate()
+            .find(|(_, layer)| layer.artifact.contains(id))
+    }
+}
+
+impl ArtifactSource for ArtifactChain {
+    fn get(&self, id: &CanonicalId) -> Option<&BuilderArtifactElement> {
+        self.owner(id)?.1.artifact.get(id)
+    }
+
+    fn contains(&self, id: &CanonicalId) -> bool {
+        self.owner(id).is_some()
+    }
+
+    fn element_error(&self, id: &CanonicalId) -> Option<&str> {
+        self.owner(id)?.1.artifact.element_error(id)
+    }
+
+    fn resolved_layer(&self, id: &CanonicalId) -> Option<(usize, &str)> {
+        let (index, layer) = self.owner(id)?;
+        Some((index, &layer.label))
+    }
+
+    fn ids(&self) -> Box<dyn Iterator<Item = &CanonicalId> + '_> {
+        let mut seen = HashSet::new();
+        Box::new(
+            self.layers
+                .iter()
+                .flat_map(|layer| layer.artifact.ids())
+                .filter(move |id| seen.insert(*id)),
+        )
+    }
+
+    fn content_hash(&self) -> Option<&str> {
+        self.content_hash.as_deref()
+    }
+
+    fn index(&self) -> &EntryIndex {
+        &self.index
+    }
+}
@@ -0,0 +1,65 @@
+//! Deduplication of prebuild definitions across artifact elements.
+//!
+//! Operations that spread the same model embed an identical copy of its
+//! fragment definition. The lazy backends intern every top-level definition
+//! through a [`DefinitionPool`] as elements are loaded, so identical
+//! definitions share one allocation and can be emitted once per file.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+use xxhash_rust::xxh64::xxh64;
+
+use crate::types::{BuilderArtifactElement, PrebuildDocument};
+
+/// Content-addressed store of shared definitions.
+#[derive(Default)]
+pub struct DefinitionPool {
+    /// Definitions bucketed by the hash of their JSON serialization.
+    definitions: Mutex<HashMap<u64, Vec<Arc<Value>>>>,
+}
+
+impl DefinitionPool {
+    /// Replace each definition of `document` with its interned copy.
+    pub fn intern(&self, document: &mut PrebuildDocument) {
+        for definition in &mut document.definitions {
+            let Ok(json) = serde_json::to_vec(definition.as_ref()) else {
+                continue;
+            };
+
+            let mut definitions = self.definitions.lock().unwrap_or_else(|e| e.into_inner());
+            let bucket = definitions.entry(xxh64(&json, 0)).or_default();
+
+            match bucket.iter().find(|shared| shared.as_ref() == definition.as_ref()) {
+                Some(shared) => *definition = Arc::clone(shared),
+                None => bucket.push(Arc::clone(definition)),
+            }
+        }
+    }
+}
+
+/// Find interned definitions embedded by more than one of `elements`.
+///
+/// Results are in first-seen order, so callers that pass elements in a stable
+/// order get stable output.
+pub fn shared_definitions<'a>(elements: impl IntoIterator<Item = &'a BuilderArtifactElement>) -> Vec<Arc<Value>> {
+    let mut seen: Vec<(Arc<Value>, usize)> = Vec::new();
+
+    for element in elements {
+        let BuilderArtifactElement::Operation { prebuild, .. } = element else {
+            continue;
+        };
+        for definition in &prebuild.document.definitions {
+            match seen.iter_mut().find(|(shared, _)| Arc::ptr_eq(shared, definition)) {
+                Some((_, count)) => *count += 1,
+                None => seen.push((Arc::clone(definition), 1)),
+            }
+        }
+    }
+
+    seen.into_iter()
+        .filter(|(_, count)| *count > 1)
+        .map(|(definition, _)| definition)
+        .collect()
+}
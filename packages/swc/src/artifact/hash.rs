@@ -0,0 +1,28 @@
+//! Artifact integrity hashing.
+//!
+//! Hashes are XXH64 (seed 0) rendered as 16 lowercase hex digits, matching the
+//! `xxhash-wasm` `h64` output used by the builder, so JS tooling can compute
+//! the same value from the artifact file.
+
+use xxhash_rust::xxh64::xxh64;
+
+/// Compute the content hash of raw artifact bytes.
+pub fn content_hash(bytes: &[u8]) -> String {
+    format!("{:016x}", xxh64(bytes, 0))
+}
+
+/// Check a loaded artifact's hash against the expected value, if one was configured.
+pub fn verify_hash(expected: Option<&str>, actual: Option<&str>) -> Result<(), String> {
+    let Some(expected) = expected else {
+        return Ok(());
+    };
+
+    match actual {
+        Some(actual) if actual.eq_ignore_ascii_case(expected) => Ok(()),
+        Some(actual) => Err(format!(
+            "Artifact hash mismatch: expected '{}', got '{}'. The artifact may be stale or from a different build.",
+            expected, actual
+        )),
+        None => Err("Artifact hash verification requested, but the artifact backing does not provide a hash".to_string()),
+    }
+}
@@ -0,0 +1,99 @@
+//! Lookup tables over an artifact's canonical IDs.
+//!
+//! Every artifact instance owns one [`EntryIndex`], so a reload (which creates
+//! a new instance) starts from a fresh index. Each table is built the first
+//! time a lookup needs it: grouping IDs by file only reads the IDs, while
+//! indexing operation names loads every element once.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::types::{BuilderArtifactElement, CanonicalId};
+
+/// Lazily built lookup tables of one artifact.
+#[derive(Default)]
+pub struct EntryIndex {
+    /// Canonical IDs by the canonical path of the file defining them.
+    by_file: OnceLock<HashMap<String, Vec<CanonicalId>>>,
+    operations: OnceLock<OperationTable>,
+}
+
+/// Operation entries of an artifact.
+#[derive(Default)]
+struct OperationTable {
+    /// Smallest canonical ID of the operations with each operation name.
+    by_name: HashMap<String, CanonicalId>,
+    /// Canonical IDs of every operation.
+    ids: Vec<CanonicalId>,
+}
+
+impl EntryIndex {
+    /// Canonical IDs of the entries defined in the file at canonical path `path`.
+    pub fn ids_in_file<'a>(
+        &'a self,
+        ids: impl FnOnce() -> Box<dyn Iterator<Item = &'a CanonicalId> + 'a>,
+        path: &str,
+    ) -> &'a [CanonicalId] {
+        let by_file = self.by_file.get_or_init(|| {
+            let mut by_file: HashMap<String, Vec<CanonicalId>> = HashMap::new();
+            for id in ids() {
+                if let Some((file, _)) = id.split_once("::") {
+                    by_file
+                        .entry(file.to_string())
+                        .or_default()
+                        .push(id.clone());
+                }
+            }
+            by_file
+        });
+        by_file.get(path).map_or(&[], Vec::as_slice)
+    }
+
+    /// Canonical ID of the operation named `name`.
+    ///
+    /// Several entries may share an operation name; the smallest canonical ID
+    /// is returned so the choice is stable.
+    pub fn operation_id<'a, 'b>(
+        &'a self,
+        ids: impl FnOnce() -> Box<dyn Iterator<Item = &'b CanonicalId> + 'b>,
+        get: impl Fn(&CanonicalId) -> Option<&'b BuilderArtifactElement>,
+        name: &str,
+    ) -> Option<&'a CanonicalId> {
+        self.operations(ids, get).by_name.get(name)
+    }
+
+    /// Canonical IDs of every operation entry.
+    pub fn operation_ids<'a, 'b>(
+        &'a self,
+        ids: impl FnOnce() -> Box<dyn Iterator<Item = &'b CanonicalId> + 'b>,
+        get: impl Fn(&CanonicalId) -> Option<&'b BuilderArtifactElement>,
+    ) -> &'a [CanonicalId] {
+        &self.operations(ids, get).ids
+    }
+
+    fn operations<'b>(
+        &self,
+        ids: impl FnOnce() -> Box<dyn Iterator<Item = &'b CanonicalId> + 'b>,
+        get: impl Fn(&CanonicalId) -> Option<&'b BuilderArtifactElement>,
+    ) -> &OperationTable {
+        self.operations.get_or_init(|| {
+            let mut table = OperationTable::default();
+            for id in ids() {
+                let Some(BuilderArtifactElement::Operation { prebuild, .. }) = get(id) else {
+                    continue;
+                };
+                table
+                    .by_name
+                    .entry(prebuild.operation_name.to_string())
+                    .and_modify(|current| {
+                        if id < current {
+                            *current = id.clone();
+                        }
+                    })
+                    .or_insert_with(|| id.clone());
+                table.ids.push(id.clone());
+            }
+            table
+        })
+    }
+}
@@ -0,0 +1,103 @@
+//! Lazily deserialized artifact backing.
+//!
+//! The artifact JSON is only validated and split into raw per-element JSON at
+//! construction time. Each element is deserialized into a
+//! [`BuilderArtifactElement`] the first time a file references it, which keeps
+//! constructor time low when most entries are never touched by a process.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+use serde_json::value::RawValue;
+
+use crate::types::{BuilderArtifactElement, CanonicalId};
+
+use super::dedup::DefinitionPool;
+use super::hash::content_hash;
+use super::{normalize_id, parse_element, ArtifactSource, EntryIndex};
+
+/// Top-level artifact shape with elements kept as raw JSON.
+#[derive(Deserialize)]
+struct RawArtifact {
+    elements: HashMap<CanonicalId, Box<RawValue>>,
+}
+
+/// A raw element and its parsed form, populated on first lookup.
+struct LazyEntry {
+    raw: Box<RawValue>,
+    parsed: OnceLock<Result<BuilderArtifactElement, String>>,
+}
+
+/// Artifact whose elements are deserialized on demand.
+pub struct LazyArtifact {
+    entries: HashMap<CanonicalId, LazyEntry>,
+    content_hash: String,
+    /// Interned document definitions shared between parsed elements.
+    pool: DefinitionPool,
+    index: EntryIndex,
+}
+
+impl LazyArtifact {
+    /// Split a JSON-serialized BuilderArtifact into lazily parsed entries.
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let artifact: RawArtifact =
+            serde_json::from_str(json).map_err(|e| format!("Failed to parse artifact: {}", e))?;
+
+        let entries = artifact
+            .elements
+            .into_iter()
+            .map(|(id, raw)| {
+                let entry = LazyEntry {
+                    raw,
+                    parsed: OnceLock::new(),
+                };
+                (normalize_id(id), entry)
+            })
+            .collect();
+
+        Ok(Self {
+            entries,
+            content_hash: content_hash(json.as_bytes()),
+            pool: DefinitionPool::default(),
+            index: EntryIndex::default(),
+        })
+    }
+}
+
+impl ArtifactSource for LazyArtifact {
+    fn get(&self, id: &CanonicalId) -> Option<&BuilderArtifactElement> {
+        let entry = self.entries.get(id)?;
+        entry
+            .parsed
+            .get_or_init(|| parse_element(entry.raw.get().as_bytes(), &self.pool))
+            .as_ref()
+            .ok()
+    }
+
+    fn contains(&self, id: &CanonicalId) -> bool {
+        self.entries.contains_key(id)
+    }
+
+    fn element_error(&self, id: &CanonicalId) -> Option<&str> {
+        self.entries
+            .get(id)?
+            .parsed
+            .get()?
+            .as_ref()
+            .err()
+            .map(String::as_str)
+    }
+
+    fn ids(&self) -> Box<dyn Iterator<Item = &CanonicalId> + '_> {
+        Box::new(self.entries.keys())
+    }
+
+    fn content_hash(&self) -> Option<&str> {
+        Some(&self.content_hash)
+    }
+
+    fn index(&self) -> &EntryIndex {
+        &self.index
+    }
+}
@@ -0,0 +1,178 @@
+//! Memory-mapped artifact backing.
+//!
+//! The artifact file is mapped into memory and only scanned once to record the
+//! byte range of each element. Elements are deserialized on first lookup, so
+//! worker processes sharing one large artifact only materialize the entries
+//! their files actually reference, and the raw bytes are shared via the page cache.
+//!
+//! Artifact files must be replaced by writing a new file and renaming it into
+//! place, as the builder does. Rewriting a mapped file in place changes the
+//! bytes under the recorded element ranges, and truncating it crashes the
+//! process with SIGBUS on the next read of a truncated page. Lazy lookups
+//! refuse to parse once the file size or modification time changed, which
+//! catches most rewrites but cannot prevent the crash: the file can still be
+//! truncated between the check and the read.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::ops::Range;
+use std::sync::OnceLock;
+use std::time::SystemTime;
+
+use memmap2::Mmap;
+use serde::Deserialize;
+use serde_json::value::RawValue;
+
+use crate::types::{BuilderArtifactElement, CanonicalId};
+
+use super::dedup::DefinitionPool;
+use super::hash::content_hash;
+use super::{normalize_id, parse_element, ArtifactSource, EntryIndex};
+
+/// Top-level artifact shape used while indexing.
+/// Elements are borrowed as raw JSON so nothing is deserialized up front.
+#[derive(Deserialize)]
+struct ArtifactIndex<'a> {
+    #[serde(borrow)]
+    elements: HashMap<CanonicalId, &'a RawValue>,
+}
+
+/// A lazily deserialized element inside the mapped file.
+struct MappedEntry {
+    /// Byte range of the element's JSON within the mapping.
+    range: Range<usize>,
+    /// Parsed element, populated on first lookup.
+    parsed: OnceLock<Result<BuilderArtifactElement, String>>,
+}
+
+/// Length and modification time of the mapped file when it was indexed.
+#[derive(PartialEq)]
+struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FileStamp {
+    fn of(file: &File) -> Option<Self> {
+        let metadata = file.metadata().ok()?;
+        Some(FileStamp {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+/// Artifact backed by a memory-mapped JSON file.
+pub struct MappedArtifact {
+    mmap: Mmap,
+    /// The mapped file, kept open to detect in-place rewrites.
+    file: File,
+    stamp: Option<FileStamp>,
+    path: String,
+    entries: HashMap<CanonicalId, MappedEntry>,
+    content_hash: String,
+    /// Interned document definitions shared between parsed elements.
+    pool: DefinitionPool,
+    index: EntryIndex,
+}
+
+impl MappedArtifact {
+    /// Map the artifact file at `path` and index its elements.
+    pub fn open(path: &str) -> Result<Self, String> {
+        let file =
+            File::open(path).map_err(|e| format!("Failed to open artifact '{}': {}", path, e))?;
+
+        // SAFETY: the mapping is read-only, and callers must replace artifact files by
+        // renaming a new file into place (see the module docs) so the mapped inode is never
+        // truncated. Nothing here can enforce that; truncating it in place is undefined behavior.
+        let stamp = FileStamp::of(&file);
+        let mmap = unsafe { Mmap::map(&file) }
+            .map_err(|e| format!("Failed to map artifact '{}': {}", path, e))?;
+
+        let index: ArtifactIndex = serde_json::from_slice(&mmap)
+            .map_err(|e| format!("Failed to parse artifact '{}': {}", path, e))?;
+
+        let base = mmap.as_ptr() as usize;
+        let entries = index
+            .elements
+            .into_iter()
+            .map(|(id, raw)| {
+                let start = raw.get().as_ptr() as usize - base;
+                let entry = MappedEntry {
+                    range: start..start + raw.get().len(),
+                    parsed: OnceLock::new(),
+                };
+                (normalize_id(id), entry)
+            })
+            .collect();
+
+        let content_hash = content_hash(&mmap);
+
+        let artifact = Self {
+            mmap,
+            file,
+            stamp,
+            path: path.to_string(),
+            entries,
+            content_hash,
+            pool: DefinitionPool::default(),
+            index: EntryIndex::default(),
+        };
+        // A file rewritten while it was indexed (e.g., a reload racing the builder)
+        // has ranges and a content hash that match neither version
+        artifact.check_unmodified()?;
+        Ok(artifact)
+    }
+
+    /// Fail if the mapped file was rewritten in place since it was indexed,
+    /// since the recorded element ranges no longer match its bytes.
+    fn check_unmodified(&self) -> Result<(), String> {
+        if FileStamp::of(&self.file) == self.stamp {
+            return Ok(());
+        }
+        Err(format!(
+            "Artifact '{}' was rewritten in place while mapped; replace it by renaming a new file into place",
+            self.path
+        ))
+    }
+}
+
+impl ArtifactSource for MappedArtifact {
+    fn get(&self, id: &CanonicalId) -> Option<&BuilderArtifactElement> {
+        let entry = self.entries.get(id)?;
+        entry
+            .parsed
+            .get_or_init(|| {
+                self.check_unmodified()?;
+                parse_element(&self.mmap[entry.range.clone()], &self.pool)
+            })
+            .as_ref()
+            .ok()
+    }
+
+    fn contains(&self, id: &CanonicalId) -> bool {
+        self.entries.contains_key(id)
+    }
+
+    fn element_error(&self, id: &CanonicalId) -> Option<&str> {
+        self.entries
+            .get(id)?
+            .parsed
+            .get()?
+            .as_ref()
+            .err()
+            .map(String::as_str)
+    }
+
+    fn ids(&self) -> Box<dyn Iterator<Item = &CanonicalId> + '_> {
+        Box::new(self.entries.keys())
+    }
+
+    fn content_hash(&self) -> Option<&str> {
+        Some(&self.content_hash)
+    }
+
+    fn index(&self) -> &EntryIndex {
+        &self.index
+    }
+}
@@ -0,0 +1,180 @@
+//! Artifact storage backends.
+//!
+//! The transformer only performs point lookups by canonical ID, so every
+//! backing strategy (fully parsed, memory-mapped, ...) is accessed through
+//! the [`ArtifactSource`] trait.
+
+pub mod chain;
+pub mod dedup;
+pub mod hash;
+pub mod index;
+pub mod lazy;
+pub mod mapped;
+pub mod slot;
+pub mod stats;
+#[cfg(feature = "watch")]
+pub mod watch;
+
+pub use chain::{ArtifactChain, ArtifactLayer};
+pub use index::EntryIndex;
+pub use lazy::LazyArtifact;
+pub use mapped::MappedArtifact;
+pub use slot::ArtifactSlot;
+
+use std::borrow::Cow;
+
+use crate::graphql::validate::validate_operation_document;
+use crate::transform::paths::normalize_path;
+use crate::types::{BuilderArtifactElement, CanonicalId};
+use dedup::DefinitionPool;
+
+/// Maximum number of invalid elements listed when eager validation fails.
+const MAX_REPORTED_INVALID_ELEMENTS: usize = 10;
+
+/// Minimum number of elements given to each thread by [`load_all`], so small
+/// artifacts are not split across threads that cost more to start than they save.
+const MIN_ELEMENTS_PER_THREAD: usize = 256;
+
+/// Read access to artifact elements by canonical ID.
+pub trait ArtifactSource: Send + Sync {
+    /// Look up an element by its canonical ID.
+    fn get(&self, id: &CanonicalId) -> Option<&BuilderArtifactElement>;
+
+    /// Check whether an element exists, without loading it.
+    fn contains(&self, id: &CanonicalId) -> bool;
+
+    /// Reason an element exists but could not be loaded (malformed JSON or document).
+    /// Only meaningful after `get` has been called for the same ID.
+    fn element_error(&self, _id: &CanonicalId) -> Option<&str> {
+        None
+    }
+
+    /// Position and label of the layer an element resolved from, for layered artifacts.
+    fn resolved_layer(&self, _id: &CanonicalId) -> Option<(usize, &str)> {
+        None
+    }
+
+    /// Iterate over every canonical ID in the artifact.
+    fn ids(&self) -> Box<dyn Iterator<Item = &CanonicalId> + '_>;
+
+    /// Content hash of the raw artifact, if the backing retained its bytes.
+    fn content_hash(&self) -> Option<&str> {
+        None
+    }
+
+    /// Lookup tables over the artifact's canonical IDs, built on first use.
+    fn index(&self) -> &EntryIndex;
+
+    /// Canonical IDs of the entries defined in the file at canonical path `path`.
+    fn ids_in_file(&self, path: &str) -> &[CanonicalId] {
+        self.index().ids_in_file(|| self.ids(), path)
+    }
+
+    /// Canonical ID of the operation named `name`; the smallest if several share it.
+    fn operation_id(&self, name: &str) -> Option<&CanonicalId> {
+        self.index()
+            .operation_id(|| self.ids(), |id| self.get(id), name)
+    }
+
+    /// Canonical IDs of every operation entry.
+    fn operation_ids(&self) -> &[CanonicalId] {
+        self.index().operation_ids(|| self.ids(), |id| self.get(id))
+    }
+}
+
+/// Deserialize every element, spreading the work across threads.
+///
+/// Elements are independent, and the lazy backends keep each parsed element
+/// in a `OnceLock`, so entries can be parsed concurrently and later lookups
+/// return the stored result. Used before walking the whole artifact, where
+/// deserialization dominates startup for big projects.
+pub fn load_all(source: &dyn ArtifactSource) {
+    let ids: Vec<&CanonicalId> = source.ids().collect();
+    let threads = std::thread::available_parallelism()
+        .map_or(1, usize::from)
+        .min(ids.len() / MIN_ELEMENTS_PER_THREAD)
+        .max(1);
+
+    if threads == 1 {
+        for id in ids {
+            let _ = source.get(id);
+        }
+        return;
+    }
+
+    std::thread::scope(|scope| {
+        for chunk in ids.chunks(ids.len().div_ceil(threads)) {
+            scope.spawn(move || {
+                for id in chunk {
+                    let _ = source.get(id);
+                }
+            });
+        }
+    });
+}
+
+/// Load and validate every element up front, failing with the offending canonical IDs.
+pub fn validate_all(source: &dyn ArtifactSource) -> Result<(), String> {
+    load_all(source);
+    let mut invalid: Vec<String> = source
+        .ids()
+        .filter(|id| source.get(id).is_none())
+        .map(|id| {
+            format!(
+                "  {}: {}",
+                id,
+                source.element_error(id).unwrap_or("failed to load")
+            )
+        })
+        .collect();
+
+    if invalid.is_empty() {
+        return Ok(());
+    }
+
+    invalid.sort();
+    let total = invalid.len();
+    invalid.truncate(MAX_REPORTED_INVALID_ELEMENTS);
+    let mut message = format!(
+        "Artifact contains {} invalid element(s):\n{}",
+        total,
+        invalid.join("\n")
+    );
+    if total > MAX_REPORTED_INVALID_ELEMENTS {
+        message.push_str(&format!(
+            "\n  ... and {} more",
+            total - MAX_REPORTED_INVALID_ELEMENTS
+        ));
+    }
+    Err(message)
+}
+
+/// Normalize the file path part of a canonical ID recorded by the builder,
+/// so keys written from Windows long or backslashed paths match resolved IDs.
+fn normalize_id(id: CanonicalId) -> CanonicalId {
+    match normalize_path(&id) {
+        Cow::Borrowed(_) => id,
+        Cow::Owned(normalized) => normalized.into(),
+    }
+}
+
+/// Deserialize and validate a single element from its raw JSON.
+///
+/// Used by the lazy backends on first lookup. The error is kept so analysis can
+/// report why an existing element was rejected. Document definitions are
+/// interned through `pool` so identical ones are shared between elements.
+fn parse_element(json: &[u8], pool: &DefinitionPool) -> Result<BuilderArtifactElement, String> {
+    let mut element: BuilderArtifactElement =
+        serde_json::from_slice(json).map_err(|e| format!("Failed to parse element: {}", e))?;
+
+    if let BuilderArtifactElement::Operation { prebuild, .. } = &mut element {
+        validate_operation_document(
+            &prebuild.document,
+            &prebuild.operation_type,
+            &prebuild.operation_name,
+        )?;
+        pool.intern(&mut prebuild.document);
+    }
+
+    Ok(element)
+}
@@ -0,0 +1,41 @@
+//! Swappable artifact handle.
+//!
+//! Transforms take a snapshot of the current artifact, so a reload can replace
+//! it at any time without affecting calls that are already running. Each
+//! replacement bumps a generation counter so caches derived from the artifact
+//! know when to reset.
+
+use std::sync::{Arc, RwLock};
+
+use super::ArtifactSource;
+
+/// Holds the current artifact and allows replacing it atomically.
+pub struct ArtifactSlot {
+    current: RwLock<(u64, Arc<dyn ArtifactSource>)>,
+}
+
+impl ArtifactSlot {
+    pub fn new(artifact: Arc<dyn ArtifactSource>) -> Self {
+        Self {
+            current: RwLock::new((0, artifact)),
+        }
+    }
+
+    /// Get the current artifact.
+    pub fn load(&self) -> Arc<dyn ArtifactSource> {
+        self.snapshot().1
+    }
+
+    /// Get the current artifact together with its generation.
+    pub fn snapshot(&self) -> (u64, Arc<dyn ArtifactSource>) {
+        let current = self.current.read().unwrap_or_else(|e| e.into_inner());
+        (current.0, Arc::clone(&current.1))
+    }
+
+    /// Replace the current artifact.
+    #[cfg_attr(not(feature = "watch"), allow(dead_code))]
+    pub fn store(&self, artifact: Arc<dyn ArtifactSource>) {
+        let mut current = self.current.write().unwrap_or_else(|e| e.into_inner());
+        *current = (current.0 + 1, artifact);
+    }
+}
@@ -0,0 +1,89 @@
+//! Artifact-wide statistics.
+//!
+//! Aggregates operation complexity metrics over every element so CI can
+//! enforce budgets without re-parsing documents on the JavaScript side.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use swc_core::ecma::atoms::Atom;
+
+use crate::graphql::metrics::{operation_metrics, OperationMetrics};
+use crate::types::{BuilderArtifactElement, CanonicalId};
+
+use super::{load_all, ArtifactSource};
+
+/// Metrics for a single operation element.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationStats {
+    pub operation_name: Atom,
+    pub operation_type: String,
+    #[serde(flatten)]
+    pub metrics: OperationMetrics,
+    /// Whether the operation needs incremental delivery (`@defer` or `@stream`)
+    /// from the server or gateway.
+    pub incremental: bool,
+}
+
+impl OperationStats {
+    /// Compute stats for an element, or `None` for fragments.
+    pub fn from_element(element: &BuilderArtifactElement) -> Option<Self> {
+        match element {
+            BuilderArtifactElement::Operation { prebuild, .. } => {
+                let metrics = operation_metrics(&prebuild.document, &prebuild.operation_name);
+                Some(Self {
+                    operation_name: prebuild.operation_name.clone(),
+                    operation_type: prebuild.operation_type.clone(),
+                    incremental: metrics.is_incremental(),
+                    metrics,
+                })
+            }
+            BuilderArtifactElement::Fragment { .. } => None,
+        }
+    }
+}
+
+/// Summary of every element in an artifact.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactStats {
+    pub fragment_count: usize,
+    pub operation_count: usize,
+    /// Elements that failed to load (see `validateArtifact`).
+    pub invalid_count: usize,
+    /// Largest depth across all operations.
+    pub max_depth: usize,
+    /// Largest field count across all operations.
+    pub max_field_count: usize,
+    /// Operations that need incremental delivery.
+    pub incremental_count: usize,
+    /// Per-operation metrics keyed by canonical ID.
+    pub operations: BTreeMap<CanonicalId, OperationStats>,
+}
+
+/// Load every element and collect statistics.
+pub fn collect_stats(source: &dyn ArtifactSource) -> ArtifactStats {
+    let mut stats = ArtifactStats::default();
+    load_all(source);
+
+    for id in source.ids() {
+        let Some(element) = source.get(id) else {
+            stats.invalid_count += 1;
+            continue;
+        };
+
+        match OperationStats::from_element(element) {
+            Some(operation) => {
+                stats.operation_count += 1;
+                stats.max_depth = stats.max_depth.max(operation.metrics.depth);
+                stats.max_field_count = stats.max_field_count.max(operation.metrics.field_count);
+                stats.incremental_count += usize::from(operation.incremental);
+                stats.operations.insert(id.clone(), operation);
+            }
+            None => stats.fragment_count += 1,
+        }
+    }
+
+    stats
+}
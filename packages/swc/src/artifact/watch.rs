@@ -0,0 +1,88 @@
+//! Artifact file watching.
+//!
+//! The builder replaces the artifact by writing a new file, so the parent
+//! directory is watched and events are filtered by file name. Bursts of events
+//! from a single write are coalesced before the change callback runs.
+
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+
+/// Quiet period after the last event before the change callback runs.
+const DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Watches an artifact file until dropped.
+pub struct ArtifactWatcher {
+    /// Dropping the watcher closes the event channel and ends the worker thread.
+    _watcher: RecommendedWatcher,
+}
+
+impl ArtifactWatcher {
+    /// Start watching `path`, calling `on_change` on a background thread after it changes.
+    pub fn start(path: &str, mut on_change: impl FnMut() + Send + 'static) -> Result<Self, String> {
+        let path = std::fs::canonicalize(path)
+            .map_err(|e| format!("Failed to resolve artifact path '{}': {}", path, e))?;
+        let directory = path
+            .parent()
+            .ok_or_else(|| format!("Artifact path '{}' has no parent directory", path.display()))?
+            .to_path_buf();
+
+        let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+        let mut watcher =
+            notify::recommended_watcher(tx).map_err(|e| format!("Failed to create artifact watcher: {}", e))?;
+        watcher
+            .watch(&directory, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch '{}': {}", directory.display(), e))?;
+
+        thread::spawn(move || {
+            while let Ok(event) = rx.recv() {
+                if !is_artifact_change(&event, &path) {
+                    continue;
+                }
+
+                // Coalesce the remaining events of this write
+                loop {
+                    match rx.recv_timeout(DEBOUNCE) {
+                        Ok(_) => continue,
+                        Err(mpsc::RecvTimeoutError::Timeout) => break,
+                        Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+
+                on_change();
+            }
+        });
+
+        Ok(Self { _watcher: watcher })
+    }
+}
+
+/// Check whether an event creates or modifies the watched file.
+fn is_artifact_change(event: &notify::Result<Event>, path: &Path) -> bool {
+    let Ok(event) = event else {
+        return false;
+    };
+
+    matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+        && event.paths.iter().any(|changed| same_file(changed, path))
+}
+
+/// Compare paths, resolving the event path in case it was reported via a symlinked directory.
+fn same_file(changed: &Path, path: &Path) -> bool {
+    changed == path || std::fs::canonicalize(changed).is_ok_and(|changed| changed == path)
+}
+
+/// Outcome of a reload, delivered to the JavaScript callback.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ArtifactWatchEvent {
+    /// The artifact was reloaded and swapped in.
+    #[serde(rename_all = "camelCase")]
+    Reloaded { artifact_hash: Option<String> },
+    /// The changed artifact could not be loaded; the previous one stays active.
+    Error { message: String },
+}
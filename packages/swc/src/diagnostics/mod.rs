@@ -0,0 +1,242 @@
+//! Long-running diagnostics server for editor integrations.
+//!
+//! Keeps the artifact loaded and answers analyze-only requests, so editor
+//! extensions can flag gql calls without an artifact entry without spawning
+//! Node tooling per file. The server speaks JSON-RPC 2.0 with LSP framing over
+//! stdio or a Unix domain socket.
+//!
+//! Methods:
+//! - `initialize` → `{ serverInfo, artifactHash }`
+//! - `analyze` with `{ sourcePath, sourceCode }` → `{ sourcePath, diagnostics }`
+//! - `shutdown` → `null`, then the server stops
+//!
+//! The `exit` notification stops the server without a response.
+
+mod protocol;
+
+use std::io::{BufRead, BufReader, Write};
+use std::sync::Arc;
+
+use napi::{Env, Task};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::artifact::ArtifactSlot;
+use crate::graphql::schema::SchemaIndex;
+use crate::transform::transformer::analyze_source_ref;
+use crate::types::config::{TransformConfig, TransformContext, TransformInputRef};
+use crate::types::error::{ErrorSpan, PluginError, Severity};
+use protocol::{read_message, write_message, Request, Response};
+
+/// Where the server reads requests and writes responses.
+#[derive(Debug, Clone)]
+pub enum Transport {
+    /// The process's stdin and stdout.
+    Stdio,
+    /// A Unix domain socket at the given path, serving one connection at a time.
+    Socket(String),
+}
+
+/// Diagnostics server, run on the libuv thread pool until it is shut down.
+pub struct DiagnosticsServer {
+    /// Shared with the transformer, so artifact reloads apply to the server too
+    artifact: Arc<ArtifactSlot>,
+    schema: Option<Arc<SchemaIndex>>,
+    config: TransformConfig,
+    transport: Transport,
+}
+
+/// A problem found in an analyzed file.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub canonical_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub span: Option<ErrorSpan>,
+}
+
+impl From<PluginError> for Diagnostic {
+    fn from(error: PluginError) -> Self {
+        Self {
+            severity: error.severity,
+            code: error.code,
+            message: error.message,
+            canonical_id: error.canonical_id,
+            span: error.span,
+        }
+    }
+}
+
+/// Result of a method call, or a JSON-RPC error code and message.
+type MethodResult = Result<Value, (i64, String)>;
+
+/// Whether to keep serving after handling a message.
+enum Flow {
+    Continue,
+    Stop,
+}
+
+impl DiagnosticsServer {
+    pub fn new(
+        artifact: Arc<ArtifactSlot>,
+        schema: Option<Arc<SchemaIndex>>,
+        config: TransformConfig,
+        transport: Transport,
+    ) -> Self {
+        Self {
+            artifact,
+            schema,
+            config,
+            transport,
+        }
+    }
+
+    /// Serve until a `shutdown` request, an `exit` notification, or end of input.
+    fn run(&self) -> Result<(), String> {
+        match &self.transport {
+            Transport::Stdio => {
+                let stdin = std::io::stdin();
+                self.serve(&mut stdin.lock(), &mut std::io::stdout().lock())?;
+                Ok(())
+            }
+            Transport::Socket(path) => self.run_socket(path),
+        }
+    }
+
+    #[cfg(unix)]
+    fn run_socket(&self, path: &str) -> Result<(), String> {
+        use std::os::unix::net::UnixListener;
+
+        let listener =
+            UnixListener::bind(path).map_err(|e| format!("Failed to bind diagnostics socket '{}': {}", path, e))?;
+
+        let result = (|| {
+            for stream in listener.incoming() {
+                let stream = stream.map_err(|e| format!("Failed to accept diagnostics connection: {}", e))?;
+                let mut reader =
+                    BufReader::new(stream.try_clone().map_err(|e| format!("Failed to clone connection: {}", e))?);
+                let mut writer = stream;
+
+                // A closed connection waits for the next client; shutdown stops the server
+                if let Flow::Stop = self.serve(&mut reader, &mut writer)? {
+                    break;
+                }
+            }
+            Ok(())
+        })();
+
+        let _ = std::fs::remove_file(path);
+        result
+    }
+
+    #[cfg(not(unix))]
+    fn run_socket(&self, path: &str) -> Result<(), String> {
+        Err(format!(
+            "Diagnostics socket '{}' is not supported on this platform; use stdio instead",
+            path
+        ))
+    }
+
+    /// Handle messages from one connection until it closes or the server stops.
+    fn serve(&self, reader: &mut impl BufRead, writer: &mut impl Write) -> Result<Flow, String> {
+        while let Some(payload) = read_message(reader)? {
+            let request: Request = match serde_json::from_slice(&payload) {
+                Ok(request) => request,
+                Err(e) => {
+                    let response = Response::error(Value::Null, protocol::PARSE_ERROR, format!("Invalid request: {}", e));
+                    write_message(writer, &response)?;
+                    continue;
+                }
+            };
+
+            let (result, flow) = self.handle(&request);
+            // Notifications get no response
+            if let (Some(id), Some(result)) = (request.id, result) {
+                let response = match result {
+                    Ok(value) => Response::ok(id, value),
+                    Err((code, message)) => Response::error(id, code, message),
+                };
+                write_message(writer, &response)?;
+            }
+
+            if let Flow::Stop = flow {
+                return Ok(Flow::Stop);
+            }
+        }
+
+        Ok(Flow::Continue)
+    }
+
+    /// Handle one request, returning its result (if it has one) and whether to keep serving.
+    fn handle(&self, request: &Request) -> (Option<MethodResult>, Flow) {
+        match request.method.as_str() {
+            "initialize" => (Some(Ok(self.initialize())), Flow::Continue),
+            "analyze" => (Some(self.analyze(&request.params)), Flow::Continue),
+            "shutdown" => (Some(Ok(Value::Null)), Flow::Stop),
+            "exit" => (None, Flow::Stop),
+            method => (
+                Some(Err((protocol::METHOD_NOT_FOUND, format!("Unknown method '{}'", method)))),
+                Flow::Continue,
+            ),
+        }
+    }
+
+    fn initialize(&self) -> Value {
+        json!({
+            "serverInfo": { "name": "soda-gql-swc", "version": env!("CARGO_PKG_VERSION") },
+            "artifactHash": self.artifact.load().content_hash(),
+        })
+    }
+
+    /// Analyze a file and report its diagnostics.
+    fn analyze(&self, params: &Value) -> MethodResult {
+        let field = |name: &str| {
+            params
+                .get(name)
+                .and_then(Value::as_str)
+                .ok_or_else(|| (protocol::INVALID_PARAMS, format!("Missing string parameter '{}'", name)))
+        };
+        let source_path = field("sourcePath")?;
+        let source_code = field("sourceCode")?;
+
+        let config = self
+            .config
+            .for_path(source_path)
+            .map_err(|message| (protocol::INVALID_PARAMS, message))?;
+        let artifact = self.artifact.load();
+        let input = TransformInputRef {
+            source_code: source_code.to_string(),
+            source_path: source_path.to_string(),
+            artifact: artifact.as_ref(),
+            schema: self.schema.as_deref(),
+            prebuild_cache: None,
+            module_cache: None,
+            artifact_files: &[],
+            config,
+            context: TransformContext::default(),
+            log_sink: None,
+        };
+
+        let result = analyze_source_ref(&input).map_err(|message| (protocol::SOURCE_PARSE_FAILED, message))?;
+        let diagnostics: Vec<Diagnostic> = result.errors.into_iter().map(Diagnostic::from).collect();
+
+        Ok(json!({ "sourcePath": source_path, "diagnostics": diagnostics }))
+    }
+}
+
+impl Task for DiagnosticsServer {
+    type Output = ();
+    type JsValue = ();
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        self.run().map_err(napi::Error::from_reason)
+    }
+
+    fn resolve(&mut self, _env: Env, _output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+//! Message framing and JSON-RPC types for the diagnostics server.
+//!
+//! Messages use the Language Server Protocol base protocol: a `Content-Length`
+//! header, a blank line, then a JSON-RPC 2.0 payload.
+
+use std::io::{BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// JSON-RPC error code for payloads that are not valid JSON.
+pub const PARSE_ERROR: i64 = -32700;
+/// JSON-RPC error code for unknown methods.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC error code for malformed request parameters.
+pub const INVALID_PARAMS: i64 = -32602;
+/// Server error code for sources that could not be parsed.
+pub const SOURCE_PARSE_FAILED: i64 = -32001;
+
+/// An incoming request or notification (a request without an `id`).
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    #[serde(default)]
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+/// A response to a request.
+#[derive(Debug, Serialize)]
+pub struct Response {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ResponseError>,
+}
+
+#[derive(Debug, Serialize)]
+struct ResponseError {
+    code: i64,
+    message: String,
+}
+
+impl Response {
+    pub fn ok(id: Value, result: Value) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    pub fn error(id: Value, code: i64, message: String) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            id,
+            result: None,
+            error: Some(ResponseError { code, message }),
+        }
+    }
+}
+
+/// Read the next message payload, or `None` at end of input.
+pub fn read_message(reader: &mut impl BufRead) -> Result<Option<Vec<u8>>, String> {
+    let mut content_length: Option<usize> = None;
+
+    loop {
+        let mut line = String::new();
+        let read = reader
+            .read_line(&mut line)
+            .map_err(|e| format!("Failed to read message header: {}", e))?;
+        if read == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.is_empty() {
+            // Tolerate blank lines between messages
+            if content_length.is_none() {
+                continue;
+            }
+            break;
+        }
+
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                let length = value
+                    .trim()
+                    .parse()
+                    .map_err(|e| format!("Invalid Content-Length '{}': {}", value.trim(), e))?;
+                content_length = Some(length);
+            }
+        }
+    }
+
+    let mut payload = vec![0; content_length.unwrap_or_default()];
+    reader
+        .read_exact(&mut payload)
+        .map_err(|e| format!("Failed to read message body: {}", e))?;
+    Ok(Some(payload))
+}
+
+/// Write a response with a `Content-Length` header.
+pub fn write_message(writer: &mut impl Write, response: &Response) -> Result<(), String> {
+    let payload =
+        serde_json::to_vec(response).map_err(|e| format!("Failed to serialize response: {}", e))?;
+
+    write!(writer, "Content-Length: {}\r\n\r\n", payload.len())
+        .and_then(|_| writer.write_all(&payload))
+        .and_then(|_| writer.flush())
+        .map_err(|e| format!("Failed to write response: {}", e))
+}
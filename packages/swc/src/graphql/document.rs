@@ -0,0 +1,44 @@
+//! Accessors for graphql-js AST nodes serialized as JSON.
+
+use serde_json::Value;
+
+/// Get the `kind` of an AST node.
+pub fn kind(node: &Value) -> Option<&str> {
+    node.get("kind")?.as_str()
+}
+
+/// Get the `name.value` of an AST node.
+pub fn name(node: &Value) -> Option<&str> {
+    node.get("name")?.get("value")?.as_str()
+}
+
+/// Get the `typeCondition.name.value` of a fragment or inline fragment.
+pub fn type_condition(node: &Value) -> Option<&str> {
+    node.get("typeCondition")?.get("name")?.get("value")?.as_str()
+}
+
+/// Check if a node has the directive `directive_name` (e.g., "defer" for `@defer`).
+pub fn has_directive(node: &Value, directive_name: &str) -> bool {
+    node.get("directives")
+        .and_then(Value::as_array)
+        .is_some_and(|directives| directives.iter().any(|directive| name(directive) == Some(directive_name)))
+}
+
+/// Print a type reference node (`NamedType`, `ListType`, `NonNullType`) as GraphQL, e.g. `[ID!]!`.
+pub fn type_ref(node: &Value) -> Option<String> {
+    match kind(node)? {
+        "NamedType" => name(node).map(str::to_string),
+        "ListType" => Some(format!("[{}]", type_ref(node.get("type")?)?)),
+        "NonNullType" => Some(format!("{}!", type_ref(node.get("type")?)?)),
+        _ => None,
+    }
+}
+
+/// Get the selections of a node's selection set (empty for leaf nodes).
+pub fn selections(node: &Value) -> &[Value] {
+    node.get("selectionSet")
+        .and_then(|set| set.get("selections"))
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
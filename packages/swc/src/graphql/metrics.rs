@@ -0,0 +1,104 @@
+//! Complexity metrics for operation documents.
+//!
+//! Fragment spreads are expanded through the fragment definitions embedded in
+//! the same document, so the numbers reflect what a server actually executes.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::types::PrebuildDocument;
+
+use super::document;
+
+/// Size and nesting metrics for a single operation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationMetrics {
+    /// Deepest level of nested field selections (root fields are depth 1).
+    pub depth: usize,
+    /// Total number of field selections.
+    pub field_count: usize,
+    /// Total number of fragment spreads and inline fragments.
+    pub fragment_count: usize,
+    /// Number of fragments deferred with `@defer`.
+    pub defer_count: usize,
+    /// Number of list fields streamed with `@stream`.
+    pub stream_count: usize,
+}
+
+impl OperationMetrics {
+    /// Whether the operation requests incremental delivery (`@defer` or `@stream`).
+    pub fn is_incremental(&self) -> bool {
+        self.defer_count > 0 || self.stream_count > 0
+    }
+}
+
+/// Compute metrics for the operation named `operation_name` in a graphql-js document.
+pub fn operation_metrics(document: &PrebuildDocument, operation_name: &str) -> OperationMetrics {
+    let definitions = &document.definitions;
+
+    let fragments: HashMap<&str, &Value> = definitions
+        .iter()
+        .map(AsRef::as_ref)
+        .filter(|definition| document::kind(definition) == Some("FragmentDefinition"))
+        .filter_map(|definition| Some((document::name(definition)?, definition)))
+        .collect();
+
+    let mut metrics = OperationMetrics::default();
+    let operation = definitions.iter().map(AsRef::as_ref).find(|definition: &&Value| {
+        document::kind(definition) == Some("OperationDefinition") && document::name(definition) == Some(operation_name)
+    });
+
+    if let Some(operation) = operation {
+        let mut expanding = Vec::new();
+        collect(operation, 0, &fragments, &mut expanding, &mut metrics);
+    }
+
+    metrics
+}
+
+/// Accumulate metrics for a node's selections at the given depth.
+///
+/// `expanding` holds the fragments currently being expanded, so cyclic spreads
+/// (invalid, but possible in a hand-edited artifact) terminate.
+fn collect<'a>(
+    node: &'a Value,
+    depth: usize,
+    fragments: &HashMap<&'a str, &'a Value>,
+    expanding: &mut Vec<&'a str>,
+    metrics: &mut OperationMetrics,
+) {
+    for selection in document::selections(node) {
+        match document::kind(selection) {
+            Some("Field") => {
+                metrics.field_count += 1;
+                metrics.stream_count += usize::from(document::has_directive(selection, "stream"));
+                metrics.depth = metrics.depth.max(depth + 1);
+                collect(selection, depth + 1, fragments, expanding, metrics);
+            }
+            Some("InlineFragment") => {
+                metrics.fragment_count += 1;
+                metrics.defer_count += usize::from(document::has_directive(selection, "defer"));
+                collect(selection, depth, fragments, expanding, metrics);
+            }
+            Some("FragmentSpread") => {
+                metrics.fragment_count += 1;
+                metrics.defer_count += usize::from(document::has_directive(selection, "defer"));
+                let Some(name) = document::name(selection) else {
+                    continue;
+                };
+                if expanding.contains(&name) {
+                    continue;
+                }
+                if let Some(fragment) = fragments.get(name) {
+                    expanding.push(name);
+                    collect(fragment, depth, fragments, expanding, metrics);
+                    expanding.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+}
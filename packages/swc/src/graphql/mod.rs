@@ -0,0 +1,13 @@
+//! GraphQL document and schema utilities.
+//!
+//! Operation prebuilds carry their document as a graphql-js AST serialized to
+//! JSON. These modules read that shape directly rather than converting it.
+
+pub mod document;
+pub mod metrics;
+pub mod parse;
+pub mod print;
+pub mod prune;
+pub mod schema;
+pub mod typenames;
+pub mod validate;
@@ -0,0 +1,216 @@
+//! Parsing GraphQL text into graphql-js AST nodes.
+//!
+//! Prebuilds carry documents in the shape graphql-js produces, so documents
+//! derived from source text (see `crate::transform::extract`) are converted to
+//! the same JSON shape. Locations are not recorded.
+
+use serde_json::Value;
+
+/// Parse a single executable definition into a graphql-js `DocumentNode`.
+#[cfg(feature = "artifact-free")]
+pub fn parse_document(text: &str) -> Result<Value, String> {
+    use graphql_parser::query::Definition;
+
+    let document =
+        graphql_parser::parse_query::<String>(text).map_err(|e| format!("Failed to parse document: {}", e))?;
+    let definitions = document
+        .definitions
+        .iter()
+        .map(|definition| match definition {
+            Definition::Operation(operation) => convert::operation(operation),
+            Definition::Fragment(fragment) => convert::fragment(fragment),
+        })
+        .collect::<Vec<_>>();
+
+    Ok(serde_json::json!({ "kind": "Document", "definitions": definitions }))
+}
+
+#[cfg(not(feature = "artifact-free"))]
+pub fn parse_document(_text: &str) -> Result<Value, String> {
+    Err("Prebuild extraction is not available: the native module was built without the \
+        `artifact-free` feature"
+        .to_string())
+}
+
+/// Conversion of graphql-parser nodes into graphql-js nodes.
+#[cfg(feature = "artifact-free")]
+mod convert {
+    use graphql_parser::query::{
+        Directive, FragmentDefinition, OperationDefinition, Selection, SelectionSet, Type, TypeCondition, Value as GqlValue,
+        VariableDefinition,
+    };
+    use serde_json::{json, Map, Value};
+
+    pub fn operation(operation: &OperationDefinition<'_, String>) -> Value {
+        let (kind, name, variables, directives, selection_set) = match operation {
+            OperationDefinition::SelectionSet(selection_set) => ("query", None, &[][..], &[][..], selection_set),
+            OperationDefinition::Query(query) => (
+                "query",
+                query.name.as_deref(),
+                &query.variable_definitions[..],
+                &query.directives[..],
+                &query.selection_set,
+            ),
+            OperationDefinition::Mutation(mutation) => (
+                "mutation",
+                mutation.name.as_deref(),
+                &mutation.variable_definitions[..],
+                &mutation.directives[..],
+                &mutation.selection_set,
+            ),
+            OperationDefinition::Subscription(subscription) => (
+                "subscription",
+                subscription.name.as_deref(),
+                &subscription.variable_definitions[..],
+                &subscription.directives[..],
+                &subscription.selection_set,
+            ),
+        };
+
+        let mut node = node("OperationDefinition");
+        node.insert("operation".to_string(), json!(kind));
+        if let Some(name) = name {
+            node.insert("name".to_string(), self::name(name));
+        }
+        node.insert(
+            "variableDefinitions".to_string(),
+            variables.iter().map(variable_definition).collect(),
+        );
+        node.insert("directives".to_string(), self::directives(directives));
+        node.insert("selectionSet".to_string(), self::selection_set(selection_set));
+        Value::Object(node)
+    }
+
+    pub fn fragment(fragment: &FragmentDefinition<'_, String>) -> Value {
+        let TypeCondition::On(type_condition) = &fragment.type_condition;
+        json!({
+            "kind": "FragmentDefinition",
+            "name": name(&fragment.name),
+            "typeCondition": named_type(type_condition),
+            "directives": directives(&fragment.directives),
+            "selectionSet": selection_set(&fragment.selection_set),
+        })
+    }
+
+    fn variable_definition(variable: &VariableDefinition<'_, String>) -> Value {
+        let mut node = node("VariableDefinition");
+        node.insert("variable".to_string(), json!({ "kind": "Variable", "name": name(&variable.name) }));
+        node.insert("type".to_string(), type_ref(&variable.var_type));
+        if let Some(default_value) = &variable.default_value {
+            node.insert("defaultValue".to_string(), value(default_value));
+        }
+        node.insert("directives".to_string(), json!([]));
+        Value::Object(node)
+    }
+
+    fn selection_set(selection_set: &SelectionSet<'_, String>) -> Value {
+        json!({
+            "kind": "SelectionSet",
+            "selections": selection_set.items.iter().map(selection).collect::<Vec<_>>(),
+        })
+    }
+
+    fn selection(selection: &Selection<'_, String>) -> Value {
+        match selection {
+            Selection::Field(field) => {
+                let mut node = node("Field");
+                if let Some(alias) = &field.alias {
+                    node.insert("alias".to_string(), name(alias));
+                }
+                node.insert("name".to_string(), name(&field.name));
+                node.insert("arguments".to_string(), arguments(&field.arguments));
+                node.insert("directives".to_string(), directives(&field.directives));
+                if !field.selection_set.items.is_empty() {
+                    node.insert("selectionSet".to_string(), selection_set(&field.selection_set));
+                }
+                Value::Object(node)
+            }
+            Selection::FragmentSpread(spread) => json!({
+                "kind": "FragmentSpread",
+                "name": name(&spread.fragment_name),
+                "directives": directives(&spread.directives),
+            }),
+            Selection::InlineFragment(inline) => {
+                let mut node = node("InlineFragment");
+                if let Some(TypeCondition::On(type_condition)) = &inline.type_condition {
+                    node.insert("typeCondition".to_string(), named_type(type_condition));
+                }
+                node.insert("directives".to_string(), directives(&inline.directives));
+                node.insert("selectionSet".to_string(), selection_set(&inline.selection_set));
+                Value::Object(node)
+            }
+        }
+    }
+
+    fn directives(directives: &[Directive<'_, String>]) -> Value {
+        directives
+            .iter()
+            .map(|directive| {
+                json!({
+                    "kind": "Directive",
+                    "name": name(&directive.name),
+                    "arguments": arguments(&directive.arguments),
+                })
+            })
+            .collect()
+    }
+
+    fn arguments(arguments: &[(String, GqlValue<'_, String>)]) -> Value {
+        arguments
+            .iter()
+            .map(|(argument, argument_value)| {
+                json!({ "kind": "Argument", "name": name(argument), "value": value(argument_value) })
+            })
+            .collect()
+    }
+
+    fn value(value: &GqlValue<'_, String>) -> Value {
+        match value {
+            GqlValue::Variable(variable) => json!({ "kind": "Variable", "name": name(variable) }),
+            GqlValue::Int(number) => json!({
+                "kind": "IntValue",
+                "value": number.as_i64().map(|number| number.to_string()).unwrap_or_default(),
+            }),
+            GqlValue::Float(number) => json!({ "kind": "FloatValue", "value": format!("{:?}", number) }),
+            GqlValue::String(string) => json!({ "kind": "StringValue", "value": string, "block": false }),
+            GqlValue::Boolean(boolean) => json!({ "kind": "BooleanValue", "value": boolean }),
+            GqlValue::Null => json!({ "kind": "NullValue" }),
+            GqlValue::Enum(enum_value) => json!({ "kind": "EnumValue", "value": enum_value }),
+            GqlValue::List(values) => json!({
+                "kind": "ListValue",
+                "values": values.iter().map(self::value).collect::<Vec<_>>(),
+            }),
+            GqlValue::Object(fields) => json!({
+                "kind": "ObjectValue",
+                "fields": fields
+                    .iter()
+                    .map(|(field, field_value)| {
+                        json!({ "kind": "ObjectField", "name": name(field), "value": self::value(field_value) })
+                    })
+                    .collect::<Vec<_>>(),
+            }),
+        }
+    }
+
+    fn type_ref(ty: &Type<'_, String>) -> Value {
+        match ty {
+            Type::NamedType(type_name) => named_type(type_name),
+            Type::ListType(inner) => json!({ "kind": "ListType", "type": type_ref(inner) }),
+            Type::NonNullType(inner) => json!({ "kind": "NonNullType", "type": type_ref(inner) }),
+        }
+    }
+
+    fn named_type(type_name: &str) -> Value {
+        json!({ "kind": "NamedType", "name": name(type_name) })
+    }
+
+    fn name(value: &str) -> Value {
+        json!({ "kind": "Name", "value": value })
+    }
+
+    fn node(kind: &str) -> Map<String, Value> {
+        let mut node = Map::new();
+        node.insert("kind".to_string(), json!(kind));
+        node
+    }
+}
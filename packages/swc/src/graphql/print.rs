@@ -0,0 +1,177 @@
+//! Normalized printing of prebuild documents.
+//!
+//! Hashes of operation documents (e.g., hashed operation names used as
+//! persisted-query IDs) are computed over this printed form instead of the
+//! serialized AST, so regenerating the artifact with different formatting,
+//! `loc` ranges, or selection order keeps them unchanged.
+//!
+//! Normalization rules:
+//! - Whitespace, commas, and comments are canonical; everything is printed on one line.
+//! - Fields and other selections, arguments, object fields, and variable
+//!   definitions are sorted, since their order does not change what is
+//!   selected. Directives keep their order, which can be significant.
+//! - Fragments are not inlined: spreads stay as they are, and definitions
+//!   follow the operations, sorted by name.
+
+use serde_json::Value;
+
+use crate::types::PrebuildDocument;
+
+use super::document;
+
+/// Print a document in normalized form.
+///
+/// Returns `None` for documents with nodes that are not executable GraphQL
+/// (e.g., type system definitions), which have no normalized form.
+pub fn print_normalized(document: &PrebuildDocument) -> Option<String> {
+    let mut definitions = document
+        .definitions
+        .iter()
+        .map(|definition| {
+            let order = match document::kind(definition)? {
+                "OperationDefinition" => 0,
+                "FragmentDefinition" => 1,
+                _ => return None,
+            };
+            Some((order, document::name(definition).unwrap_or_default(), print_definition(definition)?))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    definitions.sort();
+
+    Some(
+        definitions
+            .into_iter()
+            .map(|(_, _, printed)| printed)
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+fn print_definition(node: &Value) -> Option<String> {
+    let mut printed = match document::kind(node)? {
+        "OperationDefinition" => {
+            let mut printed = node.get("operation")?.as_str()?.to_string();
+            if let Some(name) = document::name(node) {
+                printed.push(' ');
+                printed.push_str(name);
+            }
+            printed
+        }
+        "FragmentDefinition" => format!("fragment {}", document::name(node)?),
+        _ => return None,
+    };
+
+    let mut variables = list(node, "variableDefinitions")
+        .iter()
+        .map(print_variable_definition)
+        .collect::<Option<Vec<_>>>()?;
+    if !variables.is_empty() {
+        variables.sort();
+        printed.push_str(&format!("({})", variables.join(", ")));
+    }
+    if let Some(type_condition) = document::type_condition(node) {
+        printed.push_str(&format!(" on {}", type_condition));
+    }
+    printed.push_str(&print_directives(node)?);
+    printed.push(' ');
+    printed.push_str(&print_selection_set(node)?);
+    Some(printed)
+}
+
+fn print_variable_definition(node: &Value) -> Option<String> {
+    let mut printed = format!(
+        "${}: {}",
+        document::name(node.get("variable")?)?,
+        document::type_ref(node.get("type")?)?
+    );
+    if let Some(default_value) = node.get("defaultValue").filter(|value| !value.is_null()) {
+        printed.push_str(" = ");
+        printed.push_str(&print_value(default_value)?);
+    }
+    printed.push_str(&print_directives(node)?);
+    Some(printed)
+}
+
+fn print_selection_set(node: &Value) -> Option<String> {
+    let mut selections = document::selections(node)
+        .iter()
+        .map(print_selection)
+        .collect::<Option<Vec<_>>>()?;
+    selections.sort();
+    Some(format!("{{ {} }}", selections.join(" ")))
+}
+
+fn print_selection(node: &Value) -> Option<String> {
+    let mut printed = match document::kind(node)? {
+        "Field" => {
+            let mut printed = match node.get("alias").filter(|alias| !alias.is_null()) {
+                Some(alias) => format!("{}: {}", alias.get("value")?.as_str()?, document::name(node)?),
+                None => document::name(node)?.to_string(),
+            };
+            printed.push_str(&print_arguments(node)?);
+            printed
+        }
+        "FragmentSpread" => format!("...{}", document::name(node)?),
+        "InlineFragment" => match document::type_condition(node) {
+            Some(type_condition) => format!("... on {}", type_condition),
+            None => "...".to_string(),
+        },
+        _ => return None,
+    };
+
+    printed.push_str(&print_directives(node)?);
+    if !document::selections(node).is_empty() {
+        printed.push(' ');
+        printed.push_str(&print_selection_set(node)?);
+    }
+    Some(printed)
+}
+
+fn print_arguments(node: &Value) -> Option<String> {
+    let mut arguments = list(node, "arguments")
+        .iter()
+        .map(|argument| Some(format!("{}: {}", document::name(argument)?, print_value(argument.get("value")?)?)))
+        .collect::<Option<Vec<_>>>()?;
+    if arguments.is_empty() {
+        return Some(String::new());
+    }
+    arguments.sort();
+    Some(format!("({})", arguments.join(", ")))
+}
+
+fn print_directives(node: &Value) -> Option<String> {
+    list(node, "directives")
+        .iter()
+        .map(|directive| Some(format!(" @{}{}", document::name(directive)?, print_arguments(directive)?)))
+        .collect()
+}
+
+fn print_value(node: &Value) -> Option<String> {
+    let literal = || node.get("value").and_then(Value::as_str).map(str::to_string);
+    match document::kind(node)? {
+        "Variable" => Some(format!("${}", document::name(node)?)),
+        "IntValue" | "FloatValue" | "EnumValue" => literal(),
+        // Block strings are printed as regular strings, so both forms of the same value match
+        "StringValue" => serde_json::to_string(node.get("value")?.as_str()?).ok(),
+        "BooleanValue" => Some(node.get("value")?.as_bool()?.to_string()),
+        "NullValue" => Some("null".to_string()),
+        "ListValue" => {
+            let values = list(node, "values").iter().map(print_value).collect::<Option<Vec<_>>>()?;
+            Some(format!("[{}]", values.join(", ")))
+        }
+        "ObjectValue" => {
+            let mut fields = list(node, "fields")
+                .iter()
+                .map(|field| Some(format!("{}: {}", document::name(field)?, print_value(field.get("value")?)?)))
+                .collect::<Option<Vec<_>>>()?;
+            fields.sort();
+            Some(format!("{{{}}}", fields.join(", ")))
+        }
+        _ => None,
+    }
+}
+
+/// Get an array field of a node (empty when absent).
+fn list<'a>(node: &'a Value, key: &str) -> &'a [Value] {
+    node.get(key).and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default()
+}
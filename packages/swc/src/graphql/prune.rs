@@ -0,0 +1,119 @@
+//! Field policy pruning of operation prebuilds.
+//!
+//! Selections of fields the policy does not permit (e.g., PII fields) are
+//! removed from operation documents before they are emitted, so security
+//! policies are enforced at build time. Field return types come from the
+//! schema, so nested selections are checked against their own types.
+
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+
+use crate::types::{FieldPolicy, PrebuildDocument};
+
+use super::document;
+use super::schema::SchemaIndex;
+
+/// A field selection removed by the policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrunedField {
+    pub type_name: String,
+    pub field_name: String,
+}
+
+/// Remove selections `policy` does not permit from `document`.
+///
+/// Returns the pruned document and the removed fields, or `None` if the
+/// document selects nothing restricted. A selection set left empty gets
+/// `__typename`, so the document stays valid.
+pub fn prune_document(
+    policy: &FieldPolicy,
+    schema: &SchemaIndex,
+    document: &PrebuildDocument,
+) -> Option<(PrebuildDocument, Vec<PrunedField>)> {
+    let mut pruned = Vec::new();
+    let definitions: Vec<Arc<Value>> = document
+        .definitions
+        .iter()
+        .map(|definition| {
+            let parent = match document::kind(definition) {
+                Some("OperationDefinition") => definition
+                    .get("operation")
+                    .and_then(Value::as_str)
+                    .and_then(|operation| schema.root_type(operation)),
+                Some("FragmentDefinition") => document::type_condition(definition),
+                _ => None,
+            };
+            parent
+                .and_then(|parent| prune_selections(policy, schema, parent, definition, &mut pruned))
+                .map_or_else(|| definition.clone(), Arc::new)
+        })
+        .collect();
+
+    if pruned.is_empty() {
+        return None;
+    }
+
+    let document = PrebuildDocument {
+        rest: document.rest.clone(),
+        definitions,
+    };
+    Some((document, pruned))
+}
+
+/// Prune a node's selections under type `parent`, or `None` if nothing below it was pruned.
+fn prune_selections(
+    policy: &FieldPolicy,
+    schema: &SchemaIndex,
+    parent: &str,
+    node: &Value,
+    pruned: &mut Vec<PrunedField>,
+) -> Option<Value> {
+    let selections = document::selections(node);
+    let mut changed = false;
+    let mut kept = Vec::with_capacity(selections.len());
+
+    for selection in selections {
+        let target = match document::kind(selection) {
+            Some("Field") => match document::name(selection) {
+                // Introspection fields (__typename) are always permitted
+                Some(field_name) if !field_name.starts_with("__") => {
+                    if !policy.permits(parent, field_name) {
+                        pruned.push(PrunedField {
+                            type_name: parent.to_string(),
+                            field_name: field_name.to_string(),
+                        });
+                        changed = true;
+                        continue;
+                    }
+                    schema.field_type(parent, field_name)
+                }
+                _ => None,
+            },
+            Some("InlineFragment") => Some(document::type_condition(selection).unwrap_or(parent)),
+            // Fragment spreads are pruned through their own definitions
+            _ => None,
+        };
+
+        match target.and_then(|target| prune_selections(policy, schema, target, selection, pruned)) {
+            Some(selection) => {
+                changed = true;
+                kept.push(Cow::Owned(selection));
+            }
+            None => kept.push(Cow::Borrowed(selection)),
+        }
+    }
+
+    if !changed {
+        return None;
+    }
+
+    let mut kept: Vec<Value> = kept.into_iter().map(Cow::into_owned).collect();
+    if kept.is_empty() {
+        kept.push(json!({ "kind": "Field", "name": { "kind": "Name", "value": "__typename" } }));
+    }
+    let mut node = node.clone();
+    node["selectionSet"]["selections"] = Value::Array(kept);
+    Some(node)
+}
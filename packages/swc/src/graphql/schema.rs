@@ -0,0 +1,259 @@
+//! Schema index for validating prebuilds against the project's SDL.
+//!
+//! Only the information needed to check selections is kept: type names, the
+//! named return type of each field, and union members. Parsing SDL requires the
+//! `schema-validation` feature.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::types::PrebuildDocument;
+
+use super::document;
+
+/// A named type in the schema.
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "schema-validation"), allow(dead_code))]
+pub enum SchemaType {
+    /// Object or interface type: field name to named return type.
+    Composite { fields: HashMap<String, String> },
+    /// Union type with its member type names.
+    Union { members: Vec<String> },
+    /// Scalar, enum, or input object type.
+    Leaf,
+}
+
+/// A selection that no longer matches the schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaIssue {
+    /// A referenced type does not exist.
+    UnknownType { type_name: String },
+    /// A selected field does not exist on its parent type.
+    UnknownField { type_name: String, field_name: String },
+}
+
+/// Lookup structure built from SDL.
+#[derive(Debug, Clone)]
+pub struct SchemaIndex {
+    query_type: String,
+    mutation_type: String,
+    subscription_type: String,
+    types: HashMap<String, SchemaType>,
+}
+
+impl SchemaIndex {
+    /// Parse SDL into a schema index.
+    #[cfg(feature = "schema-validation")]
+    pub fn parse(sdl: &str) -> Result<Self, String> {
+        use graphql_parser::schema::{Definition, TypeDefinition, TypeExtension};
+
+        let parsed = graphql_parser::parse_schema::<String>(sdl)
+            .map_err(|e| format!("Failed to parse schema SDL: {}", e))?;
+
+        let mut index = Self {
+            query_type: "Query".to_string(),
+            mutation_type: "Mutation".to_string(),
+            subscription_type: "Subscription".to_string(),
+            types: HashMap::new(),
+        };
+
+        for definition in parsed.definitions {
+            match definition {
+                Definition::SchemaDefinition(schema) => {
+                    if let Some(query) = schema.query {
+                        index.query_type = query;
+                    }
+                    if let Some(mutation) = schema.mutation {
+                        index.mutation_type = mutation;
+                    }
+                    if let Some(subscription) = schema.subscription {
+                        index.subscription_type = subscription;
+                    }
+                }
+                Definition::TypeDefinition(TypeDefinition::Object(object)) => {
+                    index.add_fields(object.name, fields_of(&object.fields));
+                }
+                Definition::TypeDefinition(TypeDefinition::Interface(interface)) => {
+                    index.add_fields(interface.name, fields_of(&interface.fields));
+                }
+                Definition::TypeDefinition(TypeDefinition::Union(union)) => {
+                    index.add_members(union.name, union.types);
+                }
+                Definition::TypeDefinition(TypeDefinition::Scalar(scalar)) => {
+                    index.types.insert(scalar.name, SchemaType::Leaf);
+                }
+                Definition::TypeDefinition(TypeDefinition::Enum(enum_type)) => {
+                    index.types.insert(enum_type.name, SchemaType::Leaf);
+                }
+                Definition::TypeDefinition(TypeDefinition::InputObject(input)) => {
+                    index.types.insert(input.name, SchemaType::Leaf);
+                }
+                Definition::TypeExtension(TypeExtension::Object(object)) => {
+                    index.add_fields(object.name, fields_of(&object.fields));
+                }
+                Definition::TypeExtension(TypeExtension::Interface(interface)) => {
+                    index.add_fields(interface.name, fields_of(&interface.fields));
+                }
+                Definition::TypeExtension(TypeExtension::Union(union)) => {
+                    index.add_members(union.name, union.types);
+                }
+                _ => {}
+            }
+        }
+
+        // Built-in scalars are implicit in SDL
+        for scalar in ["String", "Int", "Float", "Boolean", "ID"] {
+            index.types.entry(scalar.to_string()).or_insert(SchemaType::Leaf);
+        }
+
+        Ok(index)
+    }
+
+    /// Parse SDL into a schema index.
+    #[cfg(not(feature = "schema-validation"))]
+    pub fn parse(_sdl: &str) -> Result<Self, String> {
+        Err("Schema validation is not available: the native module was built without the \
+            `schema-validation` feature"
+            .to_string())
+    }
+
+    /// Check whether a named type exists.
+    pub fn has_type(&self, type_name: &str) -> bool {
+        self.types.contains_key(type_name)
+    }
+
+    /// Get the root type name for an operation type ("query", "mutation", "subscription").
+    pub fn root_type(&self, operation_type: &str) -> Option<&str> {
+        match operation_type {
+            "query" => Some(&self.query_type),
+            "mutation" => Some(&self.mutation_type),
+            "subscription" => Some(&self.subscription_type),
+            _ => None,
+        }
+    }
+
+    /// Get the named return type of a field on an object or interface type.
+    pub fn field_type(&self, type_name: &str, field_name: &str) -> Option<&str> {
+        match self.types.get(type_name)? {
+            SchemaType::Composite { fields } => fields.get(field_name).map(String::as_str),
+            _ => None,
+        }
+    }
+
+    /// Get the object types a value of `type_name` can have: the members of a
+    /// union, the type itself for objects and interfaces, none for leaf types.
+    pub fn object_types(&self, type_name: &str) -> Vec<&str> {
+        match self.types.get_key_value(type_name) {
+            Some((name, SchemaType::Composite { .. })) => vec![name.as_str()],
+            Some((_, SchemaType::Union { members })) => members.iter().map(String::as_str).collect(),
+            Some((_, SchemaType::Leaf)) | None => Vec::new(),
+        }
+    }
+
+    /// Validate every definition in a graphql-js document against the schema.
+    pub fn validate_document(&self, document: &PrebuildDocument) -> Vec<SchemaIssue> {
+        let mut issues = Vec::new();
+
+        for definition in &document.definitions {
+            let parent = match document::kind(definition) {
+                Some("OperationDefinition") => definition
+                    .get("operation")
+                    .and_then(Value::as_str)
+                    .and_then(|operation| self.root_type(operation)),
+                Some("FragmentDefinition") => document::type_condition(definition),
+                _ => None,
+            };
+
+            if let Some(parent) = parent {
+                self.validate_selections(parent, definition, &mut issues);
+            }
+        }
+
+        issues
+    }
+
+    /// Recursively validate a node's selections against its parent type.
+    fn validate_selections(&self, parent: &str, node: &Value, issues: &mut Vec<SchemaIssue>) {
+        let Some(parent_type) = self.types.get(parent) else {
+            issues.push(SchemaIssue::UnknownType {
+                type_name: parent.to_string(),
+            });
+            return;
+        };
+
+        for selection in document::selections(node) {
+            match document::kind(selection) {
+                Some("Field") => {
+                    let Some(field_name) = document::name(selection) else {
+                        continue;
+                    };
+                    if field_name.starts_with("__") {
+                        // Introspection fields (__typename, __schema, __type) are always valid
+                        continue;
+                    }
+
+                    let field_type = match parent_type {
+                        SchemaType::Composite { fields } => fields.get(field_name),
+                        _ => None,
+                    };
+
+                    match field_type {
+                        Some(field_type) => self.validate_selections(field_type, selection, issues),
+                        None => issues.push(SchemaIssue::UnknownField {
+                            type_name: parent.to_string(),
+                            field_name: field_name.to_string(),
+                        }),
+                    }
+                }
+                Some("InlineFragment") => {
+                    let target = document::type_condition(selection).unwrap_or(parent);
+                    self.validate_selections(target, selection, issues);
+                }
+                // Fragment spreads are validated through their own definitions
+                _ => {}
+            }
+        }
+    }
+
+    #[cfg(feature = "schema-validation")]
+    fn add_fields(&mut self, type_name: String, new_fields: HashMap<String, String>) {
+        let entry = self.types.entry(type_name).or_insert_with(|| SchemaType::Composite {
+            fields: HashMap::new(),
+        });
+        if let SchemaType::Composite { fields } = entry {
+            fields.extend(new_fields);
+        }
+    }
+
+    #[cfg(feature = "schema-validation")]
+    fn add_members(&mut self, type_name: String, new_members: Vec<String>) {
+        let entry = self
+            .types
+            .entry(type_name)
+            .or_insert_with(|| SchemaType::Union { members: Vec::new() });
+        if let SchemaType::Union { members } = entry {
+            members.extend(new_members);
+        }
+    }
+}
+
+/// Map SDL field definitions to their named return types.
+#[cfg(feature = "schema-validation")]
+fn fields_of(fields: &[graphql_parser::schema::Field<'_, String>]) -> HashMap<String, String> {
+    fields
+        .iter()
+        .map(|field| (field.name.clone(), named_type(&field.field_type).to_string()))
+        .collect()
+}
+
+/// Unwrap list and non-null wrappers to the underlying named type.
+#[cfg(feature = "schema-validation")]
+fn named_type<'a>(ty: &'a graphql_parser::schema::Type<'_, String>) -> &'a str {
+    use graphql_parser::schema::Type;
+
+    match ty {
+        Type::NamedType(name) => name,
+        Type::ListType(inner) | Type::NonNullType(inner) => named_type(inner),
+    }
+}
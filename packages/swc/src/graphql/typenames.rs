@@ -0,0 +1,74 @@
+//! Typenames selected by an operation document.
+//!
+//! Mutation registrations carry them as cache-invalidation hints, so the
+//! runtime's cache layer can invalidate entries without analyzing documents.
+//! Field return types come from the schema; without one, only the type
+//! conditions of fragments are known.
+
+use std::collections::BTreeSet;
+
+use serde_json::Value;
+
+use crate::types::PrebuildDocument;
+
+use super::document;
+use super::schema::SchemaIndex;
+
+/// Collect the object typenames a document selects, excluding root types.
+pub fn selected_typenames(document: &PrebuildDocument, schema: Option<&SchemaIndex>) -> BTreeSet<String> {
+    let mut typenames = BTreeSet::new();
+
+    for definition in &document.definitions {
+        let parent = match document::kind(definition) {
+            Some("OperationDefinition") => definition
+                .get("operation")
+                .and_then(Value::as_str)
+                .and_then(|operation| schema?.root_type(operation)),
+            Some("FragmentDefinition") => {
+                let type_condition = document::type_condition(definition);
+                add_typename(schema, type_condition, &mut typenames);
+                type_condition
+            }
+            _ => continue,
+        };
+        collect_selections(schema, parent, definition, &mut typenames);
+    }
+
+    typenames
+}
+
+/// Recursively collect the typenames of a node's selections under type `parent`, if known.
+fn collect_selections(schema: Option<&SchemaIndex>, parent: Option<&str>, node: &Value, typenames: &mut BTreeSet<String>) {
+    for selection in document::selections(node) {
+        match document::kind(selection) {
+            Some("Field") => {
+                let Some(field_name) = document::name(selection).filter(|name| !name.starts_with("__")) else {
+                    continue;
+                };
+                let field_type = parent.zip(schema).and_then(|(parent, schema)| schema.field_type(parent, field_name));
+                add_typename(schema, field_type, typenames);
+                collect_selections(schema, field_type, selection, typenames);
+            }
+            Some("InlineFragment") => {
+                let type_condition = document::type_condition(selection);
+                add_typename(schema, type_condition, typenames);
+                collect_selections(schema, type_condition.or(parent), selection, typenames);
+            }
+            // Fragment spreads are collected through their own definitions
+            _ => {}
+        }
+    }
+}
+
+/// Add the object types behind `type_name`: union members for unions, nothing for leaf types.
+fn add_typename(schema: Option<&SchemaIndex>, type_name: Option<&str>, typenames: &mut BTreeSet<String>) {
+    let Some(type_name) = type_name else {
+        return;
+    };
+    match schema {
+        Some(schema) => typenames.extend(schema.object_types(type_name).into_iter().map(str::to_string)),
+        None => {
+            typenames.insert(type_name.to_string());
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Structural validation of prebuild documents.
+//!
+//! Operation prebuilds embed a graphql-js `DocumentNode` serialized as JSON.
+//! A malformed node would only fail at runtime inside the bundle, so documents
+//! are checked when their artifact element is loaded.
+
+use serde_json::Value;
+
+use crate::types::PrebuildDocument;
+
+use super::document;
+
+/// Validate an operation document, returning a description of the first problem found.
+pub fn validate_operation_document(
+    document: &PrebuildDocument,
+    operation_type: &str,
+    operation_name: &str,
+) -> Result<(), String> {
+    if document.kind() != Some("Document") {
+        return Err("document: expected a graphql-js DocumentNode (kind \"Document\")".to_string());
+    }
+
+    let definitions = &document.definitions;
+    if definitions.is_empty() {
+        return Err("document.definitions: expected at least one definition".to_string());
+    }
+
+    let mut found_operation = false;
+    for (i, definition) in definitions.iter().enumerate() {
+        let path = format!("document.definitions[{}]", i);
+        match document::kind(definition) {
+            Some("OperationDefinition") => {
+                let operation = definition.get("operation").and_then(Value::as_str);
+                if !matches!(operation, Some("query" | "mutation" | "subscription")) {
+                    return Err(format!("{}.operation: expected query, mutation, or subscription", path));
+                }
+                if document::name(definition) == Some(operation_name) {
+                    if operation != Some(operation_type) {
+                        return Err(format!(
+                            "{}.operation: '{}' does not match prebuild operation type '{}'",
+                            path,
+                            operation.unwrap_or_default(),
+                            operation_type
+                        ));
+                    }
+                    found_operation = true;
+                }
+                validate_selection_set(definition, &path, true)?;
+            }
+            Some("FragmentDefinition") => {
+                if document::name(definition).is_none() {
+                    return Err(format!("{}.name: fragment definition has no name", path));
+                }
+                if document::type_condition(definition).is_none() {
+                    return Err(format!("{}.typeCondition: fragment definition has no type condition", path));
+                }
+                validate_selection_set(definition, &path, true)?;
+            }
+            Some(other) => {
+                return Err(format!("{}.kind: '{}' is not an executable definition", path, other));
+            }
+            None => return Err(format!("{}.kind: missing node kind", path)),
+        }
+    }
+
+    if !found_operation {
+        return Err(format!(
+            "document: no operation definition named '{}' was found",
+            operation_name
+        ));
+    }
+
+    Ok(())
+}
+
+/// Validate a node's selection set and all nested selections.
+fn validate_selection_set(node: &Value, path: &str, required: bool) -> Result<(), String> {
+    let Some(selection_set) = node.get("selectionSet").filter(|set| !set.is_null()) else {
+        return if required {
+            Err(format!("{}.selectionSet: missing selection set", path))
+        } else {
+            Ok(())
+        };
+    };
+
+    let selections = selection_set
+        .get("selections")
+        .and_then(Value::as_array)
+        .ok_or_else(|| format!("{}.selectionSet.selections: expected an array", path))?;
+    if selections.is_empty() {
+        return Err(format!("{}.selectionSet.selections: selection set is empty", path));
+    }
+
+    for (i, selection) in selections.iter().enumerate() {
+        let path = format!("{}.selectionSet.selections[{}]", path, i);
+        match document::kind(selection) {
+            Some("Field") => {
+                if document::name(selection).is_none() {
+                    return Err(format!("{}.name: field has no name", path));
+                }
+                validate_selection_set(selection, &path, false)?;
+            }
+            Some("FragmentSpread") => {
+                if document::name(selection).is_none() {
+                    return Err(format!("{}.name: fragment spread has no name", path));
+                }
+            }
+            Some("InlineFragment") => validate_selection_set(selection, &path, true)?,
+            Some(other) => return Err(format!("{}.kind: '{}' is not a selection", path, other)),
+            None => return Err(format!("{}.kind: missing node kind", path)),
+        }
+    }
+
+    Ok(())
+}
@@ -5,6 +5,7 @@
  */
 
 import { realpathSync } from "node:fs";
+import { createRequire } from "node:module";
 import { relative, resolve } from "node:path";
 import remapping from "@ampproject/remapping";
 import type { BuilderArtifact } from "@soda-gql/builder";
@@ -15,11 +16,40 @@ let nativeModule: NativeModule | null = null;
 
 interface NativeModule {
   transform(inputJson: string): string;
+  explainError(code: string): string | null;
+  validateConfig(configJson: string): string;
+  /** Missing from native modules built before capability reporting */
+  getCapabilities?(): string;
+  flattenSourceMap(sourceMapJson: string): string;
+  toIndexSourceMap(sourceMapJson: string): string;
   SwcTransformer: new (artifactJson: string, configJson: string) => NativeTransformer;
+  createLoaderArtifact(artifactJson: string, configJson: string): NativeLoaderArtifact;
+  loaderTransform(handle: NativeLoaderArtifact, sourceCode: string, sourcePath: string, contextJson?: string | null): string;
+  loaderTransformAsync(
+    handle: NativeLoaderArtifact,
+    sourceCode: string,
+    sourcePath: string,
+    contextJson?: string | null,
+  ): Promise<string>;
 }
 
+/** Opaque handle to the immutable state shared by native loader calls */
+type NativeLoaderArtifact = { readonly __loaderArtifact: unique symbol };
+
 interface NativeTransformer {
-  transform(sourceCode: string, sourcePath: string): string;
+  transform(sourceCode: string, sourcePath: string, contextJson?: string | null): string;
+  transformDual(sourceCode: string, sourcePath: string, contextJson?: string | null): string;
+  transformIncremental(sourcePath: string, editsJson: string, contextJson?: string | null): string;
+  getCacheKey(sourceCode: string, sourcePath: string, options?: string | null): string;
+  computeCacheKey(sourcePath: string, sourceHash: string): string;
+  transformForBun(sourceCode: string, sourcePath: string): string;
+  transformForEsbuild(argsJson: string, sourceCode?: string | null): string;
+  compareWithExpected(sourceCode: string, sourcePath: string, expectedOutput: string): string;
+  explain(sourceCode: string, sourcePath: string): string;
+  verify(sourceCode: string, sourcePath: string, expectedCode: string): string;
+  startDiagnosticsServer(socketPath?: string | null): Promise<void>;
+  setLogger(callback: (method: string, message: string) => void): void;
+  getUnusedEntries(): string[];
 }
 
 /**
@@ -35,6 +65,8 @@ export type SwcPluginError = {
   readonly message: string;
   /** Stage where the error occurred */
   readonly stage: "analysis" | "transform";
+  /** How serious the problem is; only "error" means the output will not work */
+  readonly severity: "error" | "warning" | "info";
   /** Source filename if applicable */
   readonly filename?: string;
   /** Canonical ID if applicable */
@@ -45,6 +77,86 @@ export type SwcPluginError = {
   readonly builderType?: string;
   /** Argument name if applicable */
   readonly argName?: string;
+  /** Location of the offending gql call, if known */
+  readonly span?: SwcErrorSpan;
+  /** Number of identical errors collapsed into this one */
+  readonly occurrences: number;
+};
+
+/**
+ * Guidance for a plugin error code.
+ */
+export type SwcErrorExplanation = {
+  readonly code: string;
+  /** What the error means */
+  readonly description: string;
+  readonly likelyCauses: readonly string[];
+  readonly suggestedFixes: readonly string[];
+};
+
+/**
+ * A problem found by `validateConfig`.
+ */
+export type SwcConfigIssue = {
+  readonly severity: "error" | "warning" | "info";
+  /** Path of the offending option (e.g. `overrides[0].config.isCjs`); empty for the whole config */
+  readonly path: string;
+  readonly message: string;
+};
+
+/**
+ * Optional features, syntaxes, and config options of the loaded native module.
+ */
+export type SwcCapabilities = {
+  /** Version of the native crate */
+  readonly version: string;
+  /** Optional features compiled in (e.g. "watch", "schema-validation", "diagnostics-server") */
+  readonly features: readonly string[];
+  /** Source syntaxes the parser accepts */
+  readonly syntaxes: readonly string[];
+  /** Config options the native transformer understands */
+  readonly configOptions: readonly string[];
+};
+
+/**
+ * Location of a plugin error in the source file.
+ */
+export type SwcErrorSpan = {
+  /** Byte offset of the start */
+  readonly start: number;
+  /** Byte offset of the end (exclusive) */
+  readonly end: number;
+  /** 1-based line of the start */
+  readonly line: number;
+  /** 0-based column of the start, in UTF-16 code units */
+  readonly column: number;
+};
+
+/**
+ * LSP `Diagnostic` for a plugin error. Lines are 0-based; characters are UTF-16 code units.
+ */
+export type LspDiagnostic = {
+  range: LspRange;
+  /** 1 = Error, 2 = Warning, 3 = Information */
+  severity: 1 | 2 | 3;
+  code: string;
+  source: "soda-gql";
+  message: string;
+  relatedInformation?: { location: { uri: string; range: LspRange }; message: string }[];
+};
+
+export type LspRange = {
+  start: { line: number; character: number };
+  end: { line: number; character: number };
+};
+
+/**
+ * Number of reported errors per severity.
+ */
+export type SeverityCounts = {
+  error: number;
+  warning: number;
+  info: number;
 };
 
 interface TransformResult {
@@ -52,24 +164,92 @@ interface TransformResult {
   transformed: boolean;
   sourceMap?: string;
   errors?: SwcPluginError[];
+  severityCounts?: SeverityCounts;
+  diagnostics?: LspDiagnostic[];
+  estree?: EstreeProgram;
+  dependencies?: TransformDependencies;
+  sidecar?: SidecarEntry[];
+  debugDump?: DebugDump;
 }
 
 /**
- * Load the native module.
+ * A definition replaced in a transformed file, as listed in the sidecar metadata.
+ */
+export type SidecarEntry = {
+  canonicalId: string;
+  elementType: "fragment" | "operation";
+  /** Operation name, for operations */
+  operationName?: string;
+  /** Position of the replacement expression in the output (1-based line, 0-based UTF-16 column) */
+  outputPosition?: { line: number; column: number };
+};
+
+/**
+ * Intermediate state of a transform, for diagnosing canonical ID mismatches with the artifact.
+ */
+export type DebugDump = {
+  /** File path part of the file's canonical IDs, after `pathMappings` */
+  canonicalPath: string;
+  /** Export names of the module's exported bindings, by local name */
+  exportBindings: Record<string, string>;
+  /** gql definitions found in the file, in source order */
+  definitions: {
+    span?: SwcErrorSpan;
+    astPath: string;
+    /** Canonical ID resolved from the AST path, looked up in the artifact */
+    canonicalId: string;
+    isTopLevel: boolean;
+    isExported: boolean;
+    exportBinding?: string;
+  }[];
+  /** Expressions replaced with runtime calls, in source order */
+  replacements: {
+    span?: SwcErrorSpan;
+    canonicalId: string;
+    elementType: "fragment" | "operation";
+    operationName?: string;
+  }[];
+};
+
+/**
+ * Modules and files a transformed file depends on, for bundler watch registration.
+ */
+export type TransformDependencies = {
+  /** Module specifiers whose imports were added (the runtime module) */
+  addedImports: string[];
+  /** Module specifiers whose imports were removed (graphql-system imports) */
+  removedImports: string[];
+  /** Files the output logically depends on (artifact files, graphql-system file) */
+  files: string[];
+};
+
+/**
+ * ESTree `Program` node of the transformed module.
+ * Locations refer to the original source; nodes generated by the transformer have `null` locations.
+ */
+export type EstreeProgram = { type: "Program"; body: unknown[]; sourceType: "module" } & Record<string, unknown>;
+
+/**
+ * Load the native module synchronously.
  * Uses the napi-rs generated loader which handles platform detection.
  */
-const loadNativeModule = async (): Promise<NativeModule> => {
+const loadNativeModuleSync = (): NativeModule => {
   if (nativeModule) {
     return nativeModule;
   }
 
+  // Use require() for the napi-rs generated loader (CommonJS)
+  const require = createRequire(import.meta.url);
   try {
-    // Use require() for the napi-rs generated loader (CommonJS)
-    const { createRequire } = await import("node:module");
-    const require = createRequire(import.meta.url);
     nativeModule = require("./native/index.js") as NativeModule;
     return nativeModule;
   } catch (error) {
+    const bunModule = loadBunFallbackModule(require);
+    if (bunModule) {
+      nativeModule = bunModule;
+      return nativeModule;
+    }
+
     throw new Error(
       "Failed to load @soda-gql/swc native module. " +
         "Make sure the native module is built for your platform. " +
@@ -78,8 +258,66 @@ const loadNativeModule = async (): Promise<NativeModule> => {
   }
 };
 
+/**
+ * Load the glibc binding directly under Bun.
+ * Bun's process.report lacks the glibc version on some releases, so the
+ * generated loader detects musl on glibc systems and picks the wrong binary.
+ */
+const loadBunFallbackModule = (require: NodeRequire): NativeModule | null => {
+  if (!process.versions.bun || process.platform !== "linux") {
+    return null;
+  }
+
+  try {
+    return require(`./native/swc.linux-${process.arch}-gnu.node`) as NativeModule;
+  } catch {
+    return null;
+  }
+};
+
+/**
+ * Load the native module.
+ */
+const loadNativeModule = async (): Promise<NativeModule> => loadNativeModuleSync();
+
 export type ModuleFormat = "esm" | "cjs";
 
+export type OutputProfile = "default" | "hermes";
+
+/**
+ * Import map in the Deno/browser format.
+ */
+export type ImportMap = {
+  imports?: Record<string, string>;
+  scopes?: Record<string, Record<string, string>>;
+  /** Directory relative addresses and scopes are resolved against (usually the project root) */
+  baseDir?: string;
+};
+
+/**
+ * Module resolution fields of a package.json.
+ */
+export type PackageManifest = {
+  /** Directory containing the package.json, which relative targets are resolved against */
+  dir: string;
+  name?: string;
+  /** Private subpath imports (e.g. `#graphql-system`), applying to files within `dir` */
+  imports?: Record<string, unknown>;
+  /** Public subpath exports, matched as `name` plus the subpath */
+  exports?: unknown;
+};
+
+/**
+ * Logger the transformer's output is forwarded to.
+ * Methods that are missing drop messages of that kind.
+ */
+export type TransformerLogger = {
+  debug?(message: string): void;
+  info?(message: string): void;
+  warn?(message: string): void;
+  error?(message: string): void;
+};
+
 export type TransformOptions = {
   /** Compiler options for output format */
   compilerOptions?: {
@@ -92,6 +330,228 @@ export type TransformOptions = {
   artifact: BuilderArtifact;
   /** Whether to generate source maps */
   sourceMap?: boolean;
+  /**
+   * Whether to embed the original source in source maps' `sourcesContent` (default true).
+   * When false, `sources` are relative to `sourceMapDir`, for setups serving sources separately.
+   */
+  sourcesContent?: boolean;
+  /**
+   * Directory the source maps are written to or served from (default: `config.baseDir`).
+   * Without `sourcesContent`, `sources` are relative to it, so maps carry no build-machine paths.
+   */
+  sourceMapDir?: string;
+  /**
+   * Whether to emit source maps as sectioned "index maps" with one section covering the output (default false).
+   * For pipelines that merge per-file maps into chunk-level index maps.
+   */
+  indexSourceMap?: boolean;
+  /**
+   * Syntax profile for generated code.
+   * "hermes" emits ES5-compatible code with a top-level runtime require for React Native (Metro).
+   */
+  outputProfile?: OutputProfile;
+  /** Import map used to recognize graphql-system imports through mapped specifiers */
+  importMap?: ImportMap;
+  /** package.json files whose subpath `imports` and `exports` are used to recognize graphql-system imports */
+  packageManifests?: PackageManifest[];
+  /** Whether to also return the transformed program as ESTree JSON */
+  emitEstree?: boolean;
+  /** Whether to list the replaced definitions and their output positions in `sidecar` */
+  emitSidecar?: boolean;
+  /** Whether to include the collected definitions, resolved canonical IDs, and replacement table in `debugDump` */
+  debugDump?: boolean;
+  /**
+   * Emit client-safe registrations (without server-only fields) for `"use client"` modules
+   * and full registrations for all others, for React Server Components (e.g., Next.js App Router).
+   * Server modules (`"use server"` or matching `serverOnly`) register against `serverRuntimeModule`
+   * without client-only helpers: mutations get no `invalidates` hints, and subscriptions with a
+   * `subscriptionTransport` are reported as errors.
+   */
+  splitServerClient?: boolean;
+  /** Runtime module imported by `"use client"` modules when `splitServerClient` is enabled */
+  clientRuntimeModule?: string;
+  /** Runtime module imported by server modules when `splitServerClient` is enabled (default: `@soda-gql/runtime/server`) */
+  serverRuntimeModule?: string;
+  /** Glob patterns of server-only modules (e.g., `src/server/**`), handled as if marked `"use server"` */
+  serverOnly?: string[];
+  /** "lsp" also returns errors as LSP `Diagnostic`s in `diagnostics`, for IDE plugins and problem matchers */
+  diagnosticFormat?: "plugin" | "lsp";
+  /**
+   * Verbosity of the transformer's own stderr output (default "warn").
+   * Errors are returned in `errors` either way, so "silent" avoids duplicate CI output.
+   */
+  logLevel?: "silent" | "warn" | "debug";
+  /**
+   * Host logger receiving the transformer's own output instead of stderr (e.g., Vite's `config.logger`).
+   * Messages still follow `logLevel` and arrive asynchronously, after the call that logged them.
+   */
+  logger?: TransformerLogger;
+  /**
+   * What to do with a file that has errors (default "collect", or "failFirst" in production `mode`):
+   * "collect" transforms what it can and returns all errors,
+   * "failFirst" throws on the first error,
+   * "failFile" returns the original source untransformed with all errors.
+   */
+  errorPolicy?: "collect" | "failFirst" | "failFile";
+  /**
+   * Tags of graphql-tag style templates (e.g., `["gql", "graphql"]`) to replace with
+   * registered soda-gql operations, matched by operation name. Templates that cannot
+   * be resolved are left as is, so graphql-tag usage can be migrated incrementally.
+   */
+  taggedTemplateTags?: string[];
+  /**
+   * Also resolve templates tagged with the `graphql-tag` import (`import gql from "graphql-tag"`)
+   * and drop the import once all of its templates are replaced (default false).
+   */
+  graphqlTagInterop?: boolean;
+  /**
+   * Virtual module specifiers (e.g., `"soda-gql:registry"`) generated as a module exporting
+   * all operations in the artifact (`operations`: name, type, hash, source file, canonical ID,
+   * and the `documentId` registered with hashed operation names).
+   * The bundler plugin must resolve the specifier to itself for it to reach the transformer.
+   */
+  registryModules?: string[];
+  /**
+   * Compile-time flags (e.g., `{ __DEV__: false }`). Definitions guarded by a flag that is off
+   * (`if (__DEV__) { ... }`, `devOnly(...)`) are removed along with their registrations,
+   * so debug-only operations don't ship to production bundles.
+   */
+  compileFlags?: Record<string, boolean>;
+  /**
+   * GraphQL SDL of the project schema. Prebuilds are validated against it, and it resolves
+   * the types of nested selections for `fieldPolicy`.
+   */
+  schemaSdl?: string;
+  /**
+   * Fields removed from operation prebuilds, by parent type (e.g., `{ deny: { User: ["email"] } }`).
+   * A type listed in `allow` keeps only the listed fields. Each removed selection is reported
+   * as a warning. Requires `schemaSdl`.
+   */
+  fieldPolicy?: {
+    deny?: Record<string, string[]>;
+    allow?: Record<string, string[]>;
+  };
+  /**
+   * Register and reference operations by a short content hash instead of their name
+   * (default false, or true in production `mode`). Enable for production builds to keep names out of bundles.
+   * The hash covers the normalized document, so it is unchanged by formatting or selection order.
+   */
+  hashOperationNames?: boolean;
+  /**
+   * Trusted-documents mode for production builds served by a GraphQL server that only accepts
+   * pre-registered documents: operations are registered under their hashed name (implies
+   * `hashOperationNames`) without their document. Operations whose ID is missing from
+   * `manifestIds` (the IDs of the generated manifest) are reported as errors.
+   */
+  trustedDocuments?: {
+    manifestIds?: string[];
+  };
+  /**
+   * Emit document definitions shared by several operations of a file (such as a common fragment)
+   * once, as a module-level constant each prebuild references (default false).
+   * Ignored in `trustedDocuments` mode, whose prebuilds carry no documents.
+   */
+  dedupePrebuilds?: boolean;
+  /**
+   * Experimental: derive prebuilds from the builder call itself for definitions the artifact has
+   * no entry for, so simple projects can transform without running the builder. Supports
+   * tagged-template builders without interpolations or fragment spreads
+   * (`` fragment("Name", "Type")`{ ... }`() ``, `` query("Name")`($id: ID!) { ... }`() ``).
+   * Requires a native module built with the `artifact-free` feature.
+   */
+  extractPrebuilds?: boolean;
+  /**
+   * Transport hint for subscription operations. When set, subscriptions are registered with
+   * `gqlRuntime.subscription({ ..., transport })` and referenced with `getSubscription`,
+   * so the runtime can wire WebSocket or SSE handling without inspecting documents.
+   */
+  subscriptionTransport?: "websocket" | "sse";
+  /**
+   * Register mutations with the typenames they affect (`invalidates`), derived from their
+   * selections (resolved through `schemaSdl` if set) plus `typenames[operationName]`,
+   * so the runtime's cache layer can invalidate without analyzing documents.
+   */
+  mutationInvalidation?: {
+    typenames?: Record<string, string[]>;
+  };
+  /**
+   * Keep the parse and source of recently transformed files for `transformIncremental` (default false).
+   * Retention is bounded by `incrementalMaxFiles`; call `forget` when a file is closed or deleted.
+   */
+  incremental?: boolean;
+  /** Number of files kept for `transformIncremental`, least recently transformed first out (default 256) */
+  incrementalMaxFiles?: number;
+  /**
+   * Structure output for React Fast Refresh (default false): registrations are skipped when
+   * a module re-runs with unchanged definitions, and generated identifiers stay stable across edits.
+   */
+  reactRefresh?: boolean;
+  /**
+   * Add a `debug` payload (canonical ID, source path, original definition text) to registrations
+   * (default false, or true in development `mode`). It is guarded by `process.env.NODE_ENV !== "production"`, so one transform
+   * output serves both dev and prod and bundlers strip the payload from production builds.
+   */
+  debugPayloads?: boolean;
+  /**
+   * Pass operation variable definitions to the runtime as `runtime: { variables: { id: "ID!" } }`
+   * (default false), so required variables can be checked without re-deriving them from documents.
+   */
+  runtimeVariables?: boolean;
+  /**
+   * Glob patterns of files to transform (default: every file).
+   * Patterns not starting with `/` match from any directory, e.g. `src/**`.
+   * Supports `*`, `**`, `?` and `{a,b}`.
+   */
+  include?: string[];
+  /**
+   * Glob patterns of files to return unchanged without parsing, even when included,
+   * e.g. `node_modules/**` or `__fixtures__/**`.
+   */
+  exclude?: string[];
+  /**
+   * Native config options applied to files matching a glob, in order, so one transformer can
+   * handle directories with different output, e.g. `[{ files: "src/legacy/**", config: { isCjs: true } }]`.
+   */
+  overrides?: ConfigOverride[];
+  /**
+   * Build mode setting the defaults of `debugPayloads`, `pureAnnotations`, `hashOperationNames`
+   * and `errorPolicy` together. "development" adds debug payloads and collects errors;
+   * "production" adds PURE annotations, hashes operation names, and throws on the first error.
+   * Options set explicitly take precedence.
+   */
+  mode?: "development" | "production";
+  /**
+   * Mark references to registered definitions with `#__PURE__` annotations so minifiers can drop unused ones
+   * (default false, or true in production `mode`).
+   */
+  pureAnnotations?: boolean;
+  /**
+   * Replace the graphql-system and inject modules with an empty stub (default true).
+   * Disable for builds that still need their runtime exports, e.g. while adopting soda-gql incrementally.
+   */
+  stubGraphqlSystem?: boolean;
+  /**
+   * Source emitted for the stubbed graphql-system module instead of `export {};`,
+   * e.g. re-exports of runtime helpers or dev-mode warnings on access.
+   */
+  graphqlSystemStub?: string;
+  /**
+   * Resolve symlinks in source paths before matching the graphql-system module and resolving canonical IDs
+   * (default false). Enable for pnpm's virtual store or linked workspace packages.
+   */
+  resolveSymlinks?: boolean;
+};
+
+/** Native config options applied to the files matching `files` */
+export type ConfigOverride = {
+  /** Glob pattern of the files the override applies to, matched like `include` */
+  files: string;
+  /**
+   * Options replacing the base config's, keyed like the native config (e.g. `isCjs`,
+   * `graphqlSystemAliases`). `schemaSdl`, `expectedArtifactHash`, `validateArtifact`
+   * and `cacheOutput` apply to the whole transformer and cannot be overridden.
+   */
+  config: Record<string, unknown>;
 };
 
 export type TransformInput = {
@@ -99,15 +559,62 @@ export type TransformInput = {
   sourceCode: string;
   /** Path to the source file */
   sourcePath: string;
-  /** Input source map from previous transformer (JSON string) */
+  /** Input source map from previous transformer (JSON string); index maps are flattened before chaining */
   inputSourceMap?: string;
+  /** Whether the output is rendered on the server (e.g., Vite's `ssr` option) */
+  ssr?: boolean;
+  /** Name of the bundler environment the file is transformed for (e.g., Vite's `this.environment.name`) */
+  environment?: string;
+};
+
+/**
+ * Replacement of a range of the source a file was last transformed from.
+ */
+export type TextEdit = {
+  /** Start offset of the replaced range in the previous source */
+  start: number;
+  /** End offset (exclusive) of the replaced range in the previous source */
+  end: number;
+  /** Replacement text */
+  text: string;
+};
+
+/**
+ * Input for re-transforming a file from edits to its previous source.
+ */
+export type IncrementalTransformInput = Omit<TransformInput, "sourceCode"> & {
+  /** Non-overlapping edits, with offsets into the previous source */
+  edits: TextEdit[];
+};
+
+const emptyDependencies = (): TransformDependencies => ({ addedImports: [], removedImports: [], files: [] });
+
+/**
+ * Chain the transform's source map onto the input source map.
+ * Both maps are flattened first, since remapping only accepts regular maps,
+ * and the result is wrapped back into an index map when requested.
+ */
+const chainSourceMaps = (native: NativeModule, sourceMap: string, inputSourceMap: string, indexSourceMap: boolean): string => {
+  // Chain source maps: our map -> input map -> original source
+  const merged = remapping(
+    [JSON.parse(native.flattenSourceMap(sourceMap)), JSON.parse(native.flattenSourceMap(inputSourceMap))],
+    () => null,
+  );
+  const chained = JSON.stringify(merged);
+  return indexSourceMap ? native.toIndexSourceMap(chained) : chained;
 };
 
 /**
  * Normalize path separators to forward slashes (cross-platform).
- * This matches the behavior of @soda-gql/common normalizePath.
+ * This matches the behavior of @soda-gql/common normalizePath, and additionally
+ * strips Windows verbatim and device prefixes (`\\?\C:\...`, `\\?\UNC\server\...`)
+ * the same way as the native transformer.
  */
-const normalizePath = (value: string): string => value.replace(/\\/g, "/");
+const normalizePath = (value: string): string =>
+  value
+    .replace(/\\/g, "/")
+    .replace(/^\/\/\?\/UNC\//i, "//")
+    .replace(/^\/\/[?.]\/(?=[A-Za-z]:(\/|$))/, "");
 
 /**
  * Compute the path prefix for filtering artifact elements.
@@ -132,18 +639,31 @@ const computeArtifactPathPrefix = (absolutePath: string, baseDir?: string): stri
  * for matching against relative canonical IDs in the artifact.
  * The canonical IDs in the returned artifact are converted to absolute paths
  * to match what the Rust code will generate.
+ *
+ * With includeOperations, operations of other files are kept under their
+ * original IDs for tagged templates, which are resolved by operation name.
  */
-const filterArtifactForFile = (artifact: BuilderArtifact, absoluteSourcePath: string, baseDir?: string): BuilderArtifact => {
+const filterArtifactForFile = (
+  artifact: BuilderArtifact,
+  absoluteSourcePath: string,
+  baseDir?: string,
+  includeOperations = false,
+): BuilderArtifact => {
   const relativePrefix = computeArtifactPathPrefix(absoluteSourcePath, baseDir);
   const absolutePrefix = `${absoluteSourcePath}::`;
 
   const filteredElements: BuilderArtifact["elements"] = {};
-  for (const [id, element] of Object.entries(artifact.elements)) {
+  for (const [recordedId, element] of Object.entries(artifact.elements)) {
+    // Keys recorded from Windows long paths are matched in their plain form
+    const id = normalizePath(recordedId);
     if (id.startsWith(relativePrefix)) {
       // Convert the canonical ID from relative to absolute path format
       // so it matches what the Rust code will generate
       const absoluteId = id.replace(relativePrefix, absolutePrefix);
       (filteredElements as Record<string, typeof element>)[absoluteId] = element;
+    } else if (includeOperations && element.type === "operation") {
+      // Tagged templates resolve operations defined in any file by name
+      (filteredElements as Record<string, typeof element>)[id] = element;
     }
   }
 
@@ -198,15 +718,266 @@ export type TransformOutput = {
   sourceMap?: string;
   /** Errors encountered during transformation (non-fatal) */
   errors: SwcPluginError[];
+  /** Number of `errors` per severity, for deciding whether to fail the build */
+  severityCounts: SeverityCounts;
+  /** Errors as LSP diagnostics, if `diagnosticFormat` is "lsp" */
+  diagnostics?: LspDiagnostic[];
+  /** The transformed program as ESTree JSON, if `emitEstree` was enabled */
+  estree?: EstreeProgram;
+  /** Replaced definitions and their output positions, if `emitSidecar` was enabled */
+  sidecar?: SidecarEntry[];
+  /** Intermediate transform state, if `debugDump` was enabled */
+  debugDump?: DebugDump;
+  /** Modules and files the output depends on (empty when nothing was transformed) */
+  dependencies: TransformDependencies;
+};
+
+/**
+ * Output of transforming a file to both module formats.
+ */
+export type DualTransformOutput = {
+  /** Output with a runtime `import` */
+  esm: TransformOutput;
+  /** Output with a runtime `require` */
+  cjs: TransformOutput;
+};
+
+/**
+ * Return value of a Bun plugin `onLoad` callback.
+ */
+export type BunLoadResult = {
+  /** The transformed source code */
+  contents: string;
+  /** Bun loader matching the source file extension */
+  loader: "js" | "jsx" | "ts" | "tsx";
+};
+
+/** The esbuild `onLoad` arguments `transformForEsbuild` uses */
+export type EsbuildLoadArgs = {
+  path: string;
+  /** Module namespace (default: `file`) */
+  namespace?: string;
+  /** Loader to return instead of the one matching the source file extension */
+  loader?: string;
+};
+
+/** An esbuild message with its location in the source */
+export type EsbuildMessage = {
+  /** Error code */
+  id: string;
+  pluginName: string;
+  text: string;
+  location: {
+    file: string;
+    namespace: string;
+    /** 1-based */
+    line: number;
+    /** 0-based, in bytes */
+    column: number;
+    /** In bytes */
+    length: number;
+    lineText: string;
+    suggestion: string;
+  } | null;
+  notes: { text: string; location: EsbuildMessage["location"] }[];
+};
+
+/** Result of `transformForEsbuild`, returnable from an esbuild `onLoad` callback as is */
+export type EsbuildLoadResult = {
+  /** The transformed source code */
+  contents: string;
+  loader: string;
+  errors: EsbuildMessage[];
+  /** Warnings; informational diagnostics are not included */
+  warnings: EsbuildMessage[];
 };
 
+/**
+ * Structural comparison of the native output with the TypeScript transformer's output.
+ */
+export type ParityReport = {
+  /** Whether both outputs have the same program structure (formatting and comments are ignored) */
+  matches: boolean;
+  /** Differing ESTree nodes, in program order */
+  differences?: ParityDifference[];
+};
+
+export type ParityDifference = {
+  /** Path of the node in the ESTree program (e.g. `body[2].declarations[0].init`) */
+  path: string;
+  /** The node in the expected output, or `null` if only the native output has it */
+  expected: unknown;
+  /** The node in the native output, or `null` if only the expected output has it */
+  actual: unknown;
+};
+
+/**
+ * How the gql calls of a file resolve against the artifact.
+ */
+export type ExplainReport = {
+  /** File path part of the file's canonical IDs, after `pathMappings` */
+  canonicalPath: string;
+  /** Every gql call found, in source order */
+  calls: ExplainedCall[];
+  /** Errors encountered during analysis */
+  errors?: SwcPluginError[];
+};
+
+export type ExplainedCall = {
+  span?: SwcErrorSpan;
+  /** AST path of the definition, if its location in the file was identified */
+  astPath?: string;
+  /** Canonical ID looked up in the artifact */
+  canonicalId?: string;
+  /** Whether the artifact had a valid entry for the canonical ID */
+  matched: boolean;
+  /** Artifact keys closest to the canonical ID, for unmatched calls */
+  nearestKeys?: string[];
+};
+
+/**
+ * Result of checking transform output against a golden output.
+ */
+export type VerifyResult = {
+  /** Whether the output matches the expected output (line endings are normalized) */
+  matches: boolean;
+  /** Unified diff from the expected to the actual output, on mismatch */
+  diff?: string;
+};
+
+/**
+ * Strip the URL scheme and query or hash suffix from a path passed by Bun.
+ */
+const normalizeBunPath = (value: string): string => value.replace(/^file:(\/\/)?/, "").replace(/[?#].*$/, "");
+
 /**
  * Transformer interface.
  */
 export interface Transformer {
   transform(input: TransformInput): TransformOutput;
+  /**
+   * Transform a file to both ES module and CommonJS output, parsing it once.
+   * For library builds publishing both formats; `isCjs` is ignored.
+   */
+  transformDual(input: TransformInput): DualTransformOutput;
+  /**
+   * Re-transform a file from edits to the source it was last transformed from,
+   * re-parsing only the statements around them.
+   * Requires the `incremental` option. Throws if the file has not been transformed
+   * with `transform` since it was last dropped, or if its last source failed to parse.
+   */
+  transformIncremental(input: IncrementalTransformInput): TransformOutput;
+  /** Drop the state kept for `transformIncremental` of a file, e.g. when it is closed or deleted */
+  forget(sourcePath: string): void;
+  /**
+   * Compute a cache key for the output of transforming a file.
+   * Changes with the source, path, config, the full artifact, and `options`.
+   */
+  getCacheKey(input: Pick<TransformInput, "sourceCode" | "sourcePath">, options?: string): string;
+  /**
+   * Compute a deterministic cache key from a source hash.
+   * Covers only the artifact entries defined in `sourcePath`, plus config and
+   * package version, so keys are stable across machines when `baseDir` is set.
+   */
+  computeCacheKey(sourcePath: string, sourceHash: string): string;
+  /**
+   * Transform a file for a Bun plugin `onLoad` callback.
+   * Accepts the path Bun passes to `onLoad`, including `file://` and query-suffixed forms.
+   */
+  transformForBun(input: Pick<TransformInput, "sourceCode" | "sourcePath">): BunLoadResult;
+  /**
+   * Transform a file for an esbuild plugin `onLoad` callback, returning esbuild messages.
+   * The source is read from `args.path` when omitted, which requires the `file` namespace.
+   */
+  transformForEsbuild(args: EsbuildLoadArgs, sourceCode?: string): EsbuildLoadResult;
+  /**
+   * Transform a file and compare the output with the TypeScript transformer's output for it,
+   * for validating a migration to the native transformer file by file.
+   */
+  compareWithExpected(input: Pick<TransformInput, "sourceCode" | "sourcePath">, expectedOutput: string): ParityReport;
+  /**
+   * Report every gql call in a file with its canonical ID and whether the artifact has an entry for it.
+   * Unmatched calls list the nearest artifact keys, for diagnosing "artifact not found" errors.
+   */
+  explain(input: Pick<TransformInput, "sourceCode" | "sourcePath">): ExplainReport;
+  /**
+   * Transform a file and check the output against a golden output, for snapshot-style
+   * guards against native transformer upgrades changing emitted code.
+   */
+  verify(input: Pick<TransformInput, "sourceCode" | "sourcePath">, expectedCode: string): VerifyResult;
+  /**
+   * Serve analyze-only diagnostics to editor extensions over stdio, or over a
+   * Unix domain socket when `socketPath` is given. Resolves when the server stops.
+   * Requires a native module built with the `diagnostics-server` feature.
+   */
+  startDiagnosticsServer(options?: { socketPath?: string }): Promise<void>;
+  /** Counts of the errors reported by all `transform` calls so far, for an end-of-build report */
+  getDiagnosticsSummary(): DiagnosticsSummary;
+  /** Reset the counts returned by `getDiagnosticsSummary` */
+  clearDiagnostics(): void;
+  /**
+   * Canonical IDs of artifact entries never referenced by any file transformed so far.
+   * Usually a sign of a stale artifact or AST-path drift between builder and transformer.
+   */
+  getUnusedEntries(): string[];
 }
 
+/**
+ * Errors reported across transform calls, including collapsed duplicate occurrences.
+ */
+export type DiagnosticsSummary = {
+  total: number;
+  bySeverity: SeverityCounts;
+  byCode: Record<string, number>;
+  byFile: Record<string, number>;
+};
+
+const emptyDiagnosticsSummary = (): DiagnosticsSummary => ({
+  total: 0,
+  bySeverity: { error: 0, warning: 0, info: 0 },
+  byCode: {},
+  byFile: {},
+});
+
+const recordDiagnostics = (summary: DiagnosticsSummary, sourcePath: string, errors: readonly SwcPluginError[]): void => {
+  for (const error of errors) {
+    const count = error.occurrences;
+    summary.total += count;
+    summary.bySeverity[error.severity] += count;
+    summary.byCode[error.code] = (summary.byCode[error.code] ?? 0) + count;
+    summary.byFile[sourcePath] = (summary.byFile[sourcePath] ?? 0) + count;
+  }
+};
+
+/**
+ * Record the entries of a per-file artifact that the file referenced,
+ * under their canonical IDs in the full artifact.
+ */
+const recordUsedEntries = (
+  usedEntries: Set<string>,
+  filteredArtifact: BuilderArtifact,
+  unusedIds: readonly string[],
+  absoluteSourcePath: string,
+  baseDir?: string,
+): void => {
+  const unused = new Set(unusedIds);
+  const relativePrefix = computeArtifactPathPrefix(absoluteSourcePath, baseDir);
+  const absolutePrefix = `${absoluteSourcePath}::`;
+  for (const id of Object.keys(filteredArtifact.elements)) {
+    if (!unused.has(id)) {
+      usedEntries.add(id.replace(absolutePrefix, relativePrefix));
+    }
+  }
+};
+
+/**
+ * Check if a module ID is one of the configured registry modules.
+ * Bundlers mark virtual module IDs with a leading `\0`, which is ignored.
+ */
+const isRegistryModule = (registryModules: readonly string[] | undefined, moduleId: string): boolean => {
+  return registryModules?.includes(moduleId.replace(/^\0/, "")) ?? false;
+};
+
 /**
  * Create a transformer instance.
  *
@@ -214,28 +985,247 @@ export interface Transformer {
  * @returns A transformer that can transform source files
  */
 export const createTransformer = async (options: TransformOptions): Promise<Transformer> => {
-  const native = await loadNativeModule();
+  return createTransformerWith(await loadNativeModule(), options);
+};
+
+/**
+ * Create a transformer instance synchronously.
+ *
+ * For hosts that cannot await during setup, such as Jest transformers.
+ *
+ * @param options - Transform options including config and artifact
+ * @returns A transformer that can transform source files
+ */
+export const createTransformerSync = (options: TransformOptions): Transformer => {
+  return createTransformerWith(loadNativeModuleSync(), options);
+};
+
+/**
+ * Convert a native transform result, chaining the output source map to the input's.
+ */
+const toTransformOutput = (
+  native: NativeModule,
+  options: Pick<TransformOptions, "indexSourceMap">,
+  result: TransformResult,
+  inputSourceMap: string | undefined,
+): TransformOutput => {
+  // Handle source map chaining
+  let finalSourceMap: string | undefined;
+  if (result.sourceMap) {
+    if (inputSourceMap) {
+      finalSourceMap = chainSourceMaps(native, result.sourceMap, inputSourceMap, options.indexSourceMap ?? false);
+    } else {
+      finalSourceMap = result.sourceMap;
+    }
+  }
+
+  return {
+    transformed: result.transformed,
+    sourceCode: result.outputCode,
+    sourceMap: finalSourceMap,
+    errors: result.errors ?? [],
+    severityCounts: result.severityCounts ?? { error: 0, warning: 0, info: 0 },
+    diagnostics: result.diagnostics,
+    estree: result.estree,
+    sidecar: result.sidecar,
+    debugDump: result.debugDump,
+    dependencies: result.dependencies ?? emptyDependencies(),
+  };
+};
 
+/**
+ * Serialize the native transformer config for transform options.
+ */
+const createConfigJson = (options: TransformOptions): string => {
   const isCJS = options.compilerOptions?.module === "CommonJS";
 
   // Resolve paths for internal module stubbing
   const graphqlSystemPath = resolveGraphqlSystemPath(options.config);
   const injectPaths = collectInjectPaths(options.config);
 
-  const configJson = JSON.stringify({
+  return JSON.stringify({
     graphqlSystemAliases: options.config.graphqlSystemAliases,
     isCjs: isCJS,
     graphqlSystemPath,
     injectPaths,
     sourceMap: options.sourceMap ?? false,
+    sourcesContent: options.sourcesContent ?? true,
+    sourceMapDir: options.sourceMapDir ?? options.config.baseDir,
+    indexSourceMap: options.indexSourceMap ?? false,
+    outputProfile: options.outputProfile ?? "default",
+    importMap: options.importMap,
+    packageManifests: options.packageManifests ?? [],
+    emitEstree: options.emitEstree ?? false,
+    emitSidecar: options.emitSidecar ?? false,
+    debugDump: options.debugDump ?? false,
+    splitServerClient: options.splitServerClient ?? false,
+    clientRuntimeModule: options.clientRuntimeModule,
+    serverRuntimeModule: options.serverRuntimeModule,
+    serverOnly: options.serverOnly ?? [],
+    diagnosticFormat: options.diagnosticFormat ?? "plugin",
+    logLevel: options.logLevel ?? "warn",
+    errorPolicy: options.errorPolicy,
+    taggedTemplateTags: options.taggedTemplateTags ?? [],
+    graphqlTagInterop: options.graphqlTagInterop ?? false,
+    registryModules: options.registryModules ?? [],
+    compileFlags: options.compileFlags ?? {},
+    schemaSdl: options.schemaSdl,
+    fieldPolicy: options.fieldPolicy ?? {},
+    hashOperationNames: options.hashOperationNames,
+    trustedDocuments: options.trustedDocuments,
+    dedupePrebuilds: options.dedupePrebuilds ?? false,
+    incremental: options.incremental ?? false,
+    extractPrebuilds: options.extractPrebuilds ?? false,
+    subscriptionTransport: options.subscriptionTransport,
+    mutationInvalidation: options.mutationInvalidation,
+    reactRefresh: options.reactRefresh ?? false,
+    debugPayloads: options.debugPayloads,
+    runtimeVariables: options.runtimeVariables ?? false,
+    include: options.include ?? [],
+    exclude: options.exclude ?? [],
+    overrides: options.overrides ?? [],
+    mode: options.mode,
+    pureAnnotations: options.pureAnnotations,
+    stubGraphqlSystem: options.stubGraphqlSystem ?? true,
+    graphqlSystemStub: options.graphqlSystemStub,
+    resolveSymlinks: options.resolveSymlinks ?? false,
   });
+};
+
+const createTransformerWith = (native: NativeModule, options: TransformOptions): Transformer => {
+  const configJson = createConfigJson(options);
+  const includeOperations = (options.taggedTemplateTags?.length ?? 0) > 0 || (options.graphqlTagInterop ?? false);
 
   // Store full artifact for per-file filtering
   const fullArtifact = options.artifact;
   const baseDir = options.config.baseDir;
 
+  // Every native transformer forwards its log output to the host logger, if given
+  const { logger } = options;
+  const createNative = (artifactJson: string): NativeTransformer => {
+    const nativeTransformer = new native.SwcTransformer(artifactJson, configJson);
+    if (logger) {
+      nativeTransformer.setLogger((method, message) => logger[method as keyof TransformerLogger]?.(message));
+    }
+    return nativeTransformer;
+  };
+
+  // Transformer over the full artifact, created on first use, so cache keys
+  // change whenever any part of the artifact changes
+  let fullTransformer: NativeTransformer | null = null;
+
+  // Latest per-file native transformer of each file, which holds its parse for transformIncremental,
+  // in least recently transformed order (only with the incremental option)
+  const fileTransformers = new Map<string, NativeTransformer>();
+  const incrementalMaxFiles = options.incrementalMaxFiles ?? 256;
+  const retainFileTransformer = (absolutePath: string, fileTransformer: NativeTransformer) => {
+    if (!options.incremental) {
+      return;
+    }
+    fileTransformers.set(absolutePath, fileTransformer);
+    for (const oldestPath of fileTransformers.keys()) {
+      if (fileTransformers.size <= incrementalMaxFiles) {
+        break;
+      }
+      fileTransformers.delete(oldestPath);
+    }
+  };
+
+  // Per-file native transformers are discarded, so the session summary is kept here
+  let diagnosticsSummary = emptyDiagnosticsSummary();
+  // Canonical IDs of the full artifact referenced by transformed files
+  const usedEntries = new Set<string>();
+
   return {
-    transform: ({ sourceCode, sourcePath, inputSourceMap }: TransformInput): TransformOutput => {
+    getDiagnosticsSummary: () => ({
+      ...diagnosticsSummary,
+      bySeverity: { ...diagnosticsSummary.bySeverity },
+      byCode: { ...diagnosticsSummary.byCode },
+      byFile: { ...diagnosticsSummary.byFile },
+    }),
+    clearDiagnostics: () => {
+      diagnosticsSummary = emptyDiagnosticsSummary();
+    },
+    getUnusedEntries: () => Object.keys(fullArtifact.elements).filter((id) => !usedEntries.has(id)).sort(),
+    getCacheKey: ({ sourceCode, sourcePath }, cacheOptions) => {
+      fullTransformer ??= createNative(JSON.stringify(fullArtifact));
+      return fullTransformer.getCacheKey(sourceCode, normalizePath(resolve(sourcePath)), cacheOptions);
+    },
+    computeCacheKey: (sourcePath, sourceHash) => {
+      fullTransformer ??= createNative(JSON.stringify(fullArtifact));
+      // Match the artifact's canonical IDs, which are relative to baseDir when it is set
+      const absolutePath = normalizePath(resolve(sourcePath));
+      const idPath = baseDir ? normalizePath(relative(baseDir, absolutePath)) : absolutePath;
+      return fullTransformer.computeCacheKey(idPath, sourceHash);
+    },
+    startDiagnosticsServer: (serverOptions) => {
+      fullTransformer ??= createNative(JSON.stringify(fullArtifact));
+      return fullTransformer.startDiagnosticsServer(serverOptions?.socketPath);
+    },
+    transformForBun: ({ sourceCode, sourcePath }) => {
+      const absolutePath = normalizePath(resolve(normalizeBunPath(sourcePath)));
+
+      // Filter the artifact the same way as transform()
+      const filteredArtifact = filterArtifactForFile(fullArtifact, absolutePath, baseDir, includeOperations);
+      const fileTransformer = createNative(JSON.stringify(filteredArtifact));
+
+      const result = JSON.parse(fileTransformer.transformForBun(sourceCode, absolutePath)) as BunLoadResult;
+      recordUsedEntries(usedEntries, filteredArtifact, fileTransformer.getUnusedEntries(), absolutePath, baseDir);
+      return result;
+    },
+    transformForEsbuild: (args, sourceCode) => {
+      const absolutePath = normalizePath(resolve(args.path));
+
+      // Filter the artifact the same way as transform()
+      const filteredArtifact = filterArtifactForFile(fullArtifact, absolutePath, baseDir, includeOperations);
+      const fileTransformer = createNative(JSON.stringify(filteredArtifact));
+
+      const result = JSON.parse(
+        fileTransformer.transformForEsbuild(JSON.stringify({ ...args, path: absolutePath }), sourceCode),
+      ) as EsbuildLoadResult;
+      recordUsedEntries(usedEntries, filteredArtifact, fileTransformer.getUnusedEntries(), absolutePath, baseDir);
+      return result;
+    },
+    explain: ({ sourceCode, sourcePath }) => {
+      fullTransformer ??= createNative(JSON.stringify(fullArtifact));
+      // Nearest keys come from the full artifact, whose canonical IDs are relative to baseDir when it is set
+      const absolutePath = normalizePath(resolve(sourcePath));
+      const idPath = baseDir ? normalizePath(relative(baseDir, absolutePath)) : absolutePath;
+      return JSON.parse(fullTransformer.explain(sourceCode, idPath)) as ExplainReport;
+    },
+    verify: ({ sourceCode, sourcePath }, expectedCode) => {
+      const absolutePath = normalizePath(resolve(sourcePath));
+
+      // Filter the artifact the same way as transform()
+      const filteredArtifact = filterArtifactForFile(fullArtifact, absolutePath, baseDir, includeOperations);
+      const fileTransformer = createNative(JSON.stringify(filteredArtifact));
+
+      return JSON.parse(fileTransformer.verify(sourceCode, absolutePath, expectedCode)) as VerifyResult;
+    },
+    compareWithExpected: ({ sourceCode, sourcePath }, expectedOutput) => {
+      const absolutePath = normalizePath(resolve(sourcePath));
+
+      // Filter the artifact the same way as transform()
+      const filteredArtifact = filterArtifactForFile(fullArtifact, absolutePath, baseDir, includeOperations);
+      const fileTransformer = createNative(JSON.stringify(filteredArtifact));
+
+      return JSON.parse(fileTransformer.compareWithExpected(sourceCode, absolutePath, expectedOutput)) as ParityReport;
+    },
+    transform: ({ sourceCode, sourcePath, inputSourceMap, ssr, environment }: TransformInput): TransformOutput => {
+      // Registry modules are virtual, so they are generated from the full artifact under their specifier
+      if (isRegistryModule(options.registryModules, sourcePath)) {
+        fullTransformer ??= createNative(JSON.stringify(fullArtifact));
+        const result: TransformResult = JSON.parse(fullTransformer.transform(sourceCode, sourcePath));
+        return {
+          transformed: result.transformed,
+          sourceCode: result.outputCode,
+          errors: result.errors ?? [],
+          severityCounts: result.severityCounts ?? { error: 0, warning: 0, info: 0 },
+          diagnostics: result.diagnostics,
+          dependencies: result.dependencies ?? emptyDependencies(),
+        };
+      }
+
       // Resolve to absolute path and normalize for canonical ID consistency
       // This ensures bundlers can pass relative paths safely
       const absolutePath = normalizePath(resolve(sourcePath));
@@ -244,36 +1234,133 @@ export const createTransformer = async (options: TransformOptions): Promise<Tran
       // This significantly reduces JSON serialization overhead for large codebases
       // The filter function converts relative canonical IDs to absolute paths
       // to match what the Rust code will generate
-      const filteredArtifact = filterArtifactForFile(fullArtifact, absolutePath, baseDir);
+      const filteredArtifact = filterArtifactForFile(fullArtifact, absolutePath, baseDir, includeOperations);
       const filteredArtifactJson = JSON.stringify(filteredArtifact);
 
       // Create per-file transformer with filtered artifact
-      const fileTransformer = new native.SwcTransformer(filteredArtifactJson, configJson);
+      const fileTransformer = createNative(filteredArtifactJson);
+      fileTransformers.delete(absolutePath);
 
       // Pass absolute path to native transformer for internal module stubbing detection
       // and canonical ID computation (the filtered artifact has absolute canonical IDs)
-      const resultJson = fileTransformer.transform(sourceCode, absolutePath);
+      const resultJson = fileTransformer.transform(sourceCode, absolutePath, JSON.stringify({ ssr: ssr ?? false, environment }));
+      retainFileTransformer(absolutePath, fileTransformer);
       const result: TransformResult = JSON.parse(resultJson);
+      recordDiagnostics(diagnosticsSummary, absolutePath, result.errors ?? []);
+      recordUsedEntries(usedEntries, filteredArtifact, fileTransformer.getUnusedEntries(), absolutePath, baseDir);
 
-      // Handle source map chaining
-      let finalSourceMap: string | undefined;
-      if (result.sourceMap) {
-        if (inputSourceMap) {
-          // Chain source maps: our map -> input map -> original source
-          const merged = remapping([JSON.parse(result.sourceMap), JSON.parse(inputSourceMap)], () => null);
-          finalSourceMap = JSON.stringify(merged);
-        } else {
-          finalSourceMap = result.sourceMap;
-        }
-      }
+      return toTransformOutput(native, options, result, inputSourceMap);
+    },
+    transformDual: ({ sourceCode, sourcePath, inputSourceMap, ssr, environment }) => {
+      const absolutePath = normalizePath(resolve(sourcePath));
+
+      // Filter the artifact the same way as transform()
+      const filteredArtifact = filterArtifactForFile(fullArtifact, absolutePath, baseDir, includeOperations);
+      const fileTransformer = createNative(JSON.stringify(filteredArtifact));
+
+      const resultJson = fileTransformer.transformDual(sourceCode, absolutePath, JSON.stringify({ ssr: ssr ?? false, environment }));
+      const result: { esm: TransformResult; cjs: TransformResult } = JSON.parse(resultJson);
+      recordDiagnostics(diagnosticsSummary, absolutePath, result.esm.errors ?? []);
+      recordUsedEntries(usedEntries, filteredArtifact, fileTransformer.getUnusedEntries(), absolutePath, baseDir);
 
       return {
-        transformed: result.transformed,
-        sourceCode: result.outputCode,
-        sourceMap: finalSourceMap,
-        errors: result.errors ?? [],
+        esm: toTransformOutput(native, options, result.esm, inputSourceMap),
+        cjs: toTransformOutput(native, options, result.cjs, inputSourceMap),
       };
     },
+    transformIncremental: ({ sourcePath, edits, inputSourceMap, ssr, environment }) => {
+      if (!options.incremental) {
+        throw new Error("transformIncremental requires the incremental option");
+      }
+      const absolutePath = normalizePath(resolve(sourcePath));
+      const fileTransformer = fileTransformers.get(absolutePath);
+      if (!fileTransformer) {
+        throw new Error(`No previous transform of ${absolutePath}; transform the full source before applying edits`);
+      }
+
+      // Kept only while its source parses, since edits after a parse error refer to the unparsed source
+      fileTransformers.delete(absolutePath);
+      const resultJson = fileTransformer.transformIncremental(
+        absolutePath,
+        JSON.stringify(edits),
+        JSON.stringify({ ssr: ssr ?? false, environment }),
+      );
+      retainFileTransformer(absolutePath, fileTransformer);
+      const result: TransformResult = JSON.parse(resultJson);
+      recordDiagnostics(diagnosticsSummary, absolutePath, result.errors ?? []);
+
+      return toTransformOutput(native, options, result, inputSourceMap);
+    },
+    forget: (sourcePath) => {
+      fileTransformers.delete(normalizePath(resolve(sourcePath)));
+    },
+  };
+};
+
+/**
+ * Loader for bundlers that transform many modules concurrently (e.g., Rspack, Turbopack).
+ *
+ * The artifact, schema, and config are loaded once into an immutable native
+ * handle, and every call transforms its file in isolation: no caches, diagnostics
+ * summary, or usage tracking are kept between calls.
+ */
+export interface ParallelLoader {
+  /** Transform a file on the libuv thread pool; concurrent calls run in parallel */
+  transform(input: TransformInput): Promise<TransformOutput>;
+  /** Transform a file on the calling thread */
+  transformSync(input: TransformInput): TransformOutput;
+}
+
+/** Options of a parallel loader; there is no logger, so native log output goes to stderr */
+export type ParallelLoaderOptions = Omit<TransformOptions, "logger">;
+
+/**
+ * Rewrite the canonical IDs of an artifact to absolute paths, matching
+ * what the native transformer computes for absolute source paths.
+ */
+const absolutizeArtifact = (artifact: BuilderArtifact, baseDir?: string): BuilderArtifact => {
+  if (!baseDir) {
+    return artifact;
+  }
+
+  const elements: BuilderArtifact["elements"] = {};
+  for (const [recordedId, element] of Object.entries(artifact.elements)) {
+    const id = normalizePath(recordedId);
+    const separator = id.indexOf("::");
+    const absoluteId =
+      separator === -1 ? id : `${normalizePath(resolve(baseDir, id.slice(0, separator)))}${id.slice(separator)}`;
+    (elements as Record<string, typeof element>)[absoluteId] = element;
+  }
+
+  return { ...artifact, elements };
+};
+
+/**
+ * Create a parallel loader.
+ *
+ * The handle belongs to the calling JS thread; create one loader per worker thread.
+ */
+export const createParallelLoader = (options: ParallelLoaderOptions): ParallelLoader => {
+  const native = loadNativeModuleSync();
+  const artifactJson = JSON.stringify(absolutizeArtifact(options.artifact, options.config.baseDir));
+  const handle = native.createLoaderArtifact(artifactJson, createConfigJson(options));
+
+  const toCall = ({ sourcePath, ssr, environment }: TransformInput): [string, string] => [
+    normalizePath(resolve(sourcePath)),
+    JSON.stringify({ ssr: ssr ?? false, environment }),
+  ];
+
+  return {
+    transform: async (input) => {
+      const [absolutePath, contextJson] = toCall(input);
+      const resultJson = await native.loaderTransformAsync(handle, input.sourceCode, absolutePath, contextJson);
+      return toTransformOutput(native, options, JSON.parse(resultJson) as TransformResult, input.inputSourceMap);
+    },
+    transformSync: (input) => {
+      const [absolutePath, contextJson] = toCall(input);
+      const resultJson = native.loaderTransform(handle, input.sourceCode, absolutePath, contextJson);
+      return toTransformOutput(native, options, JSON.parse(resultJson) as TransformResult, input.inputSourceMap);
+    },
   };
 };
 
@@ -291,6 +1378,8 @@ export const transform = async (
     config: ResolvedSodaGqlConfig;
     isCjs?: boolean;
     sourceMap?: boolean;
+    indexSourceMap?: boolean;
+    outputProfile?: OutputProfile;
   },
 ): Promise<TransformOutput> => {
   const native = await loadNativeModule();
@@ -318,7 +1407,10 @@ export const transform = async (
       graphqlSystemPath,
       injectPaths,
       sourceMap: input.sourceMap ?? false,
+      indexSourceMap: input.indexSourceMap ?? false,
+      outputProfile: input.outputProfile ?? "default",
     },
+    context: { ssr: input.ssr ?? false, environment: input.environment },
   });
 
   const resultJson = native.transform(inputJson);
@@ -328,9 +1420,7 @@ export const transform = async (
   let finalSourceMap: string | undefined;
   if (result.sourceMap) {
     if (input.inputSourceMap) {
-      // Chain source maps: our map -> input map -> original source
-      const merged = remapping([JSON.parse(result.sourceMap), JSON.parse(input.inputSourceMap)], () => null);
-      finalSourceMap = JSON.stringify(merged);
+      finalSourceMap = chainSourceMaps(native, result.sourceMap, input.inputSourceMap, input.indexSourceMap ?? false);
     } else {
       finalSourceMap = result.sourceMap;
     }
@@ -341,5 +1431,38 @@ export const transform = async (
     sourceCode: result.outputCode,
     sourceMap: finalSourceMap,
     errors: result.errors ?? [],
+    severityCounts: result.severityCounts ?? { error: 0, warning: 0, info: 0 },
+    dependencies: result.dependencies ?? emptyDependencies(),
   };
 };
+
+/**
+ * Explain a plugin error code (e.g., "SODA_GQL_ANALYSIS_ARTIFACT_NOT_FOUND").
+ *
+ * @returns Description, likely causes, and suggested fixes, or null for unknown codes
+ */
+export const explainError = (code: string): SwcErrorExplanation | null => {
+  const explanation = loadNativeModuleSync().explainError(code);
+  return explanation === null ? null : (JSON.parse(explanation) as SwcErrorExplanation);
+};
+
+/**
+ * Validate a native transformer config (types, unknown keys, and inconsistent combinations),
+ * so plugins can report misconfiguration at startup.
+ *
+ * @returns Issues found in the config, empty when it is valid
+ */
+export const validateConfig = (configJson: string): SwcConfigIssue[] => {
+  return JSON.parse(loadNativeModuleSync().validateConfig(configJson)) as SwcConfigIssue[];
+};
+
+/**
+ * Report what the loaded native module supports, so callers can skip options
+ * and features it lacks instead of failing.
+ *
+ * @returns Capabilities of the native module, or null for native modules too old to report them
+ */
+export const getCapabilities = (): SwcCapabilities | null => {
+  const capabilities = loadNativeModuleSync().getCapabilities?.();
+  return capabilities === undefined ? null : (JSON.parse(capabilities) as SwcCapabilities);
+};
@@ -3,13 +3,46 @@
 //! This crate provides a native Node.js module using napi-rs that transforms
 //! `gql.default()` calls into `gqlRuntime.*` calls at build time.
 
+mod artifact;
+#[cfg(feature = "diagnostics-server")]
+mod diagnostics;
+mod graphql;
 mod transform;
 mod types;
 
+use std::sync::Arc;
+
+use artifact::hash::verify_hash;
+use artifact::stats::collect_stats;
+use artifact::validate_all;
+#[cfg(feature = "watch")]
+use artifact::watch::{ArtifactWatchEvent, ArtifactWatcher};
+#[cfg(feature = "watch")]
+use transform::watch::{SourceWatcher, WatchTransformer};
+#[cfg(feature = "diagnostics-server")]
+use diagnostics::{DiagnosticsServer, Transport};
+use artifact::{ArtifactChain, ArtifactLayer, ArtifactSlot, ArtifactSource, LazyArtifact, MappedArtifact};
+use graphql::schema::SchemaIndex;
+use transform::logger::LogSink;
+use transform::bun::{loader_for_path, normalize_bun_path, BunLoadResult};
+use transform::esbuild::{esbuild_messages, EsbuildLoadArgs, EsbuildLoadResult};
+use transform::loader::{LoaderArtifact, LoaderTransformTask};
+use transform::analysis::canonical_path;
+use transform::incremental::{edit_cached, TextEdit};
+use transform::cache::{cache_key, file_cache_key, output_cache_key, source_hash, ModuleCache, OutputCache, PrebuildCache};
+use transform::parity::compare_outputs;
+use transform::realpath::{realpath, resolve_config_paths, RealpathCache};
+use transform::summary::DiagnosticsLog;
+use transform::registry::is_registry_module;
+use transform::transformer::{DualTransformResult, TransformResult};
+use transform::usage::ArtifactUsage;
+use transform::verify::verify_output;
 use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Env, JsFunction, JsObject};
 use napi_derive::napi;
-use types::config::{TransformConfig, TransformInput, TransformInputRef};
-use types::BuilderArtifact;
+use types::config::{TransformConfig, TransformContext, TransformInput, TransformInputRef};
+use types::CanonicalId;
 
 /// Transform a single source file.
 ///
@@ -20,24 +53,227 @@ use types::BuilderArtifact;
 /// JSON-serialized TransformResult containing the transformed code
 #[napi]
 pub fn transform(input_json: String) -> Result<String> {
-    let input: TransformInput = serde_json::from_str(&input_json)
+    let mut input: TransformInput = serde_json::from_str(&input_json)
         .map_err(|e| Error::from_reason(format!("Failed to parse input: {}", e)))?;
+    input.config.check_overrides().map_err(Error::from_reason)?;
+    if input.config.resolve_symlinks {
+        resolve_config_paths(&mut input.config);
+        input.source_path = realpath(&input.source_path);
+    }
+    input.config = input.config.for_path(&input.source_path).map_err(Error::from_reason)?;
 
     let result = transform::transformer::transform_source(&input)
-        .map_err(|e| Error::from_reason(e))?;
+        .map_err(Error::from_reason)?;
 
     serde_json::to_string(&result)
         .map_err(|e| Error::from_reason(format!("Failed to serialize result: {}", e)))
 }
 
+/// Explain an error code reported in `PluginError.code`.
+///
+/// # Arguments
+/// * `code` - Error code (e.g., `SODA_GQL_ANALYSIS_ARTIFACT_NOT_FOUND`)
+///
+/// # Returns
+/// JSON-serialized ErrorExplanation with description, likely causes, and suggested fixes,
+/// or null for unknown codes
+#[napi]
+pub fn explain_error(code: String) -> Result<Option<String>> {
+    types::error_catalogue::explain(&code)
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| Error::from_reason(format!("Failed to serialize explanation: {}", e)))
+}
+
+/// Validate a transformer config without creating a transformer.
+///
+/// Checks value types, unknown keys, and inconsistent option combinations, so
+/// plugins can report misconfiguration at startup.
+///
+/// # Arguments
+/// * `config_json` - JSON-serialized TransformConfig
+///
+/// # Returns
+/// JSON-serialized array of ConfigIssue (`severity`, `path`, `message`), empty when the config is valid
+#[napi]
+pub fn validate_config(config_json: String) -> Result<String> {
+    let issues = types::config_validation::validate_config(&config_json);
+
+    serde_json::to_string(&issues)
+        .map_err(|e| Error::from_reason(format!("Failed to serialize config issues: {}", e)))
+}
+
+/// Report the optional features, syntaxes, and config options of this build.
+///
+/// Lets the JS wrapper check what the loaded binary supports before using it,
+/// instead of failing on native modules built without a feature or from an
+/// older version.
+///
+/// # Returns
+/// JSON-serialized Capabilities (`version`, `features`, `syntaxes`, `configOptions`)
+#[napi]
+pub fn get_capabilities() -> Result<String> {
+    serde_json::to_string(&types::capabilities::capabilities())
+        .map_err(|e| Error::from_reason(format!("Failed to serialize capabilities: {}", e)))
+}
+
+/// Flatten a sectioned "index map" source map into a regular source map.
+///
+/// Lets plugins chain maps of concatenated or pre-bundled inputs with tools
+/// that only accept regular maps. Regular maps are returned unchanged.
+///
+/// # Arguments
+/// * `source_map_json` - JSON-serialized source map
+///
+/// # Returns
+/// JSON-serialized regular source map
+#[napi]
+pub fn flatten_source_map(source_map_json: String) -> Result<String> {
+    transform::index_map::flatten_source_map(&source_map_json).map_err(Error::from_reason)
+}
+
+/// Wrap a regular source map into an index map with a single section.
+///
+/// # Arguments
+/// * `source_map_json` - JSON-serialized source map
+///
+/// # Returns
+/// JSON-serialized index map; index maps are returned unchanged
+#[napi]
+pub fn to_index_source_map(source_map_json: String) -> Result<String> {
+    transform::index_map::wrap_source_map(&source_map_json).map_err(Error::from_reason)
+}
+
+/// Create the shared state for parallel loader calls from a serialized artifact.
+///
+/// The handle is immutable and holds no caches, so `loaderTransform` and
+/// `loaderTransformAsync` can be called with it for any number of files at
+/// once. Handles belong to the JS thread that created them; create one per
+/// worker thread.
+///
+/// # Arguments
+/// * `artifact_json` - JSON-serialized BuilderArtifact
+/// * `config_json` - JSON-serialized TransformConfig
+#[napi(ts_return_type = "ExternalObject<LoaderArtifact>")]
+pub fn create_loader_artifact(artifact_json: String, config_json: String) -> Result<External<Arc<LoaderArtifact>>> {
+    let config: TransformConfig = serde_json::from_str(&config_json)
+        .map_err(|e| Error::from_reason(format!("Failed to parse config: {}", e)))?;
+
+    let artifact = LazyArtifact::parse(&artifact_json).map_err(Error::from_reason)?;
+
+    loader_artifact(Arc::new(artifact), Vec::new(), config)
+}
+
+/// Create the shared state for parallel loader calls from a memory-mapped artifact file.
+///
+/// Worker threads that each load the same file share its pages instead of
+/// holding a parsed copy each.
+///
+/// # Arguments
+/// * `artifact_path` - Path to the JSON artifact file written by the builder
+/// * `config_json` - JSON-serialized TransformConfig
+#[napi(ts_return_type = "ExternalObject<LoaderArtifact>")]
+pub fn load_loader_artifact(artifact_path: String, config_json: String) -> Result<External<Arc<LoaderArtifact>>> {
+    let config: TransformConfig = serde_json::from_str(&config_json)
+        .map_err(|e| Error::from_reason(format!("Failed to parse config: {}", e)))?;
+
+    let artifact = MappedArtifact::open(&artifact_path).map_err(Error::from_reason)?;
+
+    loader_artifact(Arc::new(artifact), vec![artifact_path], config)
+}
+
+/// Transform a single source file with a loader handle.
+///
+/// Each call parses into its own `SourceMap` and records no state, so results
+/// do not depend on which calls ran before or alongside it.
+///
+/// # Arguments
+/// * `handle` - Handle returned by `createLoaderArtifact` or `loadLoaderArtifact`
+/// * `source_code` - The source code to transform
+/// * `source_path` - The file path of the source
+/// * `context_json` - Optional JSON-serialized TransformContext (`ssr`, `environment`) from the bundler
+///
+/// # Returns
+/// JSON-serialized TransformResult
+#[napi(ts_args_type = "handle: ExternalObject<LoaderArtifact>, sourceCode: string, sourcePath: string, contextJson?: string | undefined | null")]
+pub fn loader_transform(
+    handle: External<Arc<LoaderArtifact>>,
+    source_code: String,
+    source_path: String,
+    context_json: Option<String>,
+) -> Result<String> {
+    let context = parse_context(context_json.as_deref())?;
+    let result = handle
+        .transform(source_code, source_path, context)
+        .map_err(Error::from_reason)?;
+
+    serde_json::to_string(&result)
+        .map_err(|e| Error::from_reason(format!("Failed to serialize result: {}", e)))
+}
+
+/// Transform a single source file with a loader handle on the libuv thread pool.
+///
+/// Concurrent calls run on separate threads and share the handle's artifact.
+///
+/// # Arguments
+/// * `handle` - Handle returned by `createLoaderArtifact` or `loadLoaderArtifact`
+/// * `source_code` - The source code to transform
+/// * `source_path` - The file path of the source
+/// * `context_json` - Optional JSON-serialized TransformContext (`ssr`, `environment`) from the bundler
+///
+/// # Returns
+/// Promise of a JSON-serialized TransformResult
+#[napi(ts_args_type = "handle: ExternalObject<LoaderArtifact>, sourceCode: string, sourcePath: string, contextJson?: string | undefined | null")]
+pub fn loader_transform_async(
+    handle: External<Arc<LoaderArtifact>>,
+    source_code: String,
+    source_path: String,
+    context_json: Option<String>,
+) -> Result<AsyncTask<LoaderTransformTask>> {
+    Ok(AsyncTask::new(LoaderTransformTask {
+        loader: Arc::clone(&handle),
+        source_code,
+        source_path,
+        context: parse_context(context_json.as_deref())?,
+    }))
+}
+
 /// Stateful transformer that caches artifact and config for multiple file transformations.
 ///
-/// The artifact is parsed once in the constructor and reused for all subsequent
-/// transform calls, avoiding repeated JSON parsing overhead.
+/// The artifact is indexed once in the constructor and reused for all subsequent
+/// transform calls. Elements are deserialized lazily, the first time a file
+/// references them.
 #[napi]
 pub struct SwcTransformer {
-    /// Pre-parsed BuilderArtifact (parsed once in constructor)
-    artifact: BuilderArtifact,
+    /// Artifact loaded at construction (parsed or memory-mapped), swapped on reload
+    artifact: Arc<ArtifactSlot>,
+    /// Path of the artifact file, for transformers created with `fromArtifactFile`
+    #[cfg_attr(not(feature = "watch"), allow(dead_code))]
+    artifact_path: Option<String>,
+    /// Artifact files read by the transformer, reported as dependencies of transformed files
+    artifact_files: Vec<String>,
+    /// Active artifact file watcher, if `watchArtifact` was called
+    #[cfg(feature = "watch")]
+    watcher: Option<ArtifactWatcher>,
+    /// Active source file watcher, if `watch` was called
+    #[cfg(feature = "watch")]
+    source_watcher: Option<SourceWatcher>,
+    /// Schema parsed once from `schemaSdl`, if configured
+    schema: Option<Arc<SchemaIndex>>,
+    /// Serialized prebuilds reused across files, reset when the artifact is reloaded
+    prebuild_cache: PrebuildCache,
+    /// Parsed modules reused while a file's source is unchanged
+    module_cache: ModuleCache,
+    /// Transform results reused for unchanged files, if `cacheOutput` is enabled
+    output_cache: OutputCache,
+    /// Real paths of source paths, if `resolveSymlinks` is enabled
+    realpaths: RealpathCache,
+    /// Errors recorded across transform calls, until `clearDiagnostics`
+    diagnostics: DiagnosticsLog,
+    /// Artifact entries referenced by transformed files, for `getUnusedEntries`
+    usage: ArtifactUsage,
+    /// Host logger set with `setLogger`, replacing stderr output
+    log_sink: Option<LogSink>,
     config: TransformConfig,
 }
 
@@ -53,11 +289,479 @@ impl SwcTransformer {
         let config: TransformConfig = serde_json::from_str(&config_json)
             .map_err(|e| Error::from_reason(format!("Failed to parse config: {}", e)))?;
 
-        // Parse artifact once in constructor to avoid repeated parsing
-        let artifact: BuilderArtifact = serde_json::from_str(&artifact_json)
-            .map_err(|e| Error::from_reason(format!("Failed to parse artifact: {}", e)))?;
+        // Index artifact once in constructor; elements are parsed on first use
+        let artifact = LazyArtifact::parse(&artifact_json).map_err(Error::from_reason)?;
+
+        SwcTransformer::with_artifact(Arc::new(artifact), None, config)
+    }
+
+    /// Create a transformer backed by a memory-mapped artifact file.
+    ///
+    /// Only the byte range of each element is indexed up front; elements are
+    /// deserialized on first use. This keeps worker processes from each holding
+    /// a fully parsed copy of very large artifacts.
+    ///
+    /// The file must be replaced by renaming a new file into place, as the
+    /// builder does. Rewriting or truncating it in place while it is mapped can
+    /// crash the process (SIGBUS); rewrites that are detected in time make
+    /// elements not loaded yet fail to resolve until the artifact is reloaded.
+    ///
+    /// # Arguments
+    /// * `artifact_path` - Path to the JSON artifact file written by the builder
+    /// * `config_json` - JSON-serialized TransformConfig
+    #[napi(factory)]
+    pub fn from_artifact_file(artifact_path: String, config_json: String) -> Result<Self> {
+        let config: TransformConfig = serde_json::from_str(&config_json)
+            .map_err(|e| Error::from_reason(format!("Failed to parse config: {}", e)))?;
+
+        let artifact = MappedArtifact::open(&artifact_path).map_err(Error::from_reason)?;
 
-        Ok(SwcTransformer { artifact, config })
+        SwcTransformer::with_artifact(Arc::new(artifact), Some(artifact_path), config)
+    }
+
+    /// Create a transformer that resolves canonical IDs through an ordered list of artifacts.
+    ///
+    /// Earlier artifacts take precedence (e.g., app-local overrides, then a shared base).
+    /// Each resolved call reports which layer matched as an analysis diagnostic.
+    ///
+    /// # Arguments
+    /// * `artifact_jsons` - JSON-serialized BuilderArtifacts, highest precedence first
+    /// * `config_json` - JSON-serialized TransformConfig
+    #[napi(factory)]
+    pub fn from_artifact_chain(artifact_jsons: Vec<String>, config_json: String) -> Result<Self> {
+        let config: TransformConfig = serde_json::from_str(&config_json)
+            .map_err(|e| Error::from_reason(format!("Failed to parse config: {}", e)))?;
+
+        let layers = artifact_jsons
+            .iter()
+            .enumerate()
+            .map(|(index, json)| {
+                Ok(ArtifactLayer {
+                    label: format!("artifact[{}]", index),
+                    artifact: Box::new(LazyArtifact::parse(json)?),
+                })
+            })
+            .collect::<std::result::Result<Vec<_>, String>>()
+            .map_err(Error::from_reason)?;
+        let artifact = ArtifactChain::new(layers).map_err(Error::from_reason)?;
+
+        SwcTransformer::with_artifact(Arc::new(artifact), None, config)
+    }
+
+    /// Create a transformer that resolves canonical IDs through an ordered list of
+    /// memory-mapped artifact files.
+    ///
+    /// # Arguments
+    /// * `artifact_paths` - Paths to JSON artifact files, highest precedence first
+    /// * `config_json` - JSON-serialized TransformConfig
+    #[napi(factory)]
+    pub fn from_artifact_files(artifact_paths: Vec<String>, config_json: String) -> Result<Self> {
+        let config: TransformConfig = serde_json::from_str(&config_json)
+            .map_err(|e| Error::from_reason(format!("Failed to parse config: {}", e)))?;
+
+        let layers = artifact_paths
+            .iter()
+            .map(|path| {
+                Ok(ArtifactLayer {
+                    label: path.clone(),
+                    artifact: Box::new(MappedArtifact::open(path)?),
+                })
+            })
+            .collect::<std::result::Result<Vec<_>, String>>()
+            .map_err(Error::from_reason)?;
+        let artifact = ArtifactChain::new(layers).map_err(Error::from_reason)?;
+
+        let mut transformer = SwcTransformer::with_artifact(Arc::new(artifact), None, config)?;
+        transformer.artifact_files = artifact_paths;
+        Ok(transformer)
+    }
+
+    /// Watch the artifact file and reload it when the builder rewrites it.
+    ///
+    /// Only available for transformers created with `fromArtifactFile`. Calls that
+    /// are already running keep using the previous artifact. After each reload
+    /// attempt the callback receives a JSON-serialized ArtifactWatchEvent; if the
+    /// new artifact fails to load, the previous one stays active.
+    ///
+    /// # Arguments
+    /// * `callback` - Called with `{ "type": "reloaded", "artifactHash" }` or `{ "type": "error", "message" }`
+    #[napi]
+    pub fn watch_artifact(&mut self, env: Env, callback: JsFunction) -> Result<()> {
+        #[cfg(feature = "watch")]
+        {
+            let path = self.artifact_path.clone().ok_or_else(|| {
+                Error::from_reason("Artifact watching requires a transformer created with fromArtifactFile")
+            })?;
+
+            let mut notify: ThreadsafeFunction<String, ErrorStrategy::Fatal> = callback
+                .create_threadsafe_function(0, |ctx: ThreadSafeCallContext<String>| Ok(vec![ctx.value]))?;
+            // The watcher alone should not keep the process alive
+            notify.unref(&env)?;
+
+            let slot = Arc::clone(&self.artifact);
+            let config = self.config.clone();
+            let reload_path = path.clone();
+            let watcher = ArtifactWatcher::start(&path, move || {
+                let event = match load_artifact_file(&reload_path, &config) {
+                    Ok(artifact) => {
+                        let artifact_hash = artifact.content_hash().map(str::to_string);
+                        slot.store(artifact);
+                        ArtifactWatchEvent::Reloaded { artifact_hash }
+                    }
+                    Err(message) => ArtifactWatchEvent::Error { message },
+                };
+
+                if let Ok(json) = serde_json::to_string(&event) {
+                    notify.call(json, ThreadsafeFunctionCallMode::NonBlocking);
+                }
+            })
+            .map_err(Error::from_reason)?;
+
+            self.watcher = Some(watcher);
+            Ok(())
+        }
+
+        #[cfg(not(feature = "watch"))]
+        {
+            let _ = (env, callback);
+            Err(Error::from_reason(
+                "Artifact watching is not available: the native module was built without the `watch` feature",
+            ))
+        }
+    }
+
+    /// Stop watching the artifact file.
+    #[napi]
+    pub fn unwatch_artifact(&mut self) {
+        #[cfg(feature = "watch")]
+        {
+            self.watcher = None;
+        }
+    }
+
+    /// Watch source files and re-transform them when they change.
+    ///
+    /// Files matching `paths` are read and transformed on a background thread
+    /// after they are created or modified, using the current artifact (including
+    /// reloads by `watchArtifact`) and config. The callback receives a
+    /// JSON-serialized SourceWatchEvent per file. Diagnostics and artifact usage
+    /// of watched files are not recorded. Replaces any previous source watcher.
+    ///
+    /// # Arguments
+    /// * `paths` - Globs of the source files to watch; relative globs are resolved against the working directory
+    /// * `callback` - Called with `{ "type": "transformed", "sourcePath", "result" }` or `{ "type": "error", "sourcePath", "message" }`
+    #[napi]
+    pub fn watch(&mut self, env: Env, paths: Vec<String>, callback: JsFunction) -> Result<()> {
+        #[cfg(feature = "watch")]
+        {
+            let mut notify: ThreadsafeFunction<String, ErrorStrategy::Fatal> = callback
+                .create_threadsafe_function(0, |ctx: ThreadSafeCallContext<String>| Ok(vec![ctx.value]))?;
+            // The watcher alone should not keep the process alive
+            notify.unref(&env)?;
+
+            let transformer = WatchTransformer {
+                artifact: Arc::clone(&self.artifact),
+                schema: self.schema.clone(),
+                artifact_files: self.artifact_files.clone(),
+                log_sink: self.log_sink.clone(),
+                config: self.config.clone(),
+                prebuild_cache: PrebuildCache::default(),
+                module_cache: ModuleCache::default(),
+            };
+            let watcher = SourceWatcher::start(paths, move |source_path| {
+                let event = transformer.transform_file(source_path);
+                if let Ok(json) = serde_json::to_string(&event) {
+                    notify.call(json, ThreadsafeFunctionCallMode::NonBlocking);
+                }
+            })
+            .map_err(Error::from_reason)?;
+
+            self.source_watcher = Some(watcher);
+            Ok(())
+        }
+
+        #[cfg(not(feature = "watch"))]
+        {
+            let _ = (env, paths, callback);
+            Err(Error::from_reason(
+                "Source watching is not available: the native module was built without the `watch` feature",
+            ))
+        }
+    }
+
+    /// Stop watching source files.
+    #[napi]
+    pub fn unwatch(&mut self) {
+        #[cfg(feature = "watch")]
+        {
+            self.source_watcher = None;
+        }
+    }
+
+    /// Forward log output to a host logger instead of stderr.
+    ///
+    /// Messages still follow `logLevel`. They are delivered asynchronously on
+    /// the event loop, after the call that logged them returns.
+    ///
+    /// # Arguments
+    /// * `callback` - Called with the logger method (`debug`, `info`, `warn`, or `error`) and the message
+    #[napi(ts_args_type = "callback: (method: string, message: string) => void")]
+    pub fn set_logger(&mut self, env: Env, callback: JsFunction) -> Result<()> {
+        let mut forward: ThreadsafeFunction<(&'static str, String), ErrorStrategy::Fatal> = callback
+            .create_threadsafe_function(0, |ctx: ThreadSafeCallContext<(&'static str, String)>| {
+                let (method, message) = ctx.value;
+                Ok(vec![method.to_string(), message])
+            })?;
+        // Logging alone should not keep the process alive
+        forward.unref(&env)?;
+
+        self.log_sink = Some(Arc::new(move |method, message| {
+            forward.call((method, message), ThreadsafeFunctionCallMode::NonBlocking);
+        }));
+        Ok(())
+    }
+
+    /// Serve analyze-only diagnostics to an editor extension.
+    ///
+    /// Keeps the loaded artifact (including reloads from `watchArtifact`) and
+    /// answers JSON-RPC requests with LSP framing until a `shutdown` request,
+    /// an `exit` notification, or end of input. Requires the native module to be
+    /// built with the `diagnostics-server` feature.
+    ///
+    /// # Arguments
+    /// * `socket_path` - Unix domain socket to listen on; stdin/stdout are used when omitted
+    ///
+    /// # Returns
+    /// Promise that resolves when the server stops
+    #[napi(ts_return_type = "Promise<void>")]
+    pub fn start_diagnostics_server(&self, env: Env, socket_path: Option<String>) -> Result<JsObject> {
+        #[cfg(feature = "diagnostics-server")]
+        {
+            let transport = socket_path.map_or(Transport::Stdio, Transport::Socket);
+            let server = DiagnosticsServer::new(
+                Arc::clone(&self.artifact),
+                self.schema.clone(),
+                self.config.clone(),
+                transport,
+            );
+            Ok(env.spawn(server)?.promise_object())
+        }
+
+        #[cfg(not(feature = "diagnostics-server"))]
+        {
+            let _ = (env, socket_path);
+            Err(Error::from_reason(
+                "The diagnostics server is not available: the native module was built without the `diagnostics-server` feature",
+            ))
+        }
+    }
+
+    /// Get the content hash (XXH64, hex) of the loaded artifact.
+    ///
+    /// Distributed builds can compare this against the hash recorded when the
+    /// artifact was generated to detect stale or mismatched artifacts.
+    #[napi]
+    pub fn artifact_hash(&self) -> Option<String> {
+        self.artifact.load().content_hash().map(str::to_string)
+    }
+
+    /// Compute a cache key for transforming a file with this transformer.
+    ///
+    /// The key changes whenever the source, path, config, `options`, or the
+    /// loaded artifact (including after a reload) changes, so build tools that
+    /// cache transform output (e.g., Jest) invalidate correctly.
+    ///
+    /// # Arguments
+    /// * `source_code` - The source code to transform
+    /// * `source_path` - The file path of the source
+    /// * `options` - Caller-specific options that also affect the output (e.g., serialized Jest config)
+    #[napi]
+    pub fn get_cache_key(&self, source_code: String, source_path: String, options: Option<String>) -> Result<String> {
+        let config_json = serde_json::to_string(&self.config)
+            .map_err(|e| Error::from_reason(format!("Failed to serialize config: {}", e)))?;
+
+        Ok(cache_key(
+            self.artifact.load().content_hash(),
+            &config_json,
+            options.as_deref(),
+            &source_path,
+            &source_code,
+        ))
+    }
+
+    /// Compute a deterministic cache key for a file from its source hash.
+    ///
+    /// Combines the crate version, config, the file's canonical path, the
+    /// caller-computed source hash, and the content hashes of the artifact
+    /// entries defined in that file. Unlike `getCacheKey`, changes to other
+    /// files' entries leave the key unchanged, so shared build caches (e.g., Nx,
+    /// Turborepo, persistent webpack caches) can reuse outputs across machines.
+    /// With `taggedTemplateTags` or `graphqlTagInterop`, every operation entry is
+    /// covered too, since templates resolve operations defined in any file.
+    ///
+    /// # Arguments
+    /// * `source_path` - The file path of the source, as used in canonical IDs
+    /// * `source_hash` - Hash of the source code, computed by the caller
+    #[napi]
+    pub fn compute_cache_key(&self, source_path: String, source_hash: String) -> Result<String> {
+        let config_json = serde_json::to_string(&self.config)
+            .map_err(|e| Error::from_reason(format!("Failed to serialize config: {}", e)))?;
+
+        let source_path = self.resolve_source_path(source_path);
+        let config = self.config.for_path(&source_path).map_err(Error::from_reason)?;
+        let path = canonical_path(&source_path, &config.path_mappings);
+        let artifact = self.artifact.load();
+        let entries = dependent_entries(&config, artifact.as_ref(), &source_path, &path);
+
+        Ok(file_cache_key(&path, &source_hash, &entries, &config_json))
+    }
+
+    /// Get counts of the errors reported by all transform calls so far.
+    ///
+    /// Lets a build print one consolidated report at the end. Counts include
+    /// collapsed duplicate occurrences.
+    ///
+    /// # Returns
+    /// JSON-serialized DiagnosticsSummary (`total`, `bySeverity`, `byCode`, `byFile`)
+    #[napi]
+    pub fn get_diagnostics_summary(&self) -> Result<String> {
+        serde_json::to_string(&self.diagnostics.summary())
+            .map_err(|e| Error::from_reason(format!("Failed to serialize diagnostics summary: {}", e)))
+    }
+
+    /// Reset the counts returned by `getDiagnosticsSummary`.
+    #[napi]
+    pub fn clear_diagnostics(&self) {
+        self.diagnostics.clear();
+    }
+
+    /// List artifact entries never referenced by any file transformed so far.
+    ///
+    /// Entries that no file resolved to usually mean the artifact is stale or
+    /// the builder and transformer disagree on AST paths. Checked against the
+    /// currently loaded artifact, so entries removed on reload are not listed.
+    ///
+    /// # Returns
+    /// Canonical IDs of the unused entries, sorted
+    #[napi]
+    pub fn get_unused_entries(&self) -> Vec<String> {
+        self.usage
+            .unused(self.artifact.load().as_ref())
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    /// Get statistics for every element in the artifact.
+    ///
+    /// Loads all elements and reports per-operation complexity metrics
+    /// (depth, field count, fragment count), for enforcing budgets in CI.
+    ///
+    /// Operations using `@defer` or `@stream` are flagged `incremental`, so
+    /// gateways can be checked for incremental delivery support at build time.
+    ///
+    /// # Returns
+    /// JSON-serialized ArtifactStats
+    #[napi]
+    pub fn get_stats(&self) -> Result<String> {
+        let stats = collect_stats(self.artifact.load().as_ref());
+
+        serde_json::to_string(&stats)
+            .map_err(|e| Error::from_reason(format!("Failed to serialize stats: {}", e)))
+    }
+
+    /// Analyze a single source file without transforming it.
+    ///
+    /// # Arguments
+    /// * `source_code` - The source code to analyze
+    /// * `source_path` - The file path of the source
+    ///
+    /// # Returns
+    /// JSON-serialized AnalyzeResult with metrics for the operations the file references
+    #[napi]
+    pub fn analyze(&self, source_code: String, source_path: String) -> Result<String> {
+        let artifact = self.artifact.load();
+        let input = self.input_ref(artifact.as_ref(), source_code, source_path, TransformContext::default())?;
+
+        let result = transform::transformer::analyze_source_ref(&input)
+            .map_err(Error::from_reason)?;
+
+        serde_json::to_string(&result)
+            .map_err(|e| Error::from_reason(format!("Failed to serialize result: {}", e)))
+    }
+
+    /// Explain how the gql calls of a source file resolve against the artifact.
+    ///
+    /// Lists every detected gql call with its span, AST path, and canonical ID,
+    /// and whether an artifact entry matched. Unmatched calls list the nearest
+    /// artifact keys, to spot file path or AST path drift.
+    ///
+    /// # Arguments
+    /// * `source_code` - The source code to explain
+    /// * `source_path` - The file path of the source
+    ///
+    /// # Returns
+    /// JSON-serialized ExplainReport
+    #[napi]
+    pub fn explain(&self, source_code: String, source_path: String) -> Result<String> {
+        let artifact = self.artifact.load();
+        let input = self.input_ref(artifact.as_ref(), source_code, source_path, TransformContext::default())?;
+
+        let report = transform::transformer::explain_source_ref(&input)
+            .map_err(Error::from_reason)?;
+
+        serde_json::to_string(&report)
+            .map_err(|e| Error::from_reason(format!("Failed to serialize report: {}", e)))
+    }
+
+    /// Transform a single source file and compare the output with the TypeScript transformer's.
+    ///
+    /// Both outputs are compared as normalized ASTs, so formatting and comments
+    /// are ignored. Diagnostics and artifact usage are not recorded.
+    ///
+    /// # Arguments
+    /// * `source_code` - The source code to transform
+    /// * `source_path` - The file path of the source
+    /// * `expected_output` - Output code of the TypeScript transformer for the same file
+    ///
+    /// # Returns
+    /// JSON-serialized ParityReport listing the differing nodes
+    #[napi]
+    pub fn compare_with_expected(&self, source_code: String, source_path: String, expected_output: String) -> Result<String> {
+        let (generation, artifact) = self.artifact.snapshot();
+        self.prebuild_cache.sync(generation);
+        let input = self.input_ref(artifact.as_ref(), source_code, source_path, TransformContext::default())?;
+
+        let result = self.transform_input(&input)?;
+        let report = compare_outputs(&input.source_path, &result.output_code, &expected_output)
+            .map_err(Error::from_reason)?;
+
+        serde_json::to_string(&report)
+            .map_err(|e| Error::from_reason(format!("Failed to serialize parity report: {}", e)))
+    }
+
+    /// Transform a single source file and check the output against a golden output.
+    ///
+    /// Diagnostics and artifact usage are not recorded.
+    ///
+    /// # Arguments
+    /// * `source_code` - The source code to transform
+    /// * `source_path` - The file path of the source
+    /// * `expected_code` - The expected output code
+    ///
+    /// # Returns
+    /// JSON-serialized VerifyResult, with a unified diff on mismatch
+    #[napi]
+    pub fn verify(&self, source_code: String, source_path: String, expected_code: String) -> Result<String> {
+        let (generation, artifact) = self.artifact.snapshot();
+        self.prebuild_cache.sync(generation);
+        let input = self.input_ref(artifact.as_ref(), source_code, source_path, TransformContext::default())?;
+
+        let result = self.transform_input(&input)?;
+        let verified = verify_output(&input.source_path, &result.output_code, &expected_code);
+
+        serde_json::to_string(&verified)
+            .map_err(|e| Error::from_reason(format!("Failed to serialize result: {}", e)))
     }
 
     /// Transform a single source file.
@@ -65,23 +769,357 @@ impl SwcTransformer {
     /// # Arguments
     /// * `source_code` - The source code to transform
     /// * `source_path` - The file path of the source
+    /// * `context_json` - Optional JSON-serialized TransformContext (`ssr`, `environment`) from the bundler
     ///
     /// # Returns
     /// JSON-serialized TransformResult
     #[napi]
-    pub fn transform(&self, source_code: String, source_path: String) -> Result<String> {
-        // Use pre-parsed artifact reference instead of re-parsing JSON
-        let input = TransformInputRef {
-            source_code,
-            source_path,
-            artifact: &self.artifact,
-            config: self.config.clone(),
-        };
+    pub fn transform(&self, source_code: String, source_path: String, context_json: Option<String>) -> Result<String> {
+        let context = parse_context(context_json.as_deref())?;
+
+        // Use the loaded artifact instead of re-parsing JSON
+        let (generation, artifact) = self.artifact.snapshot();
+        self.prebuild_cache.sync(generation);
+        let input = self.input_ref(artifact.as_ref(), source_code, source_path, context)?;
+
+        let result = self.transform_input(&input)?;
+        self.diagnostics.record(&input.source_path, &result.errors);
+        self.usage.record(&result.matched_ids);
+
+        serde_json::to_string(&result)
+            .map_err(|e| Error::from_reason(format!("Failed to serialize result: {}", e)))
+    }
+
+    /// Transform a single source file to both ES module and CommonJS output.
+    ///
+    /// The file is parsed once; the second format reuses the parsed module.
+    /// `isCjs` in the config is ignored, and the errors of the file are
+    /// recorded once.
+    ///
+    /// # Arguments
+    /// * `source_code` - The source code to transform
+    /// * `source_path` - The file path of the source
+    /// * `context_json` - Optional JSON-serialized TransformContext (`ssr`, `environment`) from the bundler
+    ///
+    /// # Returns
+    /// JSON-serialized DualTransformResult (`esm`, `cjs`)
+    #[napi]
+    pub fn transform_dual(&self, source_code: String, source_path: String, context_json: Option<String>) -> Result<String> {
+        let context = parse_context(context_json.as_deref())?;
 
-        let result = transform::transformer::transform_source_ref(&input)
-            .map_err(|e| Error::from_reason(e))?;
+        let (generation, artifact) = self.artifact.snapshot();
+        self.prebuild_cache.sync(generation);
+        let mut input = self.input_ref(artifact.as_ref(), source_code, source_path, context)?;
+
+        input.config.is_cjs = false;
+        let esm = self.transform_input(&input)?;
+        input.config.is_cjs = true;
+        let cjs = self.transform_input(&input)?;
+        self.diagnostics.record(&input.source_path, &esm.errors);
+        self.usage.record(&esm.matched_ids);
+
+        serde_json::to_string(&DualTransformResult { esm, cjs })
+            .map_err(|e| Error::from_reason(format!("Failed to serialize result: {}", e)))
+    }
+
+    /// Re-transform a file after applying text edits to the source it was last transformed from.
+    ///
+    /// Only the top-level statements around the edits are re-parsed; analysis
+    /// and emit run on the whole file. Offsets are UTF-16 offsets into the
+    /// previous source, as in editor change events. Edits always apply to the
+    /// last source that parsed, so after a parse error the file has to be
+    /// transformed with `transform` again. Requires the `incremental` option,
+    /// which keeps the sources to apply edits to.
+    ///
+    /// # Arguments
+    /// * `source_path` - The file path of the source, previously passed to `transform`
+    /// * `edits_json` - JSON-serialized array of `{ start, end, text }` edits
+    /// * `context_json` - Optional JSON-serialized TransformContext (`ssr`, `environment`) from the bundler
+    ///
+    /// # Returns
+    /// JSON-serialized TransformResult
+    #[napi]
+    pub fn transform_incremental(
+        &self,
+        source_path: String,
+        edits_json: String,
+        context_json: Option<String>,
+    ) -> Result<String> {
+        if !self.config.incremental {
+            return Err(Error::from_reason(
+                "transformIncremental requires the `incremental` option".to_string(),
+            ));
+        }
+        let edits: Vec<TextEdit> = serde_json::from_str(&edits_json)
+            .map_err(|e| Error::from_reason(format!("Failed to parse edits: {}", e)))?;
+        let context = parse_context(context_json.as_deref())?;
+
+        let source_path = self.resolve_source_path(source_path);
+        let source_code = edit_cached(&self.module_cache, &source_path, &edits).map_err(Error::from_reason)?;
+
+        let (generation, artifact) = self.artifact.snapshot();
+        self.prebuild_cache.sync(generation);
+        let input = self.input_ref(artifact.as_ref(), source_code, source_path, context)?;
+
+        let result = self.transform_input(&input)?;
+        self.diagnostics.record(&input.source_path, &result.errors);
+        self.usage.record(&result.matched_ids);
 
         serde_json::to_string(&result)
             .map_err(|e| Error::from_reason(format!("Failed to serialize result: {}", e)))
     }
+
+    /// Transform a single source file for a Bun plugin `onLoad` callback.
+    ///
+    /// Paths in `file://` or namespaced form are normalized before canonical IDs
+    /// are resolved, so they match artifact entries recorded by the builder.
+    ///
+    /// # Arguments
+    /// * `source_code` - The source code to transform
+    /// * `source_path` - The path Bun passed to `onLoad`
+    ///
+    /// # Returns
+    /// JSON-serialized `{ contents, loader }`
+    #[napi]
+    pub fn transform_for_bun(&self, source_code: String, source_path: String) -> Result<String> {
+        let source_path = normalize_bun_path(&source_path).to_string();
+        let loader = loader_for_path(&source_path);
+
+        let (generation, artifact) = self.artifact.snapshot();
+        self.prebuild_cache.sync(generation);
+        let input = self.input_ref(artifact.as_ref(), source_code, source_path, TransformContext::default())?;
+
+        let result = self.transform_input(&input)?;
+        self.diagnostics.record(&input.source_path, &result.errors);
+        self.usage.record(&result.matched_ids);
+
+        serde_json::to_string(&BunLoadResult {
+            contents: result.output_code,
+            loader,
+        })
+        .map_err(|e| Error::from_reason(format!("Failed to serialize result: {}", e)))
+    }
+
+    /// Transform a single source file for an esbuild plugin `onLoad` callback.
+    ///
+    /// Errors and warnings are returned as esbuild messages located in the
+    /// source, so the plugin can return the result as is.
+    ///
+    /// # Arguments
+    /// * `args_json` - JSON-serialized `onLoad` args (`path`, `namespace`), optionally with a `loader` override
+    /// * `source_code` - The source code, read from `path` when omitted (only in the `file` namespace)
+    ///
+    /// # Returns
+    /// JSON-serialized `{ contents, loader, errors, warnings }`
+    #[napi]
+    pub fn transform_for_esbuild(&self, args_json: String, source_code: Option<String>) -> Result<String> {
+        let args: EsbuildLoadArgs = serde_json::from_str(&args_json)
+            .map_err(|e| Error::from_reason(format!("Failed to parse onLoad args: {}", e)))?;
+        let source_code = match source_code {
+            Some(source_code) => source_code,
+            None if args.namespace == "file" => std::fs::read_to_string(&args.path)
+                .map_err(|e| Error::from_reason(format!("Failed to read '{}': {}", args.path, e)))?,
+            None => {
+                return Err(Error::from_reason(format!(
+                    "Source code is required for modules in the '{}' namespace",
+                    args.namespace
+                )))
+            }
+        };
+
+        let (generation, artifact) = self.artifact.snapshot();
+        self.prebuild_cache.sync(generation);
+        let input = self.input_ref(artifact.as_ref(), source_code, args.path.clone(), TransformContext::default())?;
+
+        let result = self.transform_input(&input)?;
+        self.diagnostics.record(&input.source_path, &result.errors);
+        self.usage.record(&result.matched_ids);
+
+        let (errors, warnings) = esbuild_messages(&result.errors, &input.source_code, &args);
+        serde_json::to_string(&EsbuildLoadResult {
+            contents: result.output_code,
+            loader: args.loader(),
+            errors,
+            warnings,
+        })
+        .map_err(|e| Error::from_reason(format!("Failed to serialize result: {}", e)))
+    }
+}
+
+impl SwcTransformer {
+    /// Build a transformer, checking the artifact and parsing `schemaSdl` if configured.
+    fn with_artifact(
+        artifact: Arc<dyn ArtifactSource>,
+        artifact_path: Option<String>,
+        mut config: TransformConfig,
+    ) -> Result<Self> {
+        check_artifact(artifact.as_ref(), &config).map_err(Error::from_reason)?;
+        config.check_overrides().map_err(Error::from_reason)?;
+        resolve_config_paths(&mut config);
+
+        let schema = config.load_schema().map_err(Error::from_reason)?.map(Arc::new);
+
+        Ok(SwcTransformer {
+            artifact: Arc::new(ArtifactSlot::new(artifact)),
+            artifact_files: artifact_path.iter().cloned().collect(),
+            artifact_path,
+            #[cfg(feature = "watch")]
+            watcher: None,
+            #[cfg(feature = "watch")]
+            source_watcher: None,
+            schema,
+            prebuild_cache: PrebuildCache::default(),
+            module_cache: ModuleCache::default().with_sources(config.incremental),
+            output_cache: OutputCache::default(),
+            realpaths: RealpathCache::default(),
+            diagnostics: DiagnosticsLog::default(),
+            usage: ArtifactUsage::default(),
+            log_sink: None,
+            config,
+        })
+    }
+
+    /// Transform a file, reusing the cached result when `cacheOutput` is enabled
+    /// and neither the source, the file's artifact entries, nor the context changed.
+    ///
+    /// Cached results are returned without logging their errors again.
+    fn transform_input(&self, input: &TransformInputRef<'_>) -> Result<TransformResult> {
+        if !self.config.cache_output {
+            return transform::transformer::transform_source_ref(input).map_err(Error::from_reason);
+        }
+
+        // The per-call config, since `transformDual` varies the module format per call
+        let config_json = serde_json::to_string(&input.config)
+            .map_err(|e| Error::from_reason(format!("Failed to serialize config: {}", e)))?;
+        let context_json = serde_json::to_string(&input.context)
+            .map_err(|e| Error::from_reason(format!("Failed to serialize transform context: {}", e)))?;
+        let path = canonical_path(&input.source_path, &input.config.path_mappings);
+        let entries = dependent_entries(&input.config, input.artifact, &input.source_path, &path);
+        let file_key = file_cache_key(&path, &source_hash(&input.source_code), &entries, &config_json);
+        let key = output_cache_key(&file_key, &input.source_path, &context_json);
+
+        if let Some(result) = self.output_cache.get(&input.source_path, &key) {
+            return Ok(result);
+        }
+
+        let result = transform::transformer::transform_source_ref(input).map_err(Error::from_reason)?;
+        self.output_cache.insert(&input.source_path, key, &result);
+        Ok(result)
+    }
+
+    /// Build a per-file input that borrows an artifact snapshot and the schema,
+    /// with the config overrides matching `source_path` applied.
+    fn input_ref<'a>(
+        &'a self,
+        artifact: &'a dyn ArtifactSource,
+        source_code: String,
+        source_path: String,
+        context: TransformContext,
+    ) -> Result<TransformInputRef<'a>> {
+        let source_path = self.resolve_source_path(source_path);
+        let config = self.config.for_path(&source_path).map_err(Error::from_reason)?;
+        // Overrides can change how prebuilds are built, so only files using the
+        // base config share the cache
+        let prebuild_cache = (!self.config.has_overrides_for(&source_path)).then_some(&self.prebuild_cache);
+        Ok(TransformInputRef {
+            source_code,
+            source_path,
+            artifact,
+            schema: self.schema.as_deref(),
+            prebuild_cache,
+            module_cache: Some(&self.module_cache),
+            artifact_files: &self.artifact_files,
+            config,
+            context,
+            log_sink: self.log_sink.clone(),
+        })
+    }
+
+    /// Resolve symlinks in a source path, if `resolveSymlinks` is enabled.
+    fn resolve_source_path(&self, source_path: String) -> String {
+        if !self.config.resolve_symlinks {
+            return source_path;
+        }
+        self.realpaths.resolve(&source_path)
+    }
+}
+
+/// `(canonical ID, content hash)` pairs of the artifact entries the output
+/// for `source_path` depends on.
+///
+/// Registry modules list every operation, so they depend on all entries;
+/// other files on the entries defined in them (canonical path `path`), plus
+/// every operation when tagged templates are resolved by operation name.
+fn dependent_entries<'a>(
+    config: &TransformConfig,
+    artifact: &'a dyn ArtifactSource,
+    source_path: &str,
+    path: &str,
+) -> Vec<(&'a str, &'a str)> {
+    if is_registry_module(config, source_path) {
+        return entry_hashes(artifact, artifact.ids());
+    }
+
+    let ids = artifact.ids_in_file(path).iter();
+    let resolves_templates = !config.tagged_template_tags.is_empty() || config.graphql_tag_interop;
+    if !resolves_templates {
+        return entry_hashes(artifact, ids);
+    }
+    // Operations of this file are already listed
+    let prefix = format!("{}::", path);
+    let operations = artifact.operation_ids().iter().filter(|id| !id.starts_with(prefix.as_str()));
+    entry_hashes(artifact, ids.chain(operations))
+}
+
+/// `(canonical ID, content hash)` pairs of the artifact entries with the given IDs.
+fn entry_hashes<'a>(
+    artifact: &'a dyn ArtifactSource,
+    ids: impl Iterator<Item = &'a CanonicalId>,
+) -> Vec<(&'a str, &'a str)> {
+    ids.map(|id| {
+        // Entries that fail to load still contribute their ID
+        let hash = artifact.get(id).map_or("", |element| element.metadata().content_hash.as_str());
+        (id.as_ref(), hash)
+    })
+    .collect()
+}
+
+/// Verify the artifact against `expectedArtifactHash` and eagerly validate
+/// its elements if `validateArtifact` is set.
+fn check_artifact(artifact: &dyn ArtifactSource, config: &TransformConfig) -> std::result::Result<(), String> {
+    verify_hash(config.expected_artifact_hash.as_deref(), artifact.content_hash())?;
+
+    if config.validate_artifact {
+        validate_all(artifact)?;
+    }
+
+    Ok(())
+}
+
+/// Check an artifact and wrap it with the config into a loader handle.
+fn loader_artifact(
+    artifact: Arc<dyn ArtifactSource>,
+    artifact_files: Vec<String>,
+    config: TransformConfig,
+) -> Result<External<Arc<LoaderArtifact>>> {
+    check_artifact(artifact.as_ref(), &config).map_err(Error::from_reason)?;
+    let loader = LoaderArtifact::new(artifact, artifact_files, config).map_err(Error::from_reason)?;
+    Ok(External::new(Arc::new(loader)))
+}
+
+/// Parse an optional JSON-serialized TransformContext.
+fn parse_context(context_json: Option<&str>) -> Result<TransformContext> {
+    Ok(context_json
+        .map(serde_json::from_str)
+        .transpose()
+        .map_err(|e| Error::from_reason(format!("Failed to parse transform context: {}", e)))?
+        .unwrap_or_default())
+}
+
+/// Map and check an artifact file, for reloads.
+#[cfg(feature = "watch")]
+fn load_artifact_file(path: &str, config: &TransformConfig) -> std::result::Result<Arc<dyn ArtifactSource>, String> {
+    let artifact = MappedArtifact::open(path)?;
+    check_artifact(&artifact, config)?;
+    Ok(Arc::new(artifact))
 }
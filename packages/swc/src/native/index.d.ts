@@ -13,11 +13,135 @@
  * JSON-serialized TransformResult containing the transformed code
  */
 export declare function transform(inputJson: string): string
+export declare class ExternalObject<T> {
+  readonly '': {
+    readonly '': unique symbol
+    [K: symbol]: T
+  }
+}
+/** Immutable artifact, schema, and config shared by parallel loader calls */
+export declare class LoaderArtifact {
+  private constructor()
+}
+/**
+ * Create the shared state for parallel loader calls from a serialized artifact.
+ *
+ * The handle is immutable and holds no caches, so `loaderTransform` and
+ * `loaderTransformAsync` can be called with it for any number of files at
+ * once. Handles belong to the JS thread that created them; create one per
+ * worker thread.
+ *
+ * # Arguments
+ * * `artifact_json` - JSON-serialized BuilderArtifact
+ * * `config_json` - JSON-serialized TransformConfig
+ */
+export declare function createLoaderArtifact(artifactJson: string, configJson: string): ExternalObject<LoaderArtifact>
+/**
+ * Create the shared state for parallel loader calls from a memory-mapped artifact file.
+ *
+ * Worker threads that each load the same file share its pages instead of
+ * holding a parsed copy each.
+ *
+ * # Arguments
+ * * `artifact_path` - Path to the JSON artifact file written by the builder
+ * * `config_json` - JSON-serialized TransformConfig
+ */
+export declare function loadLoaderArtifact(artifactPath: string, configJson: string): ExternalObject<LoaderArtifact>
+/**
+ * Transform a single source file with a loader handle.
+ *
+ * Each call parses into its own `SourceMap` and records no state, so results
+ * do not depend on which calls ran before or alongside it.
+ *
+ * # Arguments
+ * * `handle` - Handle returned by `createLoaderArtifact` or `loadLoaderArtifact`
+ * * `source_code` - The source code to transform
+ * * `source_path` - The file path of the source
+ * * `context_json` - Optional JSON-serialized TransformContext (`ssr`, `environment`) from the bundler
+ *
+ * # Returns
+ * JSON-serialized TransformResult
+ */
+export declare function loaderTransform(handle: ExternalObject<LoaderArtifact>, sourceCode: string, sourcePath: string, contextJson?: string | undefined | null): string
+/**
+ * Transform a single source file with a loader handle on the libuv thread pool.
+ *
+ * Concurrent calls run on separate threads and share the handle's artifact.
+ *
+ * # Arguments
+ * * `handle` - Handle returned by `createLoaderArtifact` or `loadLoaderArtifact`
+ * * `source_code` - The source code to transform
+ * * `source_path` - The file path of the source
+ * * `context_json` - Optional JSON-serialized TransformContext (`ssr`, `environment`) from the bundler
+ *
+ * # Returns
+ * Promise of a JSON-serialized TransformResult
+ */
+export declare function loaderTransformAsync(handle: ExternalObject<LoaderArtifact>, sourceCode: string, sourcePath: string, contextJson?: string | undefined | null): Promise<string>
+/**
+ * Explain an error code reported in `PluginError.code`.
+ *
+ * # Arguments
+ * * `code` - Error code (e.g., `SODA_GQL_ANALYSIS_ARTIFACT_NOT_FOUND`)
+ *
+ * # Returns
+ * JSON-serialized ErrorExplanation with description, likely causes, and suggested fixes,
+ * or null for unknown codes
+ */
+export declare function explainError(code: string): string | null
+/**
+ * Validate a transformer config without creating a transformer.
+ *
+ * Checks value types, unknown keys, and inconsistent option combinations, so
+ * plugins can report misconfiguration at startup.
+ *
+ * # Arguments
+ * * `config_json` - JSON-serialized TransformConfig
+ *
+ * # Returns
+ * JSON-serialized array of ConfigIssue (`severity`, `path`, `message`), empty when the config is valid
+ */
+export declare function validateConfig(configJson: string): string
+/**
+ * Report the optional features, syntaxes, and config options of this build.
+ *
+ * Lets the JS wrapper check what the loaded binary supports before using it,
+ * instead of failing on native modules built without a feature or from an
+ * older version.
+ *
+ * # Returns
+ * JSON-serialized Capabilities (`version`, `features`, `syntaxes`, `configOptions`)
+ */
+export declare function getCapabilities(): string
+/**
+ * Flatten a sectioned "index map" source map into a regular source map.
+ *
+ * Lets plugins chain maps of concatenated or pre-bundled inputs with tools
+ * that only accept regular maps. Regular maps are returned unchanged.
+ *
+ * # Arguments
+ * * `source_map_json` - JSON-serialized source map
+ *
+ * # Returns
+ * JSON-serialized regular source map
+ */
+export declare function flattenSourceMap(sourceMapJson: string): string
+/**
+ * Wrap a regular source map into an index map with a single section.
+ *
+ * # Arguments
+ * * `source_map_json` - JSON-serialized source map
+ *
+ * # Returns
+ * JSON-serialized index map; index maps are returned unchanged
+ */
+export declare function toIndexSourceMap(sourceMapJson: string): string
 /**
  * Stateful transformer that caches artifact and config for multiple file transformations.
  *
- * The artifact is parsed once in the constructor and reused for all subsequent
- * transform calls, avoiding repeated JSON parsing overhead.
+ * The artifact is indexed once in the constructor and reused for all subsequent
+ * transform calls. Elements are deserialized lazily, the first time a file
+ * references them.
  */
 export declare class SwcTransformer {
   /**
@@ -28,15 +152,297 @@ export declare class SwcTransformer {
    * * `config_json` - JSON-serialized TransformConfig
    */
   constructor(artifactJson: string, configJson: string)
+  /**
+   * Create a transformer backed by a memory-mapped artifact file.
+   *
+   * Only the byte range of each element is indexed up front; elements are
+   * deserialized on first use. This keeps worker processes from each holding
+   * a fully parsed copy of very large artifacts.
+   *
+   * The file must be replaced by renaming a new file into place, as the
+   * builder does. Rewriting or truncating it in place while it is mapped can
+   * crash the process (SIGBUS); rewrites that are detected in time make
+   * elements not loaded yet fail to resolve until the artifact is reloaded.
+   *
+   * # Arguments
+   * * `artifact_path` - Path to the JSON artifact file written by the builder
+   * * `config_json` - JSON-serialized TransformConfig
+   */
+  static fromArtifactFile(artifactPath: string, configJson: string): SwcTransformer
+  /**
+   * Create a transformer that resolves canonical IDs through an ordered list of artifacts.
+   *
+   * Earlier artifacts take precedence (e.g., app-local overrides, then a shared base).
+   * Each resolved call reports which layer matched as an analysis diagnostic.
+   *
+   * # Arguments
+   * * `artifact_jsons` - JSON-serialized BuilderArtifacts, highest precedence first
+   * * `config_json` - JSON-serialized TransformConfig
+   */
+  static fromArtifactChain(artifactJsons: Array<string>, configJson: string): SwcTransformer
+  /**
+   * Create a transformer that resolves canonical IDs through an ordered list of
+   * memory-mapped artifact files.
+   *
+   * # Arguments
+   * * `artifact_paths` - Paths to JSON artifact files, highest precedence first
+   * * `config_json` - JSON-serialized TransformConfig
+   */
+  static fromArtifactFiles(artifactPaths: Array<string>, configJson: string): SwcTransformer
+  /**
+   * Watch the artifact file and reload it when the builder rewrites it.
+   *
+   * Only available for transformers created with `fromArtifactFile`. Calls that
+   * are already running keep using the previous artifact. After each reload
+   * attempt the callback receives a JSON-serialized ArtifactWatchEvent; if the
+   * new artifact fails to load, the previous one stays active.
+   *
+   * # Arguments
+   * * `callback` - Called with `{ "type": "reloaded", "artifactHash" }` or `{ "type": "error", "message" }`
+   */
+  watchArtifact(callback: (...args: any[]) => any): void
+  /** Stop watching the artifact file. */
+  unwatchArtifact(): void
+  /**
+   * Watch source files and re-transform them when they change.
+   *
+   * Files matching `paths` are read and transformed on a background thread
+   * after they are created or modified, using the current artifact (including
+   * reloads by `watchArtifact`) and config. The callback receives a
+   * JSON-serialized SourceWatchEvent per file. Diagnostics and artifact usage
+   * of watched files are not recorded. Replaces any previous source watcher.
+   *
+   * # Arguments
+   * * `paths` - Globs of the source files to watch; relative globs are resolved against the working directory
+   * * `callback` - Called with `{ "type": "transformed", "sourcePath", "result" }` or `{ "type": "error", "sourcePath", "message" }`
+   */
+  watch(paths: Array<string>, callback: (...args: any[]) => any): void
+  /** Stop watching source files. */
+  unwatch(): void
+  /**
+   * Forward log output to a host logger instead of stderr.
+   *
+   * Messages still follow `logLevel`. They are delivered asynchronously on
+   * the event loop, after the call that logged them returns.
+   *
+   * # Arguments
+   * * `callback` - Called with the logger method (`debug`, `info`, `warn`, or `error`) and the message
+   */
+  setLogger(callback: (method: string, message: string) => void): void
+  /**
+   * Serve analyze-only diagnostics to an editor extension.
+   *
+   * Keeps the loaded artifact (including reloads from `watchArtifact`) and
+   * answers JSON-RPC requests with LSP framing until a `shutdown` request,
+   * an `exit` notification, or end of input. Requires the native module to be
+   * built with the `diagnostics-server` feature.
+   *
+   * # Arguments
+   * * `socket_path` - Unix domain socket to listen on; stdin/stdout are used when omitted
+   *
+   * # Returns
+   * Promise that resolves when the server stops
+   */
+  startDiagnosticsServer(socketPath?: string | undefined | null): Promise<void>
+  /**
+   * Get the content hash (XXH64, hex) of the loaded artifact.
+   *
+   * Distributed builds can compare this against the hash recorded when the
+   * artifact was generated to detect stale or mismatched artifacts.
+   */
+  artifactHash(): string | null
+  /**
+   * Compute a cache key for transforming a file with this transformer.
+   *
+   * The key changes whenever the source, path, config, `options`, or the
+   * loaded artifact (including after a reload) changes, so build tools that
+   * cache transform output (e.g., Jest) invalidate correctly.
+   *
+   * # Arguments
+   * * `source_code` - The source code to transform
+   * * `source_path` - The file path of the source
+   * * `options` - Caller-specific options that also affect the output (e.g., serialized Jest config)
+   */
+  getCacheKey(sourceCode: string, sourcePath: string, options?: string | undefined | null): string
+  /**
+   * Compute a deterministic cache key for a file from its source hash.
+   *
+   * Combines the crate version, config, the file's canonical path, the
+   * caller-computed source hash, and the content hashes of the artifact
+   * entries defined in that file. Unlike `getCacheKey`, changes to other
+   * files' entries leave the key unchanged, so shared build caches (e.g., Nx,
+   * Turborepo, persistent webpack caches) can reuse outputs across machines.
+   * With `taggedTemplateTags` or `graphqlTagInterop`, every operation entry is
+   * covered too, since templates resolve operations defined in any file.
+   *
+   * # Arguments
+   * * `source_path` - The file path of the source, as used in canonical IDs
+   * * `source_hash` - Hash of the source code, computed by the caller
+   */
+  computeCacheKey(sourcePath: string, sourceHash: string): string
+  /**
+   * Get counts of the errors reported by all transform calls so far.
+   *
+   * Lets a build print one consolidated report at the end. Counts include
+   * collapsed duplicate occurrences.
+   *
+   * # Returns
+   * JSON-serialized DiagnosticsSummary (`total`, `bySeverity`, `byCode`, `byFile`)
+   */
+  getDiagnosticsSummary(): string
+  /** Reset the counts returned by `getDiagnosticsSummary`. */
+  clearDiagnostics(): void
+  /**
+   * List artifact entries never referenced by any file transformed so far.
+   *
+   * Entries that no file resolved to usually mean the artifact is stale or
+   * the builder and transformer disagree on AST paths. Checked against the
+   * currently loaded artifact, so entries removed on reload are not listed.
+   *
+   * # Returns
+   * Canonical IDs of the unused entries, sorted
+   */
+  getUnusedEntries(): Array<string>
+  /**
+   * Get statistics for every element in the artifact.
+   *
+   * Loads all elements and reports per-operation complexity metrics
+   * (depth, field count, fragment count), for enforcing budgets in CI.
+   *
+   * Operations using `@defer` or `@stream` are flagged `incremental`, so
+   * gateways can be checked for incremental delivery support at build time.
+   *
+   * # Returns
+   * JSON-serialized ArtifactStats
+   */
+  getStats(): string
+  /**
+   * Analyze a single source file without transforming it.
+   *
+   * # Arguments
+   * * `source_code` - The source code to analyze
+   * * `source_path` - The file path of the source
+   *
+   * # Returns
+   * JSON-serialized AnalyzeResult with metrics for the operations the file references
+   */
+  analyze(sourceCode: string, sourcePath: string): string
+  /**
+   * Explain how the gql calls of a source file resolve against the artifact.
+   *
+   * Lists every detected gql call with its span, AST path, and canonical ID,
+   * and whether an artifact entry matched. Unmatched calls list the nearest
+   * artifact keys, to spot file path or AST path drift.
+   *
+   * # Arguments
+   * * `source_code` - The source code to explain
+   * * `source_path` - The file path of the source
+   *
+   * # Returns
+   * JSON-serialized ExplainReport
+   */
+  explain(sourceCode: string, sourcePath: string): string
+  /**
+   * Transform a single source file and compare the output with the TypeScript transformer's.
+   *
+   * Both outputs are compared as normalized ASTs, so formatting and comments
+   * are ignored. Diagnostics and artifact usage are not recorded.
+   *
+   * # Arguments
+   * * `source_code` - The source code to transform
+   * * `source_path` - The file path of the source
+   * * `expected_output` - Output code of the TypeScript transformer for the same file
+   *
+   * # Returns
+   * JSON-serialized ParityReport listing the differing nodes
+   */
+  compareWithExpected(sourceCode: string, sourcePath: string, expectedOutput: string): string
+  /**
+   * Transform a single source file and check the output against a golden output.
+   *
+   * Diagnostics and artifact usage are not recorded.
+   *
+   * # Arguments
+   * * `source_code` - The source code to transform
+   * * `source_path` - The file path of the source
+   * * `expected_code` - The expected output code
+   *
+   * # Returns
+   * JSON-serialized VerifyResult, with a unified diff on mismatch
+   */
+  verify(sourceCode: string, sourcePath: string, expectedCode: string): string
   /**
    * Transform a single source file.
    *
    * # Arguments
    * * `source_code` - The source code to transform
    * * `source_path` - The file path of the source
+   * * `context_json` - Optional JSON-serialized TransformContext (`ssr`, `environment`) from the bundler
+   *
+   * # Returns
+   * JSON-serialized TransformResult
+   */
+  transform(sourceCode: string, sourcePath: string, contextJson?: string | undefined | null): string
+  /**
+   * Transform a single source file to both ES module and CommonJS output.
+   *
+   * The file is parsed once; the second format reuses the parsed module.
+   * `isCjs` in the config is ignored, and the errors of the file are
+   * recorded once.
+   *
+   * # Arguments
+   * * `source_code` - The source code to transform
+   * * `source_path` - The file path of the source
+   * * `context_json` - Optional JSON-serialized TransformContext (`ssr`, `environment`) from the bundler
+   *
+   * # Returns
+   * JSON-serialized DualTransformResult (`esm`, `cjs`)
+   */
+  transformDual(sourceCode: string, sourcePath: string, contextJson?: string | undefined | null): string
+  /**
+   * Re-transform a file after applying text edits to the source it was last transformed from.
+   *
+   * Only the top-level statements around the edits are re-parsed; analysis
+   * and emit run on the whole file. Offsets are UTF-16 offsets into the
+   * previous source, as in editor change events. Edits always apply to the
+   * last source that parsed, so after a parse error the file has to be
+   * transformed with `transform` again.
+   *
+   * # Arguments
+   * * `source_path` - The file path of the source, previously passed to `transform`
+   * * `edits_json` - JSON-serialized array of `{ start, end, text }` edits
+   * * `context_json` - Optional JSON-serialized TransformContext (`ssr`, `environment`) from the bundler
    *
    * # Returns
    * JSON-serialized TransformResult
    */
-  transform(sourceCode: string, sourcePath: string): string
+  transformIncremental(sourcePath: string, editsJson: string, contextJson?: string | undefined | null): string
+  /**
+   * Transform a single source file for a Bun plugin `onLoad` callback.
+   *
+   * Paths in `file://` or namespaced form are normalized before canonical IDs
+   * are resolved, so they match artifact entries recorded by the builder.
+   *
+   * # Arguments
+   * * `source_code` - The source code to transform
+   * * `source_path` - The path Bun passed to `onLoad`
+   *
+   * # Returns
+   * JSON-serialized `{ contents, loader }`
+   */
+  transformForBun(sourceCode: string, sourcePath: string): string
+  /**
+   * Transform a single source file for an esbuild plugin `onLoad` callback.
+   *
+   * Errors and warnings are returned as esbuild messages located in the
+   * source, so the plugin can return the result as is.
+   *
+   * # Arguments
+   * * `args_json` - JSON-serialized `onLoad` args (`path`, `namespace`), optionally with a `loader` override
+   * * `source_code` - The source code, read from `path` when omitted (only in the `file` namespace)
+   *
+   * # Returns
+   * JSON-serialized `{ contents, loader, errors, warnings }`
+   */
+  transformForEsbuild(argsJson: string, sourceCode?: string | undefined | null): string
 }
@@ -310,7 +310,16 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { transform, SwcTransformer } = nativeBinding
+const { transform, createLoaderArtifact, loadLoaderArtifact, loaderTransform, loaderTransformAsync, explainError, validateConfig, getCapabilities, flattenSourceMap, toIndexSourceMap, SwcTransformer } = nativeBinding
 
 module.exports.transform = transform
+module.exports.createLoaderArtifact = createLoaderArtifact
+module.exports.loadLoaderArtifact = loadLoaderArtifact
+module.exports.loaderTransform = loaderTransform
+module.exports.loaderTransformAsync = loaderTransformAsync
+module.exports.explainError = explainError
+module.exports.validateConfig = validateConfig
+module.exports.getCapabilities = getCapabilities
+module.exports.flattenSourceMap = flattenSourceMap
+module.exports.toIndexSourceMap = toIndexSourceMap
 module.exports.SwcTransformer = SwcTransformer
@@ -2,17 +2,39 @@
 //!
 //! This module is responsible for:
 //! - Detecting `gql.default()` call patterns
+//! - Detecting graphql-tag style tagged templates (`` gql`query User { ... }` ``)
 //! - Extracting the inner builder call
 //! - Mapping calls to their corresponding artifacts
 
-use std::collections::HashMap;
-use swc_core::common::Span;
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use rustc_hash::FxHashSet;
+use serde_json::Value;
+use swc_core::atoms::Atom;
+use swc_core::common::util::take::Take;
+use swc_core::common::{SourceMap, SourceMapper, Span};
 use swc_core::ecma::ast::*;
 use swc_core::ecma::visit::{Visit, VisitWith};
 
-use crate::types::{BuilderArtifact, BuilderArtifactElement, CanonicalId, PluginError};
+use crate::artifact::dedup::shared_definitions;
+use crate::artifact::ArtifactSource;
+use crate::graphql::prune::prune_document;
+use crate::graphql::schema::{SchemaIndex, SchemaIssue};
+use crate::types::{
+    BuilderArtifactElement, CanonicalId, ErrorSpan, FieldPolicy, PathMapping, PluginError, Severity,
+    TrustedDocuments,
+};
 
-use super::metadata::MetadataMap;
+use super::captures::CaptureMap;
+use super::extract::extract_element;
+use super::imports::find_graphql_system_import;
+use super::logger::Logger;
+use super::paths::normalize_path;
+use super::runtime::hashed_operation_name;
+use super::metadata::{MetadataMap, SpanKey, SpanMap};
+use super::suppression::Suppressions;
 
 /// Information about a detected GQL call that needs to be transformed.
 #[allow(dead_code)]
@@ -29,46 +51,293 @@ pub struct GqlCallInfo {
 }
 
 /// Replacement information for a GQL call.
+///
+/// Borrows the element from the shared artifact, so large prebuilds are not
+/// copied per call. Only elements pruned by the field policy are owned.
 #[derive(Debug)]
-pub struct GqlReplacement {
-    #[allow(dead_code)]
+pub struct GqlReplacement<'a> {
     pub canonical_id: CanonicalId,
-    pub artifact: BuilderArtifactElement,
+    pub artifact: Cow<'a, BuilderArtifactElement>,
     pub builder_args: Vec<ExprOrSpread>,
+    pub options: GqlCallOptions,
+    /// Source text of the definition, kept for debug payloads.
+    pub source_text: Option<String>,
+}
+
+/// How a gql call, or a member of a gql call returning several builder calls, was resolved.
+#[derive(Debug, Clone)]
+pub struct CallResolution {
+    /// Span of the gql call, or of the member's builder call.
+    pub span: Span,
+    /// AST path of the definition, if its metadata was found.
+    pub ast_path: Option<String>,
+    /// Canonical ID looked up in the artifact, if one was resolved.
+    pub canonical_id: Option<CanonicalId>,
+    /// Whether the artifact had a valid element for the canonical ID.
+    pub matched: bool,
+}
+
+/// Options passed as the second argument of a gql call.
+///
+/// e.g., `gql.default(({ fragment }) => ..., { name: "UserModel", schema: "admin" })`.
+/// Only string literal values are read; other properties are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct GqlCallOptions {
+    /// Explicit name, tried as the AST path when the call's own path has no artifact entry.
+    /// Also passed to fragment registrations as their `key`.
+    pub name: Option<String>,
+    /// Schema label of the definition, passed to registrations as `schemaLabel`.
+    pub schema: Option<String>,
+}
+
+impl GqlCallOptions {
+    /// Read the options object of a gql call, if it has one.
+    fn from_call(call: &CallExpr) -> Self {
+        let Some(Expr::Object(object)) = call.args.get(1).map(|arg| &*arg.expr) else {
+            return Self::default();
+        };
+
+        let mut options = Self::default();
+        for prop in &object.props {
+            let PropOrSpread::Prop(prop) = prop else {
+                continue;
+            };
+            let Prop::KeyValue(KeyValueProp { key, value }) = &**prop else {
+                continue;
+            };
+            let (Some(key), Some(value)) = (prop_name(key), string_value(value)) else {
+                continue;
+            };
+            match key {
+                "name" => options.name = Some(value),
+                "schema" => options.schema = Some(value),
+                _ => {}
+            }
+        }
+        options
+    }
 }
 
 /// Finds GQL calls in the AST and prepares them for transformation.
 pub struct GqlCallFinder<'a> {
-    artifact: &'a BuilderArtifact,
+    artifact: &'a dyn ArtifactSource,
     metadata: &'a MetadataMap,
     source_path: &'a str,
+    /// Schema to validate prebuilds against, if configured
+    schema: Option<&'a SchemaIndex>,
+    /// Prefix rewrites applied to the source path when resolving canonical IDs
+    path_mappings: &'a [PathMapping],
+    /// Tag names of templates resolved by operation name
+    template_tags: &'a [String],
+    /// Fields removed from operation prebuilds, if configured
+    field_policy: Option<&'a FieldPolicy>,
+    /// Document IDs of the trusted-documents manifest, if one is checked
+    trusted_manifest: Option<&'a BTreeSet<String>>,
+    /// Whether to derive prebuilds the artifact is missing from builder calls
+    extract_prebuilds: bool,
+    /// Whether to keep the source text of definitions in replacements
+    keep_source_text: bool,
+    /// Whether to stop analyzing calls once an error was reported
+    stop_on_error: bool,
+    /// Source map of the parsed file, for locating errors
+    source_map: Option<&'a SourceMap>,
+    /// Suppression comments in the file
+    suppressions: Suppressions,
+    /// Runtime bindings captured by builder callbacks, by gql call span
+    captures: CaptureMap,
+    logger: Logger,
     /// Map from call span to replacement info
-    replacements: HashMap<Span, GqlReplacement>,
+    replacements: SpanMap<GqlReplacement<'a>>,
+    /// Spans of gql calls returning an object or array of builder calls, replaced by it
+    collection_definitions: FxHashSet<SpanKey>,
     has_transforms: bool,
+    /// Resolution of every gql call, in visit order
+    resolutions: Vec<CallResolution>,
     /// Errors encountered during analysis
     errors: Vec<PluginError>,
 }
 
 impl<'a> GqlCallFinder<'a> {
-    pub fn new(artifact: &'a BuilderArtifact, metadata: &'a MetadataMap, source_path: &'a str) -> Self {
+    pub fn new(
+        artifact: &'a dyn ArtifactSource,
+        metadata: &'a MetadataMap,
+        source_path: &'a str,
+        schema: Option<&'a SchemaIndex>,
+    ) -> Self {
         Self {
             artifact,
             metadata,
             source_path,
-            replacements: HashMap::new(),
+            schema,
+            path_mappings: &[],
+            template_tags: &[],
+            field_policy: None,
+            trusted_manifest: None,
+            extract_prebuilds: false,
+            keep_source_text: false,
+            stop_on_error: false,
+            source_map: None,
+            suppressions: Suppressions::default(),
+            captures: CaptureMap::default(),
+            logger: Logger::default(),
+            replacements: SpanMap::default(),
+            collection_definitions: FxHashSet::default(),
             has_transforms: false,
+            resolutions: Vec::new(),
             errors: Vec::new(),
         }
     }
 
+    /// Rewrite source path prefixes before resolving canonical IDs.
+    pub fn with_path_mappings(mut self, path_mappings: &'a [PathMapping]) -> Self {
+        self.path_mappings = path_mappings;
+        self
+    }
+
+    /// Resolve templates tagged with one of `template_tags` by operation name.
+    pub fn with_template_tags(mut self, template_tags: &'a [String]) -> Self {
+        self.template_tags = template_tags;
+        self
+    }
+
+    /// Remove selections `field_policy` does not permit from operation prebuilds.
+    ///
+    /// Pruning resolves nested selections through the schema, so it only
+    /// applies when one was given.
+    pub fn with_field_policy(mut self, field_policy: &'a FieldPolicy) -> Self {
+        self.field_policy = Some(field_policy).filter(|policy| !policy.is_empty());
+        self
+    }
+
+    /// Report operations whose document ID the trusted-documents manifest does not list.
+    pub fn with_trusted_documents(mut self, trusted_documents: Option<&'a TrustedDocuments>) -> Self {
+        self.trusted_manifest = trusted_documents.and_then(|trusted| trusted.manifest_ids.as_deref());
+        self
+    }
+
+    /// Derive the prebuild of definitions the artifact has no entry for from
+    /// their builder call (experimental, see `super::extract`).
+    pub fn with_extract_prebuilds(mut self, extract_prebuilds: bool) -> Self {
+        self.extract_prebuilds = extract_prebuilds;
+        self
+    }
+
+    /// Keep the source text of each definition in its replacement, for debug payloads.
+    ///
+    /// Requires a source map (see [`Self::with_source_map`]).
+    pub fn with_source_text(mut self, keep_source_text: bool) -> Self {
+        self.keep_source_text = keep_source_text;
+        self
+    }
+
+    /// Stop analyzing further calls once an error-severity diagnostic was reported,
+    /// for callers that fail on the first error.
+    pub fn with_stop_on_error(mut self, stop_on_error: bool) -> Self {
+        self.stop_on_error = stop_on_error;
+        self
+    }
+
+    /// Report error locations resolved through `source_map`.
+    pub fn with_source_map(mut self, source_map: &'a SourceMap) -> Self {
+        self.source_map = Some(source_map);
+        self
+    }
+
+    /// Drop errors covered by suppression comments in the file.
+    pub fn with_suppressions(mut self, suppressions: Suppressions) -> Self {
+        self.suppressions = suppressions;
+        self
+    }
+
+    /// Report gql definitions capturing runtime bindings instead of transforming them.
+    pub fn with_captures(mut self, captures: CaptureMap) -> Self {
+        self.captures = captures;
+        self
+    }
+
+    /// Log reported errors and resolution details through `logger`.
+    pub fn with_logger(mut self, logger: Logger) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Get the logger errors are reported through.
+    pub fn logger(&self) -> &Logger {
+        &self.logger
+    }
+
+    /// Check whether a suppression comment covers `error`.
+    pub fn is_suppressed(&self, error: &PluginError) -> bool {
+        self.suppressions.is_suppressed(error)
+    }
+
     /// Check if any transformations were found.
     pub fn has_transformations(&self) -> bool {
         self.has_transforms
     }
 
-    /// Get the replacement for a call expression if it should be transformed.
-    pub fn get_replacement(&self, call: &CallExpr) -> Option<&GqlReplacement> {
-        self.replacements.get(&call.span)
+    /// Get the replacement for the call or tagged template at `span`, if it should be transformed.
+    pub fn get_replacement(&self, span: Span) -> Option<&GqlReplacement<'a>> {
+        self.replacements.get(&SpanKey::from(span))
+    }
+
+    /// Check if the gql call at `span` returns an object or array whose members were all resolved.
+    ///
+    /// The call is replaced by the object or array once its members are transformed.
+    pub fn is_collection_definition(&self, span: Span) -> bool {
+        self.collection_definitions.contains(&SpanKey::from(span))
+    }
+
+    /// Iterate over all detected replacements.
+    pub fn replacements(&self) -> impl Iterator<Item = &GqlReplacement<'a>> {
+        self.replacements.values()
+    }
+
+    /// Iterate over all detected replacements with the spans of the expressions they replace.
+    pub fn replacement_spans(&self) -> impl Iterator<Item = (Span, &GqlReplacement<'a>)> {
+        self.replacements.iter().map(|(key, replacement)| (key.span(), replacement))
+    }
+
+    /// Canonical IDs of all detected replacements, sorted.
+    pub fn matched_ids(&self) -> Vec<CanonicalId> {
+        let mut ids: Vec<CanonicalId> = self
+            .replacements
+            .values()
+            .map(|replacement| replacement.canonical_id.clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Find document definitions shared by more than one replacement in this file.
+    pub fn shared_definitions(&self) -> Vec<Arc<Value>> {
+        let mut replacements: Vec<&GqlReplacement<'a>> = self.replacements.values().collect();
+        replacements.sort_by(|a, b| a.canonical_id.cmp(&b.canonical_id));
+        shared_definitions(replacements.into_iter().map(|replacement| &*replacement.artifact))
+    }
+
+    /// Resolution of every gql call found, in visit order.
+    pub fn resolutions(&self) -> &[CallResolution] {
+        &self.resolutions
+    }
+
+    /// Report the graphql-system import of a file with no recognized gql definitions.
+    ///
+    /// Nothing is reported when a definition was found or an error already
+    /// points at one.
+    pub fn report_orphan_import(&mut self, module: &Module, graphql_system_aliases: &[String]) {
+        if self.has_transforms || !self.resolutions.is_empty() || !self.errors.is_empty() {
+            return;
+        }
+        if let Some((span, specifier)) = find_graphql_system_import(module, graphql_system_aliases) {
+            let error = PluginError::orphan_import(self.source_path, &specifier);
+            self.report(error, span);
+        }
+    }
+
+    /// Get the first error-severity diagnostic reported so far.
+    pub fn first_error(&self) -> Option<&PluginError> {
+        self.errors.iter().find(|error| error.severity == Severity::Error)
     }
 
     /// Take collected errors.
@@ -79,62 +348,471 @@ impl<'a> GqlCallFinder<'a> {
     /// Process a potential GQL call expression.
     fn process_call(&mut self, call: &CallExpr) {
         // Check if this is a gql.default() or gql.* call
-        if let Some(builder_call) = find_gql_builder_call(call) {
-            // Get metadata for this call
-            if let Some(meta) = self.metadata.get(&call.span) {
-                let canonical_id = resolve_canonical_id(self.source_path, &meta.ast_path);
-
-                // Look up the artifact
-                if let Some(artifact) = self.artifact.get(&canonical_id) {
-                    self.replacements.insert(
-                        call.span,
-                        GqlReplacement {
-                            canonical_id,
-                            artifact: artifact.clone(),
-                            builder_args: builder_call.args.clone(),
-                        },
-                    );
-                    self.has_transforms = true;
+        let Some(body) = find_gql_builder_body(call) else {
+            return;
+        };
+
+        let metadata = self.metadata;
+        let meta = metadata.get(&SpanKey::from(call.span));
+
+        // The artifact has no entry for a callback depending on runtime values
+        if let Some(captures) = self.captures.remove(&SpanKey::from(call.span)) {
+            for capture in captures {
+                let error = PluginError::non_static_builder(self.source_path, &capture.name);
+                self.report(error, capture.span);
+            }
+            self.record_resolution(call.span, meta.map(|meta| meta.ast_path.clone()), None, false);
+            return;
+        }
+
+        // Get metadata for this call
+        let Some(meta) = meta else {
+            let error = PluginError::metadata_not_found(self.source_path);
+            self.report(error, call.span);
+            self.record_resolution(call.span, None, None, false);
+            return;
+        };
+        let options = GqlCallOptions::from_call(call);
+
+        match body {
+            BuilderBody::Call(builder_call) => {
+                let canonical_id = self.resolve_id(&meta.ast_path, options.name.as_deref());
+                let matched = self.insert_replacement(canonical_id.clone(), call.span, builder_call, options);
+                self.record_resolution(call.span, Some(meta.ast_path.clone()), Some(canonical_id), matched);
+            }
+            BuilderBody::Collection(members) => {
+                self.process_collection_definition(call.span, &meta.ast_path, members, &options)
+            }
+        }
+    }
+
+    /// Process a gql call returning an object or array of builder calls.
+    ///
+    /// Each member resolves through its own sub-path (`{astPath}.{key}` for
+    /// properties, `{astPath}.{index}` for elements). The definition is only
+    /// transformed when every member resolves, since a partially replaced
+    /// collection would still need the builder.
+    fn process_collection_definition(
+        &mut self,
+        span: Span,
+        ast_path: &str,
+        members: Vec<(Atom, &CallExpr)>,
+        options: &GqlCallOptions,
+    ) {
+        let members: Vec<(String, CanonicalId, GqlCallOptions, &CallExpr)> = members
+            .into_iter()
+            .map(|(key, builder_call)| {
+                let options = GqlCallOptions {
+                    name: options.name.as_ref().map(|name| format!("{}.{}", name, key)),
+                    schema: options.schema.clone(),
+                };
+                let member_path = format!("{}.{}", ast_path, key);
+                let canonical_id = self.resolve_id(&member_path, options.name.as_deref());
+                (member_path, canonical_id, options, builder_call)
+            })
+            .collect();
+
+        if !members
+            .iter()
+            .all(|(_, canonical_id, _, builder_call)| self.has_element(canonical_id, builder_call))
+        {
+            for (member_path, canonical_id, _, builder_call) in members {
+                let matched = self.has_element(&canonical_id, builder_call);
+                if !matched {
+                    self.report_unresolved(&canonical_id, builder_call.span);
+                }
+                self.record_resolution(builder_call.span, Some(member_path), Some(canonical_id), matched);
+            }
+            return;
+        }
+
+        let mut resolved = true;
+        for (member_path, canonical_id, options, builder_call) in members {
+            let matched = self.insert_replacement(canonical_id.clone(), builder_call.span, builder_call, options);
+            self.record_resolution(builder_call.span, Some(member_path), Some(canonical_id), matched);
+            resolved &= matched;
+        }
+        if resolved {
+            self.collection_definitions.insert(SpanKey::from(span));
+        }
+    }
+
+    /// Resolve the canonical ID of the definition at `ast_path`, falling back to
+    /// the explicit `name` when the AST path has no entry.
+    fn resolve_id(&self, ast_path: &str, name: Option<&str>) -> CanonicalId {
+        let canonical_id = resolve_canonical_id(self.source_path, ast_path, self.path_mappings);
+        if let Some(name) = name {
+            let named_id = resolve_canonical_id(self.source_path, name, self.path_mappings);
+            if !self.artifact.contains(&canonical_id) && self.artifact.contains(&named_id) {
+                return named_id;
+            }
+        }
+        canonical_id
+    }
+
+    /// Check if `canonical_id` has an artifact element or, when extracting, a derivable prebuild.
+    fn has_element(&self, canonical_id: &CanonicalId, builder_call: &CallExpr) -> bool {
+        self.artifact.contains(canonical_id)
+            || (self.extract_prebuilds && extract_element(canonical_id, self.source_path, builder_call).is_ok())
+    }
+
+    /// Get the artifact element of `canonical_id`, deriving it from `builder_call`
+    /// when the artifact has none and extraction is on.
+    fn lookup_element(&self, canonical_id: &CanonicalId, builder_call: &CallExpr) -> Option<Cow<'a, BuilderArtifactElement>> {
+        if let Some(artifact) = self.artifact.get(canonical_id) {
+            return Some(Cow::Borrowed(artifact));
+        }
+        if !self.extract_prebuilds {
+            return None;
+        }
+        match extract_element(canonical_id, self.source_path, builder_call) {
+            Ok(element) => {
+                self.logger
+                    .debug(format_args!("Extracted prebuild of '{}' from its builder call", canonical_id));
+                Some(Cow::Owned(element))
+            }
+            Err(reason) => {
+                self.logger
+                    .debug(format_args!("Cannot extract prebuild of '{}': {}", canonical_id, reason));
+                None
+            }
+        }
+    }
+
+    /// Look up `canonical_id` and record the replacement of the expression at `span`.
+    ///
+    /// Returns whether the artifact had (or extraction derived) a valid element for it.
+    fn insert_replacement(
+        &mut self,
+        canonical_id: CanonicalId,
+        span: Span,
+        builder_call: &CallExpr,
+        options: GqlCallOptions,
+    ) -> bool {
+        let Some(artifact) = self.lookup_element(&canonical_id, builder_call) else {
+            self.report_unresolved(&canonical_id, span);
+            return false;
+        };
+        if let Some((builder_type, arg_name, position)) = missing_builder_arg(&artifact, builder_call) {
+            let error =
+                PluginError::missing_positional_arg(self.source_path, &canonical_id, builder_type, arg_name, position);
+            self.report(error, span);
+            return false;
+        }
+
+        if let Some((layer_index, layer_label)) = self.artifact.resolved_layer(&canonical_id) {
+            let error = PluginError::artifact_layer_matched(self.source_path, &canonical_id, layer_index, layer_label);
+            self.report(error, span);
+        }
+
+        self.logger
+            .debug(format_args!("Resolved '{}' in '{}'", canonical_id, self.source_path));
+
+        if let Some(schema) = self.schema {
+            self.validate_against_schema(schema, &canonical_id, &artifact, span);
+        }
+        let artifact = self.apply_field_policy(&canonical_id, artifact, span);
+        if let Some(manifest_ids) = self.trusted_manifest {
+            self.check_trusted(manifest_ids, &canonical_id, &artifact, span);
+        }
+
+        self.replacements.insert(
+            SpanKey::from(span),
+            GqlReplacement {
+                canonical_id,
+                artifact,
+                builder_args: builder_call.args.clone(),
+                options,
+                source_text: self.source_text(span),
+            },
+        );
+        self.has_transforms = true;
+        true
+    }
+
+    /// Record how the gql call (or collection member) at `span` was resolved.
+    fn record_resolution(&mut self, span: Span, ast_path: Option<String>, canonical_id: Option<CanonicalId>, matched: bool) {
+        self.resolutions.push(CallResolution {
+            span,
+            ast_path,
+            canonical_id,
+            matched,
+        });
+    }
+
+    /// Report a canonical ID with no valid artifact element.
+    fn report_unresolved(&mut self, canonical_id: &CanonicalId, span: Span) {
+        let error = match self.artifact.element_error(canonical_id) {
+            Some(reason) => PluginError::artifact_invalid(self.source_path, canonical_id, reason),
+            None => PluginError::artifact_not_found(self.source_path, canonical_id),
+        };
+        self.report(error, span);
+    }
+
+    /// Process a potential graphql-tag style tagged template.
+    fn process_tagged_template(&mut self, tagged: &TaggedTpl) {
+        let Expr::Ident(tag) = &*tagged.tag else {
+            return;
+        };
+        if !self.template_tags.iter().any(|name| tag.sym == name.as_str()) {
+            return;
+        }
+
+        if !tagged.tpl.exprs.is_empty() {
+            let error = PluginError::template_unresolved(self.source_path, "templates with interpolations are not supported");
+            self.report(error, tagged.span);
+            return;
+        }
+
+        let document = tagged.tpl.quasis.first().map_or("", |quasi| quasi.raw.as_str());
+        let Some(operation_name) = template_operation_name(document) else {
+            let error = PluginError::template_unresolved(self.source_path, "the template has no named operation");
+            self.report(error, tagged.span);
+            return;
+        };
+
+        let canonical_id = self.artifact.operation_id(operation_name).cloned();
+        let Some((canonical_id, artifact)) =
+            canonical_id.and_then(|id| self.artifact.get(&id).map(|artifact| (id, artifact)))
+        else {
+            let reason = format!("no operation named '{}' in the artifact", operation_name);
+            let error = PluginError::template_unresolved(self.source_path, &reason);
+            self.report(error, tagged.span);
+            return;
+        };
+
+        self.logger.debug(format_args!(
+            "Resolved template '{}' to '{}' in '{}'",
+            operation_name, canonical_id, self.source_path
+        ));
+
+        if let Some(schema) = self.schema {
+            self.validate_against_schema(schema, &canonical_id, artifact, tagged.span);
+        }
+        let artifact = self.apply_field_policy(&canonical_id, Cow::Borrowed(artifact), tagged.span);
+
+        self.replacements.insert(
+            SpanKey::from(tagged.span),
+            GqlReplacement {
+                canonical_id,
+                artifact,
+                builder_args: Vec::new(),
+                options: GqlCallOptions::default(),
+                source_text: self.source_text(tagged.span),
+            },
+        );
+        self.has_transforms = true;
+    }
+
+    /// Resolve the location of `span` for error reporting, if a source map was given.
+    pub fn locate(&self, span: Span) -> Option<ErrorSpan> {
+        self.source_map.and_then(|cm| ErrorSpan::resolve(cm, span))
+    }
+
+    /// Get the source text at `span`, if kept.
+    fn source_text(&self, span: Span) -> Option<String> {
+        if !self.keep_source_text {
+            return None;
+        }
+        self.source_map?.span_to_snippet(span).ok()
+    }
+
+    /// Log and collect an error located at `span`.
+    fn report(&mut self, error: PluginError, span: Span) {
+        let error = error.with_span(self.locate(span));
+        if self.is_suppressed(&error) {
+            return;
+        }
+        self.logger.error(&error);
+        self.errors.push(error);
+    }
+
+    /// Prune an operation's prebuild by the field policy, reporting each removed selection.
+    fn apply_field_policy(
+        &mut self,
+        canonical_id: &str,
+        artifact: Cow<'a, BuilderArtifactElement>,
+        span: Span,
+    ) -> Cow<'a, BuilderArtifactElement> {
+        let (Some(policy), Some(schema)) = (self.field_policy, self.schema) else {
+            return artifact;
+        };
+        let BuilderArtifactElement::Operation { id, metadata, prebuild } = &*artifact else {
+            return artifact;
+        };
+        let Some((document, pruned)) = prune_document(policy, schema, &prebuild.document) else {
+            return artifact;
+        };
+
+        for field in pruned {
+            let error =
+                PluginError::policy_field_pruned(self.source_path, canonical_id, &field.type_name, &field.field_name);
+            self.report(error, span);
+        }
+
+        let mut prebuild = prebuild.clone();
+        prebuild.document = document;
+        Cow::Owned(BuilderArtifactElement::Operation {
+            id: id.clone(),
+            metadata: metadata.clone(),
+            prebuild,
+        })
+    }
+
+    /// Report an operation whose document ID is missing from the trusted-documents manifest.
+    ///
+    /// Checked after the field policy, since pruning changes the document and its ID.
+    fn check_trusted(
+        &mut self,
+        manifest_ids: &BTreeSet<String>,
+        canonical_id: &str,
+        artifact: &BuilderArtifactElement,
+        span: Span,
+    ) {
+        let BuilderArtifactElement::Operation { prebuild, .. } = artifact else {
+            return;
+        };
+        let Some(document_id) = hashed_operation_name(prebuild) else {
+            return;
+        };
+        if !manifest_ids.contains(&document_id) {
+            let error = PluginError::untrusted_document(self.source_path, canonical_id, &document_id);
+            self.report(error, span);
+        }
+    }
+
+    /// Report prebuild selections that no longer exist in the schema.
+    fn validate_against_schema(
+        &mut self,
+        schema: &SchemaIndex,
+        canonical_id: &str,
+        artifact: &BuilderArtifactElement,
+        span: Span,
+    ) {
+        let issues = match artifact {
+            BuilderArtifactElement::Fragment { prebuild, .. } => {
+                if schema.has_type(&prebuild.typename) {
+                    Vec::new()
                 } else {
-                    let error = PluginError::artifact_not_found(self.source_path, &canonical_id);
-                    eprintln!("[swc] {}", error.format());
-                    self.errors.push(error);
+                    vec![SchemaIssue::UnknownType {
+                        type_name: prebuild.typename.clone(),
+                    }]
                 }
-            } else {
-                let error = PluginError::metadata_not_found(self.source_path);
-                eprintln!("[swc] {}", error.format());
-                self.errors.push(error);
             }
+            BuilderArtifactElement::Operation { prebuild, .. } => schema.validate_document(&prebuild.document),
+        };
+
+        for issue in issues {
+            let error = match issue {
+                SchemaIssue::UnknownType { type_name } => PluginError::schema_unknown_type(
+                    self.source_path,
+                    canonical_id,
+                    artifact.element_type(),
+                    &type_name,
+                ),
+                SchemaIssue::UnknownField { type_name, field_name } => {
+                    PluginError::schema_unknown_field(self.source_path, canonical_id, &type_name, &field_name)
+                }
+            };
+            self.report(error, span);
         }
     }
 }
 
 impl Visit for GqlCallFinder<'_> {
     fn visit_call_expr(&mut self, call: &CallExpr) {
+        if self.stop_on_error && self.first_error().is_some() {
+            return;
+        }
+
         // First check this call
         self.process_call(call);
 
         // Then visit children
         call.visit_children_with(self);
     }
+
+    fn visit_tagged_tpl(&mut self, tagged: &TaggedTpl) {
+        if self.stop_on_error && self.first_error().is_some() {
+            return;
+        }
+
+        self.process_tagged_template(tagged);
+        tagged.visit_children_with(self);
+    }
+}
+
+/// Extract the name of the first operation in a GraphQL document.
+///
+/// Skips whitespace, commas and comments, then expects
+/// `query|mutation|subscription Name`. Returns `None` for anonymous operations
+/// and documents starting with a fragment.
+fn template_operation_name(document: &str) -> Option<&str> {
+    let mut rest = document;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        match rest.strip_prefix('#') {
+            Some(comment) => rest = comment.split_once('\n').map_or("", |(_, after)| after),
+            None => break,
+        }
+    }
+
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let keyword_end = rest.find(|c: char| !is_name_char(c)).unwrap_or(rest.len());
+    if !matches!(&rest[..keyword_end], "query" | "mutation" | "subscription") {
+        return None;
+    }
+
+    let rest = rest[keyword_end..].trim_start();
+    let name_end = rest.find(|c: char| !is_name_char(c)).unwrap_or(rest.len());
+    Some(&rest[..name_end]).filter(|name| !name.is_empty())
+}
+
+/// Check if a call is a gql definition (`gql.default(({ ... }) => ...)`).
+pub fn is_gql_definition(call: &CallExpr) -> bool {
+    find_gql_builder_body(call).is_some()
 }
 
-/// Find the inner builder call from a gql.default() call.
+/// What the builder callback of a gql call returns.
+enum BuilderBody<'a> {
+    /// A single builder call (e.g., `fragment.User(...)`)
+    Call(&'a CallExpr),
+    /// An object or array of builder calls, keyed by property name or index
+    /// (e.g., `({ user: fragment.User(...), post: fragment.Post(...) })`, `[query(...), query(...)]`)
+    Collection(Vec<(Atom, &'a CallExpr)>),
+}
+
+/// Find what the builder callback of a gql.default() call returns.
 ///
 /// Supports both arrow functions and function expressions:
 /// - `gql.default(({ fragment }) => fragment.User(...))`
 /// - `gql.default(function({ fragment }) { return fragment.User(...); })`
+/// - `gql.default(({ fragment }) => ({ user: fragment.User(...), post: fragment.Post(...) }))`
+/// - `gql.default(({ query }) => [query(...), query(...)])`
 ///
-/// Returns: The inner builder call expression (e.g., `fragment.User(...)`)
-fn find_gql_builder_call(call: &CallExpr) -> Option<&CallExpr> {
+/// Objects are only recognized when every property is a plain `key: call`
+/// pair, and arrays when every element is a call.
+fn find_gql_builder_body(call: &CallExpr) -> Option<BuilderBody<'_>> {
+    match builder_return_expr(call)?.unwrap_parens() {
+        Expr::Call(builder_call) => Some(BuilderBody::Call(builder_call)),
+        Expr::Object(object) => builder_object_properties(object).map(BuilderBody::Collection),
+        Expr::Array(array) => builder_array_elements(array).map(BuilderBody::Collection),
+        _ => None,
+    }
+}
+
+/// Find the expression returned by the builder callback of a gql.default() call.
+fn builder_return_expr(call: &CallExpr) -> Option<&Expr> {
     // Check if callee is gql.* pattern
     if !is_gql_member_expression(&call.callee) {
         return None;
     }
 
-    // Should have exactly one argument
-    if call.args.len() != 1 {
-        return None;
+    // Should have the builder argument, optionally followed by an options object
+    match call.args.as_slice() {
+        [_] => {}
+        [_, options] if options.spread.is_none() && matches!(&*options.expr, Expr::Object(_)) => {}
+        _ => return None,
     }
 
     // The argument should be an arrow function or function expression
@@ -144,12 +822,169 @@ fn find_gql_builder_call(call: &CallExpr) -> Option<&CallExpr> {
     }
 
     match &*arg.expr {
-        Expr::Arrow(arrow) => extract_builder_call_from_arrow(arrow),
-        Expr::Fn(fn_expr) => extract_builder_call_from_fn(fn_expr),
+        Expr::Arrow(arrow) => match &*arrow.body {
+            BlockStmtOrExpr::Expr(expr) => Some(expr),
+            BlockStmtOrExpr::BlockStmt(block) => return_expr_of_block(block),
+        },
+        Expr::Fn(fn_expr) => fn_expr.function.body.as_ref().and_then(return_expr_of_block),
         _ => None,
     }
 }
 
+/// Find the first returned call, object or array in a block statement (shared by arrow and fn).
+fn return_expr_of_block(block: &BlockStmt) -> Option<&Expr> {
+    block.stmts.iter().find_map(|stmt| match stmt {
+        Stmt::Return(ReturnStmt { arg: Some(arg), .. })
+            if matches!(arg.unwrap_parens(), Expr::Call(_) | Expr::Object(_) | Expr::Array(_)) =>
+        {
+            Some(&**arg)
+        }
+        _ => None,
+    })
+}
+
+/// Get the `key: call` properties of an object returned by a builder callback.
+fn builder_object_properties(object: &ObjectLit) -> Option<Vec<(Atom, &CallExpr)>> {
+    object
+        .props
+        .iter()
+        .map(|prop| {
+            let PropOrSpread::Prop(prop) = prop else {
+                return None;
+            };
+            let Prop::KeyValue(KeyValueProp { key, value }) = &**prop else {
+                return None;
+            };
+            let Expr::Call(builder_call) = &**value else {
+                return None;
+            };
+            Some((Atom::from(prop_name(key)?), builder_call))
+        })
+        .collect::<Option<Vec<_>>>()
+        .filter(|properties| !properties.is_empty())
+}
+
+/// Get the calls of an array returned by a builder callback, keyed by index.
+fn builder_array_elements(array: &ArrayLit) -> Option<Vec<(Atom, &CallExpr)>> {
+    array
+        .elems
+        .iter()
+        .enumerate()
+        .map(|(index, element)| match element {
+            Some(ExprOrSpread { spread: None, expr }) => match &**expr {
+                Expr::Call(builder_call) => Some((Atom::from(index.to_string()), builder_call)),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()
+        .filter(|elements| !elements.is_empty())
+}
+
+/// Take the object or array returned by the builder callback of a gql call,
+/// for replacing the call once its members have been transformed.
+pub fn take_builder_collection(call: &mut CallExpr) -> Option<Expr> {
+    let returned = match &mut *call.args.first_mut()?.expr {
+        Expr::Arrow(arrow) => match &mut *arrow.body {
+            BlockStmtOrExpr::Expr(expr) => &mut **expr,
+            BlockStmtOrExpr::BlockStmt(block) => return_expr_of_block_mut(block)?,
+        },
+        Expr::Fn(fn_expr) => return_expr_of_block_mut(fn_expr.function.body.as_mut()?)?,
+        _ => return None,
+    };
+    let collection = returned.unwrap_parens_mut();
+    matches!(collection, Expr::Object(_) | Expr::Array(_)).then(|| collection.take())
+}
+
+fn return_expr_of_block_mut(block: &mut BlockStmt) -> Option<&mut Expr> {
+    block.stmts.iter_mut().find_map(|stmt| match stmt {
+        Stmt::Return(ReturnStmt { arg: Some(arg), .. })
+            if matches!(arg.unwrap_parens(), Expr::Call(_) | Expr::Object(_) | Expr::Array(_)) =>
+        {
+            Some(&mut **arg)
+        }
+        _ => None,
+    })
+}
+
+/// Find a required argument missing from the builder call of `artifact`.
+///
+/// Checks the builder forms whose shape is known: the tagged templates
+/// `` fragment(fragmentName, onType)`...` `` and `` query(operationName)`...` ``,
+/// and the options form `query(operationName)({ variables, fields })`. Returns
+/// the builder name, the argument (or `options.fields`), and its 1-based
+/// position. Other forms, and builders not matching the element type, are left
+/// to the builder.
+fn missing_builder_arg(
+    artifact: &BuilderArtifactElement,
+    builder_call: &CallExpr,
+) -> Option<(&'static str, &'static str, usize)> {
+    // Walk down the call chain to the call of the builder itself, remembering
+    // the call applied to its result
+    let mut current = builder_call;
+    let mut applied = None;
+    let mut tagged = false;
+    let builder = loop {
+        let Callee::Expr(callee) = &current.callee else {
+            return None;
+        };
+        match callee.unwrap_parens() {
+            Expr::Ident(ident) => break ident,
+            Expr::Call(inner) => {
+                applied = Some(current);
+                current = inner;
+            }
+            Expr::TaggedTpl(TaggedTpl { tag, .. }) => {
+                let Expr::Call(inner) = tag.unwrap_parens() else {
+                    return None;
+                };
+                tagged = true;
+                current = inner;
+            }
+            _ => return None,
+        }
+    };
+
+    let (builder_type, required): (_, &[_]) = match (artifact, builder.sym.as_ref()) {
+        (BuilderArtifactElement::Fragment { .. }, "fragment") => ("fragment", &["fragmentName", "onType"]),
+        (BuilderArtifactElement::Operation { .. }, "query") => ("query", &["operationName"]),
+        (BuilderArtifactElement::Operation { .. }, "mutation") => ("mutation", &["operationName"]),
+        (BuilderArtifactElement::Operation { .. }, "subscription") => ("subscription", &["operationName"]),
+        _ => return None,
+    };
+    let missing = (0..required.len()).find(|&index| current.args.get(index).is_none_or(|arg| arg.spread.is_some()));
+    if let Some(index) = missing {
+        return Some((builder_type, required[index], index + 1));
+    }
+
+    // The options form takes the selection through `fields`
+    if tagged || builder_type == "fragment" {
+        return None;
+    }
+    let options_call = applied?;
+    match options_call.args.first() {
+        None => Some((builder_type, "options", 1)),
+        Some(ExprOrSpread { spread: None, expr }) => match expr.unwrap_parens() {
+            Expr::Object(object) if !has_fields_prop(object) => Some((builder_type, "options.fields", 1)),
+            _ => None,
+        },
+        Some(_) => None,
+    }
+}
+
+/// Check if an options object has a `fields` property (or a spread that may provide it).
+fn has_fields_prop(object: &ObjectLit) -> bool {
+    object.props.iter().any(|prop| match prop {
+        PropOrSpread::Spread(_) => true,
+        PropOrSpread::Prop(prop) => match &**prop {
+            Prop::KeyValue(KeyValueProp { key, .. }) => prop_name(key).is_none_or(|name| name == "fields"),
+            Prop::Method(MethodProp { key, .. }) => prop_name(key).is_none_or(|name| name == "fields"),
+            Prop::Shorthand(ident) => ident.sym == "fields",
+            _ => false,
+        },
+    })
+}
+
 /// Check if the callee is a gql.* member expression.
 fn is_gql_member_expression(callee: &Callee) -> bool {
     match callee {
@@ -182,48 +1017,27 @@ fn is_gql_reference(expr: &Expr) -> bool {
     }
 }
 
-/// Helper to compare an atom with a string.
-fn atom_eq<T: AsRef<str>>(atom: &T, s: &str) -> bool {
-    atom.as_ref() == s
-}
-
-/// Extract the builder call from an arrow function body.
-fn extract_builder_call_from_arrow(arrow: &ArrowExpr) -> Option<&CallExpr> {
-    match &*arrow.body {
-        BlockStmtOrExpr::Expr(expr) => {
-            if let Expr::Call(call) = &**expr {
-                Some(call)
-            } else {
-                None
-            }
-        }
-        BlockStmtOrExpr::BlockStmt(block) => extract_call_from_block(block),
+/// Get the name of a non-computed property key.
+fn prop_name(key: &PropName) -> Option<&str> {
+    match key {
+        PropName::Ident(ident) => Some(ident.sym.as_ref()),
+        PropName::Str(s) => s.value.as_str(),
+        _ => None,
     }
 }
 
-/// Extract the builder call from a function expression body.
-fn extract_builder_call_from_fn(fn_expr: &FnExpr) -> Option<&CallExpr> {
-    // Function body is Option<BlockStmt>
-    fn_expr
-        .function
-        .body
-        .as_ref()
-        .and_then(|block| extract_call_from_block(block))
+/// Get the value of a string literal or a template literal without substitutions.
+fn string_value(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(Lit::Str(s)) => s.value.as_str().map(str::to_string),
+        Expr::Tpl(tpl) if tpl.exprs.is_empty() => Some(tpl.quasis.first()?.cooked.as_ref()?.as_str()?.to_string()),
+        _ => None,
+    }
 }
 
-/// Extract a call expression from a block statement (shared by arrow and fn).
-fn extract_call_from_block(block: &BlockStmt) -> Option<&CallExpr> {
-    // Look for a return statement with a call expression
-    for stmt in &block.stmts {
-        if let Stmt::Return(ret) = stmt {
-            if let Some(arg) = &ret.arg {
-                if let Expr::Call(call) = &**arg {
-                    return Some(call);
-                }
-            }
-        }
-    }
-    None
+/// Helper to compare an atom with a string.
+fn atom_eq<T: AsRef<str>>(atom: &T, s: &str) -> bool {
+    atom.as_ref() == s
 }
 
 /// Resolve a canonical ID from file path and AST path.
@@ -232,9 +1046,22 @@ fn extract_call_from_block(block: &BlockStmt) -> Option<&CallExpr> {
 /// This mirrors the TypeScript implementation in @soda-gql/common:
 /// - Normalizes path separators to forward slashes (cross-platform)
 /// - Format matches builder artifact keys exactly
-fn resolve_canonical_id(file_path: &str, ast_path: &str) -> CanonicalId {
+///
+/// Windows verbatim prefixes are stripped too (`\\?\C:\...` becomes `C:/...`),
+/// so files reached through long paths match the keys the builder recorded.
+///
+/// The first matching entry in `path_mappings` rewrites the path prefix.
+pub fn resolve_canonical_id(file_path: &str, ast_path: &str, path_mappings: &[PathMapping]) -> CanonicalId {
+    format!("{}::{}", canonical_path(file_path, path_mappings), ast_path).into()
+}
+
+/// Resolve the file path part of the canonical IDs for elements in `file_path`.
+pub fn canonical_path(file_path: &str, path_mappings: &[PathMapping]) -> String {
     // Normalize path separators to forward slashes for cross-platform compatibility
     // This matches the TypeScript normalizePath function behavior
-    let normalized_path = file_path.replace('\\', "/");
-    format!("{}::{}", normalized_path, ast_path)
+    let normalized_path = normalize_path(file_path).into_owned();
+    path_mappings
+        .iter()
+        .find_map(|mapping| mapping.apply(&normalized_path))
+        .unwrap_or(normalized_path)
 }
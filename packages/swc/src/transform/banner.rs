@@ -0,0 +1,38 @@
+//! License banner preservation.
+//!
+//! Leading comments are attached to the first statement of the source, so
+//! imports and declarations inserted above it, or removing it altogether,
+//! would move a legal banner down or drop it. Legal comments are taken out of
+//! the comment map in that case and written at the very top of the output.
+
+use swc_core::common::comments::{Comment, CommentKind, Comments, SingleThreadedComments};
+use swc_core::common::Spanned;
+use swc_core::ecma::ast::Module;
+
+/// Check if a comment is a legal banner: `/*! ... */`, or a block comment
+/// with `@license` or `@preserve`.
+pub fn is_legal_comment(comment: &Comment) -> bool {
+    comment.kind == CommentKind::Block
+        && (comment.text.starts_with('!') || comment.text.contains("@license") || comment.text.contains("@preserve"))
+}
+
+/// Take the legal comments leading the source, if the statement they are
+/// attached to is no longer the first one.
+///
+/// Must run after all statements are inserted and removed. Files with a
+/// shebang keep their comments in place, since the shebang has to stay first.
+pub fn take_legal_banner(module: &Module, comments: &SingleThreadedComments) -> Vec<Comment> {
+    let first_token = module.span.lo;
+    if module.shebang.is_some() || module.body.first().is_some_and(|item| item.span().lo == first_token) {
+        return Vec::new();
+    }
+    let Some(leading) = comments.take_leading(first_token) else {
+        return Vec::new();
+    };
+
+    let (legal, other): (Vec<_>, Vec<_>) = leading.into_iter().partition(is_legal_comment);
+    if !other.is_empty() {
+        comments.add_leading_comments(first_token, other);
+    }
+    legal
+}
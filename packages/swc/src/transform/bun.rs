@@ -0,0 +1,47 @@
+//! Bun plugin support.
+//!
+//! Bun's `onLoad` callbacks return `{ contents, loader }` instead of a code and
+//! source map pair, and may hand over paths in URL or namespaced form. This
+//! module adapts transform output and source paths to that shape.
+
+use serde::Serialize;
+
+/// Result shape of a Bun plugin `onLoad` callback.
+#[derive(Debug, Clone, Serialize)]
+pub struct BunLoadResult {
+    /// The transformed source code.
+    pub contents: String,
+    /// Bun loader for the contents, derived from the source extension.
+    pub loader: &'static str,
+}
+
+/// Pick the Bun loader for a source path.
+///
+/// The transformer keeps TypeScript and JSX syntax, so the loader follows the
+/// original file extension.
+pub fn loader_for_path(path: &str) -> &'static str {
+    let extension = path.rsplit_once('.').map_or("", |(_, extension)| extension);
+    match extension {
+        "ts" | "mts" | "cts" => "ts",
+        "tsx" => "tsx",
+        "jsx" => "jsx",
+        _ => "js",
+    }
+}
+
+/// Normalize a path received from Bun into a file path.
+///
+/// Strips the `file://` URL scheme, the `file:` namespace prefix Bun uses for
+/// namespaced module paths, and any query or hash suffix from virtual module
+/// paths (e.g., `/src/a.ts?bun-virtual`).
+pub fn normalize_bun_path(path: &str) -> &str {
+    let path = path
+        .strip_prefix("file://")
+        .or_else(|| path.strip_prefix("file:"))
+        .unwrap_or(path);
+
+    match path.find(['?', '#']) {
+        Some(end) => &path[..end],
+        None => path,
+    }
+}
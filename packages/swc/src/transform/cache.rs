@@ -0,0 +1,228 @@
+//! Memoization of generated prebuild expressions.
+//!
+//! Serializing a prebuild and building its `JSON.parse(...)` expression is the
+//! same work for every file that references an operation, so `SwcTransformer`
+//! caches the result per canonical ID. Entries belong to one artifact
+//! generation and are dropped when the artifact is reloaded.
+//!
+//! Parsed modules are cached per source path as well, so re-transforming an
+//! unchanged file (e.g., Vite requesting a module once per environment) skips
+//! parsing. With `cacheOutput`, whole transform results are cached the same
+//! way, keyed by everything the output depends on.
+//!
+//! Also computes cache keys for build tools (e.g., Jest, Nx, Turborepo) that
+//! cache transform output themselves.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+use swc_core::common::comments::{SingleThreadedComments, SingleThreadedCommentsMapInner};
+use swc_core::ecma::ast::{Expr, Module};
+use xxhash_rust::xxh64::{xxh64, Xxh64};
+
+use crate::types::CanonicalId;
+
+use super::transformer::TransformResult;
+
+/// Cache of prebuild expressions keyed by canonical ID.
+#[derive(Default)]
+pub struct PrebuildCache {
+    state: Mutex<CacheState>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    generation: u64,
+    entries: HashMap<CanonicalId, Expr>,
+}
+
+impl PrebuildCache {
+    /// Drop all entries if they were built from a different artifact generation.
+    pub fn sync(&self, generation: u64) {
+        let mut state = self.lock();
+        if state.generation != generation {
+            state.generation = generation;
+            state.entries.clear();
+        }
+    }
+
+    /// Get the cached expression for `canonical_id`, building it on a miss.
+    pub fn get_or_build(&self, canonical_id: &CanonicalId, build: impl FnOnce() -> Option<Expr>) -> Option<Expr> {
+        if let Some(expr) = self.lock().entries.get(canonical_id) {
+            return Some(expr.clone());
+        }
+
+        let expr = build()?;
+        self.lock().entries.insert(canonical_id.clone(), expr.clone());
+        Some(expr)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Cache of parsed modules, one entry per source path.
+///
+/// An entry is reused while the source hashes the same. Spans stay valid
+/// because every transform parses into a fresh source map holding only that
+/// file, so the file always starts at the same position.
+#[derive(Default)]
+pub struct ModuleCache {
+    entries: Mutex<HashMap<String, ParsedModule>>,
+    /// Whether to keep each file's source, for `transformIncremental`
+    keep_sources: bool,
+}
+
+/// A module as parsed, before any transformation.
+struct ParsedModule {
+    source_hash: u64,
+    /// Kept for `transformIncremental`, which applies edits to it, if enabled
+    source_code: Option<String>,
+    module: Module,
+    leading_comments: SingleThreadedCommentsMapInner,
+    trailing_comments: SingleThreadedCommentsMapInner,
+}
+
+impl ModuleCache {
+    /// Also keep the source of each parsed module, so edits can be applied to it.
+    pub fn with_sources(mut self, keep_sources: bool) -> Self {
+        self.keep_sources = keep_sources;
+        self
+    }
+
+    /// Get a copy of the module parsed from `source_code`, restoring its comments into `comments`.
+    pub fn get(&self, source_path: &str, source_code: &str, comments: &SingleThreadedComments) -> Option<Module> {
+        let entries = self.lock();
+        let parsed = entries
+            .get(source_path)
+            .filter(|parsed| parsed.source_hash == xxh64(source_code.as_bytes(), 0))?;
+
+        let (mut leading, mut trailing) = comments.borrow_all_mut();
+        leading.clone_from(&parsed.leading_comments);
+        trailing.clone_from(&parsed.trailing_comments);
+        Some(parsed.module.clone())
+    }
+
+    /// Store the module parsed from `source_code`, replacing any older version of the file.
+    pub fn insert(&self, source_path: &str, source_code: &str, module: &Module, comments: &SingleThreadedComments) {
+        let (leading, trailing) = comments.borrow_all();
+        let parsed = ParsedModule {
+            source_hash: xxh64(source_code.as_bytes(), 0),
+            source_code: self.keep_sources.then(|| source_code.to_string()),
+            module: module.clone(),
+            leading_comments: leading.clone(),
+            trailing_comments: trailing.clone(),
+        };
+        self.lock().insert(source_path.to_string(), parsed);
+    }
+
+    /// Get the source of the latest parse of a file, with a copy of its module
+    /// and its comments restored into `comments`.
+    ///
+    /// Returns `None` unless sources are kept (see [`Self::with_sources`]).
+    pub fn latest(&self, source_path: &str, comments: &SingleThreadedComments) -> Option<(String, Module)> {
+        let entries = self.lock();
+        let parsed = entries.get(source_path)?;
+        let source_code = parsed.source_code.clone()?;
+
+        let (mut leading, mut trailing) = comments.borrow_all_mut();
+        leading.clone_from(&parsed.leading_comments);
+        trailing.clone_from(&parsed.trailing_comments);
+        Some((source_code, parsed.module.clone()))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, ParsedModule>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Cache of transform results, one entry per source path.
+#[derive(Default)]
+pub struct OutputCache {
+    entries: Mutex<HashMap<String, (String, TransformResult)>>,
+}
+
+impl OutputCache {
+    /// Get the result cached for `source_path` if it was stored under `key`.
+    pub fn get(&self, source_path: &str, key: &str) -> Option<TransformResult> {
+        let entries = self.lock();
+        let (cached_key, result) = entries.get(source_path)?;
+        (cached_key == key).then(|| result.clone())
+    }
+
+    /// Store the result for `source_path`, replacing any older version of the file.
+    pub fn insert(&self, source_path: &str, key: String, result: &TransformResult) {
+        self.lock().insert(source_path.to_string(), (key, result.clone()));
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, (String, TransformResult)>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Compute the key of a cached transform result.
+///
+/// `file_key` is the [`file_cache_key`] of the file; the source path and
+/// serialized transform context are added because both affect the output.
+pub fn output_cache_key(file_key: &str, source_path: &str, context_json: &str) -> String {
+    hash_parts([file_key, source_path, context_json])
+}
+
+/// Source hash used in output cache keys (XXH64, hex).
+pub fn source_hash(source_code: &str) -> String {
+    format!("{:016x}", xxh64(source_code.as_bytes(), 0))
+}
+
+/// Compute a cache key for the output of transforming one file.
+///
+/// Covers everything the output depends on: the crate version, the artifact
+/// hash, the transformer config, the caller's own options, the source path,
+/// and the source code.
+pub fn cache_key(
+    artifact_hash: Option<&str>,
+    config_json: &str,
+    options: Option<&str>,
+    source_path: &str,
+    source_code: &str,
+) -> String {
+    hash_parts([
+        env!("CARGO_PKG_VERSION"),
+        artifact_hash.unwrap_or_default(),
+        config_json,
+        options.unwrap_or_default(),
+        source_path,
+        source_code,
+    ])
+}
+
+/// Compute a cache key for one file from its source hash and the artifact
+/// entries it can resolve.
+///
+/// Unlike [`cache_key`], only the content hashes of the file's own artifact
+/// entries are covered, so edits elsewhere in the project keep the key
+/// unchanged. `canonical_path` is the path part of the file's canonical IDs,
+/// which stays the same across machines when the artifact uses relative or
+/// mapped paths. `entries` are `(canonical ID, content hash)` pairs in any order.
+pub fn file_cache_key(canonical_path: &str, source_hash: &str, entries: &[(&str, &str)], config_json: &str) -> String {
+    let mut entries = entries.to_vec();
+    entries.sort_unstable();
+
+    let head = [
+        env!("CARGO_PKG_VERSION"),
+        config_json,
+        canonical_path,
+        source_hash,
+    ];
+    hash_parts(head.into_iter().chain(entries.iter().flat_map(|(id, hash)| [*id, *hash])))
+}
+
+/// Hash length-prefixed parts, so field boundaries cannot shift.
+fn hash_parts<'a>(parts: impl IntoIterator<Item = &'a str>) -> String {
+    let mut hasher = Xxh64::new(0);
+    for part in parts {
+        hasher.update(&(part.len() as u64).to_le_bytes());
+        hasher.update(part.as_bytes());
+    }
+    format!("{:016x}", hasher.digest())
+}
@@ -0,0 +1,297 @@
+//! Closure-capture analysis of builder callbacks.
+//!
+//! The builder evaluates gql definitions at build time, so a callback can only
+//! reference module-level bindings and globals. A definition nested in a
+//! function that references the function's parameters or locals cannot be
+//! represented in the artifact; such captures are collected here and reported
+//! as `SODA_GQL_NON_STATIC_BUILDER` instead of a missing artifact entry.
+//!
+//! Scopes are tracked per function, block, catch clause and `for` head. `var`
+//! declarations are treated as scoped to their block, which is enough to tell
+//! module-level bindings from runtime ones.
+
+use rustc_hash::FxHashSet;
+use swc_core::common::Span;
+use swc_core::ecma::atoms::Atom;
+use swc_core::ecma::ast::*;
+use swc_core::ecma::visit::{Visit, VisitWith};
+
+use super::analysis::is_gql_definition;
+use super::metadata::{SpanKey, SpanMap};
+
+/// A reference from a builder callback to a binding that only exists at runtime.
+#[derive(Debug, Clone)]
+pub struct Capture {
+    /// Name of the captured binding.
+    pub name: Atom,
+    /// Span of the first reference in the callback.
+    pub span: Span,
+}
+
+/// Map from gql call span to the bindings its builder callback captures.
+pub type CaptureMap = SpanMap<Vec<Capture>>;
+
+/// Collect the non-static captures of every gql definition in a module.
+pub fn collect_captures(module: &Module) -> CaptureMap {
+    let mut collector = CaptureCollector {
+        scopes: Vec::new(),
+        definitions: Vec::new(),
+        captures: CaptureMap::default(),
+    };
+    module.visit_with(&mut collector);
+    collector.captures
+}
+
+struct CaptureCollector {
+    scopes: Vec<Scope>,
+    /// gql definitions being visited, innermost last
+    definitions: Vec<Definition>,
+    captures: CaptureMap,
+}
+
+struct Scope {
+    bindings: FxHashSet<Atom>,
+    /// Whether the bindings exist when the builder evaluates the module (module scope only)
+    is_static: bool,
+}
+
+struct Definition {
+    span: Span,
+    /// Number of scopes enclosing the gql call; scopes above it belong to the callback
+    depth: usize,
+}
+
+impl CaptureCollector {
+    fn with_scope(&mut self, bindings: FxHashSet<Atom>, visit: impl FnOnce(&mut Self)) {
+        self.scopes.push(Scope {
+            bindings,
+            is_static: false,
+        });
+        visit(self);
+        self.scopes.pop();
+    }
+
+    /// Record a reference if it resolves to a runtime binding outside an enclosing callback.
+    fn reference(&mut self, ident: &Ident) {
+        if self.definitions.is_empty() {
+            return;
+        }
+        // Unresolved references are globals
+        let Some(index) = self.scopes.iter().rposition(|scope| scope.bindings.contains(&ident.sym)) else {
+            return;
+        };
+        if self.scopes[index].is_static {
+            return;
+        }
+
+        for definition in &self.definitions {
+            if index >= definition.depth {
+                continue;
+            }
+            let captures = self.captures.entry(SpanKey::from(definition.span)).or_default();
+            if !captures.iter().any(|capture| capture.name == ident.sym) {
+                captures.push(Capture {
+                    name: ident.sym.clone(),
+                    span: ident.span,
+                });
+            }
+        }
+    }
+}
+
+impl Visit for CaptureCollector {
+    fn visit_module(&mut self, module: &Module) {
+        let mut bindings = FxHashSet::default();
+        for item in &module.body {
+            match item {
+                ModuleItem::ModuleDecl(ModuleDecl::Import(import)) => {
+                    bindings.extend(import.specifiers.iter().map(|specifier| match specifier {
+                        ImportSpecifier::Named(named) => named.local.sym.clone(),
+                        ImportSpecifier::Default(default) => default.local.sym.clone(),
+                        ImportSpecifier::Namespace(namespace) => namespace.local.sym.clone(),
+                    }));
+                }
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export)) => declared_names(&export.decl, &mut bindings),
+                ModuleItem::Stmt(Stmt::Decl(decl)) => declared_names(decl, &mut bindings),
+                _ => {}
+            }
+        }
+
+        self.scopes.push(Scope {
+            bindings,
+            is_static: true,
+        });
+        module.visit_children_with(self);
+        self.scopes.pop();
+    }
+
+    fn visit_function(&mut self, function: &Function) {
+        let mut bindings = FxHashSet::default();
+        for param in &function.params {
+            pat_names(&param.pat, &mut bindings);
+        }
+        self.with_scope(bindings, |this| function.visit_children_with(this));
+    }
+
+    fn visit_fn_expr(&mut self, fn_expr: &FnExpr) {
+        // A named function expression binds its name inside itself
+        let bindings = fn_expr.ident.iter().map(|ident| ident.sym.clone()).collect();
+        self.with_scope(bindings, |this| fn_expr.function.visit_with(this));
+    }
+
+    fn visit_constructor(&mut self, constructor: &Constructor) {
+        let mut bindings = FxHashSet::default();
+        for param in &constructor.params {
+            match param {
+                ParamOrTsParamProp::Param(param) => pat_names(&param.pat, &mut bindings),
+                ParamOrTsParamProp::TsParamProp(prop) => match &prop.param {
+                    TsParamPropParam::Ident(ident) => {
+                        bindings.insert(ident.id.sym.clone());
+                    }
+                    TsParamPropParam::Assign(assign) => pat_names(&assign.left, &mut bindings),
+                },
+            }
+        }
+        self.with_scope(bindings, |this| constructor.visit_children_with(this));
+    }
+
+    fn visit_arrow_expr(&mut self, arrow: &ArrowExpr) {
+        let mut bindings = FxHashSet::default();
+        for pat in &arrow.params {
+            pat_names(pat, &mut bindings);
+        }
+        self.with_scope(bindings, |this| arrow.visit_children_with(this));
+    }
+
+    fn visit_block_stmt(&mut self, block: &BlockStmt) {
+        let mut bindings = FxHashSet::default();
+        for stmt in &block.stmts {
+            if let Stmt::Decl(decl) = stmt {
+                declared_names(decl, &mut bindings);
+            }
+        }
+        self.with_scope(bindings, |this| block.visit_children_with(this));
+    }
+
+    fn visit_catch_clause(&mut self, clause: &CatchClause) {
+        let mut bindings = FxHashSet::default();
+        if let Some(param) = &clause.param {
+            pat_names(param, &mut bindings);
+        }
+        self.with_scope(bindings, |this| clause.visit_children_with(this));
+    }
+
+    fn visit_for_stmt(&mut self, stmt: &ForStmt) {
+        let mut bindings = FxHashSet::default();
+        if let Some(VarDeclOrExpr::VarDecl(var_decl)) = &stmt.init {
+            var_decl_names(var_decl, &mut bindings);
+        }
+        self.with_scope(bindings, |this| stmt.visit_children_with(this));
+    }
+
+    fn visit_for_in_stmt(&mut self, stmt: &ForInStmt) {
+        let mut bindings = FxHashSet::default();
+        for_head_names(&stmt.left, &mut bindings);
+        self.with_scope(bindings, |this| stmt.visit_children_with(this));
+    }
+
+    fn visit_for_of_stmt(&mut self, stmt: &ForOfStmt) {
+        let mut bindings = FxHashSet::default();
+        for_head_names(&stmt.left, &mut bindings);
+        self.with_scope(bindings, |this| stmt.visit_children_with(this));
+    }
+
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        if !is_gql_definition(call) {
+            call.visit_children_with(self);
+            return;
+        }
+
+        self.definitions.push(Definition {
+            span: call.span,
+            depth: self.scopes.len(),
+        });
+        call.visit_children_with(self);
+        self.definitions.pop();
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Ident(ident) => self.reference(ident),
+            _ => expr.visit_children_with(self),
+        }
+    }
+
+    fn visit_prop(&mut self, prop: &Prop) {
+        match prop {
+            // `{ id }` reads `id`
+            Prop::Shorthand(ident) => self.reference(ident),
+            _ => prop.visit_children_with(self),
+        }
+    }
+}
+
+/// Add the names bound by a declaration.
+fn declared_names(decl: &Decl, names: &mut FxHashSet<Atom>) {
+    match decl {
+        Decl::Var(var_decl) => var_decl_names(var_decl, names),
+        Decl::Fn(fn_decl) => {
+            names.insert(fn_decl.ident.sym.clone());
+        }
+        Decl::Class(class_decl) => {
+            names.insert(class_decl.ident.sym.clone());
+        }
+        Decl::Using(using_decl) => {
+            for declarator in &using_decl.decls {
+                pat_names(&declarator.name, names);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn var_decl_names(var_decl: &VarDecl, names: &mut FxHashSet<Atom>) {
+    for declarator in &var_decl.decls {
+        pat_names(&declarator.name, names);
+    }
+}
+
+fn for_head_names(head: &ForHead, names: &mut FxHashSet<Atom>) {
+    match head {
+        ForHead::VarDecl(var_decl) => var_decl_names(var_decl, names),
+        ForHead::UsingDecl(using_decl) => {
+            for declarator in &using_decl.decls {
+                pat_names(&declarator.name, names);
+            }
+        }
+        ForHead::Pat(_) => {}
+    }
+}
+
+/// Add the names bound by a binding pattern.
+fn pat_names(pat: &Pat, names: &mut FxHashSet<Atom>) {
+    match pat {
+        Pat::Ident(binding) => {
+            names.insert(binding.id.sym.clone());
+        }
+        Pat::Array(array) => {
+            for element in array.elems.iter().flatten() {
+                pat_names(element, names);
+            }
+        }
+        Pat::Object(object) => {
+            for prop in &object.props {
+                match prop {
+                    ObjectPatProp::KeyValue(key_value) => pat_names(&key_value.value, names),
+                    ObjectPatProp::Assign(assign) => {
+                        names.insert(assign.key.id.sym.clone());
+                    }
+                    ObjectPatProp::Rest(rest) => pat_names(&rest.arg, names),
+                }
+            }
+        }
+        Pat::Rest(rest) => pat_names(&rest.arg, names),
+        Pat::Assign(assign) => pat_names(&assign.left, names),
+        Pat::Invalid(_) | Pat::Expr(_) => {}
+    }
+}
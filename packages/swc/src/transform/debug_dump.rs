@@ -0,0 +1,112 @@
+//! Debug dump of intermediate transform state.
+//!
+//! With `debugDump`, each transform result includes what the transformer saw
+//! while resolving canonical IDs: the collected gql definitions with their AST
+//! paths, the module's export bindings, and the replacement table. Comparing
+//! the resolved IDs with the artifact's keys usually explains "artifact not
+//! found" errors without rebuilding the crate with print statements.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use swc_core::common::SourceMap;
+use swc_core::ecma::ast::Module;
+
+use crate::types::{BuilderArtifactElement, ErrorSpan, PathMapping};
+
+use super::analysis::{canonical_path, resolve_canonical_id, GqlCallFinder};
+use super::metadata::{MetadataCollector, MetadataMap};
+
+/// Intermediate state of a transform.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugDump {
+    /// File path part of the file's canonical IDs, after `pathMappings`.
+    pub canonical_path: String,
+    /// Export names of the module's exported bindings, by local name.
+    pub export_bindings: BTreeMap<String, String>,
+    /// gql definitions found by metadata collection, in source order.
+    pub definitions: Vec<DumpedDefinition>,
+    /// Expressions replaced with runtime calls, in source order.
+    pub replacements: Vec<DumpedReplacement>,
+}
+
+/// A gql definition found by metadata collection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DumpedDefinition {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<ErrorSpan>,
+    pub ast_path: String,
+    /// Canonical ID resolved from the AST path, looked up in the artifact.
+    pub canonical_id: String,
+    pub is_top_level: bool,
+    pub is_exported: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub export_binding: Option<String>,
+}
+
+/// An entry of the replacement table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DumpedReplacement {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<ErrorSpan>,
+    /// Canonical ID of the artifact entry the expression was replaced with.
+    pub canonical_id: String,
+    /// "fragment" or "operation".
+    pub element_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation_name: Option<String>,
+}
+
+/// Collect the dump of a file after gql calls were analyzed.
+pub fn debug_dump(
+    cm: &SourceMap,
+    module: &Module,
+    metadata: &MetadataMap,
+    finder: &GqlCallFinder<'_>,
+    source_path: &str,
+    path_mappings: &[PathMapping],
+) -> DebugDump {
+    let export_bindings = MetadataCollector::collect_export_bindings(module)
+        .into_iter()
+        .map(|(local, exported)| (local.to_string(), exported.to_string()))
+        .collect();
+
+    let mut definitions: Vec<_> = metadata.iter().map(|(key, meta)| (key.span(), meta)).collect();
+    definitions.sort_by_key(|(span, _)| span.lo);
+    let definitions = definitions
+        .into_iter()
+        .map(|(span, meta)| DumpedDefinition {
+            span: ErrorSpan::resolve(cm, span),
+            ast_path: meta.ast_path.clone(),
+            canonical_id: resolve_canonical_id(source_path, &meta.ast_path, path_mappings).to_string(),
+            is_top_level: meta.is_top_level,
+            is_exported: meta.is_exported,
+            export_binding: meta.export_binding.as_ref().map(ToString::to_string),
+        })
+        .collect();
+
+    let mut replacements: Vec<_> = finder.replacement_spans().collect();
+    replacements.sort_by_key(|(span, _)| span.lo);
+    let replacements = replacements
+        .into_iter()
+        .map(|(span, replacement)| DumpedReplacement {
+            span: ErrorSpan::resolve(cm, span),
+            canonical_id: replacement.canonical_id.to_string(),
+            element_type: replacement.artifact.element_type().to_string(),
+            operation_name: match &*replacement.artifact {
+                BuilderArtifactElement::Operation { prebuild, .. } => Some(prebuild.operation_name.to_string()),
+                BuilderArtifactElement::Fragment { .. } => None,
+            },
+        })
+        .collect();
+
+    DebugDump {
+        canonical_path: canonical_path(source_path, path_mappings),
+        export_bindings,
+        definitions,
+        replacements,
+    }
+}
@@ -0,0 +1,42 @@
+//! React Server Components directive detection.
+//!
+//! Frameworks like the Next.js App Router split modules between server and
+//! client bundles based on a `"use client"` or `"use server"` directive in the
+//! module's directive prologue.
+
+use swc_core::ecma::ast::*;
+
+use crate::types::config::ModuleDirective;
+
+/// Find the RSC directive in the module's directive prologue, if any.
+///
+/// Only string literal statements at the very top of the module count, as in
+/// the JavaScript directive prologue.
+pub fn module_directive(module: &Module) -> Option<ModuleDirective> {
+    module
+        .body
+        .iter()
+        .map_while(directive_value)
+        .find_map(|directive| match directive.as_ref() {
+            "use client" => Some(ModuleDirective::Client),
+            "use server" => Some(ModuleDirective::Server),
+            _ => None,
+        })
+}
+
+/// Check if a module item is a directive (a string literal statement).
+///
+/// Callers must only apply this to items at the top of the module.
+pub fn is_directive(item: &ModuleItem) -> bool {
+    directive_value(item).is_some()
+}
+
+fn directive_value(item: &ModuleItem) -> Option<std::borrow::Cow<'_, str>> {
+    let ModuleItem::Stmt(Stmt::Expr(ExprStmt { expr, .. })) = item else {
+        return None;
+    };
+    let Expr::Lit(Lit::Str(directive)) = &**expr else {
+        return None;
+    };
+    Some(directive.value.to_string_lossy())
+}
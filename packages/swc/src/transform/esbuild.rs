@@ -0,0 +1,142 @@
+//! esbuild plugin support.
+//!
+//! esbuild's `onLoad` callbacks receive `{ path, namespace, ... }` and return
+//! `{ contents, loader, errors, warnings }`, with diagnostics as esbuild
+//! messages carrying their own location objects. This module adapts transform
+//! input and output to that shape, so a plugin only forwards the arguments.
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{PluginError, Severity};
+
+use super::bun::loader_for_path;
+
+/// Name reported as the `pluginName` of every message.
+const PLUGIN_NAME: &str = "soda-gql";
+
+/// The `onLoad` arguments the transform uses.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EsbuildLoadArgs {
+    /// Path of the module, absolute in the `file` namespace.
+    pub path: String,
+    /// Namespace of the module.
+    #[serde(default = "file_namespace")]
+    pub namespace: String,
+    /// Loader to return instead of the one derived from the path extension.
+    #[serde(default)]
+    pub loader: Option<String>,
+}
+
+fn file_namespace() -> String {
+    "file".to_string()
+}
+
+impl EsbuildLoadArgs {
+    /// Pick the loader for the contents.
+    ///
+    /// The transformer keeps TypeScript and JSX syntax, so unless overridden
+    /// the loader follows the original file extension.
+    pub fn loader(&self) -> String {
+        self.loader
+            .clone()
+            .unwrap_or_else(|| loader_for_path(&self.path).to_string())
+    }
+}
+
+/// Result shape of an esbuild plugin `onLoad` callback.
+#[derive(Debug, Clone, Serialize)]
+pub struct EsbuildLoadResult {
+    /// The transformed source code.
+    pub contents: String,
+    pub loader: String,
+    pub errors: Vec<EsbuildMessage>,
+    pub warnings: Vec<EsbuildMessage>,
+}
+
+/// An esbuild `PartialMessage`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EsbuildMessage {
+    /// Error code (e.g., `SODA_GQL_ANALYSIS_ARTIFACT_NOT_FOUND`).
+    pub id: String,
+    pub plugin_name: &'static str,
+    pub text: String,
+    pub location: Option<EsbuildLocation>,
+    pub notes: Vec<EsbuildNote>,
+}
+
+/// An esbuild message note.
+#[derive(Debug, Clone, Serialize)]
+pub struct EsbuildNote {
+    pub text: String,
+    pub location: Option<EsbuildLocation>,
+}
+
+/// An esbuild `Location`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EsbuildLocation {
+    pub file: String,
+    pub namespace: String,
+    /// 1-based line.
+    pub line: usize,
+    /// 0-based column, in bytes.
+    pub column: usize,
+    /// Length of the located range on its first line, in bytes.
+    pub length: usize,
+    /// Text of the line the range starts on.
+    pub line_text: String,
+    pub suggestion: String,
+}
+
+/// Convert transform errors into esbuild errors and warnings.
+///
+/// Informational diagnostics are dropped, since esbuild has no level for them.
+pub fn esbuild_messages(
+    errors: &[PluginError],
+    source_code: &str,
+    args: &EsbuildLoadArgs,
+) -> (Vec<EsbuildMessage>, Vec<EsbuildMessage>) {
+    let mut error_messages = Vec::new();
+    let mut warning_messages = Vec::new();
+    for error in errors {
+        let messages = match error.severity {
+            Severity::Error => &mut error_messages,
+            Severity::Warning => &mut warning_messages,
+            Severity::Info => continue,
+        };
+        let notes = match error.occurrences {
+            1 => Vec::new(),
+            occurrences => vec![EsbuildNote {
+                text: format!("Reported {} times in this file", occurrences),
+                location: None,
+            }],
+        };
+        messages.push(EsbuildMessage {
+            id: error.code.clone(),
+            plugin_name: PLUGIN_NAME,
+            text: error.message.clone(),
+            location: error.span.and_then(|span| {
+                location(source_code, args, span.start as usize, span.end as usize, span.line)
+            }),
+            notes,
+        });
+    }
+    (error_messages, warning_messages)
+}
+
+/// Build the location of the byte range `start..end` starting on `line`.
+fn location(source_code: &str, args: &EsbuildLoadArgs, start: usize, end: usize, line: usize) -> Option<EsbuildLocation> {
+    let line_start = source_code.get(..start)?.rfind('\n').map_or(0, |index| index + 1);
+    let line_end = source_code[start..].find('\n').map_or(source_code.len(), |index| start + index);
+    Some(EsbuildLocation {
+        file: args.path.clone(),
+        namespace: args.namespace.clone(),
+        line,
+        column: start - line_start,
+        length: end.min(line_end).saturating_sub(start),
+        line_text: source_code[line_start..line_end].trim_end_matches('\r').to_string(),
+        suggestion: String::new(),
+    })
+}
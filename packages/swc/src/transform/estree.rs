@@ -0,0 +1,1423 @@
+//! ESTree conversion of the transformed module.
+//!
+//! Produces the post-transform program as ESTree JSON (the shape used by
+//! acorn, espree and ESLint), so JavaScript tooling can consume it without
+//! re-parsing the emitted code.
+//!
+//! TypeScript-only syntax is erased the way a type-stripping compiler would:
+//! type annotations are dropped, type-only declarations and imports are
+//! omitted, and `as`/`satisfies`/non-null expressions are replaced by their
+//! operand. JSX is converted to the ESTree JSX extension.
+//!
+//! Locations (`start`, `end`, `range`, `loc`) refer to the original source.
+//! Nodes generated by the transformer have no location and carry `null`.
+
+use serde_json::{json, Map, Value};
+use swc_core::common::{BytePos, Span};
+use swc_core::ecma::ast::*;
+
+/// Convert a module to an ESTree `Program`.
+///
+/// # Arguments
+/// * `module` - The transformed module
+/// * `source_code` - The original source the module was parsed from
+/// * `start_pos` - Position of the source file's first byte in the source map
+pub fn module_to_estree(module: &Module, source_code: &str, start_pos: BytePos) -> Value {
+    let converter = EstreeConverter {
+        positions: Positions::new(source_code, start_pos),
+    };
+    converter.program(module)
+}
+
+/// Maps source map byte positions to UTF-16 offsets and line/column pairs.
+struct Positions {
+    start_pos: u32,
+    /// UTF-16 offset of each byte offset in the source (plus one past the end)
+    utf16_offsets: Vec<u32>,
+    /// UTF-16 offset of the start of each line
+    line_starts: Vec<u32>,
+}
+
+impl Positions {
+    fn new(source_code: &str, start_pos: BytePos) -> Self {
+        let mut utf16_offsets = Vec::with_capacity(source_code.len() + 1);
+        let mut line_starts = vec![0];
+        let mut offset = 0u32;
+
+        for ch in source_code.chars() {
+            for _ in 0..ch.len_utf8() {
+                utf16_offsets.push(offset);
+            }
+            offset += ch.len_utf16() as u32;
+            if ch == '\n' {
+                line_starts.push(offset);
+            }
+        }
+        utf16_offsets.push(offset);
+
+        Self {
+            start_pos: start_pos.0,
+            utf16_offsets,
+            line_starts,
+        }
+    }
+
+    /// UTF-16 offset of a byte position, or `None` for generated positions.
+    fn offset(&self, pos: BytePos) -> Option<u32> {
+        let index = pos.0.checked_sub(self.start_pos)?;
+        self.utf16_offsets.get(index as usize).copied()
+    }
+
+    /// ESTree `loc` position (1-based line, 0-based column) of a UTF-16 offset.
+    fn line_column(&self, offset: u32) -> Value {
+        let line = self.line_starts.partition_point(|start| *start <= offset);
+        json!({ "line": line, "column": offset - self.line_starts[line - 1] })
+    }
+
+    /// Location fields for a span.
+    fn location(&self, span: Span) -> [(&'static str, Value); 4] {
+        let range = if span.is_dummy() {
+            None
+        } else {
+            self.offset(span.lo).zip(self.offset(span.hi))
+        };
+
+        match range {
+            Some((start, end)) => [
+                ("start", json!(start)),
+                ("end", json!(end)),
+                ("range", json!([start, end])),
+                (
+                    "loc",
+                    json!({ "start": self.line_column(start), "end": self.line_column(end) }),
+                ),
+            ],
+            None => [
+                ("start", Value::Null),
+                ("end", Value::Null),
+                ("range", Value::Null),
+                ("loc", Value::Null),
+            ],
+        }
+    }
+}
+
+struct EstreeConverter {
+    positions: Positions,
+}
+
+impl EstreeConverter {
+    /// Create a node of the given type with location fields for `span`.
+    fn node(&self, kind: &str, span: Span, fields: Value) -> Value {
+        let mut object = match fields {
+            Value::Object(object) => object,
+            _ => Map::new(),
+        };
+        object.insert("type".to_string(), json!(kind));
+        for (key, value) in self.positions.location(span) {
+            object.insert(key.to_string(), value);
+        }
+        Value::Object(object)
+    }
+
+    // ----- Program and module declarations -----
+
+    fn program(&self, module: &Module) -> Value {
+        let body: Vec<Value> = module.body.iter().filter_map(|item| self.module_item(item)).collect();
+        self.node("Program", module.span, json!({ "sourceType": "module", "body": body }))
+    }
+
+    fn module_item(&self, item: &ModuleItem) -> Option<Value> {
+        match item {
+            ModuleItem::ModuleDecl(decl) => self.module_decl(decl),
+            ModuleItem::Stmt(stmt) => self.stmt(stmt),
+        }
+    }
+
+    fn module_decl(&self, decl: &ModuleDecl) -> Option<Value> {
+        match decl {
+            ModuleDecl::Import(import) => {
+                if import.type_only {
+                    return None;
+                }
+                let specifiers: Vec<Value> = import
+                    .specifiers
+                    .iter()
+                    .filter_map(|specifier| self.import_specifier(specifier))
+                    .collect();
+                Some(self.node(
+                    "ImportDeclaration",
+                    import.span,
+                    json!({
+                        "specifiers": specifiers,
+                        "source": self.str_lit(&import.src),
+                        "attributes": self.import_attributes(import.with.as_deref()),
+                    }),
+                ))
+            }
+            ModuleDecl::ExportDecl(export) => {
+                let declaration = self.decl(&export.decl)?;
+                Some(self.node(
+                    "ExportNamedDeclaration",
+                    export.span,
+                    json!({ "declaration": declaration, "specifiers": [], "source": null, "attributes": [] }),
+                ))
+            }
+            ModuleDecl::ExportNamed(export) => {
+                if export.type_only {
+                    return None;
+                }
+                if let [ExportSpecifier::Namespace(namespace)] = export.specifiers.as_slice() {
+                    return Some(self.node(
+                        "ExportAllDeclaration",
+                        export.span,
+                        json!({
+                            "exported": self.module_export_name(&namespace.name),
+                            "source": export.src.as_deref().map(|src| self.str_lit(src)),
+                            "attributes": self.import_attributes(export.with.as_deref()),
+                        }),
+                    ));
+                }
+                let specifiers: Vec<Value> = export
+                    .specifiers
+                    .iter()
+                    .filter_map(|specifier| self.export_specifier(specifier))
+                    .collect();
+                Some(self.node(
+                    "ExportNamedDeclaration",
+                    export.span,
+                    json!({
+                        "declaration": null,
+                        "specifiers": specifiers,
+                        "source": export.src.as_deref().map(|src| self.str_lit(src)),
+                        "attributes": self.import_attributes(export.with.as_deref()),
+                    }),
+                ))
+            }
+            ModuleDecl::ExportDefaultDecl(export) => {
+                let declaration = match &export.decl {
+                    DefaultDecl::Class(class) => self.class(
+                        "ClassDeclaration",
+                        class.ident.as_ref(),
+                        &class.class,
+                    ),
+                    DefaultDecl::Fn(function) => self.function(
+                        "FunctionDeclaration",
+                        function.ident.as_ref(),
+                        &function.function,
+                    )?,
+                    DefaultDecl::TsInterfaceDecl(_) => return None,
+                };
+                Some(self.node(
+                    "ExportDefaultDeclaration",
+                    export.span,
+                    json!({ "declaration": declaration }),
+                ))
+            }
+            ModuleDecl::ExportDefaultExpr(export) => Some(self.node(
+                "ExportDefaultDeclaration",
+                export.span,
+                json!({ "declaration": self.expr(&export.expr) }),
+            )),
+            ModuleDecl::ExportAll(export) => {
+                if export.type_only {
+                    return None;
+                }
+                Some(self.node(
+                    "ExportAllDeclaration",
+                    export.span,
+                    json!({
+                        "exported": null,
+                        "source": self.str_lit(&export.src),
+                        "attributes": self.import_attributes(export.with.as_deref()),
+                    }),
+                ))
+            }
+            ModuleDecl::TsImportEquals(_) | ModuleDecl::TsExportAssignment(_) | ModuleDecl::TsNamespaceExport(_) => {
+                None
+            }
+        }
+    }
+
+    fn import_specifier(&self, specifier: &ImportSpecifier) -> Option<Value> {
+        match specifier {
+            ImportSpecifier::Named(named) => {
+                if named.is_type_only {
+                    return None;
+                }
+                let imported = match &named.imported {
+                    Some(imported) => self.module_export_name(imported),
+                    None => self.ident(&named.local),
+                };
+                Some(self.node(
+                    "ImportSpecifier",
+                    named.span,
+                    json!({ "imported": imported, "local": self.ident(&named.local) }),
+                ))
+            }
+            ImportSpecifier::Default(default) => Some(self.node(
+                "ImportDefaultSpecifier",
+                default.span,
+                json!({ "local": self.ident(&default.local) }),
+            )),
+            ImportSpecifier::Namespace(namespace) => Some(self.node(
+                "ImportNamespaceSpecifier",
+                namespace.span,
+                json!({ "local": self.ident(&namespace.local) }),
+            )),
+        }
+    }
+
+    fn export_specifier(&self, specifier: &ExportSpecifier) -> Option<Value> {
+        match specifier {
+            ExportSpecifier::Named(named) => {
+                if named.is_type_only {
+                    return None;
+                }
+                let local = self.module_export_name(&named.orig);
+                let exported = match &named.exported {
+                    Some(exported) => self.module_export_name(exported),
+                    None => local.clone(),
+                };
+                Some(self.node(
+                    "ExportSpecifier",
+                    named.span,
+                    json!({ "local": local, "exported": exported }),
+                ))
+            }
+            ExportSpecifier::Default(default) => Some(self.node(
+                "ExportSpecifier",
+                default.exported.span,
+                json!({
+                    "local": self.node("Identifier", default.exported.span, json!({ "name": "default" })),
+                    "exported": self.ident(&default.exported),
+                }),
+            )),
+            ExportSpecifier::Namespace(namespace) => Some(self.node(
+                "ExportSpecifier",
+                namespace.span,
+                json!({
+                    "local": self.module_export_name(&namespace.name),
+                    "exported": self.module_export_name(&namespace.name),
+                }),
+            )),
+        }
+    }
+
+    fn module_export_name(&self, name: &ModuleExportName) -> Value {
+        match name {
+            ModuleExportName::Ident(ident) => self.ident(ident),
+            ModuleExportName::Str(str) => self.str_lit(str),
+        }
+    }
+
+    fn import_attributes(&self, with: Option<&ObjectLit>) -> Vec<Value> {
+        let Some(with) = with else {
+            return Vec::new();
+        };
+        with.props
+            .iter()
+            .filter_map(|prop| match prop {
+                PropOrSpread::Prop(prop) => match prop.as_ref() {
+                    Prop::KeyValue(key_value) => Some(self.node(
+                        "ImportAttribute",
+                        with.span,
+                        json!({ "key": self.prop_name(&key_value.key), "value": self.expr(&key_value.value) }),
+                    )),
+                    _ => None,
+                },
+                PropOrSpread::Spread(_) => None,
+            })
+            .collect()
+    }
+
+    // ----- Statements and declarations -----
+
+    /// Convert a statement, or `None` if it only exists at the type level.
+    fn stmt(&self, stmt: &Stmt) -> Option<Value> {
+        let value = match stmt {
+            Stmt::Block(block) => self.block(block),
+            Stmt::Empty(empty) => self.node("EmptyStatement", empty.span, json!({})),
+            Stmt::Debugger(debugger) => self.node("DebuggerStatement", debugger.span, json!({})),
+            Stmt::With(with) => self.node(
+                "WithStatement",
+                with.span,
+                json!({ "object": self.expr(&with.obj), "body": self.body_stmt(&with.body) }),
+            ),
+            Stmt::Return(ret) => self.node(
+                "ReturnStatement",
+                ret.span,
+                json!({ "argument": ret.arg.as_deref().map(|arg| self.expr(arg)) }),
+            ),
+            Stmt::Labeled(labeled) => self.node(
+                "LabeledStatement",
+                labeled.span,
+                json!({ "label": self.ident(&labeled.label), "body": self.body_stmt(&labeled.body) }),
+            ),
+            Stmt::Break(brk) => self.node(
+                "BreakStatement",
+                brk.span,
+                json!({ "label": brk.label.as_ref().map(|label| self.ident(label)) }),
+            ),
+            Stmt::Continue(cont) => self.node(
+                "ContinueStatement",
+                cont.span,
+                json!({ "label": cont.label.as_ref().map(|label| self.ident(label)) }),
+            ),
+            Stmt::If(if_stmt) => self.node(
+                "IfStatement",
+                if_stmt.span,
+                json!({
+                    "test": self.expr(&if_stmt.test),
+                    "consequent": self.body_stmt(&if_stmt.cons),
+                    "alternate": if_stmt.alt.as_deref().map(|alt| self.body_stmt(alt)),
+                }),
+            ),
+            Stmt::Switch(switch) => {
+                let cases: Vec<Value> = switch
+                    .cases
+                    .iter()
+                    .map(|case| {
+                        self.node(
+                            "SwitchCase",
+                            case.span,
+                            json!({
+                                "test": case.test.as_deref().map(|test| self.expr(test)),
+                                "consequent": self.stmts(&case.cons),
+                            }),
+                        )
+                    })
+                    .collect();
+                self.node(
+                    "SwitchStatement",
+                    switch.span,
+                    json!({ "discriminant": self.expr(&switch.discriminant), "cases": cases }),
+                )
+            }
+            Stmt::Throw(throw) => self.node("ThrowStatement", throw.span, json!({ "argument": self.expr(&throw.arg) })),
+            Stmt::Try(try_stmt) => {
+                let handler = try_stmt.handler.as_ref().map(|handler| {
+                    self.node(
+                        "CatchClause",
+                        handler.span,
+                        json!({
+                            "param": handler.param.as_ref().map(|param| self.pat(param)),
+                            "body": self.block(&handler.body),
+                        }),
+                    )
+                });
+                self.node(
+                    "TryStatement",
+                    try_stmt.span,
+                    json!({
+                        "block": self.block(&try_stmt.block),
+                        "handler": handler,
+                        "finalizer": try_stmt.finalizer.as_ref().map(|finalizer| self.block(finalizer)),
+                    }),
+                )
+            }
+            Stmt::While(while_stmt) => self.node(
+                "WhileStatement",
+                while_stmt.span,
+                json!({ "test": self.expr(&while_stmt.test), "body": self.body_stmt(&while_stmt.body) }),
+            ),
+            Stmt::DoWhile(do_while) => self.node(
+                "DoWhileStatement",
+                do_while.span,
+                json!({ "body": self.body_stmt(&do_while.body), "test": self.expr(&do_while.test) }),
+            ),
+            Stmt::For(for_stmt) => {
+                let init = for_stmt.init.as_ref().map(|init| match init {
+                    VarDeclOrExpr::VarDecl(var) => self.var_decl(var),
+                    VarDeclOrExpr::Expr(expr) => self.expr(expr),
+                });
+                self.node(
+                    "ForStatement",
+                    for_stmt.span,
+                    json!({
+                        "init": init,
+                        "test": for_stmt.test.as_deref().map(|test| self.expr(test)),
+                        "update": for_stmt.update.as_deref().map(|update| self.expr(update)),
+                        "body": self.body_stmt(&for_stmt.body),
+                    }),
+                )
+            }
+            Stmt::ForIn(for_in) => self.node(
+                "ForInStatement",
+                for_in.span,
+                json!({
+                    "left": self.for_head(&for_in.left),
+                    "right": self.expr(&for_in.right),
+                    "body": self.body_stmt(&for_in.body),
+                }),
+            ),
+            Stmt::ForOf(for_of) => self.node(
+                "ForOfStatement",
+                for_of.span,
+                json!({
+                    "await": for_of.is_await,
+                    "left": self.for_head(&for_of.left),
+                    "right": self.expr(&for_of.right),
+                    "body": self.body_stmt(&for_of.body),
+                }),
+            ),
+            Stmt::Decl(decl) => return self.decl(decl),
+            Stmt::Expr(expr) => self.node(
+                "ExpressionStatement",
+                expr.span,
+                json!({ "expression": self.expr(&expr.expr) }),
+            ),
+        };
+        Some(value)
+    }
+
+    /// Convert a statement in a position that requires one (e.g., a loop body).
+    fn body_stmt(&self, stmt: &Stmt) -> Value {
+        self.stmt(stmt)
+            .unwrap_or_else(|| self.node("EmptyStatement", Span::default(), json!({})))
+    }
+
+    fn stmts(&self, stmts: &[Stmt]) -> Vec<Value> {
+        stmts.iter().filter_map(|stmt| self.stmt(stmt)).collect()
+    }
+
+    fn block(&self, block: &BlockStmt) -> Value {
+        self.node("BlockStatement", block.span, json!({ "body": self.stmts(&block.stmts) }))
+    }
+
+    fn for_head(&self, head: &ForHead) -> Value {
+        match head {
+            ForHead::VarDecl(var) => self.var_decl(var),
+            ForHead::UsingDecl(using) => self.using_decl(using),
+            ForHead::Pat(pat) => self.pat(pat),
+        }
+    }
+
+    /// Convert a declaration, or `None` for ambient and type-level declarations.
+    fn decl(&self, decl: &Decl) -> Option<Value> {
+        match decl {
+            Decl::Class(class) if !class.declare => {
+                Some(self.class("ClassDeclaration", Some(&class.ident), &class.class))
+            }
+            Decl::Fn(function) if !function.declare => {
+                self.function("FunctionDeclaration", Some(&function.ident), &function.function)
+            }
+            Decl::Var(var) if !var.declare => Some(self.var_decl(var)),
+            Decl::Using(using) => Some(self.using_decl(using)),
+            _ => None,
+        }
+    }
+
+    fn var_decl(&self, var: &VarDecl) -> Value {
+        self.node(
+            "VariableDeclaration",
+            var.span,
+            json!({ "kind": var.kind.as_str(), "declarations": self.var_declarators(&var.decls) }),
+        )
+    }
+
+    fn using_decl(&self, using: &UsingDecl) -> Value {
+        let kind = if using.is_await { "await using" } else { "using" };
+        self.node(
+            "VariableDeclaration",
+            using.span,
+            json!({ "kind": kind, "declarations": self.var_declarators(&using.decls) }),
+        )
+    }
+
+    fn var_declarators(&self, decls: &[VarDeclarator]) -> Vec<Value> {
+        decls
+            .iter()
+            .map(|decl| {
+                self.node(
+                    "VariableDeclarator",
+                    decl.span,
+                    json!({
+                        "id": self.pat(&decl.name),
+                        "init": decl.init.as_deref().map(|init| self.expr(init)),
+                    }),
+                )
+            })
+            .collect()
+    }
+
+    // ----- Functions and classes -----
+
+    /// Convert a function, or `None` for bodiless signatures (overloads, `declare`).
+    fn function(&self, kind: &str, ident: Option<&Ident>, function: &Function) -> Option<Value> {
+        let body = function.body.as_ref()?;
+        let params: Vec<Value> = function.params.iter().map(|param| self.pat(&param.pat)).collect();
+        Some(self.node(
+            kind,
+            function.span,
+            json!({
+                "id": ident.map(|ident| self.ident(ident)),
+                "params": params,
+                "body": self.block(body),
+                "generator": function.is_generator,
+                "async": function.is_async,
+                "expression": false,
+            }),
+        ))
+    }
+
+    fn class(&self, kind: &str, ident: Option<&Ident>, class: &Class) -> Value {
+        let members: Vec<Value> = class
+            .body
+            .iter()
+            .filter_map(|member| self.class_member(member))
+            .collect();
+        self.node(
+            kind,
+            class.span,
+            json!({
+                "id": ident.map(|ident| self.ident(ident)),
+                "superClass": class.super_class.as_deref().map(|super_class| self.expr(super_class)),
+                "body": self.node("ClassBody", class.span, json!({ "body": members })),
+            }),
+        )
+    }
+
+    fn class_member(&self, member: &ClassMember) -> Option<Value> {
+        match member {
+            ClassMember::Constructor(constructor) => {
+                let body = constructor.body.as_ref()?;
+                let params: Vec<Value> = constructor
+                    .params
+                    .iter()
+                    .map(|param| match param {
+                        ParamOrTsParamProp::Param(param) => self.pat(&param.pat),
+                        ParamOrTsParamProp::TsParamProp(prop) => match &prop.param {
+                            TsParamPropParam::Ident(ident) => self.binding_ident(ident),
+                            TsParamPropParam::Assign(assign) => self.assign_pat(assign),
+                        },
+                    })
+                    .collect();
+                let value = self.node(
+                    "FunctionExpression",
+                    constructor.span,
+                    json!({
+                        "id": null,
+                        "params": params,
+                        "body": self.block(body),
+                        "generator": false,
+                        "async": false,
+                        "expression": false,
+                    }),
+                );
+                Some(self.node(
+                    "MethodDefinition",
+                    constructor.span,
+                    json!({
+                        "kind": "constructor",
+                        "key": self.prop_name(&constructor.key),
+                        "computed": false,
+                        "static": false,
+                        "value": value,
+                    }),
+                ))
+            }
+            ClassMember::Method(method) => {
+                if method.is_abstract {
+                    return None;
+                }
+                let value = self.function("FunctionExpression", None, &method.function)?;
+                Some(self.node(
+                    "MethodDefinition",
+                    method.span,
+                    json!({
+                        "kind": method_kind(method.kind),
+                        "key": self.prop_name(&method.key),
+                        "computed": matches!(method.key, PropName::Computed(_)),
+                        "static": method.is_static,
+                        "value": value,
+                    }),
+                ))
+            }
+            ClassMember::PrivateMethod(method) => {
+                let value = self.function("FunctionExpression", None, &method.function)?;
+                Some(self.node(
+                    "MethodDefinition",
+                    method.span,
+                    json!({
+                        "kind": method_kind(method.kind),
+                        "key": self.private_name(&method.key),
+                        "computed": false,
+                        "static": method.is_static,
+                        "value": value,
+                    }),
+                ))
+            }
+            ClassMember::ClassProp(prop) => {
+                if prop.declare || prop.is_abstract {
+                    return None;
+                }
+                Some(self.node(
+                    "PropertyDefinition",
+                    prop.span,
+                    json!({
+                        "key": self.prop_name(&prop.key),
+                        "value": prop.value.as_deref().map(|value| self.expr(value)),
+                        "computed": matches!(prop.key, PropName::Computed(_)),
+                        "static": prop.is_static,
+                    }),
+                ))
+            }
+            ClassMember::PrivateProp(prop) => Some(self.node(
+                "PropertyDefinition",
+                prop.span,
+                json!({
+                    "key": self.private_name(&prop.key),
+                    "value": prop.value.as_deref().map(|value| self.expr(value)),
+                    "computed": false,
+                    "static": prop.is_static,
+                }),
+            )),
+            ClassMember::StaticBlock(block) => Some(self.node(
+                "StaticBlock",
+                block.span,
+                json!({ "body": self.stmts(&block.body.stmts) }),
+            )),
+            ClassMember::AutoAccessor(accessor) => {
+                if accessor.is_abstract {
+                    return None;
+                }
+                let (key, computed) = match &accessor.key {
+                    Key::Private(name) => (self.private_name(name), false),
+                    Key::Public(name) => (self.prop_name(name), matches!(name, PropName::Computed(_))),
+                };
+                Some(self.node(
+                    "AccessorProperty",
+                    accessor.span,
+                    json!({
+                        "key": key,
+                        "value": accessor.value.as_deref().map(|value| self.expr(value)),
+                        "computed": computed,
+                        "static": accessor.is_static,
+                    }),
+                ))
+            }
+            ClassMember::TsIndexSignature(_) | ClassMember::Empty(_) => None,
+        }
+    }
+
+    // ----- Patterns -----
+
+    fn pat(&self, pat: &Pat) -> Value {
+        match pat {
+            Pat::Ident(ident) => self.binding_ident(ident),
+            Pat::Array(array) => self.array_pat(array),
+            Pat::Rest(rest) => self.rest_pat(rest),
+            Pat::Object(object) => self.object_pat(object),
+            Pat::Assign(assign) => self.assign_pat(assign),
+            Pat::Expr(expr) => self.expr(expr),
+            Pat::Invalid(_) => Value::Null,
+        }
+    }
+
+    fn binding_ident(&self, ident: &BindingIdent) -> Value {
+        self.ident(&ident.id)
+    }
+
+    fn array_pat(&self, array: &ArrayPat) -> Value {
+        let elements: Vec<Value> = array
+            .elems
+            .iter()
+            .map(|elem| elem.as_ref().map_or(Value::Null, |elem| self.pat(elem)))
+            .collect();
+        self.node("ArrayPattern", array.span, json!({ "elements": elements }))
+    }
+
+    fn rest_pat(&self, rest: &RestPat) -> Value {
+        self.node("RestElement", rest.span, json!({ "argument": self.pat(&rest.arg) }))
+    }
+
+    fn assign_pat(&self, assign: &AssignPat) -> Value {
+        self.node(
+            "AssignmentPattern",
+            assign.span,
+            json!({ "left": self.pat(&assign.left), "right": self.expr(&assign.right) }),
+        )
+    }
+
+    fn object_pat(&self, object: &ObjectPat) -> Value {
+        let properties: Vec<Value> = object
+            .props
+            .iter()
+            .map(|prop| match prop {
+                ObjectPatProp::KeyValue(key_value) => self.property(
+                    object.span,
+                    "init",
+                    &key_value.key,
+                    self.pat(&key_value.value),
+                    false,
+                    false,
+                ),
+                ObjectPatProp::Assign(assign) => {
+                    let key = self.binding_ident(&assign.key);
+                    let value = match &assign.value {
+                        Some(default) => self.node(
+                            "AssignmentPattern",
+                            assign.span,
+                            json!({ "left": key.clone(), "right": self.expr(default) }),
+                        ),
+                        None => key.clone(),
+                    };
+                    self.node(
+                        "Property",
+                        assign.span,
+                        json!({
+                            "kind": "init",
+                            "key": key,
+                            "value": value,
+                            "computed": false,
+                            "method": false,
+                            "shorthand": true,
+                        }),
+                    )
+                }
+                ObjectPatProp::Rest(rest) => self.rest_pat(rest),
+            })
+            .collect();
+        self.node("ObjectPattern", object.span, json!({ "properties": properties }))
+    }
+
+    fn assign_target(&self, target: &AssignTarget) -> Value {
+        match target {
+            AssignTarget::Simple(simple) => match simple {
+                SimpleAssignTarget::Ident(ident) => self.binding_ident(ident),
+                SimpleAssignTarget::Member(member) => self.member(member),
+                SimpleAssignTarget::SuperProp(super_prop) => self.super_prop(super_prop),
+                SimpleAssignTarget::Paren(paren) => self.expr(&paren.expr),
+                SimpleAssignTarget::OptChain(chain) => self.opt_chain(chain),
+                SimpleAssignTarget::TsAs(ts) => self.expr(&ts.expr),
+                SimpleAssignTarget::TsSatisfies(ts) => self.expr(&ts.expr),
+                SimpleAssignTarget::TsNonNull(ts) => self.expr(&ts.expr),
+                SimpleAssignTarget::TsTypeAssertion(ts) => self.expr(&ts.expr),
+                SimpleAssignTarget::TsInstantiation(ts) => self.expr(&ts.expr),
+                SimpleAssignTarget::Invalid(_) => Value::Null,
+            },
+            AssignTarget::Pat(pat) => match pat {
+                AssignTargetPat::Array(array) => self.array_pat(array),
+                AssignTargetPat::Object(object) => self.object_pat(object),
+                AssignTargetPat::Invalid(_) => Value::Null,
+            },
+        }
+    }
+
+    // ----- Expressions -----
+
+    fn expr(&self, expr: &Expr) -> Value {
+        match expr {
+            Expr::This(this) => self.node("ThisExpression", this.span, json!({})),
+            Expr::Array(array) => {
+                let elements: Vec<Value> = array
+                    .elems
+                    .iter()
+                    .map(|elem| elem.as_ref().map_or(Value::Null, |elem| self.expr_or_spread(elem)))
+                    .collect();
+                self.node("ArrayExpression", array.span, json!({ "elements": elements }))
+            }
+            Expr::Object(object) => self.object(object),
+            Expr::Fn(function) => self
+                .function("FunctionExpression", function.ident.as_ref(), &function.function)
+                .unwrap_or(Value::Null),
+            Expr::Unary(unary) => self.node(
+                "UnaryExpression",
+                unary.span,
+                json!({ "operator": unary.op.as_str(), "prefix": true, "argument": self.expr(&unary.arg) }),
+            ),
+            Expr::Update(update) => self.node(
+                "UpdateExpression",
+                update.span,
+                json!({ "operator": update.op.as_str(), "prefix": update.prefix, "argument": self.expr(&update.arg) }),
+            ),
+            Expr::Bin(bin) => {
+                let kind = match bin.op {
+                    BinaryOp::LogicalAnd | BinaryOp::LogicalOr | BinaryOp::NullishCoalescing => "LogicalExpression",
+                    _ => "BinaryExpression",
+                };
+                self.node(
+                    kind,
+                    bin.span,
+                    json!({ "operator": bin.op.as_str(), "left": self.expr(&bin.left), "right": self.expr(&bin.right) }),
+                )
+            }
+            Expr::Assign(assign) => self.node(
+                "AssignmentExpression",
+                assign.span,
+                json!({
+                    "operator": assign.op.as_str(),
+                    "left": self.assign_target(&assign.left),
+                    "right": self.expr(&assign.right),
+                }),
+            ),
+            Expr::Member(member) => self.member(member),
+            Expr::SuperProp(super_prop) => self.super_prop(super_prop),
+            Expr::Cond(cond) => self.node(
+                "ConditionalExpression",
+                cond.span,
+                json!({
+                    "test": self.expr(&cond.test),
+                    "consequent": self.expr(&cond.cons),
+                    "alternate": self.expr(&cond.alt),
+                }),
+            ),
+            Expr::Call(call) => self.call(call),
+            Expr::New(new) => {
+                let arguments: Vec<Value> = new
+                    .args
+                    .iter()
+                    .flatten()
+                    .map(|arg| self.expr_or_spread(arg))
+                    .collect();
+                self.node(
+                    "NewExpression",
+                    new.span,
+                    json!({ "callee": self.expr(&new.callee), "arguments": arguments }),
+                )
+            }
+            Expr::Seq(seq) => {
+                let expressions: Vec<Value> = seq.exprs.iter().map(|expr| self.expr(expr)).collect();
+                self.node("SequenceExpression", seq.span, json!({ "expressions": expressions }))
+            }
+            Expr::Ident(ident) => self.ident(ident),
+            Expr::Lit(lit) => self.lit(lit),
+            Expr::Tpl(tpl) => self.tpl(tpl),
+            Expr::TaggedTpl(tagged) => self.node(
+                "TaggedTemplateExpression",
+                tagged.span,
+                json!({ "tag": self.expr(&tagged.tag), "quasi": self.tpl(&tagged.tpl) }),
+            ),
+            Expr::Arrow(arrow) => {
+                let params: Vec<Value> = arrow.params.iter().map(|param| self.pat(param)).collect();
+                let (body, expression) = match arrow.body.as_ref() {
+                    BlockStmtOrExpr::BlockStmt(block) => (self.block(block), false),
+                    BlockStmtOrExpr::Expr(expr) => (self.expr(expr), true),
+                };
+                self.node(
+                    "ArrowFunctionExpression",
+                    arrow.span,
+                    json!({
+                        "id": null,
+                        "params": params,
+                        "body": body,
+                        "generator": false,
+                        "async": arrow.is_async,
+                        "expression": expression,
+                    }),
+                )
+            }
+            Expr::Class(class) => self.class("ClassExpression", class.ident.as_ref(), &class.class),
+            Expr::Yield(yield_expr) => self.node(
+                "YieldExpression",
+                yield_expr.span,
+                json!({
+                    "argument": yield_expr.arg.as_deref().map(|arg| self.expr(arg)),
+                    "delegate": yield_expr.delegate,
+                }),
+            ),
+            Expr::MetaProp(meta) => {
+                let (meta_name, property) = match meta.kind {
+                    MetaPropKind::NewTarget => ("new", "target"),
+                    MetaPropKind::ImportMeta => ("import", "meta"),
+                };
+                self.node(
+                    "MetaProperty",
+                    meta.span,
+                    json!({
+                        "meta": self.node("Identifier", Span::default(), json!({ "name": meta_name })),
+                        "property": self.node("Identifier", Span::default(), json!({ "name": property })),
+                    }),
+                )
+            }
+            Expr::Await(await_expr) => self.node(
+                "AwaitExpression",
+                await_expr.span,
+                json!({ "argument": self.expr(&await_expr.arg) }),
+            ),
+            Expr::Paren(paren) => self.expr(&paren.expr),
+            Expr::JSXMember(member) => self.jsx_member(member),
+            Expr::JSXNamespacedName(name) => self.jsx_namespaced_name(name),
+            Expr::JSXEmpty(empty) => self.node("JSXEmptyExpression", empty.span, json!({})),
+            Expr::JSXElement(element) => self.jsx_element(element),
+            Expr::JSXFragment(fragment) => self.jsx_fragment(fragment),
+            Expr::TsTypeAssertion(ts) => self.expr(&ts.expr),
+            Expr::TsConstAssertion(ts) => self.expr(&ts.expr),
+            Expr::TsNonNull(ts) => self.expr(&ts.expr),
+            Expr::TsAs(ts) => self.expr(&ts.expr),
+            Expr::TsInstantiation(ts) => self.expr(&ts.expr),
+            Expr::TsSatisfies(ts) => self.expr(&ts.expr),
+            Expr::PrivateName(name) => self.private_name(name),
+            Expr::OptChain(chain) => self.opt_chain(chain),
+            Expr::Invalid(_) => Value::Null,
+        }
+    }
+
+    fn expr_or_spread(&self, expr: &ExprOrSpread) -> Value {
+        match expr.spread {
+            Some(spread) => self.node(
+                "SpreadElement",
+                spread,
+                json!({ "argument": self.expr(&expr.expr) }),
+            ),
+            None => self.expr(&expr.expr),
+        }
+    }
+
+    fn call(&self, call: &CallExpr) -> Value {
+        let arguments: Vec<Value> = call.args.iter().map(|arg| self.expr_or_spread(arg)).collect();
+        let callee = match &call.callee {
+            Callee::Super(super_token) => self.node("Super", super_token.span, json!({})),
+            Callee::Import(_) => {
+                return self.node(
+                    "ImportExpression",
+                    call.span,
+                    json!({
+                        "source": arguments.first().cloned().unwrap_or(Value::Null),
+                        "options": arguments.get(1).cloned(),
+                    }),
+                );
+            }
+            Callee::Expr(callee) => self.expr(callee),
+        };
+        self.node(
+            "CallExpression",
+            call.span,
+            json!({ "callee": callee, "arguments": arguments, "optional": false }),
+        )
+    }
+
+    fn member(&self, member: &MemberExpr) -> Value {
+        self.member_in(member, false, false)
+    }
+
+    /// Convert a member expression. `optional` marks a `?.` link, and `in_chain`
+    /// continues an enclosing optional chain through the object.
+    fn member_in(&self, member: &MemberExpr, optional: bool, in_chain: bool) -> Value {
+        let (property, computed) = match &member.prop {
+            MemberProp::Ident(ident) => (self.ident_name(ident), false),
+            MemberProp::PrivateName(name) => (self.private_name(name), false),
+            MemberProp::Computed(computed) => (self.expr(&computed.expr), true),
+        };
+        let object = if in_chain {
+            self.chain_element(&member.obj)
+        } else {
+            self.expr(&member.obj)
+        };
+        self.node(
+            "MemberExpression",
+            member.span,
+            json!({ "object": object, "property": property, "computed": computed, "optional": optional }),
+        )
+    }
+
+    fn super_prop(&self, super_prop: &SuperPropExpr) -> Value {
+        let (property, computed) = match &super_prop.prop {
+            SuperProp::Ident(ident) => (self.ident_name(ident), false),
+            SuperProp::Computed(computed) => (self.expr(&computed.expr), true),
+        };
+        self.node(
+            "MemberExpression",
+            super_prop.span,
+            json!({
+                "object": self.node("Super", super_prop.obj.span, json!({})),
+                "property": property,
+                "computed": computed,
+                "optional": false,
+            }),
+        )
+    }
+
+    /// Convert an optional chain, wrapping the outermost link in a `ChainExpression`.
+    fn opt_chain(&self, chain: &OptChainExpr) -> Value {
+        self.node(
+            "ChainExpression",
+            chain.span,
+            json!({ "expression": self.opt_chain_link(chain) }),
+        )
+    }
+
+    /// Convert one link of an optional chain without the `ChainExpression` wrapper.
+    fn opt_chain_link(&self, chain: &OptChainExpr) -> Value {
+        match chain.base.as_ref() {
+            OptChainBase::Member(member) => self.member_in(member, chain.optional, true),
+            OptChainBase::Call(call) => {
+                let arguments: Vec<Value> = call.args.iter().map(|arg| self.expr_or_spread(arg)).collect();
+                self.node(
+                    "CallExpression",
+                    call.span,
+                    json!({
+                        "callee": self.chain_element(&call.callee),
+                        "arguments": arguments,
+                        "optional": chain.optional,
+                    }),
+                )
+            }
+        }
+    }
+
+    /// Convert the object or callee of a chain link, continuing the same chain.
+    fn chain_element(&self, expr: &Expr) -> Value {
+        match expr {
+            Expr::OptChain(chain) => self.opt_chain_link(chain),
+            _ => self.expr(expr),
+        }
+    }
+
+    fn object(&self, object: &ObjectLit) -> Value {
+        let properties: Vec<Value> = object
+            .props
+            .iter()
+            .map(|prop| match prop {
+                PropOrSpread::Spread(spread) => self.node(
+                    "SpreadElement",
+                    spread.dot3_token,
+                    json!({ "argument": self.expr(&spread.expr) }),
+                ),
+                PropOrSpread::Prop(prop) => self.prop(object.span, prop),
+            })
+            .collect();
+        self.node("ObjectExpression", object.span, json!({ "properties": properties }))
+    }
+
+    fn prop(&self, object_span: Span, prop: &Prop) -> Value {
+        match prop {
+            Prop::Shorthand(ident) => {
+                let key = self.ident(ident);
+                self.node(
+                    "Property",
+                    ident.span,
+                    json!({
+                        "kind": "init",
+                        "key": key.clone(),
+                        "value": key,
+                        "computed": false,
+                        "method": false,
+                        "shorthand": true,
+                    }),
+                )
+            }
+            Prop::KeyValue(key_value) => self.property(
+                object_span,
+                "init",
+                &key_value.key,
+                self.expr(&key_value.value),
+                false,
+                false,
+            ),
+            Prop::Assign(assign) => {
+                let key = self.ident(&assign.key);
+                let value = self.node(
+                    "AssignmentPattern",
+                    assign.span,
+                    json!({ "left": key.clone(), "right": self.expr(&assign.value) }),
+                );
+                self.node(
+                    "Property",
+                    assign.span,
+                    json!({
+                        "kind": "init",
+                        "key": key,
+                        "value": value,
+                        "computed": false,
+                        "method": false,
+                        "shorthand": true,
+                    }),
+                )
+            }
+            Prop::Getter(getter) => {
+                let value = self.accessor_function(getter.span, Vec::new(), getter.body.as_ref());
+                self.property(getter.span, "get", &getter.key, value, false, false)
+            }
+            Prop::Setter(setter) => {
+                let value = self.accessor_function(setter.span, vec![self.pat(&setter.param)], setter.body.as_ref());
+                self.property(setter.span, "set", &setter.key, value, false, false)
+            }
+            Prop::Method(method) => {
+                let value = self
+                    .function("FunctionExpression", None, &method.function)
+                    .unwrap_or(Value::Null);
+                self.property(method.function.span, "init", &method.key, value, true, false)
+            }
+        }
+    }
+
+    fn property(&self, span: Span, kind: &str, key: &PropName, value: Value, method: bool, shorthand: bool) -> Value {
+        self.node(
+            "Property",
+            span,
+            json!({
+                "kind": kind,
+                "key": self.prop_name(key),
+                "value": value,
+                "computed": matches!(key, PropName::Computed(_)),
+                "method": method,
+                "shorthand": shorthand,
+            }),
+        )
+    }
+
+    fn accessor_function(&self, span: Span, params: Vec<Value>, body: Option<&BlockStmt>) -> Value {
+        self.node(
+            "FunctionExpression",
+            span,
+            json!({
+                "id": null,
+                "params": params,
+                "body": body.map(|body| self.block(body)),
+                "generator": false,
+                "async": false,
+                "expression": false,
+            }),
+        )
+    }
+
+    fn prop_name(&self, name: &PropName) -> Value {
+        match name {
+            PropName::Ident(ident) => self.ident_name(ident),
+            PropName::Str(str) => self.str_lit(str),
+            PropName::Num(num) => self.num_lit(num),
+            PropName::Computed(computed) => self.expr(&computed.expr),
+            PropName::BigInt(big_int) => self.big_int_lit(big_int),
+        }
+    }
+
+    fn tpl(&self, tpl: &Tpl) -> Value {
+        let quasis: Vec<Value> = tpl
+            .quasis
+            .iter()
+            .map(|quasi| {
+                self.node(
+                    "TemplateElement",
+                    quasi.span,
+                    json!({
+                        "value": {
+                            "raw": quasi.raw.as_str(),
+                            "cooked": quasi.cooked.as_ref().map(|cooked| cooked.to_string_lossy().into_owned()),
+                        },
+                        "tail": quasi.tail,
+                    }),
+                )
+            })
+            .collect();
+        let expressions: Vec<Value> = tpl.exprs.iter().map(|expr| self.expr(expr)).collect();
+        self.node("TemplateLiteral", tpl.span, json!({ "quasis": quasis, "expressions": expressions }))
+    }
+
+    // ----- Identifiers and literals -----
+
+    fn ident(&self, ident: &Ident) -> Value {
+        self.node("Identifier", ident.span, json!({ "name": ident.sym.as_str() }))
+    }
+
+    fn ident_name(&self, ident: &IdentName) -> Value {
+        self.node("Identifier", ident.span, json!({ "name": ident.sym.as_str() }))
+    }
+
+    fn private_name(&self, name: &PrivateName) -> Value {
+        self.node("PrivateIdentifier", name.span, json!({ "name": name.name.as_str() }))
+    }
+
+    fn lit(&self, lit: &Lit) -> Value {
+        match lit {
+            Lit::Str(str) => self.str_lit(str),
+            Lit::Bool(bool) => self.node(
+                "Literal",
+                bool.span,
+                json!({ "value": bool.value, "raw": bool.value.to_string() }),
+            ),
+            Lit::Null(null) => self.node("Literal", null.span, json!({ "value": null, "raw": "null" })),
+            Lit::Num(num) => self.num_lit(num),
+            Lit::BigInt(big_int) => self.big_int_lit(big_int),
+            Lit::Regex(regex) => self.node(
+                "Literal",
+                regex.span,
+                json!({
+                    "value": null,
+                    "raw": format!("/{}/{}", regex.exp, regex.flags),
+                    "regex": { "pattern": regex.exp.as_str(), "flags": regex.flags.as_str() },
+                }),
+            ),
+            Lit::JSXText(text) => self.jsx_text(text),
+        }
+    }
+
+    fn str_lit(&self, str: &Str) -> Value {
+        let value = str.value.to_string_lossy().into_owned();
+        let raw = match &str.raw {
+            Some(raw) => raw.to_string(),
+            None => serde_json::to_string(&value).unwrap_or_default(),
+        };
+        self.node("Literal", str.span, json!({ "value": value, "raw": raw }))
+    }
+
+    fn num_lit(&self, num: &Number) -> Value {
+        let raw = match &num.raw {
+            Some(raw) => raw.to_string(),
+            None => num.value.to_string(),
+        };
+        self.node("Literal", num.span, json!({ "value": num.value, "raw": raw }))
+    }
+
+    fn big_int_lit(&self, big_int: &BigInt) -> Value {
+        let raw = match &big_int.raw {
+            Some(raw) => raw.to_string(),
+            None => format!("{}n", big_int.value),
+        };
+        self.node(
+            "Literal",
+            big_int.span,
+            json!({ "value": null, "raw": raw, "bigint": big_int.value.to_string() }),
+        )
+    }
+
+    // ----- JSX -----
+
+    fn jsx_element(&self, element: &JSXElement) -> Value {
+        let attributes: Vec<Value> = element
+            .opening
+            .attrs
+            .iter()
+            .map(|attr| match attr {
+                JSXAttrOrSpread::JSXAttr(attr) => {
+                    let name = match &attr.name {
+                        JSXAttrName::Ident(ident) => self.jsx_identifier(ident.span, ident.sym.as_str()),
+                        JSXAttrName::JSXNamespacedName(name) => self.jsx_namespaced_name(name),
+                    };
+                    let value = attr.value.as_ref().map(|value| match value {
+                        JSXAttrValue::Str(str) => self.str_lit(str),
+                        JSXAttrValue::JSXExprContainer(container) => self.jsx_expr_container(container),
+                        JSXAttrValue::JSXElement(element) => self.jsx_element(element),
+                        JSXAttrValue::JSXFragment(fragment) => self.jsx_fragment(fragment),
+                    });
+                    self.node("JSXAttribute", attr.span, json!({ "name": name, "value": value }))
+                }
+                JSXAttrOrSpread::SpreadElement(spread) => self.node(
+                    "JSXSpreadAttribute",
+                    spread.dot3_token,
+                    json!({ "argument": self.expr(&spread.expr) }),
+                ),
+            })
+            .collect();
+
+        let opening = self.node(
+            "JSXOpeningElement",
+            element.opening.span,
+            json!({
+                "name": self.jsx_element_name(&element.opening.name),
+                "attributes": attributes,
+                "selfClosing": element.opening.self_closing,
+            }),
+        );
+        let closing = element.closing.as_ref().map(|closing| {
+            self.node(
+                "JSXClosingElement",
+                closing.span,
+                json!({ "name": self.jsx_element_name(&closing.name) }),
+            )
+        });
+
+        self.node(
+            "JSXElement",
+            element.span,
+            json!({
+                "openingElement": opening,
+                "closingElement": closing,
+                "children": self.jsx_children(&element.children),
+            }),
+        )
+    }
+
+    fn jsx_fragment(&self, fragment: &JSXFragment) -> Value {
+        self.node(
+            "JSXFragment",
+            fragment.span,
+            json!({
+                "openingFragment": self.node("JSXOpeningFragment", fragment.opening.span, json!({})),
+                "closingFragment": self.node("JSXClosingFragment", fragment.closing.span, json!({})),
+                "children": self.jsx_children(&fragment.children),
+            }),
+        )
+    }
+
+    fn jsx_children(&self, children: &[JSXElementChild]) -> Vec<Value> {
+        children
+            .iter()
+            .map(|child| match child {
+                JSXElementChild::JSXText(text) => self.jsx_text(text),
+                JSXElementChild::JSXExprContainer(container) => self.jsx_expr_container(container),
+                JSXElementChild::JSXSpreadChild(spread) => self.node(
+                    "JSXSpreadChild",
+                    spread.span,
+                    json!({ "expression": self.expr(&spread.expr) }),
+                ),
+                JSXElementChild::JSXElement(element) => self.jsx_element(element),
+                JSXElementChild::JSXFragment(fragment) => self.jsx_fragment(fragment),
+            })
+            .collect()
+    }
+
+    fn jsx_expr_container(&self, container: &JSXExprContainer) -> Value {
+        let expression = match &container.expr {
+            JSXExpr::JSXEmptyExpr(empty) => self.node("JSXEmptyExpression", empty.span, json!({})),
+            JSXExpr::Expr(expr) => self.expr(expr),
+        };
+        self.node("JSXExpressionContainer", container.span, json!({ "expression": expression }))
+    }
+
+    fn jsx_text(&self, text: &JSXText) -> Value {
+        self.node(
+            "JSXText",
+            text.span,
+            json!({ "value": text.value.as_str(), "raw": text.raw.as_str() }),
+        )
+    }
+
+    fn jsx_element_name(&self, name: &JSXElementName) -> Value {
+        match name {
+            JSXElementName::Ident(ident) => self.jsx_identifier(ident.span, ident.sym.as_str()),
+            JSXElementName::JSXMemberExpr(member) => self.jsx_member(member),
+            JSXElementName::JSXNamespacedName(name) => self.jsx_namespaced_name(name),
+        }
+    }
+
+    fn jsx_member(&self, member: &JSXMemberExpr) -> Value {
+        let object = match &member.obj {
+            JSXObject::JSXMemberExpr(member) => self.jsx_member(member),
+            JSXObject::Ident(ident) => self.jsx_identifier(ident.span, ident.sym.as_str()),
+        };
+        self.node(
+            "JSXMemberExpression",
+            member.span,
+            json!({
+                "object": object,
+                "property": self.jsx_identifier(member.prop.span, member.prop.sym.as_str()),
+            }),
+        )
+    }
+
+    fn jsx_namespaced_name(&self, name: &JSXNamespacedName) -> Value {
+        self.node(
+            "JSXNamespacedName",
+            name.span,
+            json!({
+                "namespace": self.jsx_identifier(name.ns.span, name.ns.sym.as_str()),
+                "name": self.jsx_identifier(name.name.span, name.name.sym.as_str()),
+            }),
+        )
+    }
+
+    fn jsx_identifier(&self, span: Span, name: &str) -> Value {
+        self.node("JSXIdentifier", span, json!({ "name": name }))
+    }
+}
+
+/// ESTree `kind` of a method.
+fn method_kind(kind: MethodKind) -> &'static str {
+    match kind {
+        MethodKind::Method => "method",
+        MethodKind::Getter => "get",
+        MethodKind::Setter => "set",
+    }
+}
@@ -0,0 +1,113 @@
+//! Per-call resolution reports for `SwcTransformer::explain`.
+//!
+//! Lists every gql call in a file with the canonical ID it resolved to and
+//! whether the artifact had an entry for it. Unmatched calls come with the
+//! closest artifact keys, which usually show whether the file path or the AST
+//! path drifted from what the builder recorded.
+
+use serde::{Deserialize, Serialize};
+use swc_core::common::SourceMap;
+
+use crate::artifact::ArtifactSource;
+use crate::types::config_validation::edit_distance;
+use crate::types::{ErrorSpan, PluginError};
+
+use super::analysis::CallResolution;
+
+/// Number of nearest artifact keys listed for an unmatched call.
+const NEAREST_KEY_LIMIT: usize = 5;
+
+/// Resolution report of a file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplainReport {
+    /// File path part of the file's canonical IDs, after `pathMappings`.
+    pub canonical_path: String,
+
+    /// Every gql call found, in source order.
+    pub calls: Vec<ExplainedCall>,
+
+    /// Errors encountered during analysis.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<PluginError>,
+}
+
+/// How a gql call was resolved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplainedCall {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<ErrorSpan>,
+    /// AST path of the definition, if its metadata was found.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ast_path: Option<String>,
+    /// Canonical ID looked up in the artifact, if one was resolved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canonical_id: Option<String>,
+    /// Whether the artifact had a valid entry for the canonical ID.
+    pub matched: bool,
+    /// Artifact keys closest to the canonical ID, for unmatched calls.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub nearest_keys: Vec<String>,
+}
+
+/// Build the report entry of a call resolution.
+pub fn explain_call(
+    cm: &SourceMap,
+    artifact: &dyn ArtifactSource,
+    canonical_path: &str,
+    resolution: &CallResolution,
+) -> ExplainedCall {
+    let nearest_keys = if resolution.matched {
+        Vec::new()
+    } else {
+        nearest_keys(artifact, canonical_path, resolution.ast_path.as_deref())
+    };
+
+    ExplainedCall {
+        span: ErrorSpan::resolve(cm, resolution.span),
+        ast_path: resolution.ast_path.clone(),
+        canonical_id: resolution.canonical_id.as_ref().map(ToString::to_string),
+        matched: resolution.matched,
+        nearest_keys,
+    }
+}
+
+/// Artifact keys closest to a definition at `ast_path` in the file at `canonical_path`.
+///
+/// Keys of the same file are ranked by how close their AST path is. When the
+/// artifact has no keys for the file, keys of other files with the same AST
+/// path are listed instead, since the file path is then the likely mismatch.
+fn nearest_keys(artifact: &dyn ArtifactSource, canonical_path: &str, ast_path: Option<&str>) -> Vec<String> {
+    let prefix = format!("{}::", canonical_path);
+    let mut same_file: Vec<(usize, &str)> = artifact
+        .ids()
+        .filter_map(|id| {
+            let key_path = id.strip_prefix(prefix.as_str())?;
+            let distance = ast_path.map_or(0, |ast_path| edit_distance(ast_path, key_path));
+            Some((distance, id.as_ref()))
+        })
+        .collect();
+
+    if same_file.is_empty() {
+        let Some(ast_path) = ast_path else {
+            return Vec::new();
+        };
+        let suffix = format!("::{}", ast_path);
+        let mut other_files: Vec<String> = artifact
+            .ids()
+            .filter(|id| id.ends_with(suffix.as_str()))
+            .map(ToString::to_string)
+            .collect();
+        other_files.sort();
+        other_files.truncate(NEAREST_KEY_LIMIT);
+        return other_files;
+    }
+
+    same_file.sort();
+    same_file
+        .into_iter()
+        .take(NEAREST_KEY_LIMIT)
+        .map(|(_, id)| id.to_string())
+        .collect()
+}
@@ -0,0 +1,142 @@
+//! Extraction of prebuilds from builder calls (experimental).
+//!
+//! With `extractPrebuilds`, definitions the artifact has no entry for are
+//! resolved from the builder call itself, so small projects can transform
+//! without running the builder. Only the tagged-template builders are
+//! supported, and only when the template alone determines the prebuild:
+//!
+//! - `` fragment("Name", "Type")`{ ... }`() `` yields a fragment prebuild of `Type`
+//! - `` query("Name")`($id: ID!) { ... }`() `` (and `mutation` / `subscription`)
+//!   yields an operation prebuild whose document is parsed from the template
+//!
+//! Templates with interpolations, fragment spreads (which need the spread
+//! fragment's definition), and callback builders are left to the artifact.
+
+use serde_json::Value;
+use swc_core::ecma::ast::{CallExpr, Callee, Expr, ExprOrSpread, Lit, TaggedTpl};
+
+use crate::graphql::document;
+use crate::graphql::parse::parse_document;
+use crate::types::{
+    BuilderArtifactElement, BuilderArtifactElementMetadata, CanonicalId, FragmentPrebuild, OperationPrebuild,
+    PrebuildDocument,
+};
+
+use super::cache::source_hash;
+
+/// Derive the artifact element of a builder call.
+///
+/// `builder_call` is the call the builder callback returns. Returns the
+/// reason when the call is outside the supported subset.
+pub fn extract_element(
+    canonical_id: &CanonicalId,
+    source_path: &str,
+    builder_call: &CallExpr,
+) -> Result<BuilderArtifactElement, String> {
+    let Callee::Expr(callee) = &builder_call.callee else {
+        return Err("the builder is not a tagged template".to_string());
+    };
+    let Expr::TaggedTpl(tagged) = callee.unwrap_parens() else {
+        return Err("the builder is not a tagged template".to_string());
+    };
+    let Expr::Call(tag) = tagged.tag.unwrap_parens() else {
+        return Err("the template tag is not a builder call".to_string());
+    };
+    let Callee::Expr(tag_callee) = &tag.callee else {
+        return Err("the template tag is not a builder call".to_string());
+    };
+    let Expr::Ident(builder) = tag_callee.unwrap_parens() else {
+        return Err("the template tag is not a builder call".to_string());
+    };
+    let text = template_text(tagged)?;
+    let metadata = BuilderArtifactElementMetadata {
+        source_path: source_path.into(),
+        content_hash: source_hash(text),
+    };
+
+    match (builder.sym.as_ref(), string_args(&tag.args).as_deref()) {
+        ("fragment", Some([_, typename])) => {
+            // Only the type condition ends up in the prebuild; the selections are parsed to
+            // catch syntax errors the builder would report (fragment variables parse as an
+            // operation's)
+            parse_document(&format!("query {}", text))?;
+            Ok(BuilderArtifactElement::Fragment {
+                id: canonical_id.clone(),
+                metadata,
+                prebuild: FragmentPrebuild {
+                    typename: typename.clone(),
+                },
+            })
+        }
+        (operation_type @ ("query" | "mutation" | "subscription"), Some([operation_name])) => {
+            let document = parse_document(&format!("{} {}{}", operation_type, operation_name, text))?;
+            if has_fragment_spread(&document) {
+                return Err("fragment spreads require the artifact".to_string());
+            }
+            let document: PrebuildDocument =
+                serde_json::from_value(document).map_err(|e| format!("Failed to convert document: {}", e))?;
+            let [operation] = document.definitions.as_slice() else {
+                return Err("the template defines more than one operation".to_string());
+            };
+            if document::name(operation) != Some(operation_name.as_str()) {
+                return Err("the template must start with the variables or the selection set".to_string());
+            }
+            let variable_names = operation
+                .get("variableDefinitions")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|definition| Some(document::name(definition.get("variable")?)?.to_string()))
+                .collect();
+
+            Ok(BuilderArtifactElement::Operation {
+                id: canonical_id.clone(),
+                metadata,
+                prebuild: OperationPrebuild {
+                    operation_type: operation_type.to_string(),
+                    operation_name: operation_name.as_str().into(),
+                    variable_names,
+                    document,
+                    metadata: None,
+                },
+            })
+        }
+        (name, _) => Err(format!("unsupported builder '{}'", name)),
+    }
+}
+
+/// Get the text of a template without interpolations.
+fn template_text(tagged: &TaggedTpl) -> Result<&str, String> {
+    if !tagged.tpl.exprs.is_empty() {
+        return Err("templates with interpolations are not supported".to_string());
+    }
+    let Some(quasi) = tagged.tpl.quasis.first() else {
+        return Ok("");
+    };
+    Ok(quasi
+        .cooked
+        .as_ref()
+        .and_then(|cooked| cooked.as_str())
+        .unwrap_or(quasi.raw.as_str()))
+}
+
+/// Get the values of arguments that are all string literals.
+fn string_args(args: &[ExprOrSpread]) -> Option<Vec<String>> {
+    args.iter()
+        .map(|arg| match (&arg.spread, &*arg.expr) {
+            (None, Expr::Lit(Lit::Str(s))) => s.value.as_str().map(str::to_string),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Check if a graphql-js AST contains a fragment spread.
+fn has_fragment_spread(node: &Value) -> bool {
+    match node {
+        Value::Object(fields) => {
+            document::kind(node) == Some("FragmentSpread") || fields.values().any(has_fragment_spread)
+        }
+        Value::Array(values) => values.iter().any(has_fragment_spread),
+        _ => false,
+    }
+}
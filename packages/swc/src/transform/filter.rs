@@ -0,0 +1,92 @@
+//! Include/exclude filtering of source files.
+//!
+//! Files matching none of `include` (when set) or any of `exclude` are
+//! returned unchanged without being parsed, so bundler wrappers do not each
+//! need their own filter for `node_modules`, generated folders, or fixtures.
+//!
+//! Supported glob syntax: `*` (any characters within a segment), `**` (any
+//! number of segments), `?` (one character) and `{a,b}` alternatives. Paths
+//! are compared with forward slashes. A pattern not starting with `/` may
+//! match from any directory, so `node_modules/**` matches
+//! `/repo/node_modules/pkg/index.js`.
+
+use crate::types::TransformConfig;
+
+use super::paths::normalize_path;
+
+/// Check if `source_path` passes the configured `include` and `exclude` patterns.
+pub fn is_included(config: &TransformConfig, source_path: &str) -> bool {
+    let included = config.include.is_empty() || config.include.iter().any(|pattern| glob_matches(pattern, source_path));
+    included && !config.exclude.iter().any(|pattern| glob_matches(pattern, source_path))
+}
+
+/// Check if `path` matches the glob `pattern`.
+pub fn glob_matches(pattern: &str, path: &str) -> bool {
+    let path = normalize_path(path);
+    let path_segments: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+
+    expand_braces(pattern).iter().any(|pattern| {
+        let segments: Vec<&str> = pattern.split('/').filter(|segment| !segment.is_empty()).collect();
+        if pattern.starts_with('/') {
+            match_segments(&segments, &path_segments)
+        } else {
+            (0..=path_segments.len()).any(|start| match_segments(&segments, &path_segments[start..]))
+        }
+    })
+}
+
+/// Expand `{a,b}` alternatives (including nested ones) into plain patterns.
+fn expand_braces(pattern: &str) -> Vec<String> {
+    let Some(open) = pattern.find('{') else {
+        return vec![pattern.to_string()];
+    };
+
+    let mut depth = 0;
+    let mut bounds = vec![open];
+    for (offset, c) in pattern[open..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            ',' if depth == 1 => bounds.push(open + offset),
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    bounds.push(open + offset);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    // An unclosed brace is matched literally
+    if depth != 0 {
+        return vec![pattern.to_string()];
+    }
+
+    let close = bounds[bounds.len() - 1];
+    let (prefix, suffix) = (&pattern[..open], &pattern[close + 1..]);
+    bounds
+        .windows(2)
+        .flat_map(|window| expand_braces(&format!("{}{}{}", prefix, &pattern[window[0] + 1..window[1]], suffix)))
+        .collect()
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_segments(rest, &path[skip..])),
+        Some((segment, rest)) => path.split_first().is_some_and(|(first, path_rest)| {
+            let pattern: Vec<char> = segment.chars().collect();
+            let text: Vec<char> = first.chars().collect();
+            match_segment(&pattern, &text) && match_segments(rest, path_rest)
+        }),
+    }
+}
+
+fn match_segment(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => (0..=text.len()).any(|skip| match_segment(rest, &text[skip..])),
+        Some(('?', rest)) => !text.is_empty() && match_segment(rest, &text[1..]),
+        Some((c, rest)) => text.first() == Some(c) && match_segment(rest, &text[1..]),
+    }
+}
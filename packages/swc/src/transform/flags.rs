@@ -0,0 +1,169 @@
+//! Compile-time flags for dev-only definitions.
+//!
+//! With `compileFlags` (e.g., `{ "__DEV__": false }`), gql definitions guarded
+//! by a flag that is off are removed before analysis, so their registrations
+//! are not hoisted out of the dead branch into production bundles.
+//!
+//! Recognized guards:
+//! - `if (__DEV__) { ... } else { ... }` and `if (!__DEV__) ...`, replaced with the live branch
+//! - `devOnly(...)`, replaced with `undefined` when `__DEV__` is off
+//!
+//! Only guards around a gql definition are folded; other dead code is left to
+//! the bundler's minifier.
+
+use std::collections::BTreeMap;
+
+use swc_core::common::util::take::Take;
+use swc_core::common::{SyntaxContext, DUMMY_SP};
+use swc_core::ecma::ast::*;
+use swc_core::ecma::visit::{Visit, VisitMut, VisitMutWith, VisitWith};
+
+use super::analysis::is_gql_definition;
+
+/// Flag that `devOnly(...)` is tied to.
+const DEV_FLAG: &str = "__DEV__";
+const DEV_ONLY_CALLEE: &str = "devOnly";
+
+/// Remove gql definitions guarded by flags that are off.
+///
+/// Returns whether anything was removed.
+pub fn fold_compile_flags(module: &mut Module, flags: &BTreeMap<String, bool>) -> bool {
+    if flags.is_empty() {
+        return false;
+    }
+
+    let mut folder = FlagFolder { flags, folded: false };
+    module.visit_mut_with(&mut folder);
+    folder.folded
+}
+
+struct FlagFolder<'a> {
+    flags: &'a BTreeMap<String, bool>,
+    folded: bool,
+}
+
+impl FlagFolder<'_> {
+    /// Value of a guard condition made of a flag, optionally negated or parenthesized.
+    fn flag_value(&self, test: &Expr) -> Option<bool> {
+        match test {
+            Expr::Ident(ident) => self.flags.get(ident.sym.as_str()).copied(),
+            Expr::Paren(paren) => self.flag_value(&paren.expr),
+            Expr::Unary(UnaryExpr {
+                op: UnaryOp::Bang, arg, ..
+            }) => self.flag_value(arg).map(|value| !value),
+            _ => None,
+        }
+    }
+
+    fn is_dev_only_call(&self, call: &CallExpr) -> bool {
+        let Callee::Expr(callee) = &call.callee else {
+            return false;
+        };
+        matches!(&**callee, Expr::Ident(ident) if ident.sym == DEV_ONLY_CALLEE)
+            && self.flags.get(DEV_FLAG) == Some(&false)
+    }
+}
+
+impl VisitMut for FlagFolder<'_> {
+    fn visit_mut_stmt(&mut self, stmt: &mut Stmt) {
+        stmt.visit_mut_children_with(self);
+
+        let Stmt::If(if_stmt) = stmt else {
+            return;
+        };
+        // `else if (__DEV__) { ... }` with nothing live left
+        if if_stmt.alt.as_deref().is_some_and(is_removed) {
+            if_stmt.alt = None;
+        }
+        let Some(value) = self.flag_value(&if_stmt.test) else {
+            return;
+        };
+        let dead = if value { if_stmt.alt.as_deref() } else { Some(&*if_stmt.cons) };
+        if !dead.is_some_and(contains_gql_definition) {
+            return;
+        }
+
+        self.folded = true;
+        let live = if value { Some(if_stmt.cons.take()) } else { if_stmt.alt.take() };
+        // A removed statement is left as a dummy-span empty statement, and a live
+        // block that declares nothing as a dummy-span block; the enclosing list
+        // drops the former and splices in the latter
+        *stmt = match live.map(|live| *live) {
+            Some(Stmt::Block(mut block)) if !block.stmts.iter().any(|stmt| matches!(stmt, Stmt::Decl(_))) => {
+                block.span = DUMMY_SP;
+                Stmt::Block(block)
+            }
+            Some(live) => live,
+            None => Stmt::dummy(),
+        };
+    }
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        stmts.visit_mut_children_with(self);
+        if self.folded {
+            *stmts = stmts.drain(..).flat_map(splice_folded).collect();
+        }
+    }
+
+    fn visit_mut_module_items(&mut self, items: &mut Vec<ModuleItem>) {
+        items.visit_mut_children_with(self);
+        if self.folded {
+            *items = items
+                .drain(..)
+                .flat_map(|item| match item {
+                    ModuleItem::Stmt(stmt) => splice_folded(stmt).into_iter().map(ModuleItem::Stmt).collect(),
+                    item => vec![item],
+                })
+                .collect();
+        }
+    }
+
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        expr.visit_mut_children_with(self);
+
+        let Expr::Call(call) = expr else {
+            return;
+        };
+        if !self.is_dev_only_call(call) || !call.args.iter().any(|arg| contains_gql_definition(&*arg.expr)) {
+            return;
+        }
+
+        self.folded = true;
+        *expr = Expr::Ident(Ident::new("undefined".into(), call.span, SyntaxContext::empty()));
+    }
+}
+
+/// Check if a statement is an empty statement left by a removed guard.
+fn is_removed(stmt: &Stmt) -> bool {
+    matches!(stmt, Stmt::Empty(empty) if empty.span.is_dummy())
+}
+
+/// Statements a folded guard leaves in the enclosing list.
+fn splice_folded(stmt: Stmt) -> Vec<Stmt> {
+    match stmt {
+        stmt if is_removed(&stmt) => Vec::new(),
+        Stmt::Block(block) if block.span.is_dummy() => block.stmts,
+        stmt => vec![stmt],
+    }
+}
+
+/// Check if a node contains a gql definition call.
+fn contains_gql_definition<N: VisitWith<GqlDefinitionFinder>>(node: &N) -> bool {
+    let mut finder = GqlDefinitionFinder { found: false };
+    node.visit_with(&mut finder);
+    finder.found
+}
+
+struct GqlDefinitionFinder {
+    found: bool,
+}
+
+impl Visit for GqlDefinitionFinder {
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        if is_gql_definition(call) {
+            self.found = true;
+            return;
+        }
+        call.visit_children_with(self);
+    }
+}
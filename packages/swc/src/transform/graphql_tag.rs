@@ -0,0 +1,73 @@
+//! graphql-tag interop.
+//!
+//! With `graphqlTagInterop`, templates tagged with a binding imported from
+//! `graphql-tag` are resolved like configured template tags, and the import is
+//! dropped once none of its bindings are referenced anymore. Apollo codebases
+//! can then move to the runtime without rewriting their documents first.
+
+use std::collections::HashSet;
+
+use swc_core::atoms::Atom;
+use swc_core::ecma::ast::*;
+use swc_core::ecma::visit::{Visit, VisitWith};
+
+pub const GRAPHQL_TAG_MODULE: &str = "graphql-tag";
+
+/// Check if `import` imports from graphql-tag.
+pub fn is_graphql_tag_import(import: &ImportDecl) -> bool {
+    import.src.value.to_string_lossy() == GRAPHQL_TAG_MODULE
+}
+
+/// Local names of the graphql-tag tag function imported by the module.
+///
+/// Covers `import gql from "graphql-tag"` and `import { gql } from "graphql-tag"`
+/// under any local name. Type-only imports are skipped.
+pub fn graphql_tag_bindings(module: &Module) -> Vec<String> {
+    let mut bindings = Vec::new();
+    for item in &module.body {
+        let ModuleItem::ModuleDecl(ModuleDecl::Import(import)) = item else {
+            continue;
+        };
+        if import.type_only || !is_graphql_tag_import(import) {
+            continue;
+        }
+
+        for specifier in &import.specifiers {
+            let local = match specifier {
+                ImportSpecifier::Default(default) => &default.local,
+                ImportSpecifier::Named(named) if !named.is_type_only && imported_name(named) == "gql" => &named.local,
+                _ => continue,
+            };
+            bindings.push(local.sym.to_string());
+        }
+    }
+    bindings
+}
+
+/// Identifiers referenced anywhere in the module outside import declarations.
+pub fn referenced_names(module: &Module) -> HashSet<Atom> {
+    let mut collector = ReferenceCollector::default();
+    module.visit_with(&mut collector);
+    collector.names
+}
+
+fn imported_name(named: &ImportNamedSpecifier) -> String {
+    match &named.imported {
+        Some(ModuleExportName::Ident(ident)) => ident.sym.to_string(),
+        Some(ModuleExportName::Str(s)) => s.value.to_string_lossy().into_owned(),
+        None => named.local.sym.to_string(),
+    }
+}
+
+#[derive(Default)]
+struct ReferenceCollector {
+    names: HashSet<Atom>,
+}
+
+impl Visit for ReferenceCollector {
+    fn visit_import_decl(&mut self, _: &ImportDecl) {}
+
+    fn visit_ident(&mut self, ident: &Ident) {
+        self.names.insert(ident.sym.clone());
+    }
+}
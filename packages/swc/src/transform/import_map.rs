@@ -0,0 +1,267 @@
+//! Import map resolution for graphql-system alias matching.
+//!
+//! Projects using import maps (Deno, or import-map based Vite setups) or
+//! package.json subpath imports (`#graphql-system`) and exports refer to the
+//! graphql-system module through specifiers that only the map knows about.
+//! Specifiers that resolve to the graphql-system module are added to the
+//! aliases the import manager checks.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::types::config::{ImportMap, PackageManifest, TransformConfig};
+
+use super::paths::normalize_path;
+
+/// Aliases identifying graphql-system imports in the file at `source_path`.
+///
+/// Returns the configured aliases plus every import map specifier and
+/// package.json subpath that resolves to the graphql-system module for this
+/// importer.
+pub fn graphql_system_aliases(config: &TransformConfig, source_path: &str) -> Vec<String> {
+    let mut aliases = config.graphql_system_aliases.clone();
+    let mut add = |specifier: String| {
+        if !aliases.contains(&specifier) {
+            aliases.push(specifier);
+        }
+    };
+
+    if let Some(import_map) = &config.import_map {
+        for (specifier, address) in effective_imports(import_map, source_path) {
+            if targets_graphql_system(import_map, address, config) {
+                add(specifier.trim_end_matches('/').to_string());
+            }
+        }
+    }
+
+    // Private imports resolve through the importer's nearest package.json
+    let importer = normalize_path(source_path);
+    let importer_package = config
+        .package_manifests
+        .iter()
+        .filter(|package| is_within(&importer, &package.dir))
+        .max_by_key(|package| package.dir.len());
+    if let Some(package) = importer_package {
+        for (key, target) in &package.imports {
+            package_specifiers(package, key, target, config).into_iter().for_each(&mut add);
+        }
+    }
+
+    for package in &config.package_manifests {
+        let (Some(name), Some(exports)) = (&package.name, &package.exports) else {
+            continue;
+        };
+        for (subpath, target) in export_entries(exports) {
+            let key = match subpath.strip_prefix('.') {
+                Some(rest) => format!("{}{}", name, rest),
+                None => continue,
+            };
+            package_specifiers(package, &key, target, config).into_iter().for_each(&mut add);
+        }
+    }
+
+    aliases
+}
+
+/// Check whether `path` is `dir` or within it.
+fn is_within(path: &str, dir: &str) -> bool {
+    let dir = normalize_path(dir);
+    let dir = dir.trim_end_matches('/');
+    path.strip_prefix(dir).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// `(subpath, target)` entries of a package.json `exports` field.
+///
+/// A string, array, or conditions object (keys not starting with `.`) is
+/// shorthand for the `"."` subpath.
+fn export_entries(exports: &Value) -> Vec<(&str, &Value)> {
+    match exports {
+        Value::Object(entries) if entries.keys().all(|key| key.starts_with('.')) => {
+            entries.iter().map(|(subpath, target)| (subpath.as_str(), target)).collect()
+        }
+        _ => vec![(".", exports)],
+    }
+}
+
+/// Every string target of a package.json target, under any condition.
+fn target_strings(target: &Value) -> Vec<&str> {
+    match target {
+        Value::String(target) => vec![target.as_str()],
+        Value::Array(targets) => targets.iter().flat_map(target_strings).collect(),
+        Value::Object(conditions) => conditions.values().flat_map(target_strings).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Specifiers of a package.json `imports` or `exports` entry that resolve to
+/// the graphql-system module.
+///
+/// For subpath patterns (`"#gql/*": "./src/gql/*.ts"`), the specifiers are
+/// the pattern with `*` replaced by the part matching the graphql-system file.
+fn package_specifiers(package: &PackageManifest, key: &str, target: &Value, config: &TransformConfig) -> Vec<String> {
+    let mut specifiers = Vec::new();
+    for target in target_strings(target) {
+        // Targets outside the package (imports may map to other packages) can only name an alias
+        if !target.starts_with("./") {
+            if !key.contains('*') && names_alias(target, config) {
+                specifiers.push(key.to_string());
+            }
+            continue;
+        }
+
+        let Some(system_path) = &config.graphql_system_path else {
+            continue;
+        };
+        let package_dir = normalize_path(&package.dir);
+        let resolved = normalize_segments(&format!("{}/{}", package_dir.trim_end_matches('/'), target));
+        let system_path = normalize_path(system_path);
+
+        let Some((key_prefix, key_suffix)) = key.split_once('*') else {
+            if matches_system_path(&resolved, &system_path) {
+                specifiers.push(key.to_string());
+            }
+            continue;
+        };
+        let Some((target_prefix, target_suffix)) = resolved.split_once('*') else {
+            continue;
+        };
+
+        let system_module = strip_extension(&system_path);
+        let forms = [Some(system_path.as_ref()), Some(system_module), system_module.strip_suffix("/index")];
+        for form in forms.into_iter().flatten() {
+            let matched = form.strip_prefix(target_prefix).and_then(|rest| rest.strip_suffix(target_suffix));
+            if let Some(matched) = matched.filter(|matched| !matched.is_empty()) {
+                specifiers.push(format!("{}{}{}", key_prefix, matched, key_suffix));
+            }
+        }
+    }
+    specifiers
+}
+
+/// Specifier mappings that apply to `referrer`.
+///
+/// Scopes matching the referrer override top-level imports, and more specific
+/// (longer) scopes override less specific ones.
+fn effective_imports<'a>(import_map: &'a ImportMap, referrer: &str) -> HashMap<&'a str, &'a str> {
+    let referrer = normalize_path(referrer);
+
+    let mut scopes: Vec<(String, &HashMap<String, String>)> = import_map
+        .scopes
+        .iter()
+        .map(|(prefix, imports)| (resolve_address(import_map, prefix), imports))
+        .filter(|(prefix, _)| {
+            if prefix.ends_with('/') {
+                referrer.starts_with(prefix.as_str())
+            } else {
+                referrer == *prefix
+            }
+        })
+        .collect();
+    scopes.sort_by_key(|(prefix, _)| prefix.len());
+
+    let mut imports: HashMap<&str, &str> = import_map
+        .imports
+        .iter()
+        .map(|(specifier, address)| (specifier.as_str(), address.as_str()))
+        .collect();
+    for (_, scope) in scopes {
+        imports.extend(scope.iter().map(|(specifier, address)| (specifier.as_str(), address.as_str())));
+    }
+
+    imports
+}
+
+/// Check whether an import map address points at the graphql-system module.
+///
+/// The address may name a configured alias, or a path to the graphql-system
+/// file or its directory (with or without the extension).
+fn targets_graphql_system(import_map: &ImportMap, address: &str, config: &TransformConfig) -> bool {
+    let address = address.trim_end_matches('/');
+    if names_alias(address, config) {
+        return true;
+    }
+
+    let Some(system_path) = &config.graphql_system_path else {
+        return false;
+    };
+    if !is_path_address(address) {
+        return false;
+    }
+
+    let resolved = resolve_address(import_map, address);
+    matches_system_path(&resolved, &normalize_path(system_path))
+}
+
+/// Check whether a specifier or address names a configured alias or one of its subpaths.
+fn names_alias(address: &str, config: &TransformConfig) -> bool {
+    config
+        .graphql_system_aliases
+        .iter()
+        .any(|alias| address == alias || address.starts_with(&format!("{}/", alias)))
+}
+
+/// Check whether a resolved path is the graphql-system file or its directory
+/// (with or without the extension).
+fn matches_system_path(resolved: &str, system_path: &str) -> bool {
+    let system_module = strip_extension(system_path);
+    let system_dir = system_module.strip_suffix("/index");
+
+    resolved == system_path || strip_extension(resolved) == system_module || system_dir == Some(resolved)
+}
+
+/// Check whether an address is a path or file URL rather than a bare specifier.
+fn is_path_address(address: &str) -> bool {
+    address.starts_with('/') || address.starts_with("./") || address.starts_with("../") || address.starts_with("file://")
+}
+
+/// Resolve an address against the import map's base directory.
+///
+/// Trailing slashes are kept, since they distinguish prefix mappings.
+fn resolve_address(import_map: &ImportMap, address: &str) -> String {
+    let address = address.replace('\\', "/");
+    let address = address.strip_prefix("file://").unwrap_or(&address);
+
+    let joined = match &import_map.base_dir {
+        Some(base_dir) if !address.starts_with('/') => {
+            format!("{}/{}", base_dir.replace('\\', "/").trim_end_matches('/'), address)
+        }
+        _ => address.to_string(),
+    };
+
+    normalize_segments(&joined)
+}
+
+/// Collapse `.` and `..` segments in a forward-slash path.
+fn normalize_segments(path: &str) -> String {
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "." => {}
+            ".." => match segments.last() {
+                // Already at the root of an absolute path
+                Some(&"") => {}
+                Some(last) if *last != ".." => {
+                    segments.pop();
+                }
+                _ => segments.push(".."),
+            },
+            _ => segments.push(segment),
+        }
+    }
+
+    let mut normalized = segments.join("/");
+    if path.ends_with('/') && !normalized.ends_with('/') {
+        normalized.push('/');
+    }
+    normalized
+}
+
+/// Remove the extension from the last segment of a path.
+fn strip_extension(path: &str) -> &str {
+    let name_start = path.rfind('/').map_or(0, |index| index + 1);
+    match path[name_start..].rfind('.') {
+        Some(dot) if dot > 0 => &path[..name_start + dot],
+        _ => path,
+    }
+}
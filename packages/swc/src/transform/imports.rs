@@ -1,14 +1,22 @@
 //! Import management module.
 //!
 //! This module handles:
-//! - Adding the `@soda-gql/core/runtime` import/require
+//! - Adding the runtime import/require (`@soda-gql/core/runtime` by default)
 //! - Removing the `graphql-system` imports
+//! - Removing graphql-tag imports left unused by the graphql-tag interop
 
-use swc_core::common::{SyntaxContext, DUMMY_SP};
+use swc_core::common::{Span, SyntaxContext, DUMMY_SP};
 use swc_core::ecma::ast::*;
 use swc_core::ecma::visit::{VisitMut, VisitMutWith};
 
-const RUNTIME_MODULE: &str = "@soda-gql/core/runtime";
+use crate::types::config::OutputProfile;
+
+use super::directive::is_directive;
+use super::graphql_tag::{is_graphql_tag_import, referenced_names};
+
+pub const RUNTIME_MODULE: &str = "@soda-gql/core/runtime";
+/// Default runtime module of server modules with `splitServerClient`, without the browser cache.
+pub const SERVER_RUNTIME_MODULE: &str = "@soda-gql/runtime/server";
 const RUNTIME_IMPORT_NAME: &str = "gqlRuntime";
 const CJS_RUNTIME_NAME: &str = "__soda_gql_runtime";
 
@@ -16,8 +24,27 @@ const CJS_RUNTIME_NAME: &str = "__soda_gql_runtime";
 pub struct ImportManager {
     needs_runtime_import: bool,
     is_cjs: bool,
+    /// Module the runtime is imported from
+    runtime_module: String,
+    output_profile: OutputProfile,
     graphql_system_aliases: Vec<String>,
+    /// Whether to drop graphql-tag import bindings that are no longer referenced
+    graphql_tag_interop: bool,
+    /// Whether to keep graphql-system imports because some gql calls were left in place
+    keep_graphql_system: bool,
     has_added_import: bool,
+    /// Module specifiers of imports added or removed while visiting
+    changes: ImportChanges,
+}
+
+/// Module specifiers whose imports were added or removed by the transformation.
+#[derive(Debug, Clone, Default)]
+pub struct ImportChanges {
+    /// Modules newly imported (the runtime module).
+    pub added: Vec<String>,
+    /// Modules no longer imported (graphql-system imports, and graphql-tag with
+    /// the interop), without duplicates.
+    pub removed: Vec<String>,
 }
 
 impl ImportManager {
@@ -25,16 +52,55 @@ impl ImportManager {
         Self {
             needs_runtime_import,
             is_cjs,
+            runtime_module: RUNTIME_MODULE.to_string(),
+            output_profile: OutputProfile::Default,
             graphql_system_aliases: graphql_system_aliases.to_vec(),
+            graphql_tag_interop: false,
+            keep_graphql_system: false,
             has_added_import: false,
+            changes: ImportChanges::default(),
+        }
+    }
+
+    /// Take the imports added and removed by the last visit.
+    pub fn take_changes(&mut self) -> ImportChanges {
+        std::mem::take(&mut self.changes)
+    }
+
+    /// Record the removal of an import of `specifier`.
+    fn record_removed(&mut self, specifier: String) {
+        if !self.changes.removed.contains(&specifier) {
+            self.changes.removed.push(specifier);
         }
     }
 
+    /// Import the runtime from `runtime_module` instead of the default module.
+    pub fn with_runtime_module(mut self, runtime_module: &str) -> Self {
+        self.runtime_module = runtime_module.to_string();
+        self
+    }
+
+    /// Generate the runtime require with the syntax of `profile`.
+    pub fn with_output_profile(mut self, profile: OutputProfile) -> Self {
+        self.output_profile = profile;
+        self
+    }
+
+    /// Drop graphql-tag import bindings whose templates were all replaced.
+    pub fn with_graphql_tag_interop(mut self, enabled: bool) -> Self {
+        self.graphql_tag_interop = enabled;
+        self
+    }
+
+    /// Keep graphql-system imports, for modules still calling `gql` after the transform.
+    pub fn with_keep_graphql_system(mut self, keep: bool) -> Self {
+        self.keep_graphql_system = keep;
+        self
+    }
+
     /// Check if a specifier is a graphql-system import.
     fn is_graphql_system_import(&self, specifier: &str) -> bool {
-        self.graphql_system_aliases.iter().any(|alias| {
-            specifier == alias || specifier.starts_with(&format!("{}/", alias))
-        })
+        is_graphql_system_specifier(&self.graphql_system_aliases, specifier)
     }
 
     /// Create the ESM runtime import.
@@ -50,7 +116,7 @@ impl ImportManager {
             })],
             src: Box::new(Str {
                 span: DUMMY_SP,
-                value: RUNTIME_MODULE.into(),
+                value: self.runtime_module.as_str().into(),
                 raw: None,
             }),
             type_only: false,
@@ -65,7 +131,7 @@ impl ImportManager {
         ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
             span: DUMMY_SP,
             ctxt: SyntaxContext::empty(),
-            kind: VarDeclKind::Const,
+            kind: self.output_profile.declaration_kind(),
             declare: false,
             decls: vec![VarDeclarator {
                 span: DUMMY_SP,
@@ -85,7 +151,7 @@ impl ImportManager {
                         spread: None,
                         expr: Box::new(Expr::Lit(Lit::Str(Str {
                             span: DUMMY_SP,
-                            value: RUNTIME_MODULE.into(),
+                            value: self.runtime_module.as_str().into(),
                             raw: None,
                         }))),
                     }],
@@ -108,7 +174,7 @@ impl ImportManager {
 
     /// Check if an import already has the runtime import.
     fn has_runtime_import(&self, import: &ImportDecl) -> bool {
-        if !wtf8_eq(&import.src.value, RUNTIME_MODULE) {
+        if !wtf8_eq(&import.src.value, &self.runtime_module) {
             return false;
         }
 
@@ -132,20 +198,52 @@ impl VisitMut for ImportManager {
         let mut import_insert_pos = 0;
         let mut found_non_import = false;
         let mut existing_runtime_import_idx: Option<usize> = None;
+        let mut in_prologue = true;
+        let referenced = self.graphql_tag_interop.then(|| referenced_names(module));
+
+        for item in module.body.iter() {
+            // Keep the directive prologue (e.g., "use client") ahead of added imports
+            if in_prologue && is_directive(item) {
+                new_body.push(item.clone());
+                import_insert_pos = new_body.len();
+                continue;
+            }
+            in_prologue = false;
 
-        for (_idx, item) in module.body.iter().enumerate() {
             match item {
                 // Handle ESM imports
                 ModuleItem::ModuleDecl(ModuleDecl::Import(import)) => {
                     let specifier = wtf8_to_string(&import.src.value);
 
                     // Skip graphql-system imports
-                    if self.is_graphql_system_import(&specifier) {
+                    if self.is_graphql_system_import(&specifier) && !self.keep_graphql_system {
+                        self.record_removed(specifier);
+                        continue;
+                    }
+
+                    // Drop graphql-tag bindings no longer referenced after template replacement
+                    let drops_unused = !import.specifiers.is_empty() && is_graphql_tag_import(import);
+                    if let Some(referenced) = referenced.as_ref().filter(|_| drops_unused) {
+                        let mut import = import.clone();
+                        import.specifiers.retain(|specifier| {
+                            let local = match specifier {
+                                ImportSpecifier::Named(named) => &named.local,
+                                ImportSpecifier::Default(default) => &default.local,
+                                ImportSpecifier::Namespace(namespace) => &namespace.local,
+                            };
+                            referenced.contains(&local.sym)
+                        });
+                        if import.specifiers.is_empty() {
+                            self.record_removed(specifier);
+                            continue;
+                        }
+                        import_insert_pos = new_body.len() + 1;
+                        new_body.push(ModuleItem::ModuleDecl(ModuleDecl::Import(import)));
                         continue;
                     }
 
                     // Check if this is already the runtime import
-                    if specifier == RUNTIME_MODULE {
+                    if specifier == self.runtime_module {
                         existing_runtime_import_idx = Some(new_body.len());
                     }
 
@@ -156,12 +254,16 @@ impl VisitMut for ImportManager {
                 // Handle CJS require statements
                 ModuleItem::Stmt(Stmt::Decl(Decl::Var(var_decl))) => {
                     // Filter out graphql-system requires
-                    let filtered_decls: Vec<VarDeclarator> = var_decl
+                    let (removed_decls, filtered_decls): (Vec<VarDeclarator>, Vec<VarDeclarator>) = var_decl
                         .decls
                         .iter()
-                        .filter(|decl| !self.is_graphql_system_require(decl))
                         .cloned()
-                        .collect();
+                        .partition(|decl| !self.keep_graphql_system && self.is_graphql_system_require(decl));
+                    for decl in &removed_decls {
+                        if let Some(specifier) = decl.init.as_deref().and_then(extract_require_specifier) {
+                            self.record_removed(specifier);
+                        }
+                    }
 
                     if filtered_decls.is_empty() {
                         // All declarations were graphql-system requires, skip
@@ -202,7 +304,7 @@ impl VisitMut for ImportManager {
         // Add runtime import if needed
         if self.needs_runtime_import && !self.has_added_import {
             // Check if we already have the runtime import
-            let already_has_import = existing_runtime_import_idx.map_or(false, |idx| {
+            let already_has_import = existing_runtime_import_idx.is_some_and(|idx| {
                 if let ModuleItem::ModuleDecl(ModuleDecl::Import(import)) = &new_body[idx] {
                     self.has_runtime_import(import)
                 } else {
@@ -231,6 +333,7 @@ impl VisitMut for ImportManager {
                         self.create_esm_import()
                     };
                     new_body.insert(import_insert_pos, runtime_import);
+                    self.changes.added.push(self.runtime_module.clone());
                 }
                 self.has_added_import = true;
             }
@@ -240,6 +343,61 @@ impl VisitMut for ImportManager {
     }
 }
 
+/// Check if a module item is the runtime require added by [`ImportManager`].
+pub fn is_runtime_require(item: &ModuleItem) -> bool {
+    let ModuleItem::Stmt(Stmt::Decl(Decl::Var(var_decl))) = item else {
+        return false;
+    };
+    var_decl.decls.iter().any(|decl| {
+        matches!(&decl.name, Pat::Ident(binding) if atom_eq(&binding.id.sym, CJS_RUNTIME_NAME))
+    })
+}
+
+/// Find the runtime import or require left by a previous transform.
+///
+/// # Returns
+/// Span of the import of `gqlRuntime` from one of `runtime_modules`, or of the CJS runtime require
+pub fn find_runtime_import(module: &Module, runtime_modules: &[&str]) -> Option<Span> {
+    module.body.iter().find_map(|item| match item {
+        ModuleItem::ModuleDecl(ModuleDecl::Import(import)) => {
+            let from_runtime = runtime_modules.iter().any(|runtime| wtf8_eq(&import.src.value, runtime));
+            let imports_runtime = import.specifiers.iter().any(|spec| {
+                matches!(spec, ImportSpecifier::Named(named) if atom_eq(&named.local.sym, RUNTIME_IMPORT_NAME))
+            });
+            (from_runtime && imports_runtime).then_some(import.span)
+        }
+        ModuleItem::Stmt(Stmt::Decl(Decl::Var(var_decl))) if is_runtime_require(item) => Some(var_decl.span),
+        _ => None,
+    })
+}
+
+/// Check if a specifier is one of the graphql-system aliases or a subpath of one.
+fn is_graphql_system_specifier(graphql_system_aliases: &[String], specifier: &str) -> bool {
+    graphql_system_aliases
+        .iter()
+        .any(|alias| specifier == alias || specifier.starts_with(&format!("{}/", alias)))
+}
+
+/// Find the first value import or require of the graphql-system.
+///
+/// Type-only imports are skipped, since they are erased rather than transformed.
+///
+/// # Returns
+/// Span and specifier of the import or require declaration
+pub fn find_graphql_system_import(module: &Module, graphql_system_aliases: &[String]) -> Option<(Span, String)> {
+    module.body.iter().find_map(|item| match item {
+        ModuleItem::ModuleDecl(ModuleDecl::Import(import)) if !import.type_only => {
+            let specifier = wtf8_to_string(&import.src.value);
+            is_graphql_system_specifier(graphql_system_aliases, &specifier).then_some((import.span, specifier))
+        }
+        ModuleItem::Stmt(Stmt::Decl(Decl::Var(var_decl))) => var_decl.decls.iter().find_map(|decl| {
+            let specifier = extract_require_specifier(decl.init.as_deref()?)?;
+            is_graphql_system_specifier(graphql_system_aliases, &specifier).then_some((var_decl.span, specifier))
+        }),
+        _ => None,
+    })
+}
+
 /// Extract the module specifier from a require() call.
 fn extract_require_specifier(expr: &Expr) -> Option<String> {
     match expr {
@@ -0,0 +1,265 @@
+//! Incremental re-parsing of edited sources.
+//!
+//! Editors and HMR servers re-transform a file after every few keystrokes.
+//! `transformIncremental` receives the edits instead of the whole file, applies
+//! them to the previous source, and re-parses only the top-level statements
+//! around the changed range. Statements before it are reused as parsed, and
+//! statements after it are reused with their spans shifted by the length
+//! change. Analysis and emit then run on the whole module as usual.
+//!
+//! The re-parsed range always includes one unchanged statement on each side,
+//! so edits that join or split statements (e.g., through automatic semicolon
+//! insertion) are parsed the same as in a full parse. When the range cannot be
+//! parsed on its own (an unterminated comment or template, an unbalanced
+//! brace), the caller falls back to parsing the whole file.
+
+use std::ops::Range;
+
+use serde::Deserialize;
+use swc_core::common::comments::{Comments, SingleThreadedComments};
+use swc_core::common::input::StringInput;
+use swc_core::common::{BytePos, FileName, SourceMap, Span, Spanned};
+use swc_core::ecma::ast::{EsVersion, Module};
+use swc_core::ecma::parser::{lexer::Lexer, Parser};
+use swc_core::ecma::visit::{VisitMut, VisitMutWith};
+
+use super::cache::ModuleCache;
+use super::transformer::parser_syntax;
+
+/// A replacement of a range of the previous source.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextEdit {
+    /// Start of the replaced range, in UTF-16 code units (JavaScript string offsets).
+    pub start: usize,
+    /// End of the replaced range (exclusive), in UTF-16 code units.
+    pub end: usize,
+    /// Replacement text.
+    pub text: String,
+}
+
+/// Source after applying edits, with the byte range of the previous source they changed.
+pub struct EditedSource {
+    pub source_code: String,
+    /// Bytes of the previous source covering every edit.
+    pub changed: Range<usize>,
+    /// Length change in bytes; positions after `changed` move by this much.
+    pub delta: i64,
+}
+
+/// Apply edits to the source a file was last parsed from, returning the edited source.
+///
+/// The statements around the edits are re-parsed and the updated module is
+/// cached under the edited source, so the following transform of it skips
+/// parsing. If they cannot be re-parsed on their own, nothing is cached and
+/// the transform parses the whole file.
+pub fn edit_cached(cache: &ModuleCache, source_path: &str, edits: &[TextEdit]) -> Result<String, String> {
+    let comments = SingleThreadedComments::default();
+    let (previous_source, previous) = cache.latest(source_path, &comments).ok_or_else(|| {
+        format!(
+            "No previous source for {}; transform the full source before applying edits",
+            source_path
+        )
+    })?;
+    let edited = apply_edits(&previous_source, edits)?;
+
+    // Every transform parses into a fresh source map, where the file starts at the same position
+    let file_start = SourceMap::default()
+        .new_source_file(FileName::Anon.into(), String::new())
+        .start_pos;
+    if let Some(module) = reparse_edited(&previous, &comments, source_path, &edited, file_start) {
+        cache.insert(source_path, &edited.source_code, &module, &comments);
+    }
+    Ok(edited.source_code)
+}
+
+/// Apply edits to the previous source.
+///
+/// All offsets refer to the previous source, so edits must not overlap; they
+/// may be given in any order.
+pub fn apply_edits(source_code: &str, edits: &[TextEdit]) -> Result<EditedSource, String> {
+    let mut ranges = edits
+        .iter()
+        .map(|edit| {
+            let start = byte_offset(source_code, edit.start);
+            let end = byte_offset(source_code, edit.end);
+            match (start, end) {
+                (Some(start), Some(end)) if start <= end => Ok((start..end, edit.text.as_str())),
+                _ => Err(format!(
+                    "Edit range {}..{} is not within the previous source",
+                    edit.start, edit.end
+                )),
+            }
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    ranges.sort_by_key(|(range, _)| range.start);
+
+    let mut edited = String::with_capacity(source_code.len());
+    let mut copied = 0;
+    for (range, text) in &ranges {
+        if range.start < copied {
+            return Err("Edits overlap".to_string());
+        }
+        edited.push_str(&source_code[copied..range.start]);
+        edited.push_str(text);
+        copied = range.end;
+    }
+    edited.push_str(&source_code[copied..]);
+
+    let changed = match (ranges.first(), ranges.last()) {
+        (Some((first, _)), Some((last, _))) => first.start..last.end,
+        _ => 0..0,
+    };
+    Ok(EditedSource {
+        delta: edited.len() as i64 - source_code.len() as i64,
+        source_code: edited,
+        changed,
+    })
+}
+
+/// Convert a UTF-16 offset into a byte offset, if it falls on a character boundary.
+fn byte_offset(source_code: &str, utf16_offset: usize) -> Option<usize> {
+    let mut units = 0;
+    for (index, c) in source_code.char_indices() {
+        if units == utf16_offset {
+            return Some(index);
+        }
+        if units > utf16_offset {
+            return None;
+        }
+        units += c.len_utf16();
+    }
+    (units == utf16_offset).then_some(source_code.len())
+}
+
+/// Re-parse the statements of `previous` around the edited range.
+///
+/// `previous` and `comments` are the parse of the previous source, which
+/// started at `file_start`. Returns the module of the edited source with
+/// `comments` updated to match, or `None` when the whole file has to be
+/// parsed instead.
+pub fn reparse_edited(
+    previous: &Module,
+    comments: &SingleThreadedComments,
+    source_path: &str,
+    edited: &EditedSource,
+    file_start: BytePos,
+) -> Option<Module> {
+    if previous.shebang.is_some() || previous.body.is_empty() {
+        return None;
+    }
+    let offset = |pos: BytePos| (pos.0 - file_start.0) as usize;
+
+    // The statements touching the edits, plus one unchanged statement on each side
+    let body = &previous.body;
+    let before = body.iter().take_while(|item| offset(item.span().hi) < edited.changed.start).count();
+    let after = body.iter().take_while(|item| offset(item.span().lo) <= edited.changed.end).count();
+    let first = before.saturating_sub(1);
+    let last = after.min(body.len() - 1);
+    if first > last {
+        return None;
+    }
+
+    // Edits before the first statement or after the last one touch text that
+    // belongs to no statement (e.g., the file's leading comments)
+    let region_start = offset(body[first].span().lo);
+    let region_end = offset(body[last].span().hi);
+    if edited.changed.start < region_start || edited.changed.end > region_end {
+        return None;
+    }
+
+    let new_end = usize::try_from(region_end as i64 + edited.delta).ok()?;
+    let lo = BytePos(file_start.0 + region_start as u32);
+    let hi = BytePos(file_start.0 + region_end as u32);
+    let new_hi = BytePos(file_start.0 + new_end as u32);
+
+    let region_comments = SingleThreadedComments::default();
+    let lexer = Lexer::new(
+        parser_syntax(source_path),
+        EsVersion::Es2022,
+        StringInput::new(edited.source_code.get(region_start..new_end)?, lo, new_hi),
+        Some(&region_comments),
+    );
+    let mut parser = Parser::new_from(lexer);
+    let region = parser.parse_module().ok()?;
+    // Recovered errors (e.g., an unterminated comment) mean the region ends
+    // inside a construct that continues past it
+    if !parser.take_errors().is_empty() {
+        return None;
+    }
+
+    let mut module = previous.clone();
+    let mut shift = ShiftSpans { delta: edited.delta };
+    let mut tail: Vec<_> = module.body.drain(last + 1..).collect();
+    tail.iter_mut().for_each(|item| item.visit_mut_with(&mut shift));
+    module.body.truncate(first);
+    module.body.extend(region.body);
+    module.body.extend(tail);
+    module.span.hi = shift.pos(module.span.hi);
+
+    replace_region_comments(comments, &region_comments, lo, hi, edited.delta);
+    Some(module)
+}
+
+/// Replace the comments inside `lo..hi` with the re-parsed ones, shifting those after it.
+///
+/// Leading comments at `lo` sit before the region and trailing comments at
+/// `hi` after it, so both are kept.
+fn replace_region_comments(
+    comments: &SingleThreadedComments,
+    region_comments: &SingleThreadedComments,
+    lo: BytePos,
+    hi: BytePos,
+    delta: i64,
+) {
+    let shift = ShiftSpans { delta };
+    let (mut leading, mut trailing) = comments.borrow_all_mut();
+    for map in [&mut *leading, &mut *trailing] {
+        *map = std::mem::take(map)
+            .into_iter()
+            .filter(|(pos, _)| *pos <= lo || *pos >= hi)
+            .map(|(pos, mut list)| {
+                if pos < hi {
+                    return (pos, list);
+                }
+                for comment in &mut list {
+                    comment.span = shift.span(comment.span);
+                }
+                (shift.pos(pos), list)
+            })
+            .collect();
+    }
+    drop((leading, trailing));
+
+    let (region_leading, region_trailing) = region_comments.borrow_all();
+    for (pos, list) in region_leading.iter() {
+        comments.add_leading_comments(*pos, list.clone());
+    }
+    for (pos, list) in region_trailing.iter() {
+        comments.add_trailing_comments(*pos, list.clone());
+    }
+}
+
+/// Moves every span by a fixed number of bytes.
+struct ShiftSpans {
+    delta: i64,
+}
+
+impl ShiftSpans {
+    fn pos(&self, pos: BytePos) -> BytePos {
+        BytePos((pos.0 as i64 + self.delta) as u32)
+    }
+
+    fn span(&self, span: Span) -> Span {
+        if span.is_dummy() {
+            return span;
+        }
+        Span::new(self.pos(span.lo), self.pos(span.hi))
+    }
+}
+
+impl VisitMut for ShiftSpans {
+    fn visit_mut_span(&mut self, span: &mut Span) {
+        *span = self.span(*span);
+    }
+}
@@ -0,0 +1,51 @@
+//! Sectioned "index map" source maps.
+//!
+//! Bundlers that concatenate modules into chunks describe the chunk with an
+//! index map, a list of per-module maps placed at line/column offsets. Tools
+//! that chain source maps mostly accept only regular maps, so incoming index
+//! maps are flattened into one regular map, and outgoing maps can be wrapped
+//! into an index map for pipelines that merge them into chunk-level maps.
+
+use swc_sourcemap::{decode_slice, DecodedMap, SourceMap, SourceMapIndex, SourceMapSection};
+
+/// Flatten a source map into a regular source map.
+///
+/// Regular maps are returned unchanged. Every section of an index map must
+/// embed its map; sections that only reference a map by `url` cannot be
+/// resolved here.
+pub fn flatten_source_map(source_map: &str) -> Result<String, String> {
+    let decoded = decode_slice(source_map.as_bytes()).map_err(|e| format!("Invalid source map: {}", e))?;
+    let index = match decoded {
+        DecodedMap::Index(index) => index,
+        DecodedMap::Regular(_) | DecodedMap::Hermes(_) => return Ok(source_map.to_string()),
+    };
+
+    let flattened = index
+        .flatten()
+        .map_err(|e| format!("Failed to flatten index map: {}", e))?;
+    write_map(|buf| flattened.to_writer(buf))
+}
+
+/// Wrap a regular source map into an index map with a single section at the start of the output.
+pub fn to_index_map(map: SourceMap) -> Result<String, String> {
+    let file = map.get_file().cloned();
+    let section = SourceMapSection::new((0, 0), None, Some(DecodedMap::Regular(map)));
+    let index = SourceMapIndex::new(file, vec![section]);
+    write_map(|buf| index.to_writer(buf))
+}
+
+/// Wrap a serialized regular source map into an index map.
+pub fn wrap_source_map(source_map: &str) -> Result<String, String> {
+    match decode_slice(source_map.as_bytes()).map_err(|e| format!("Invalid source map: {}", e))? {
+        DecodedMap::Regular(map) => to_index_map(map),
+        DecodedMap::Hermes(map) => to_index_map(SourceMap::clone(&map)),
+        DecodedMap::Index(_) => Ok(source_map.to_string()),
+    }
+}
+
+/// Serialize a map through its writer.
+fn write_map(write: impl FnOnce(&mut Vec<u8>) -> swc_sourcemap::Result<()>) -> Result<String, String> {
+    let mut buf = Vec::new();
+    write(&mut buf).map_err(|e| format!("Source map error: {}", e))?;
+    String::from_utf8(buf).map_err(|e| format!("Source map UTF-8 error: {}", e))
+}
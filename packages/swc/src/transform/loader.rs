@@ -0,0 +1,114 @@
+//! Stateless transforms for parallel loaders.
+//!
+//! Rust-based bundlers (Rspack, Turbopack) run loaders for many modules at
+//! once. A `LoaderArtifact` holds only immutable state (the artifact, the
+//! parsed schema, and the config), so any number of calls can share it from
+//! different threads without locking. Each call parses into its own SWC
+//! `SourceMap` and records nothing: there are no prebuild, module, or output
+//! caches, no diagnostics log, and no usage tracking. Hosts that want those
+//! use `SwcTransformer` instead.
+
+use std::sync::Arc;
+
+use napi::bindgen_prelude::Task;
+use napi::Env;
+
+use crate::artifact::ArtifactSource;
+use crate::graphql::schema::SchemaIndex;
+use crate::types::config::{TransformConfig, TransformContext, TransformInputRef};
+
+use super::realpath::{realpath, resolve_config_paths};
+use super::transformer::{transform_source_ref, TransformResult};
+
+/// Immutable transform state shared by loader calls.
+pub struct LoaderArtifact {
+    artifact: Arc<dyn ArtifactSource>,
+    /// Artifact files the state was loaded from, reported as dependencies
+    artifact_files: Vec<String>,
+    /// Schema parsed once from `schemaSdl`, if configured
+    schema: Option<SchemaIndex>,
+    config: TransformConfig,
+}
+
+impl LoaderArtifact {
+    /// Build the shared state, resolving config paths and parsing `schemaSdl` if configured.
+    ///
+    /// The artifact is expected to be checked against the config already.
+    pub fn new(
+        artifact: Arc<dyn ArtifactSource>,
+        artifact_files: Vec<String>,
+        mut config: TransformConfig,
+    ) -> Result<Self, String> {
+        config.check_overrides()?;
+        resolve_config_paths(&mut config);
+        let schema = config.load_schema()?;
+
+        Ok(LoaderArtifact {
+            artifact,
+            artifact_files,
+            schema,
+            config,
+        })
+    }
+
+    /// Transform a single source file.
+    ///
+    /// Safe to call concurrently: every call builds its own input and reads
+    /// the shared state only.
+    pub fn transform(
+        &self,
+        source_code: String,
+        source_path: String,
+        context: TransformContext,
+    ) -> Result<TransformResult, String> {
+        // No realpath cache, so resolved paths are not shared between calls
+        let source_path = match self.config.resolve_symlinks {
+            true => realpath(&source_path),
+            false => source_path,
+        };
+        let config = self.config.for_path(&source_path)?;
+        let input = TransformInputRef {
+            source_code,
+            source_path,
+            artifact: self.artifact.as_ref(),
+            schema: self.schema.as_ref(),
+            prebuild_cache: None,
+            module_cache: None,
+            artifact_files: &self.artifact_files,
+            config,
+            context,
+            log_sink: None,
+        };
+
+        transform_source_ref(&input)
+    }
+}
+
+/// A loader transform run on the libuv thread pool.
+pub struct LoaderTransformTask {
+    pub loader: Arc<LoaderArtifact>,
+    pub source_code: String,
+    pub source_path: String,
+    pub context: TransformContext,
+}
+
+impl Task for LoaderTransformTask {
+    type Output = String;
+    type JsValue = String;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        let source_code = std::mem::take(&mut self.source_code);
+        let source_path = std::mem::take(&mut self.source_path);
+        let result = self
+            .loader
+            .transform(source_code, source_path, std::mem::take(&mut self.context))
+            .map_err(napi::Error::from_reason)?;
+
+        serde_json::to_string(&result)
+            .map_err(|e| napi::Error::from_reason(format!("Failed to serialize result: {}", e)))
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(output)
+    }
+}
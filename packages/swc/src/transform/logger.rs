@@ -0,0 +1,72 @@
+//! Log-level controlled output.
+//!
+//! Messages go to stderr unless the host injected a logger, in which case they
+//! are forwarded to it so they follow the build tool's own log formatting.
+//! Errors are also returned structurally in `TransformResult`, so callers that
+//! report them themselves can silence the transformer's own output.
+
+use std::fmt::{self, Debug, Display};
+use std::sync::Arc;
+
+use crate::types::config::LogLevel;
+use crate::types::{PluginError, Severity};
+
+/// Host logger receiving messages instead of stderr.
+///
+/// Called with the name of the logger method to use (`debug`, `info`, `warn`,
+/// or `error`) and the message.
+pub type LogSink = Arc<dyn Fn(&'static str, String) + Send + Sync>;
+
+/// Writes messages according to the configured level, to the host logger if
+/// one was injected and as `[swc]`-prefixed lines on stderr otherwise.
+#[derive(Clone, Default)]
+pub struct Logger {
+    level: LogLevel,
+    sink: Option<LogSink>,
+}
+
+impl Logger {
+    pub fn new(level: LogLevel) -> Self {
+        Self { level, sink: None }
+    }
+
+    /// Forward messages to a host logger instead of stderr.
+    pub fn with_sink(mut self, sink: Option<LogSink>) -> Self {
+        self.sink = sink;
+        self
+    }
+
+    /// Log a reported error; informational ones only at `debug`.
+    pub fn error(&self, error: &PluginError) {
+        let (level, method) = match error.severity {
+            Severity::Error => (LogLevel::Warn, "error"),
+            Severity::Warning => (LogLevel::Warn, "warn"),
+            Severity::Info => (LogLevel::Debug, "info"),
+        };
+        self.log(level, method, error.format());
+    }
+
+    /// Log details useful when investigating transformer behavior.
+    pub fn debug(&self, message: impl Display) {
+        self.log(LogLevel::Debug, "debug", message);
+    }
+
+    fn log(&self, level: LogLevel, method: &'static str, message: impl Display) {
+        if level > self.level {
+            return;
+        }
+        match &self.sink {
+            Some(sink) => sink(method, message.to_string()),
+            None => eprintln!("[swc] {}", message),
+        }
+    }
+}
+
+impl Debug for Logger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Logger")
+            .field("level", &self.level)
+            .field("sink", &self.sink.is_some())
+            .finish()
+    }
+}
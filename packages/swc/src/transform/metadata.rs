@@ -4,9 +4,16 @@
 //! - AST path (canonical path)
 //! - Export bindings
 //! - Scope tracking
+//!
+//! Identifiers are kept as interned `Atom`s (cloned by reference count), so
+//! scope tracking does not allocate per identifier; only AST paths of gql
+//! definitions are built as owned strings.
 
 use std::collections::HashMap;
-use swc_core::common::Span;
+
+use rustc_hash::FxHashMap;
+use swc_core::common::{BytePos, Span};
+use swc_core::ecma::atoms::{Atom, Wtf8Atom};
 use swc_core::ecma::ast::*;
 use swc_core::ecma::visit::{Visit, VisitWith};
 
@@ -16,21 +23,40 @@ pub struct GqlDefinitionMetadata {
     /// The AST path for canonical ID resolution.
     pub ast_path: String,
     /// Whether this is a top-level definition.
-    #[allow(dead_code)]
     pub is_top_level: bool,
     /// Whether this definition is exported.
-    #[allow(dead_code)]
     pub is_exported: bool,
     /// The export binding name, if exported.
-    #[allow(dead_code)]
-    pub export_binding: Option<String>,
+    pub export_binding: Option<Atom>,
 }
 
+/// Hash key for a span, with both byte positions packed into one `u64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SpanKey(u64);
+
+impl From<Span> for SpanKey {
+    fn from(span: Span) -> Self {
+        Self((u64::from(span.lo.0) << 32) | u64::from(span.hi.0))
+    }
+}
+
+impl SpanKey {
+    /// The span this key was packed from.
+    pub fn span(self) -> Span {
+        Span::new(BytePos((self.0 >> 32) as u32), BytePos(self.0 as u32))
+    }
+}
+
+/// Map keyed by the span of a call expression.
+/// Uses `FxHash`, which hashes a `SpanKey` with a single multiply; spans are
+/// not attacker-chosen, so a DoS-resistant hasher is unnecessary.
+pub type SpanMap<V> = FxHashMap<SpanKey, V>;
+
 /// Map from call expression span to metadata.
-pub type MetadataMap = HashMap<Span, GqlDefinitionMetadata>;
+pub type MetadataMap = SpanMap<GqlDefinitionMetadata>;
 
 /// Map from local name to export name.
-type ExportBindingMap = HashMap<String, String>;
+pub type ExportBindingMap = HashMap<Atom, Atom>;
 
 /// Collects metadata about GQL definitions in a module.
 pub struct MetadataCollector {
@@ -45,9 +71,9 @@ pub struct MetadataCollector {
 }
 
 struct ScopeFrame {
-    segment: String,
+    segment: Atom,
     #[allow(dead_code)]
-    kind: String,
+    kind: &'static str,
 }
 
 impl MetadataCollector {
@@ -59,7 +85,7 @@ impl MetadataCollector {
             source_path: source_path.to_string(),
             export_bindings,
             scope_stack: Vec::new(),
-            metadata: HashMap::new(),
+            metadata: MetadataMap::default(),
             anonymous_counters: HashMap::new(),
             definition_counter: 0,
         };
@@ -69,27 +95,25 @@ impl MetadataCollector {
     }
 
     /// Collect export bindings from the module.
-    fn collect_export_bindings(module: &Module) -> ExportBindingMap {
+    pub fn collect_export_bindings(module: &Module) -> ExportBindingMap {
         let mut bindings = HashMap::new();
 
         for item in &module.body {
             match item {
                 // ESM: export { foo }
-                ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(export)) => {
-                    if export.src.is_none() {
-                        for spec in &export.specifiers {
-                            if let ExportSpecifier::Named(named) = spec {
-                                let local = match &named.orig {
-                                    ModuleExportName::Ident(id) => atom_to_string(&id.sym),
-                                    ModuleExportName::Str(s) => wtf8_to_string(&s.value),
-                                };
-                                let exported = match &named.exported {
-                                    Some(ModuleExportName::Ident(id)) => atom_to_string(&id.sym),
-                                    Some(ModuleExportName::Str(s)) => wtf8_to_string(&s.value),
-                                    None => local.clone(),
-                                };
-                                bindings.insert(local, exported);
-                            }
+                ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(export)) if export.src.is_none() => {
+                    for spec in &export.specifiers {
+                        if let ExportSpecifier::Named(named) = spec {
+                            let local = match &named.orig {
+                                ModuleExportName::Ident(id) => id.sym.clone(),
+                                ModuleExportName::Str(s) => wtf8_to_atom(&s.value),
+                            };
+                            let exported = match &named.exported {
+                                Some(ModuleExportName::Ident(id)) => id.sym.clone(),
+                                Some(ModuleExportName::Str(s)) => wtf8_to_atom(&s.value),
+                                None => local.clone(),
+                            };
+                            bindings.insert(local, exported);
                         }
                     }
                 }
@@ -99,15 +123,15 @@ impl MetadataCollector {
                     if let Decl::Var(var_decl) = &export.decl {
                         for decl in &var_decl.decls {
                             if let Pat::Ident(ident) = &decl.name {
-                                let name = atom_to_string(&ident.id.sym);
+                                let name = ident.id.sym.clone();
                                 bindings.insert(name.clone(), name);
                             }
                         }
                     } else if let Decl::Fn(fn_decl) = &export.decl {
-                        let name = atom_to_string(&fn_decl.ident.sym);
+                        let name = fn_decl.ident.sym.clone();
                         bindings.insert(name.clone(), name);
                     } else if let Decl::Class(class_decl) = &export.decl {
-                        let name = atom_to_string(&class_decl.ident.sym);
+                        let name = class_decl.ident.sym.clone();
                         bindings.insert(name.clone(), name);
                     }
                 }
@@ -130,20 +154,34 @@ impl MetadataCollector {
 
     /// Get the current AST path.
     fn get_ast_path(&self) -> String {
-        self.scope_stack
-            .iter()
-            .map(|f| f.segment.clone())
-            .collect::<Vec<_>>()
-            .join(".")
+        let mut path = String::new();
+        for (index, frame) in self.scope_stack.iter().enumerate() {
+            if index > 0 {
+                path.push('.');
+            }
+            path.push_str(&frame.segment);
+        }
+        path
+    }
+
+    /// Return the number of times `key` was seen before and count this occurrence.
+    /// Only allocates the key the first time it is seen.
+    fn next_occurrence(&mut self, key: &str) -> usize {
+        if let Some(count) = self.anonymous_counters.get_mut(key) {
+            let previous = *count;
+            *count += 1;
+            previous
+        } else {
+            self.anonymous_counters.insert(key.to_string(), 1);
+            0
+        }
     }
 
     /// Get an anonymous name for a scope kind (not currently used but kept for future).
     #[allow(dead_code)]
-    fn get_anonymous_name(&mut self, kind: &str) -> String {
-        let count = self.anonymous_counters.entry(kind.to_string()).or_insert(0);
-        let name = format!("{}#{}", kind, count);
-        *count += 1;
-        name
+    fn get_anonymous_name(&mut self, kind: &str) -> Atom {
+        let count = self.next_occurrence(kind);
+        format!("{}#{}", kind, count).into()
     }
 
     /// Register a definition and get its AST path.
@@ -152,22 +190,15 @@ impl MetadataCollector {
         let base_path = self.get_ast_path();
 
         // Track occurrences for uniqueness
-        let count = self.anonymous_counters.entry(base_path.clone()).or_insert(0);
-        let path = if *count == 0 {
-            base_path.clone()
-        } else {
-            format!("{}${}", base_path, count)
-        };
-        *count += 1;
-        path
+        match self.next_occurrence(&base_path) {
+            0 => base_path,
+            count => format!("{}${}", base_path, count),
+        }
     }
 
     /// Enter a scope.
-    fn enter_scope(&mut self, segment: String, kind: &str) {
-        self.scope_stack.push(ScopeFrame {
-            segment,
-            kind: kind.to_string(),
-        });
+    fn enter_scope(&mut self, segment: Atom, kind: &'static str) {
+        self.scope_stack.push(ScopeFrame { segment, kind });
     }
 
     /// Exit a scope.
@@ -192,7 +223,7 @@ impl MetadataCollector {
     }
 
     /// Resolve top-level export info for a call.
-    fn resolve_export_info(&self, _call: &CallExpr) -> Option<String> {
+    fn resolve_export_info(&self, _call: &CallExpr) -> Option<Atom> {
         // This is a simplified version - in practice, you'd need to track
         // parent nodes to find the variable declaration or assignment
         // For now, we'll look at the scope stack
@@ -208,8 +239,7 @@ impl MetadataCollector {
 impl Visit for MetadataCollector {
     fn visit_var_declarator(&mut self, decl: &VarDeclarator) {
         if let Pat::Ident(ident) = &decl.name {
-            let name = atom_to_string(&ident.id.sym);
-            self.enter_scope(name, "variable");
+            self.enter_scope(ident.id.sym.clone(), "variable");
             decl.visit_children_with(self);
             self.exit_scope();
         } else {
@@ -218,8 +248,7 @@ impl Visit for MetadataCollector {
     }
 
     fn visit_fn_decl(&mut self, decl: &FnDecl) {
-        let name = atom_to_string(&decl.ident.sym);
-        self.enter_scope(name, "function");
+        self.enter_scope(decl.ident.sym.clone(), "function");
         decl.visit_children_with(self);
         self.exit_scope();
     }
@@ -228,7 +257,7 @@ impl Visit for MetadataCollector {
         let name = expr
             .ident
             .as_ref()
-            .map(|i| atom_to_string(&i.sym))
+            .map(|i| i.sym.clone())
             .unwrap_or_else(|| self.get_anonymous_name("function"));
         self.enter_scope(name, "function");
         expr.visit_children_with(self);
@@ -243,16 +272,14 @@ impl Visit for MetadataCollector {
     }
 
     fn visit_class_decl(&mut self, decl: &ClassDecl) {
-        let name = atom_to_string(&decl.ident.sym);
-        self.enter_scope(name, "class");
+        self.enter_scope(decl.ident.sym.clone(), "class");
         decl.visit_children_with(self);
         self.exit_scope();
     }
 
     fn visit_class_method(&mut self, method: &ClassMethod) {
         if let PropName::Ident(ident) = &method.key {
-            let name = atom_to_string(&ident.sym);
-            self.enter_scope(name, "method");
+            self.enter_scope(ident.sym.clone(), "method");
             method.visit_children_with(self);
             self.exit_scope();
         } else {
@@ -262,8 +289,8 @@ impl Visit for MetadataCollector {
 
     fn visit_key_value_prop(&mut self, prop: &KeyValueProp) {
         let name = match &prop.key {
-            PropName::Ident(ident) => Some(atom_to_string(&ident.sym)),
-            PropName::Str(s) => Some(wtf8_to_string(&s.value)),
+            PropName::Ident(ident) => Some(ident.sym.clone()),
+            PropName::Str(s) => Some(wtf8_to_atom(&s.value)),
             _ => None,
         };
 
@@ -294,7 +321,7 @@ impl Visit for MetadataCollector {
             let export_binding = self.resolve_export_info(call);
 
             self.metadata.insert(
-                call.span,
+                SpanKey::from(call.span),
                 GqlDefinitionMetadata {
                     ast_path,
                     is_top_level,
@@ -328,7 +355,7 @@ fn is_gql_reference(expr: &Expr) -> bool {
 }
 
 /// Get the export name from a CommonJS export pattern.
-fn get_commonjs_export_name(target: &AssignTarget) -> Option<String> {
+fn get_commonjs_export_name(target: &AssignTarget) -> Option<Atom> {
     match target {
         AssignTarget::Simple(SimpleAssignTarget::Member(member)) => {
             // Check for exports.foo or module.exports.foo
@@ -346,7 +373,7 @@ fn get_commonjs_export_name(target: &AssignTarget) -> Option<String> {
 
             // Extract property name
             if let MemberProp::Ident(ident) = &member.prop {
-                Some(atom_to_string(&ident.sym))
+                Some(ident.sym.clone())
             } else {
                 None
             }
@@ -360,12 +387,7 @@ fn atom_eq<T: AsRef<str>>(atom: &T, s: &str) -> bool {
     atom.as_ref() == s
 }
 
-/// Helper to convert an Atom to String.
-fn atom_to_string<T: AsRef<str>>(atom: &T) -> String {
-    atom.as_ref().to_string()
-}
-
-/// Helper to convert a Wtf8Atom (string literal value) to String.
-fn wtf8_to_string(atom: &swc_core::atoms::Wtf8Atom) -> String {
-    atom.to_string_lossy().into_owned()
+/// Helper to convert a Wtf8Atom (string literal value) to an Atom.
+fn wtf8_to_atom(atom: &Wtf8Atom) -> Atom {
+    Atom::from(&*atom.to_string_lossy())
 }
@@ -1,7 +1,38 @@
 //! Transformation modules for the SWC transformer.
 
 pub mod analysis;
+pub mod banner;
+pub mod bun;
+pub mod captures;
+pub mod cache;
+pub mod debug_dump;
+pub mod directive;
+pub mod esbuild;
+pub mod estree;
+pub mod explain;
+pub mod extract;
+pub mod filter;
+pub mod flags;
+pub mod graphql_tag;
+pub mod import_map;
+pub mod index_map;
 pub mod imports;
+pub mod incremental;
+pub mod loader;
+pub mod logger;
 pub mod metadata;
+pub mod parity;
+pub mod paths;
+pub mod realpath;
+pub mod registry;
 pub mod runtime;
+pub mod scratch;
+pub mod sidecar;
+pub mod source_names;
+pub mod summary;
+pub mod suppression;
 pub mod transformer;
+pub mod usage;
+pub mod verify;
+#[cfg(feature = "watch")]
+pub mod watch;
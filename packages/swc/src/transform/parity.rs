@@ -0,0 +1,133 @@
+//! Structural comparison of transform output against the TypeScript transformer.
+//!
+//! Both outputs are parsed and converted to ESTree without locations or raw
+//! literal text, so formatting, quoting, and comment differences between the
+//! two code generators are ignored. Only differences in program structure are
+//! reported, to validate a migration to the native transformer file by file.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use swc_core::common::comments::SingleThreadedComments;
+use swc_core::common::sync::Lrc;
+use swc_core::common::SourceMap;
+
+use super::estree::module_to_estree;
+use super::transformer::parse_module;
+
+/// ESTree fields that depend on formatting rather than structure.
+const FORMATTING_FIELDS: &[&str] = &["start", "end", "range", "loc", "raw"];
+
+/// Result of comparing the native output with the expected output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParityReport {
+    /// Whether both outputs have the same program structure.
+    pub matches: bool,
+
+    /// Nodes that differ, in program order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub differences: Vec<ParityDifference>,
+}
+
+/// A node that differs between the outputs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParityDifference {
+    /// Path of the node in the ESTree program (e.g., `body[2].declarations[0].init`).
+    pub path: String,
+    /// The node in the expected output, or `null` if only the native output has it.
+    pub expected: Value,
+    /// The node in the native output, or `null` if only the expected output has it.
+    pub actual: Value,
+}
+
+/// Compare the native output with the expected output of the same file.
+///
+/// # Arguments
+/// * `source_path` - The file path of the source, which selects the parser syntax
+/// * `actual` - Output code of the native transformer
+/// * `expected` - Output code of the TypeScript transformer
+pub fn compare_outputs(source_path: &str, actual: &str, expected: &str) -> Result<ParityReport, String> {
+    let actual = normalized_program(source_path, actual).map_err(|e| format!("Native output: {}", e))?;
+    let expected = normalized_program(source_path, expected).map_err(|e| format!("Expected output: {}", e))?;
+
+    let mut differences = Vec::new();
+    diff_values("", &expected, &actual, &mut differences);
+
+    Ok(ParityReport {
+        matches: differences.is_empty(),
+        differences,
+    })
+}
+
+/// Parse output code into an ESTree program without formatting fields.
+fn normalized_program(source_path: &str, code: &str) -> Result<Value, String> {
+    let cm: Lrc<SourceMap> = Default::default();
+    let comments = SingleThreadedComments::default();
+    let module = parse_module(&cm, source_path, code, &comments)?;
+    let start_pos = cm.files()[0].start_pos;
+
+    let mut program = module_to_estree(&module, code, start_pos);
+    strip_formatting(&mut program);
+    Ok(program)
+}
+
+/// Remove the formatting fields from every node.
+fn strip_formatting(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            fields.retain(|key, _| !FORMATTING_FIELDS.contains(&key.as_str()));
+            fields.values_mut().for_each(strip_formatting);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_formatting),
+        _ => {}
+    }
+}
+
+/// Record the differences between two values at `path`.
+///
+/// Nodes of different types are reported as a whole; nodes of the same type
+/// are compared field by field, so the report points at the innermost change.
+fn diff_values(path: &str, expected: &Value, actual: &Value, differences: &mut Vec<ParityDifference>) {
+    if expected == actual {
+        return;
+    }
+
+    match (expected, actual) {
+        (Value::Object(expected_fields), Value::Object(actual_fields))
+            if expected_fields.get("type") == actual_fields.get("type") =>
+        {
+            let keys = expected_fields
+                .keys()
+                .chain(actual_fields.keys().filter(|key| !expected_fields.contains_key(*key)));
+            for key in keys {
+                let field_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                diff_values(
+                    &field_path,
+                    expected_fields.get(key).unwrap_or(&Value::Null),
+                    actual_fields.get(key).unwrap_or(&Value::Null),
+                    differences,
+                );
+            }
+        }
+        (Value::Array(expected_items), Value::Array(actual_items)) => {
+            for index in 0..expected_items.len().max(actual_items.len()) {
+                diff_values(
+                    &format!("{}[{}]", path, index),
+                    expected_items.get(index).unwrap_or(&Value::Null),
+                    actual_items.get(index).unwrap_or(&Value::Null),
+                    differences,
+                );
+            }
+        }
+        _ => differences.push(ParityDifference {
+            path: path.to_string(),
+            expected: expected.clone(),
+            actual: actual.clone(),
+        }),
+    }
+}
@@ -0,0 +1,91 @@
+//! Windows path normalization.
+//!
+//! Paths reach the transformer in several spellings of the same file: with
+//! backslashes, as verbatim long paths (`\\?\C:\...`, `\\?\UNC\server\...`),
+//! as device paths (`\\.\C:\...`), or as UNC paths of mapped network drives.
+//! They are all brought to one forward-slash form before canonical IDs are
+//! resolved or paths are compared, matching the keys the builder records.
+//! Relative paths (e.g., source map `sources`) are computed on that form too.
+
+use std::borrow::Cow;
+
+/// Normalize a path to forward slashes without a verbatim or device prefix.
+///
+/// Verbatim UNC paths become plain UNC paths (`//server/share/...`), and
+/// verbatim drive paths become plain drive paths (`C:/...`).
+pub fn normalize_path(path: &str) -> Cow<'_, str> {
+    if !path.contains('\\') && !path.starts_with("//") {
+        return Cow::Borrowed(path);
+    }
+
+    let slashed = path.replace('\\', "/");
+    if slashed.get(..8).is_some_and(|prefix| prefix.eq_ignore_ascii_case("//?/UNC/")) {
+        return Cow::Owned(format!("//{}", &slashed[8..]));
+    }
+    match slashed.strip_prefix("//?/").or_else(|| slashed.strip_prefix("//./")) {
+        // Only drive paths; other verbatim paths (e.g., volume GUIDs) have no plain form
+        Some(rest) if is_drive_path(rest) => Cow::Owned(rest.to_string()),
+        _ => Cow::Owned(slashed),
+    }
+}
+
+/// Check if a forward-slash path starts with a drive letter (`C:/` or `C:`).
+fn is_drive_path(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && (bytes.len() == 2 || bytes[2] == b'/')
+}
+
+/// Express `path` relative to the directory `base`, like `pathdiff::diff_paths`.
+///
+/// Both paths are normalized first. A path under a different root (another
+/// drive or UNC share, or an absolute path against a relative base) has no
+/// relative form and is returned normalized.
+pub fn relative_path(base: &str, path: &str) -> String {
+    let base = normalize_path(base);
+    let path = normalize_path(path);
+    let (base_root, base_rest) = split_root(&base);
+    let (path_root, path_rest) = split_root(&path);
+    if !base_root.eq_ignore_ascii_case(path_root) {
+        return path.into_owned();
+    }
+
+    let base_parts: Vec<&str> = components(base_rest).collect();
+    let path_parts: Vec<&str> = components(path_rest).collect();
+    let common = base_parts
+        .iter()
+        .zip(&path_parts)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let parts: Vec<&str> = std::iter::repeat_n("..", base_parts.len() - common)
+        .chain(path_parts[common..].iter().copied())
+        .collect();
+    if parts.is_empty() {
+        ".".to_string()
+    } else {
+        parts.join("/")
+    }
+}
+
+/// Split a normalized path into its root (`/`, `C:`, `//server/share`, or
+/// empty for relative paths) and the rest.
+fn split_root(path: &str) -> (&str, &str) {
+    if let Some(unc) = path.strip_prefix("//") {
+        let end = unc
+            .match_indices('/')
+            .nth(1)
+            .map_or(path.len(), |(index, _)| index + 2);
+        return path.split_at(end);
+    }
+    if is_drive_path(path) {
+        return path.split_at(2);
+    }
+    if path.starts_with('/') {
+        return path.split_at(1);
+    }
+    ("", path)
+}
+
+/// Path components, without empty and `.` segments.
+fn components(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|part| !part.is_empty() && *part != ".")
+}
@@ -0,0 +1,54 @@
+//! Symlink resolution of source paths.
+//!
+//! With `resolveSymlinks`, source paths are resolved to their real paths
+//! before graphql-system files are matched and canonical IDs are resolved, so
+//! files reached through symlinks (pnpm's virtual store, linked workspace
+//! packages) match the paths the builder recorded. Paths that do not exist on
+//! disk (virtual modules, Yarn PnP archives) are used as given.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::types::TransformConfig;
+
+use super::paths::normalize_path;
+
+/// Real paths of source paths, resolved once per path.
+///
+/// Symlinks are assumed not to change while the transformer is alive.
+#[derive(Default)]
+pub struct RealpathCache {
+    paths: Mutex<HashMap<String, String>>,
+}
+
+impl RealpathCache {
+    /// Get the real path of `path`, resolving it on a miss.
+    pub fn resolve(&self, path: &str) -> String {
+        let mut paths = self.paths.lock().unwrap_or_else(|e| e.into_inner());
+        paths.entry(path.to_string()).or_insert_with(|| realpath(path)).clone()
+    }
+}
+
+/// Resolve symlinks in `path`, with forward slashes; `path` as given if it does not exist.
+pub fn realpath(path: &str) -> String {
+    // Virtual module IDs are not files
+    if path.starts_with('\0') {
+        return path.to_string();
+    }
+
+    match std::fs::canonicalize(path) {
+        // Windows returns verbatim paths (`\\?\C:\...`), which never match the given ones
+        Ok(real) => normalize_path(&real.to_string_lossy()).into_owned(),
+        Err(_) => path.to_string(),
+    }
+}
+
+/// Resolve the internal module paths of `config`, so they match resolved source paths.
+pub fn resolve_config_paths(config: &mut TransformConfig) {
+    if !config.resolve_symlinks {
+        return;
+    }
+
+    config.graphql_system_path = config.graphql_system_path.as_deref().map(realpath);
+    config.inject_paths = config.inject_paths.iter().map(|path| realpath(path)).collect();
+}
@@ -0,0 +1,73 @@
+//! Operation registry virtual module.
+//!
+//! Transforming a path listed in `registryModules` (e.g., `soda-gql:registry`)
+//! generates a module exporting every operation in the artifact, so apps can
+//! enumerate operations at runtime (e.g., for persisted query uploads or
+//! prefetch manifests) without maintaining the list by hand.
+
+use serde::Serialize;
+
+use crate::artifact::ArtifactSource;
+use crate::types::{BuilderArtifactElement, TransformConfig};
+
+use super::runtime::hashed_operation_name;
+
+/// An operation as listed in the registry module.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RegistryEntry<'a> {
+    name: &'a str,
+    operation_type: &'a str,
+    /// Content hash of the definition, from the artifact metadata.
+    hash: &'a str,
+    source_file: &'a str,
+    canonical_id: &'a str,
+    /// Hashed name the operation is registered under, with hashed operation names.
+    /// This is its ID in the trusted-documents manifest.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    document_id: Option<String>,
+}
+
+/// Check if `source_path` is one of the configured registry modules.
+///
+/// Bundlers mark virtual module IDs with a leading `\0`, which is ignored.
+pub fn is_registry_module(config: &TransformConfig, source_path: &str) -> bool {
+    let specifier = source_path.strip_prefix('\0').unwrap_or(source_path);
+    config.registry_modules.iter().any(|module| module == specifier)
+}
+
+/// Generate the registry module for the operations in `artifact`.
+///
+/// Exports `operations` (also the default export), sorted by canonical ID.
+/// Elements that fail to parse are left out.
+pub fn registry_module_code(config: &TransformConfig, artifact: &dyn ArtifactSource) -> Result<String, String> {
+    let mut ids: Vec<_> = artifact.ids().collect();
+    ids.sort();
+
+    let entries: Vec<RegistryEntry<'_>> = ids
+        .into_iter()
+        .filter_map(|id| match artifact.get(id)? {
+            BuilderArtifactElement::Operation { id, metadata, prebuild } => Some(RegistryEntry {
+                name: &prebuild.operation_name,
+                operation_type: &prebuild.operation_type,
+                hash: &metadata.content_hash,
+                source_file: &metadata.source_path,
+                canonical_id: id,
+                document_id: config.hash_operation_names().then(|| hashed_operation_name(prebuild)).flatten(),
+            }),
+            BuilderArtifactElement::Fragment { .. } => None,
+        })
+        .collect();
+
+    let operations = serde_json::to_string_pretty(&entries)
+        .map_err(|e| format!("Failed to serialize operation registry: {}", e))?;
+
+    Ok(if config.emits_cjs() {
+        format!(
+            "const operations = {};\nexports.operations = operations;\nexports.default = operations;\n",
+            operations
+        )
+    } else {
+        format!("export const operations = {};\nexport default operations;\n", operations)
+    })
+}
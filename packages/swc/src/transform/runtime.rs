@@ -2,87 +2,332 @@
 //!
 //! This module generates the `gqlRuntime.*` calls that replace `gql.default()` calls.
 
+use std::sync::Arc;
+
+use serde_json::Value;
+use swc_core::atoms::{atom, Atom};
 use swc_core::common::{SyntaxContext, DUMMY_SP};
 use swc_core::ecma::ast::*;
+use xxhash_rust::xxh64::xxh64;
 
-use crate::types::{BuilderArtifactElement, FragmentPrebuild, OperationPrebuild};
+use crate::graphql::document;
+use crate::graphql::metrics::operation_metrics;
+use crate::graphql::print::print_normalized;
+use crate::graphql::schema::SchemaIndex;
+use crate::graphql::typenames::selected_typenames;
+use crate::types::config::{MutationInvalidation, OutputProfile, SubscriptionTransport};
+use crate::types::{BuilderArtifactElement, CanonicalId, FragmentPrebuild, OperationPrebuild};
 
-use super::analysis::GqlReplacement;
+use super::analysis::{GqlCallOptions, GqlReplacement};
+use super::cache::PrebuildCache;
+use super::logger::Logger;
 
 const RUNTIME_IMPORT_NAME: &str = "gqlRuntime";
 const CJS_RUNTIME_NAME: &str = "__soda_gql_runtime";
+const SHARED_DEFINITION_PREFIX: &str = "__soda_gql_shared_";
+/// Binding of the registered content hashes, keyed by canonical ID.
+const REGISTERED_NAME: &str = "__soda_gql_registered";
+/// Global holding the registered content hashes across module re-executions.
+const REGISTERED_GLOBAL: &str = "__SODA_GQL_REGISTERED__";
+/// Prefix of hashed operation names; GraphQL names cannot start with a digit.
+const HASHED_NAME_PREFIX: &str = "Op";
+
+/// Callees shared by every generated call.
+///
+/// Built once per builder and cloned into each call, instead of rebuilding the
+/// member chains (and re-interning their names) for every replacement.
+struct CalleeTemplates {
+    /// `gqlRuntime.fragment` (or `__soda_gql_runtime.gqlRuntime.fragment`)
+    fragment: Callee,
+    /// `gqlRuntime.operation`
+    operation: Callee,
+    /// `gqlRuntime.getOperation`
+    get_operation: Callee,
+    /// `gqlRuntime.subscription`
+    subscription: Callee,
+    /// `gqlRuntime.getSubscription`
+    get_subscription: Callee,
+    /// `JSON.parse`
+    json_parse: Callee,
+    /// `Object.assign`
+    object_assign: Callee,
+}
+
+impl CalleeTemplates {
+    fn new(is_cjs: bool) -> Self {
+        let runtime = if is_cjs {
+            // __soda_gql_runtime.gqlRuntime
+            member_expr(Expr::Ident(global_ident(CJS_RUNTIME_NAME.into())), RUNTIME_IMPORT_NAME.into())
+        } else {
+            Expr::Ident(global_ident(RUNTIME_IMPORT_NAME.into()))
+        };
+        let runtime_method = |method: Atom| Callee::Expr(Box::new(member_expr(runtime.clone(), method)));
+
+        Self {
+            fragment: runtime_method(atom!("fragment")),
+            operation: runtime_method(atom!("operation")),
+            get_operation: runtime_method(atom!("getOperation")),
+            subscription: runtime_method(atom!("subscription")),
+            get_subscription: runtime_method(atom!("getSubscription")),
+            json_parse: Callee::Expr(Box::new(member_expr(
+                Expr::Ident(global_ident(atom!("JSON"))),
+                atom!("parse"),
+            ))),
+            object_assign: Callee::Expr(Box::new(member_expr(
+                Expr::Ident(global_ident(atom!("Object"))),
+                atom!("assign"),
+            ))),
+        }
+    }
+}
 
 /// Builds runtime calls for GQL transformations.
-pub struct RuntimeCallBuilder {
-    is_cjs: bool,
+pub struct RuntimeCallBuilder<'a> {
+    callees: CalleeTemplates,
+    /// Definitions emitted once per file and referenced by each prebuild that embeds them.
+    shared_definitions: Vec<Arc<Value>>,
+    /// Prebuild expressions reused across files, if the caller keeps one
+    prebuild_cache: Option<&'a PrebuildCache>,
+    output_profile: OutputProfile,
+    /// Whether to omit server-only fields from operation prebuilds
+    client_safe: bool,
+    /// Whether the module only runs on the server, so client-only helpers are not emitted
+    server_module: bool,
+    /// Whether to register operations under a content hash instead of their name
+    hash_operation_names: bool,
+    /// Whether to omit documents from operation prebuilds, for trusted-documents mode
+    trusted_documents: bool,
+    /// Transport hint for subscriptions, which get their own runtime calls when set
+    subscription_transport: Option<SubscriptionTransport>,
+    /// Whether to guard registrations and name generated bindings by content, for Fast Refresh
+    react_refresh: bool,
+    /// Whether to add dev-only debug payloads to registrations
+    debug_payloads: bool,
+    /// Whether to pass variable definitions in the `runtime` object
+    runtime_variables: bool,
+    /// Invalidation hints added to mutation registrations, if enabled
+    mutation_invalidation: Option<&'a MutationInvalidation>,
+    /// Schema resolving the typenames mutations select
+    schema: Option<&'a SchemaIndex>,
+    logger: Logger,
 }
 
-impl RuntimeCallBuilder {
+impl<'a> RuntimeCallBuilder<'a> {
     pub fn new(is_cjs: bool) -> Self {
-        Self { is_cjs }
+        Self {
+            callees: CalleeTemplates::new(is_cjs),
+            shared_definitions: Vec::new(),
+            prebuild_cache: None,
+            output_profile: OutputProfile::Default,
+            client_safe: false,
+            server_module: false,
+            hash_operation_names: false,
+            trusted_documents: false,
+            subscription_transport: None,
+            react_refresh: false,
+            debug_payloads: false,
+            runtime_variables: false,
+            mutation_invalidation: None,
+            schema: None,
+            logger: Logger::default(),
+        }
+    }
+
+    /// Omit server-only fields (operation `metadata`) from registrations, for
+    /// modules bundled for the browser.
+    pub fn with_client_safe(mut self, client_safe: bool) -> Self {
+        self.client_safe = client_safe;
+        self
+    }
+
+    /// Build registrations for a server module: mutations get no invalidation
+    /// hints, which only feed the browser cache, and subscriptions with a
+    /// transport are rejected (see [`Self::client_only_helper`]).
+    pub fn with_server_module(mut self, server_module: bool) -> Self {
+        self.server_module = server_module;
+        self
+    }
+
+    /// Get the client-only runtime helper `artifact` would be registered with,
+    /// if this builds for a server module.
+    ///
+    /// Subscriptions with a transport are wired to a WebSocket or SSE client,
+    /// which the server runtime does not provide.
+    pub fn client_only_helper(&self, artifact: &BuilderArtifactElement) -> Option<&'static str> {
+        match artifact {
+            BuilderArtifactElement::Operation { prebuild, .. }
+                if self.server_module
+                    && self.subscription_transport.is_some()
+                    && prebuild.operation_type == "subscription" =>
+            {
+                Some("subscription")
+            }
+            _ => None,
+        }
+    }
+
+    /// Register operations and reference them by a short content hash instead
+    /// of their name, so production bundles don't carry readable names.
+    pub fn with_hashed_operation_names(mut self, hash_operation_names: bool) -> Self {
+        self.hash_operation_names = hash_operation_names;
+        self
+    }
+
+    /// Register operations without their document, for servers that only accept
+    /// pre-registered documents; requests then carry only the hashed name.
+    pub fn with_trusted_documents(mut self, trusted_documents: bool) -> Self {
+        self.trusted_documents = trusted_documents;
+        self
+    }
+
+    /// Register subscriptions with `gqlRuntime.subscription` and a `transport`
+    /// hint, so the runtime wires them without inspecting documents.
+    pub fn with_subscription_transport(mut self, transport: Option<SubscriptionTransport>) -> Self {
+        self.subscription_transport = transport;
+        self
+    }
+
+    /// Structure output for React Fast Refresh.
+    ///
+    /// Registrations are skipped when a module re-runs with an unchanged
+    /// definition, and shared definitions are named by content instead of
+    /// position, so edits don't rename unrelated bindings.
+    pub fn with_react_refresh(mut self, react_refresh: bool) -> Self {
+        self.react_refresh = react_refresh;
+        self
+    }
+
+    /// Add a `debug` payload (canonical ID, source path, original definition
+    /// text) to registrations, evaluated only outside production so bundlers
+    /// can strip it.
+    pub fn with_debug_payloads(mut self, debug_payloads: bool) -> Self {
+        self.debug_payloads = debug_payloads;
+        self
+    }
+
+    /// Pass each operation's variable definitions in its `runtime` object.
+    ///
+    /// Definitions are read from the prebuild document, which holds the
+    /// variables declared through the builder (its `variables` option or
+    /// template header) merged with those of spread fragments.
+    pub fn with_runtime_variables(mut self, runtime_variables: bool) -> Self {
+        self.runtime_variables = runtime_variables;
+        self
+    }
+
+    /// Register mutations with the typenames they affect (`invalidates`), so the
+    /// runtime's cache layer can invalidate without analyzing documents.
+    ///
+    /// Field return types are resolved through `schema`, if given.
+    pub fn with_mutation_invalidation(
+        mut self,
+        invalidation: Option<&'a MutationInvalidation>,
+        schema: Option<&'a SchemaIndex>,
+    ) -> Self {
+        self.mutation_invalidation = invalidation;
+        self.schema = schema;
+        self
+    }
+
+    /// Log build failures through `logger`.
+    pub fn with_logger(mut self, logger: Logger) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Generate code with the syntax of `profile`.
+    pub fn with_output_profile(mut self, profile: OutputProfile) -> Self {
+        self.output_profile = profile;
+        self
+    }
+
+    /// Reuse prebuild expressions from `cache` across files.
+    pub fn with_prebuild_cache(mut self, cache: Option<&'a PrebuildCache>) -> Self {
+        self.prebuild_cache = cache;
+        self
+    }
+
+    /// Emit the given document definitions once per file instead of inlining them.
+    pub fn with_shared_definitions(mut self, definitions: Vec<Arc<Value>>) -> Self {
+        self.shared_definitions = definitions;
+        self
+    }
+
+    /// Build the declarations for shared definitions.
+    ///
+    /// Output: `const __soda_gql_shared_0 = JSON.parse("...")`
+    pub fn build_shared_declarations(&self) -> Vec<Stmt> {
+        self.shared_definitions
+            .iter()
+            .enumerate()
+            .filter_map(|(index, definition)| {
+                let json = serde_json::to_string(definition.as_ref()).ok()?;
+                Some(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+                    span: DUMMY_SP,
+                    ctxt: SyntaxContext::empty(),
+                    kind: self.output_profile.declaration_kind(),
+                    declare: false,
+                    decls: vec![VarDeclarator {
+                        span: DUMMY_SP,
+                        name: Pat::Ident(BindingIdent {
+                            id: self.shared_definition_ident(index),
+                            type_ann: None,
+                        }),
+                        init: Some(Box::new(self.create_json_parse(&json))),
+                        definite: false,
+                    }],
+                }))))
+            })
+            .collect()
     }
 
     /// Build replacement expression and optional runtime statement.
     ///
     /// For fragments: returns just the replacement expression.
     /// For operations: returns both a reference expression and a runtime setup statement.
-    pub fn build_replacement(&self, replacement: &GqlReplacement) -> Option<(Expr, Option<Stmt>)> {
-        let result = match &replacement.artifact {
+    pub fn build_replacement(&self, replacement: &GqlReplacement<'_>) -> Option<(Expr, Option<Stmt>)> {
+        let result = match &*replacement.artifact {
             BuilderArtifactElement::Fragment { prebuild, .. } => self
-                .build_fragment_call(prebuild, &replacement.builder_args)
-                .map(|expr| (expr, None)),
-            BuilderArtifactElement::Operation { prebuild, .. } => {
-                self.build_operation_calls(prebuild)
-            }
+                .build_fragment_call(prebuild, &replacement.builder_args, &replacement.options)
+                .map(|mut expr| {
+                    self.attach_debug_payload(&mut expr, replacement);
+                    (expr, None)
+                }),
+            BuilderArtifactElement::Operation { prebuild, metadata, .. } => self
+                .build_operation_calls(&replacement.canonical_id, prebuild, &replacement.options)
+                .map(|(reference, stmt)| {
+                    let stmt = stmt.map(|mut stmt| {
+                        if let Stmt::Expr(ExprStmt { expr, .. }) = &mut stmt {
+                            self.attach_debug_payload(expr, replacement);
+                        }
+                        self.guard_registration(&replacement.canonical_id, &metadata.content_hash, stmt)
+                    });
+                    (reference, stmt)
+                }),
         };
 
         if result.is_none() {
-            let artifact_type = match &replacement.artifact {
+            let artifact_type = match &*replacement.artifact {
                 BuilderArtifactElement::Fragment { .. } => "Fragment",
                 BuilderArtifactElement::Operation { .. } => "Operation",
             };
-            eprintln!(
-                "[swc] Warning: Failed to build replacement for {} artifact (canonical ID: '{}'). \
+            // Reported to the caller as a structured error by the transformer
+            self.logger.debug(format_args!(
+                "Failed to build replacement for {} artifact (canonical ID: '{}'). \
                 This may indicate missing or mismatched builder arguments.",
                 artifact_type, replacement.canonical_id
-            );
+            ));
         }
 
         result
     }
 
-    /// Create the runtime accessor expression.
-    fn create_runtime_accessor(&self) -> Expr {
-        if self.is_cjs {
-            // __soda_gql_runtime.gqlRuntime
-            Expr::Member(MemberExpr {
-                span: DUMMY_SP,
-                obj: Box::new(Expr::Ident(Ident::new(
-                    CJS_RUNTIME_NAME.into(),
-                    DUMMY_SP,
-                    Default::default(),
-                ))),
-                prop: MemberProp::Ident(IdentName::new(RUNTIME_IMPORT_NAME.into(), DUMMY_SP)),
-            })
-        } else {
-            Expr::Ident(Ident::new(
-                RUNTIME_IMPORT_NAME.into(),
-                DUMMY_SP,
-                Default::default(),
-            ))
-        }
-    }
-
-    /// Create a runtime method call.
-    fn create_runtime_call(&self, method: &str, args: Vec<ExprOrSpread>) -> Expr {
+    /// Create a runtime method call from one of the callee templates.
+    fn create_runtime_call(&self, callee: &Callee, args: Vec<ExprOrSpread>) -> Expr {
         Expr::Call(CallExpr {
             span: DUMMY_SP,
             ctxt: SyntaxContext::empty(),
-            callee: Callee::Expr(Box::new(Expr::Member(MemberExpr {
-                span: DUMMY_SP,
-                obj: Box::new(self.create_runtime_accessor()),
-                prop: MemberProp::Ident(IdentName::new(method.into(), DUMMY_SP)),
-            }))),
+            callee: callee.clone(),
             args,
             type_args: None,
         })
@@ -92,18 +337,25 @@ impl RuntimeCallBuilder {
     ///
     /// Input: `fragment.User({}, fields)`
     /// Output: `gqlRuntime.fragment({ prebuild: { typename: "User" } })`
+    ///
+    /// A `name` or `schema` call option adds `key` or `schemaLabel` to the prebuild.
     fn build_fragment_call(
         &self,
         prebuild: &FragmentPrebuild,
         _builder_args: &[ExprOrSpread],
+        options: &GqlCallOptions,
     ) -> Option<Expr> {
-        let arg = self.create_object_lit(vec![(
-            "prebuild",
-            self.create_object_lit(vec![("typename", self.create_string_lit(&prebuild.typename))]),
-        )]);
+        let mut props = vec![(atom!("typename"), self.create_string_lit(&prebuild.typename))];
+        if let Some(name) = &options.name {
+            props.push((atom!("key"), self.create_string_lit(name)));
+        }
+        if let Some(schema) = &options.schema {
+            props.push((atom!("schemaLabel"), self.create_string_lit(schema)));
+        }
+        let arg = self.create_object_lit(vec![(atom!("prebuild"), self.create_object_lit(props))]);
 
         Some(self.create_runtime_call(
-            "fragment",
+            &self.callees.fragment,
             vec![ExprOrSpread {
                 spread: None,
                 expr: Box::new(arg),
@@ -116,17 +368,81 @@ impl RuntimeCallBuilder {
     /// Returns (reference_call, runtime_call) where:
     /// - runtime_call: `gqlRuntime.operation({ prebuild: JSON.parse(...), runtime: {} })`
     /// - reference_call: `gqlRuntime.getOperation("OperationName")`
-    fn build_operation_calls(&self, prebuild: &OperationPrebuild) -> Option<(Expr, Option<Stmt>)> {
+    ///
+    /// A `schema` call option overrides the prebuild's `schemaLabel`. With hashed
+    /// operation names, both calls use the hash instead of `OperationName`.
+    ///
+    /// With a subscription transport, subscriptions use
+    /// `gqlRuntime.subscription({ prebuild, runtime: {}, transport: "websocket" })`
+    /// and `gqlRuntime.getSubscription("OperationName")`. With mutation
+    /// invalidation, mutations get `invalidates: ["User", ...]`. Operations using
+    /// `@defer` or `@stream` get `incremental: true`.
+    fn build_operation_calls(
+        &self,
+        canonical_id: &CanonicalId,
+        prebuild: &OperationPrebuild,
+        options: &GqlCallOptions,
+    ) -> Option<(Expr, Option<Stmt>)> {
+        // Configured typenames are keyed by the readable name, so resolve them before hashing
+        let invalidates = self.invalidated_typenames(prebuild);
+        let incremental = operation_metrics(&prebuild.document, &prebuild.operation_name).is_incremental();
+        let runtime = self.build_runtime_object(prebuild);
+        let hashed;
+        let prebuild = if self.hash_operation_names {
+            hashed = hashed_prebuild(prebuild)?;
+            &hashed
+        } else {
+            prebuild
+        };
+
         // Build the runtime call
-        let prebuild_json = serde_json::to_string(prebuild).ok()?;
+        let prebuild_expr = if self.has_shared_definitions(prebuild) {
+            // Depends on which definitions this file shares, so never cached
+            self.build_shared_prebuild(prebuild)?
+        } else if self.client_safe || self.trusted_documents {
+            // The cache holds full prebuilds, so client-safe and document-less ones are built per file
+            self.create_json_parse(&serde_json::to_string(&self.prebuild_value(prebuild)?).ok()?)
+        } else {
+            let build = || Some(self.create_json_parse(&serde_json::to_string(prebuild).ok()?));
+            match self.prebuild_cache {
+                Some(cache) => cache.get_or_build(canonical_id, build)?,
+                None => build()?,
+            }
+        };
+        let prebuild_expr = match &options.schema {
+            Some(schema) => self.create_spread_object_lit(
+                prebuild_expr,
+                vec![(atom!("schemaLabel"), self.create_string_lit(schema))],
+            ),
+            None => prebuild_expr,
+        };
+        let subscription_transport = self
+            .subscription_transport
+            .filter(|_| prebuild.operation_type == "subscription");
+        let (register_callee, reference_callee) = match subscription_transport {
+            Some(_) => (&self.callees.subscription, &self.callees.get_subscription),
+            None => (&self.callees.operation, &self.callees.get_operation),
+        };
+
+        let mut props = vec![
+            (atom!("prebuild"), prebuild_expr),
+            (atom!("runtime"), runtime),
+        ];
+        if let Some(transport) = subscription_transport {
+            props.push((atom!("transport"), self.create_string_lit(transport.as_str())));
+        }
+        if incremental {
+            props.push((atom!("incremental"), Expr::Lit(Lit::Bool(Bool { span: DUMMY_SP, value: true }))));
+        }
+        if let Some(typenames) = invalidates {
+            let typenames = typenames.iter().map(|typename| self.create_string_lit(typename)).collect();
+            props.push((atom!("invalidates"), self.create_array_lit(typenames)));
+        }
         let runtime_call_expr = self.create_runtime_call(
-            "operation",
+            register_callee,
             vec![ExprOrSpread {
                 spread: None,
-                expr: Box::new(self.create_object_lit(vec![
-                    ("prebuild", self.create_json_parse(&prebuild_json)),
-                    ("runtime", self.create_object_lit(vec![])),
-                ])),
+                expr: Box::new(self.create_object_lit(props)),
             }],
         );
 
@@ -138,7 +454,7 @@ impl RuntimeCallBuilder {
 
         // Build the reference call
         let reference_call = self.create_runtime_call(
-            "getOperation",
+            reference_callee,
             vec![ExprOrSpread {
                 spread: None,
                 expr: Box::new(self.create_string_lit(&prebuild.operation_name)),
@@ -148,17 +464,324 @@ impl RuntimeCallBuilder {
         Some((reference_call, Some(runtime_stmt)))
     }
 
+    /// Build the `runtime` object of an operation registration.
+    ///
+    /// Output: `{}`, or `{ variables: { id: "ID!", limit: "Int" } }` with runtime variables
+    fn build_runtime_object(&self, prebuild: &OperationPrebuild) -> Expr {
+        if !self.runtime_variables {
+            return self.create_object_lit(vec![]);
+        }
+
+        let operation = prebuild.document.definitions.iter().find(|definition| {
+            document::kind(definition) == Some("OperationDefinition")
+                && document::name(definition) == Some(&prebuild.operation_name)
+        });
+        let variables = operation
+            .and_then(|operation| operation.get("variableDefinitions"))
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|definition| {
+                let name = document::name(definition.get("variable")?)?;
+                let type_ref = document::type_ref(definition.get("type")?)?;
+                Some((Atom::from(name), self.create_string_lit(&type_ref)))
+            })
+            .collect();
+
+        self.create_object_lit(vec![(atom!("variables"), self.create_object_lit(variables))])
+    }
+
+    /// Typenames a mutation invalidates, if invalidation hints are enabled.
+    fn invalidated_typenames(&self, prebuild: &OperationPrebuild) -> Option<Vec<String>> {
+        let invalidation = self
+            .mutation_invalidation
+            .filter(|_| prebuild.operation_type == "mutation" && !self.server_module)?;
+        let mut typenames = selected_typenames(&prebuild.document, self.schema);
+        if let Some(configured) = invalidation.typenames.get(prebuild.operation_name.as_str()) {
+            typenames.extend(configured.iter().cloned());
+        }
+        Some(typenames.into_iter().collect())
+    }
+
+    /// Serialize a prebuild, clearing server-only fields for client-safe output
+    /// and the document in trusted-documents mode.
+    fn prebuild_value(&self, prebuild: &OperationPrebuild) -> Option<Value> {
+        let mut value = serde_json::to_value(prebuild).ok()?;
+        if self.client_safe {
+            value.as_object_mut()?.insert("metadata".to_string(), Value::Null);
+        }
+        if self.trusted_documents {
+            value.as_object_mut()?.remove("document");
+        }
+        Some(value)
+    }
+
+    /// Check whether a prebuild embeds any shared definition.
+    fn has_shared_definitions(&self, prebuild: &OperationPrebuild) -> bool {
+        prebuild
+            .document
+            .definitions
+            .iter()
+            .any(|definition| self.shared_definition_index(definition).is_some())
+    }
+
+    /// Build a prebuild that references shared definitions.
+    ///
+    /// Output: `{ ...JSON.parse("..."), document: { ...JSON.parse("..."), definitions: [JSON.parse("..."), __soda_gql_shared_0] } }`
+    fn build_shared_prebuild(&self, prebuild: &OperationPrebuild) -> Option<Expr> {
+        let mut base = self.prebuild_value(prebuild)?;
+        base.as_object_mut()?.remove("document");
+        let base_json = serde_json::to_string(&base).ok()?;
+        let rest_json = serde_json::to_string(&prebuild.document.rest).ok()?;
+
+        let definitions = prebuild
+            .document
+            .definitions
+            .iter()
+            .map(|definition| match self.shared_definition_index(definition) {
+                Some(index) => Some(Expr::Ident(self.shared_definition_ident(index))),
+                None => Some(self.create_json_parse(&serde_json::to_string(definition.as_ref()).ok()?)),
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let document = self.create_spread_object_lit(
+            self.create_json_parse(&rest_json),
+            vec![(atom!("definitions"), self.create_array_lit(definitions))],
+        );
+
+        Some(self.create_spread_object_lit(
+            self.create_json_parse(&base_json),
+            vec![(atom!("document"), document)],
+        ))
+    }
+
+    /// Find the index of a shared definition by identity.
+    fn shared_definition_index(&self, definition: &Arc<Value>) -> Option<usize> {
+        self.shared_definitions
+            .iter()
+            .position(|shared| Arc::ptr_eq(shared, definition))
+    }
+
+    /// Create the identifier for the shared definition at `index`.
+    ///
+    /// With React Refresh, the identifier is a hash of the definition instead.
+    fn shared_definition_ident(&self, index: usize) -> Ident {
+        let suffix = match self.shared_definitions.get(index).filter(|_| self.react_refresh) {
+            Some(definition) => format!("{:016x}", xxh64(definition.to_string().as_bytes(), 0)),
+            None => index.to_string(),
+        };
+        Ident::new(
+            format!("{}{}", SHARED_DEFINITION_PREFIX, suffix).into(),
+            DUMMY_SP,
+            Default::default(),
+        )
+    }
+
+    /// Build the declaration of the registered hashes for guarded registrations, if enabled.
+    ///
+    /// Output: `const __soda_gql_registered = globalThis.__SODA_GQL_REGISTERED__ || (globalThis.__SODA_GQL_REGISTERED__ = {})`
+    pub fn build_registration_guard_declaration(&self) -> Option<Stmt> {
+        if !self.react_refresh {
+            return None;
+        }
+
+        let global = || MemberExpr {
+            span: DUMMY_SP,
+            obj: Box::new(Expr::Ident(global_ident(atom!("globalThis")))),
+            prop: MemberProp::Ident(IdentName::new(REGISTERED_GLOBAL.into(), DUMMY_SP)),
+        };
+        let init = Expr::Bin(BinExpr {
+            span: DUMMY_SP,
+            op: BinaryOp::LogicalOr,
+            left: Box::new(Expr::Member(global())),
+            right: Box::new(Expr::Paren(ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Assign(AssignExpr {
+                    span: DUMMY_SP,
+                    op: AssignOp::Assign,
+                    left: AssignTarget::Simple(SimpleAssignTarget::Member(global())),
+                    right: Box::new(self.create_object_lit(vec![])),
+                })),
+            })),
+        });
+
+        Some(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+            span: DUMMY_SP,
+            ctxt: SyntaxContext::empty(),
+            kind: self.output_profile.declaration_kind(),
+            declare: false,
+            decls: vec![VarDeclarator {
+                span: DUMMY_SP,
+                name: Pat::Ident(BindingIdent {
+                    id: global_ident(REGISTERED_NAME.into()),
+                    type_ann: None,
+                }),
+                init: Some(Box::new(init)),
+                definite: false,
+            }],
+        }))))
+    }
+
+    /// Add the dev-only `debug` property to a registration call's argument, if enabled.
+    ///
+    /// Output: `debug: process.env.NODE_ENV !== "production" ? { canonicalId, sourcePath, source } : undefined`
+    fn attach_debug_payload(&self, registration: &mut Expr, replacement: &GqlReplacement<'_>) {
+        if !self.debug_payloads {
+            return;
+        }
+        let Expr::Call(call) = registration else {
+            return;
+        };
+        let Some(Expr::Object(arg)) = call.args.first_mut().map(|arg| &mut *arg.expr) else {
+            return;
+        };
+
+        let mut props = vec![
+            (atom!("canonicalId"), self.create_string_lit(&replacement.canonical_id)),
+            (
+                atom!("sourcePath"),
+                self.create_string_lit(&replacement.artifact.metadata().source_path),
+            ),
+        ];
+        if let Some(source_text) = &replacement.source_text {
+            props.push((atom!("source"), self.create_string_lit(source_text)));
+        }
+
+        let node_env = ["env", "NODE_ENV"]
+            .into_iter()
+            .fold(Expr::Ident(global_ident(atom!("process"))), |obj, prop| member_expr(obj, prop.into()));
+        let debug = Expr::Cond(CondExpr {
+            span: DUMMY_SP,
+            test: Box::new(Expr::Bin(BinExpr {
+                span: DUMMY_SP,
+                op: BinaryOp::NotEqEq,
+                left: Box::new(node_env),
+                right: Box::new(self.create_string_lit("production")),
+            })),
+            cons: Box::new(self.create_object_lit(props)),
+            alt: Box::new(Expr::Ident(global_ident(atom!("undefined")))),
+        });
+        arg.props.extend(self.create_key_value_props(vec![(atom!("debug"), debug)]));
+    }
+
+    /// Skip a registration when the module re-runs with the same definition, if enabled.
+    ///
+    /// Output: `if (__soda_gql_registered["id"] !== "hash") { __soda_gql_registered["id"] = "hash"; gqlRuntime.operation(...); }`
+    fn guard_registration(&self, canonical_id: &CanonicalId, content_hash: &str, registration: Stmt) -> Stmt {
+        if !self.react_refresh {
+            return registration;
+        }
+
+        let entry = || MemberExpr {
+            span: DUMMY_SP,
+            obj: Box::new(Expr::Ident(global_ident(REGISTERED_NAME.into()))),
+            prop: MemberProp::Computed(ComputedPropName {
+                span: DUMMY_SP,
+                expr: Box::new(self.create_string_lit(canonical_id)),
+            }),
+        };
+        let record = Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: Box::new(Expr::Assign(AssignExpr {
+                span: DUMMY_SP,
+                op: AssignOp::Assign,
+                left: AssignTarget::Simple(SimpleAssignTarget::Member(entry())),
+                right: Box::new(self.create_string_lit(content_hash)),
+            })),
+        });
+
+        Stmt::If(IfStmt {
+            span: DUMMY_SP,
+            test: Box::new(Expr::Bin(BinExpr {
+                span: DUMMY_SP,
+                op: BinaryOp::NotEqEq,
+                left: Box::new(Expr::Member(entry())),
+                right: Box::new(self.create_string_lit(content_hash)),
+            })),
+            cons: Box::new(Stmt::Block(BlockStmt {
+                span: DUMMY_SP,
+                ctxt: SyntaxContext::empty(),
+                stmts: vec![record, registration],
+            })),
+            alt: None,
+        })
+    }
+
     /// Create an object literal expression.
-    fn create_object_lit(&self, props: Vec<(&str, Expr)>) -> Expr {
+    fn create_object_lit(&self, props: Vec<(Atom, Expr)>) -> Expr {
+        Expr::Object(ObjectLit {
+            span: DUMMY_SP,
+            props: self.create_key_value_props(props),
+        })
+    }
+
+    /// Create an object literal that spreads `base` before the given properties.
+    ///
+    /// Without object spread support: `Object.assign({}, base, { ... })`
+    fn create_spread_object_lit(&self, base: Expr, props: Vec<(Atom, Expr)>) -> Expr {
+        if !self.output_profile.supports_object_spread() {
+            return self.create_object_assign(vec![
+                self.create_object_lit(Vec::new()),
+                base,
+                self.create_object_lit(props),
+            ]);
+        }
+
+        let spread = PropOrSpread::Spread(SpreadElement {
+            dot3_token: DUMMY_SP,
+            expr: Box::new(base),
+        });
+
         Expr::Object(ObjectLit {
             span: DUMMY_SP,
-            props: props
+            props: std::iter::once(spread)
+                .chain(self.create_key_value_props(props))
+                .collect(),
+        })
+    }
+
+    /// Create an `Object.assign(...)` call expression.
+    fn create_object_assign(&self, args: Vec<Expr>) -> Expr {
+        Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            ctxt: SyntaxContext::empty(),
+            callee: self.callees.object_assign.clone(),
+            args: args
+                .into_iter()
+                .map(|expr| ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(expr),
+                })
+                .collect(),
+            type_args: None,
+        })
+    }
+
+    /// Create key-value properties for an object literal.
+    fn create_key_value_props(&self, props: Vec<(Atom, Expr)>) -> Vec<PropOrSpread> {
+        props
+            .into_iter()
+            .map(|(key, value)| {
+                PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(IdentName::new(key, DUMMY_SP)),
+                    value: Box::new(value),
+                })))
+            })
+            .collect()
+    }
+
+    /// Create an array literal expression.
+    fn create_array_lit(&self, elements: Vec<Expr>) -> Expr {
+        Expr::Array(ArrayLit {
+            span: DUMMY_SP,
+            elems: elements
                 .into_iter()
-                .map(|(key, value)| {
-                    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                        key: PropName::Ident(IdentName::new(key.into(), DUMMY_SP)),
-                        value: Box::new(value),
-                    })))
+                .map(|expr| {
+                    Some(ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(expr),
+                    })
                 })
                 .collect(),
         })
@@ -178,15 +801,7 @@ impl RuntimeCallBuilder {
         Expr::Call(CallExpr {
             span: DUMMY_SP,
             ctxt: SyntaxContext::empty(),
-            callee: Callee::Expr(Box::new(Expr::Member(MemberExpr {
-                span: DUMMY_SP,
-                obj: Box::new(Expr::Ident(Ident::new(
-                    "JSON".into(),
-                    DUMMY_SP,
-                    Default::default(),
-                ))),
-                prop: MemberProp::Ident(IdentName::new("parse".into(), DUMMY_SP)),
-            }))),
+            callee: self.callees.json_parse.clone(),
             args: vec![ExprOrSpread {
                 spread: None,
                 expr: Box::new(self.create_string_lit(json)),
@@ -195,3 +810,52 @@ impl RuntimeCallBuilder {
         })
     }
 }
+
+/// Create an unresolved identifier for a global or generated binding.
+fn global_ident(name: Atom) -> Ident {
+    Ident::new(name, DUMMY_SP, Default::default())
+}
+
+/// Create an `obj.prop` member expression.
+fn member_expr(obj: Expr, prop: Atom) -> Expr {
+    Expr::Member(MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::new(obj),
+        prop: MemberProp::Ident(IdentName::new(prop, DUMMY_SP)),
+    })
+}
+
+/// Short hash of an operation's document, used as its name with hashed
+/// operation names and as its ID in trusted-documents mode.
+///
+/// The hash covers the normalized print of the document, so regenerating the
+/// artifact with only formatting changes keeps it.
+pub fn hashed_operation_name(prebuild: &OperationPrebuild) -> Option<String> {
+    let document = match print_normalized(&prebuild.document) {
+        Some(printed) => printed,
+        None => serde_json::to_string(&prebuild.document).ok()?,
+    };
+    // 48 bits keep names short while collisions stay unlikely for any realistic operation count
+    Some(format!("{}{:012x}", HASHED_NAME_PREFIX, xxh64(document.as_bytes(), 0) >> 16))
+}
+
+/// Rename an operation to a short hash of its document.
+///
+/// The operation definition in the document is renamed too, so the name sent
+/// with requests still matches the document.
+fn hashed_prebuild(prebuild: &OperationPrebuild) -> Option<OperationPrebuild> {
+    let hashed_name = hashed_operation_name(prebuild)?;
+
+    let mut hashed = prebuild.clone();
+    for definition in &mut hashed.document.definitions {
+        if document::kind(definition) == Some("OperationDefinition")
+            && document::name(definition) == Some(&prebuild.operation_name)
+        {
+            let mut renamed = Value::clone(definition);
+            renamed["name"]["value"] = Value::String(hashed_name.clone());
+            *definition = Arc::new(renamed);
+        }
+    }
+    hashed.operation_name = hashed_name.into();
+    Some(hashed)
+}
@@ -0,0 +1,66 @@
+//! Per-thread scratch buffers reused across transform calls.
+//!
+//! Emitting code and source maps writes into growable buffers. In watch mode
+//! thousands of small files are re-transformed on the same thread, so the
+//! buffers are kept per thread and cleared between files instead of being
+//! allocated and regrown for every call. The transformer never creates
+//! hygiene marks, so there is no SWC `GLOBALS` state to set up per call.
+
+use std::cell::RefCell;
+
+use swc_core::common::{BytePos, LineCol};
+
+/// Buffers larger than this are released after use, so one huge file does not
+/// pin its memory for the lifetime of the thread.
+const MAX_RETAINED_CAPACITY: usize = 4 * 1024 * 1024;
+
+thread_local! {
+    static EMIT_SCRATCH: RefCell<EmitScratch> = RefCell::new(EmitScratch::default());
+}
+
+/// Buffers used while emitting a module.
+#[derive(Default)]
+pub struct EmitScratch {
+    /// Generated code.
+    pub code: Vec<u8>,
+    /// Source map mappings collected by the writer.
+    pub mappings: Vec<(BytePos, LineCol)>,
+    /// Serialized source map.
+    pub source_map: Vec<u8>,
+}
+
+impl EmitScratch {
+    fn clear(&mut self) {
+        self.code.clear();
+        self.mappings.clear();
+        self.source_map.clear();
+    }
+
+    fn release_oversized(&mut self) {
+        if self.code.capacity() > MAX_RETAINED_CAPACITY {
+            self.code = Vec::new();
+        }
+        if self.mappings.capacity() * size_of::<(BytePos, LineCol)>() > MAX_RETAINED_CAPACITY {
+            self.mappings = Vec::new();
+        }
+        if self.source_map.capacity() > MAX_RETAINED_CAPACITY {
+            self.source_map = Vec::new();
+        }
+    }
+}
+
+/// Run `f` with this thread's emit buffers, cleared.
+///
+/// Nested calls (not expected) get fresh buffers instead of panicking.
+pub fn with_emit_scratch<R>(f: impl FnOnce(&mut EmitScratch) -> R) -> R {
+    EMIT_SCRATCH.with(|scratch| {
+        let Ok(mut scratch) = scratch.try_borrow_mut() else {
+            return f(&mut EmitScratch::default());
+        };
+
+        scratch.clear();
+        let result = f(&mut scratch);
+        scratch.release_oversized();
+        result
+    })
+}
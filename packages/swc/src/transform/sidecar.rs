@@ -0,0 +1,66 @@
+//! Per-file sidecar metadata.
+//!
+//! With `emitSidecar`, each transform result lists the definitions replaced in
+//! the file and where their references ended up in the output, so downstream
+//! tooling (bundle analyzers, route-level prefetchers) can index GraphQL usage
+//! per module without re-parsing the output.
+
+use serde::{Deserialize, Serialize};
+use swc_core::common::{BytePos, LineCol, Span};
+
+use crate::types::BuilderArtifactElement;
+
+use super::analysis::GqlReplacement;
+
+/// A definition replaced in the file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SidecarEntry {
+    pub canonical_id: String,
+    /// "fragment" or "operation".
+    pub element_type: String,
+    /// Operation name, for operations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation_name: Option<String>,
+    /// Position of the replacement expression in the output code.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_position: Option<OutputPosition>,
+}
+
+/// A position in the output code.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OutputPosition {
+    /// 1-based line.
+    pub line: u32,
+    /// 0-based column, in UTF-16 code units.
+    pub column: u32,
+}
+
+/// Build the sidecar entries of the replacements made at `replaced` spans, in output order.
+///
+/// `mappings` are the `(source position, output position)` pairs collected
+/// while emitting; replacements keep the span of the original expression, so
+/// the first mapping at its start is where the replacement was written.
+pub fn sidecar_entries(replaced: &[(Span, &GqlReplacement<'_>)], mappings: &[(BytePos, LineCol)]) -> Vec<SidecarEntry> {
+    let mut entries: Vec<SidecarEntry> = replaced
+        .iter()
+        .map(|(span, replacement)| SidecarEntry {
+            canonical_id: replacement.canonical_id.to_string(),
+            element_type: replacement.artifact.element_type().to_string(),
+            operation_name: match &*replacement.artifact {
+                BuilderArtifactElement::Operation { prebuild, .. } => Some(prebuild.operation_name.to_string()),
+                BuilderArtifactElement::Fragment { .. } => None,
+            },
+            output_position: mappings
+                .iter()
+                .find(|(pos, _)| *pos == span.lo)
+                .map(|(_, line_col)| OutputPosition {
+                    line: line_col.line + 1,
+                    column: line_col.col,
+                }),
+        })
+        .collect();
+
+    entries.sort_by_key(|entry| entry.output_position.map(|position| (position.line, position.column)));
+    entries
+}
@@ -0,0 +1,49 @@
+//! Original identifier names for generated source maps.
+//!
+//! Source maps map generated positions back to the source, and can name the
+//! original identifier at each position so minified stack traces symbolicate
+//! to readable names. The transformer keeps the spans of every identifier it
+//! did not generate, so their names are indexed by start position.
+
+use rustc_hash::FxHashMap;
+use swc_core::common::BytePos;
+use swc_core::ecma::ast::{Ident, IdentName, Module};
+use swc_core::ecma::atoms::Atom;
+use swc_core::ecma::visit::{Visit, VisitWith};
+
+/// Identifier names in a module, by the start position of their span.
+#[derive(Default)]
+pub struct SourceNames {
+    names: FxHashMap<BytePos, Atom>,
+}
+
+impl SourceNames {
+    /// Index the identifiers of `module` that come from the source.
+    pub fn collect(module: &Module) -> Self {
+        let mut names = Self::default();
+        module.visit_with(&mut names);
+        names
+    }
+
+    /// Name of the identifier starting at `pos`, if any.
+    pub fn get(&self, pos: BytePos) -> Option<&str> {
+        self.names.get(&pos).map(|name| name.as_str())
+    }
+
+    fn insert(&mut self, pos: BytePos, name: &Atom) {
+        // Generated identifiers have no position in the source
+        if pos != BytePos::DUMMY {
+            self.names.insert(pos, name.clone());
+        }
+    }
+}
+
+impl Visit for SourceNames {
+    fn visit_ident(&mut self, ident: &Ident) {
+        self.insert(ident.span.lo, &ident.sym);
+    }
+
+    fn visit_ident_name(&mut self, ident: &IdentName) {
+        self.insert(ident.span.lo, &ident.sym);
+    }
+}
@@ -0,0 +1,63 @@
+//! Session-level diagnostics aggregation.
+//!
+//! `SwcTransformer` records the errors of every transformed file, so a build
+//! can print one consolidated report at the end instead of per-file output.
+
+use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard};
+
+use serde::Serialize;
+
+use crate::types::{PluginError, Severity, SeverityCounts};
+
+/// Errors recorded across transform calls, until cleared.
+#[derive(Default)]
+pub struct DiagnosticsLog {
+    summary: Mutex<DiagnosticsSummary>,
+}
+
+/// Counts of recorded errors, including collapsed occurrences.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsSummary {
+    pub total: usize,
+    pub by_severity: SeverityCounts,
+    pub by_code: BTreeMap<String, usize>,
+    pub by_file: BTreeMap<String, usize>,
+}
+
+impl DiagnosticsLog {
+    /// Record the errors reported for `source_path`.
+    pub fn record(&self, source_path: &str, errors: &[PluginError]) {
+        if errors.is_empty() {
+            return;
+        }
+
+        let mut summary = self.lock();
+        for error in errors {
+            let count = error.occurrences;
+            summary.total += count;
+            match error.severity {
+                Severity::Error => summary.by_severity.error += count,
+                Severity::Warning => summary.by_severity.warning += count,
+                Severity::Info => summary.by_severity.info += count,
+            }
+            *summary.by_code.entry(error.code.clone()).or_default() += count;
+            *summary.by_file.entry(source_path.to_string()).or_default() += count;
+        }
+    }
+
+    /// Get the counts recorded so far.
+    pub fn summary(&self) -> DiagnosticsSummary {
+        self.lock().clone()
+    }
+
+    /// Drop all recorded counts.
+    pub fn clear(&self) {
+        *self.lock() = DiagnosticsSummary::default();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, DiagnosticsSummary> {
+        self.summary.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
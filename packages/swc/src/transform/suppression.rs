@@ -0,0 +1,116 @@
+//! Diagnostic suppression comments.
+//!
+//! Supported forms:
+//! - `// @soda-gql-ignore-next-line [CODE ...]` suppresses diagnostics for
+//!   calls starting on the following line
+//! - `/* @soda-gql-disable [CODE ...] */` suppresses diagnostics from the
+//!   comment until a matching `/* @soda-gql-enable [CODE ...] */` or the end
+//!   of the file
+//!
+//! Without codes, every diagnostic is suppressed; otherwise only the listed
+//! codes (comma- or space-separated) are.
+
+use swc_core::common::comments::SingleThreadedComments;
+use swc_core::common::SourceMap;
+
+use crate::types::PluginError;
+
+const IGNORE_NEXT_LINE: &str = "@soda-gql-ignore-next-line";
+const DISABLE: &str = "@soda-gql-disable";
+const ENABLE: &str = "@soda-gql-enable";
+
+/// Suppression comments collected from one file.
+#[derive(Debug, Default)]
+pub struct Suppressions {
+    /// Lines (1-based) whose diagnostics are ignored, with the codes to ignore.
+    ignored_lines: Vec<(usize, CodeFilter)>,
+    /// Disabled byte ranges (file-relative; open-ended until re-enabled).
+    disabled_regions: Vec<DisabledRegion>,
+}
+
+#[derive(Debug)]
+struct DisabledRegion {
+    start: u32,
+    end: Option<u32>,
+    codes: CodeFilter,
+}
+
+/// Codes a suppression applies to; empty means every code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CodeFilter(Vec<String>);
+
+impl CodeFilter {
+    fn parse(rest: &str) -> Self {
+        Self(
+            rest.split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|code| !code.is_empty())
+                .map(str::to_string)
+                .collect(),
+        )
+    }
+
+    fn matches(&self, code: &str) -> bool {
+        self.0.is_empty() || self.0.iter().any(|listed| listed == code)
+    }
+}
+
+impl Suppressions {
+    /// Collect suppression comments from a parsed file, in source order.
+    pub fn collect(cm: &SourceMap, comments: &SingleThreadedComments) -> Self {
+        let (leading, trailing) = comments.borrow_all();
+        let mut all: Vec<_> = leading.values().chain(trailing.values()).flatten().collect();
+        all.sort_by_key(|comment| comment.span.lo);
+
+        let mut suppressions = Self::default();
+        for comment in all {
+            let text = comment.text.trim();
+            let end = cm.lookup_byte_offset(comment.span.hi).pos.0;
+
+            if let Some(rest) = directive_args(text, IGNORE_NEXT_LINE) {
+                let line = cm.lookup_char_pos(comment.span.hi).line;
+                suppressions.ignored_lines.push((line + 1, CodeFilter::parse(rest)));
+            } else if let Some(rest) = directive_args(text, DISABLE) {
+                suppressions.disabled_regions.push(DisabledRegion {
+                    start: end,
+                    end: None,
+                    codes: CodeFilter::parse(rest),
+                });
+            } else if let Some(rest) = directive_args(text, ENABLE) {
+                let codes = CodeFilter::parse(rest);
+                for region in &mut suppressions.disabled_regions {
+                    if region.end.is_none() && (codes.0.is_empty() || region.codes == codes) {
+                        region.end = Some(end);
+                    }
+                }
+            }
+        }
+
+        suppressions
+    }
+
+    /// Check whether a suppression comment covers `error`.
+    ///
+    /// Errors without a location are never suppressed.
+    pub fn is_suppressed(&self, error: &PluginError) -> bool {
+        let Some(span) = error.span else {
+            return false;
+        };
+
+        let ignored = self
+            .ignored_lines
+            .iter()
+            .any(|(line, codes)| *line == span.line && codes.matches(&error.code));
+        ignored
+            || self.disabled_regions.iter().any(|region| {
+                span.start >= region.start
+                    && region.end.is_none_or(|end| span.start < end)
+                    && region.codes.matches(&error.code)
+            })
+    }
+}
+
+/// Return the arguments of a comment directive, if `text` is that directive.
+fn directive_args<'a>(text: &'a str, directive: &str) -> Option<&'a str> {
+    let rest = text.strip_prefix(directive)?;
+    (rest.is_empty() || rest.starts_with(char::is_whitespace)).then_some(rest)
+}
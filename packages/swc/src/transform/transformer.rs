@@ -9,23 +9,45 @@
 //! 6. Emit code with SWC codegen
 
 use serde::{Deserialize, Serialize};
-use swc_core::common::comments::SingleThreadedComments;
+use swc_core::common::comments::{Comments, SingleThreadedComments};
 use swc_core::common::source_map::SourceMapGenConfig;
 use swc_core::common::sync::Lrc;
-use swc_core::common::{BytePos, FileName, SourceMap};
+use swc_core::common::{BytePos, FileName, SourceMap, Span};
 use swc_core::ecma::ast::*;
-use swc_core::ecma::codegen::{text_writer::JsWriter, Emitter};
+use swc_core::ecma::codegen::text_writer::{JsWriter, WriteJs};
+use swc_core::ecma::codegen::Emitter;
 use swc_core::ecma::parser::{lexer::Lexer, Parser, Syntax, TsSyntax};
 use swc_core::ecma::visit::{VisitMut, VisitMutWith, VisitWith};
 
-use crate::types::{BuilderArtifact, TransformInput, TransformInputRef};
-
-use super::analysis::GqlCallFinder;
-use super::imports::ImportManager;
-use super::metadata::MetadataCollector;
+use crate::artifact::stats::OperationStats;
+use crate::artifact::{ArtifactSource, LazyArtifact};
+use crate::types::{CanonicalId, ModuleDirective, TransformConfig, TransformInput, TransformInputRef};
+
+use super::banner::take_legal_banner;
+use super::analysis::{canonical_path, take_builder_collection, GqlCallFinder, GqlReplacement};
+use super::cache::ModuleCache;
+use super::debug_dump::{debug_dump, DebugDump};
+use super::directive::module_directive;
+use super::estree::module_to_estree;
+use super::explain::{explain_call, ExplainReport};
+use super::captures::collect_captures;
+use super::filter::is_included;
+use super::flags::fold_compile_flags;
+use super::graphql_tag::{graphql_tag_bindings, GRAPHQL_TAG_MODULE};
+use super::import_map::graphql_system_aliases;
+use super::index_map::to_index_map;
+use super::imports::{find_runtime_import, is_runtime_require, ImportChanges, ImportManager};
+use super::metadata::{MetadataCollector, MetadataMap};
+use super::paths::{normalize_path, relative_path};
+use super::registry::{is_registry_module, registry_module_code};
 use super::runtime::RuntimeCallBuilder;
+use super::scratch::with_emit_scratch;
+use super::sidecar::{sidecar_entries, SidecarEntry};
+use super::source_names::SourceNames;
+use super::suppression::Suppressions;
 
-use crate::types::PluginError;
+use crate::types::lsp::{DiagnosticFormat, LspDiagnostic};
+use crate::types::{collapse_duplicates, ErrorPolicy, ErrorSpan, PluginError, Severity, SeverityCounts};
 
 /// Result of a transformation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,9 +64,87 @@ pub struct TransformResult {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub errors: Vec<PluginError>,
 
+    /// Number of errors per severity, for deciding whether to fail the build.
+    #[serde(default)]
+    pub severity_counts: SeverityCounts,
+
+    /// Errors as LSP diagnostics, if `diagnosticFormat` is "lsp".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<Vec<LspDiagnostic>>,
+
     /// Source map JSON, if source map generation was enabled.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub source_map: Option<String>,
+
+    /// The transformed program as ESTree JSON, if `emitEstree` was enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estree: Option<serde_json::Value>,
+
+    /// Module and file dependencies for bundler watch registration.
+    #[serde(default, skip_serializing_if = "TransformDependencies::is_empty")]
+    pub dependencies: TransformDependencies,
+
+    /// Replaced definitions and their output positions, if `emitSidecar` was enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sidecar: Option<Vec<SidecarEntry>>,
+
+    /// Intermediate transform state, if `debugDump` was enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug_dump: Option<DebugDump>,
+
+    /// Canonical IDs of the artifact entries the file resolved to.
+    /// Not serialized; recorded by `SwcTransformer` for unused entry reporting.
+    #[serde(skip)]
+    pub matched_ids: Vec<CanonicalId>,
+}
+
+/// Result of transforming a file to both module formats.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DualTransformResult {
+    /// Output with a runtime `import`.
+    pub esm: TransformResult,
+    /// Output with a runtime `require`.
+    pub cjs: TransformResult,
+}
+
+/// Modules and files a transformed file depends on.
+///
+/// Lets bundler plugins register watch dependencies and invalidate outputs
+/// when the artifact or graphql-system module changes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransformDependencies {
+    /// Module specifiers whose imports were added (the runtime module).
+    #[serde(default)]
+    pub added_imports: Vec<String>,
+
+    /// Module specifiers whose imports were removed (graphql-system imports,
+    /// and graphql-tag with the interop).
+    #[serde(default)]
+    pub removed_imports: Vec<String>,
+
+    /// Files the output logically depends on (artifact files, graphql-system file).
+    #[serde(default)]
+    pub files: Vec<String>,
+}
+
+impl TransformDependencies {
+    fn new(changes: ImportChanges, artifact_files: &[String], graphql_system_path: Option<&str>) -> Self {
+        let mut files = artifact_files.to_vec();
+        if changes.removed.iter().any(|specifier| specifier != GRAPHQL_TAG_MODULE) {
+            files.extend(graphql_system_path.map(str::to_string));
+        }
+
+        Self {
+            added_imports: changes.added,
+            removed_imports: changes.removed,
+            files,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added_imports.is_empty() && self.removed_imports.is_empty() && self.files.is_empty()
+    }
 }
 
 /// Transform a source file.
@@ -55,239 +155,433 @@ pub struct TransformResult {
 /// # Returns
 /// Result containing the transformed code, or an error message
 pub fn transform_source(input: &TransformInput) -> Result<TransformResult, String> {
-    // Check if this is an internal module (graphql-system or inject) - if so, stub it out
+    // Index the artifact; elements are parsed and validated on first reference
+    let artifact = LazyArtifact::parse(&input.artifact_json)?;
+
+    // Parse the schema, if configured
+    let schema = input.config.load_schema()?;
+
+    transform_source_ref(&TransformInputRef {
+        source_code: input.source_code.clone(),
+        source_path: input.source_path.clone(),
+        artifact: &artifact,
+        schema: schema.as_ref(),
+        prebuild_cache: None,
+        module_cache: None,
+        artifact_files: &[],
+        config: input.config.clone(),
+        context: input.context.clone(),
+        log_sink: None,
+    })
+}
+
+/// Transform a source file with an already loaded artifact.
+///
+/// This is more efficient than `transform_source` when transforming multiple files
+/// with the same artifact, as it avoids repeated JSON parsing.
+///
+/// # Arguments
+/// * `input` - The transformation input containing source, path, artifact reference, and config
+///
+/// # Returns
+/// Result containing the transformed code, or an error message
+pub fn transform_source_ref(input: &TransformInputRef<'_>) -> Result<TransformResult, String> {
+    // Check if this is an internal module (graphql-system or inject) - if so, stub it out (unless disabled)
     if is_internal_module_file(
         &input.source_path,
         &input.config.graphql_system_path,
         &input.config.inject_paths,
     ) {
-        return Ok(TransformResult {
-            output_code: "export {};".to_string(),
-            transformed: true,
-            errors: Vec::new(),
-            source_map: None,
-        });
+        return Ok(internal_module(&input.config, &input.source_path, &input.source_code));
     }
 
-    // Parse the artifact
-    let artifact: BuilderArtifact = serde_json::from_str(&input.artifact_json)
-        .map_err(|e| format!("Failed to parse artifact: {}", e))?;
-
-    // Create source map
-    let cm: Lrc<SourceMap> = Default::default();
-    let fm = cm.new_source_file(
-        Lrc::new(FileName::Custom(input.source_path.clone())),
-        input.source_code.clone(),
-    );
+    // Generate registry virtual modules from the artifact
+    if is_registry_module(&input.config, &input.source_path) {
+        return registry_module(&input.config, input.artifact, input.artifact_files);
+    }
 
-    // Determine if this is a TSX file
-    let is_tsx = input.source_path.ends_with(".tsx");
+    // Skip parsing files filtered out by `include`/`exclude` or that cannot contain gql calls
+    if !is_included(&input.config, &input.source_path)
+        || !may_reference_gql(&input.config, &input.source_code, &input.source_path)
+    {
+        return Ok(untransformed(&input.config, &input.source_code));
+    }
 
-    // Create comments storage for preservation
+    // Create source map and comments storage for preservation
+    let cm: Lrc<SourceMap> = Default::default();
     let comments = SingleThreadedComments::default();
+    let mut module = parse_module_cached(input.module_cache, &cm, &input.source_path, &input.source_code, &comments)?;
 
-    // Create parser with comments collection
-    let lexer = Lexer::new(
-        Syntax::Typescript(TsSyntax {
-            tsx: is_tsx,
-            ..Default::default()
-        }),
-        EsVersion::Es2022,
-        (&*fm).into(),
-        Some(&comments),
-    );
-
-    let mut parser = Parser::new_from(lexer);
-    let mut module = parser
-        .parse_module()
-        .map_err(|e| format!("Parse error: {:?}", e))?;
+    // Return sources a previous transform already ran on (e.g., a loader registered
+    // twice) unchanged, since a second pass would duplicate registrations
+    if let Some(span) = find_runtime_import(&module, &input.config.runtime_modules()) {
+        return Ok(already_transformed(&input.config, &cm, span, &input.source_code, &input.source_path));
+    }
 
     // Collect metadata about GQL definitions
     let metadata = MetadataCollector::collect(&module, &input.source_path);
+    let template_tags = template_tags(&input.config, &module);
 
-    // Find and analyze GQL calls
-    let mut finder = GqlCallFinder::new(&artifact, &metadata, &input.source_path);
-    module.visit_with(&mut finder);
+    // Drop definitions guarded by compile-time flags that are off; metadata is
+    // collected first so AST paths match the builder's
+    let flags_folded = fold_compile_flags(&mut module, &input.config.compile_flags);
 
-    // If no GQL calls found, return unchanged (but may have errors)
-    if !finder.has_transformations() {
-        return Ok(TransformResult {
+    // Find and analyze GQL calls (use pre-parsed artifact reference)
+    let mut finder = call_finder(input, &cm, &comments, &module, &metadata, &template_tags)
+        .with_stop_on_error(input.config.error_policy() == ErrorPolicy::FailFirst);
+    module.visit_with(&mut finder);
+    // `failFirst` fails the call before anything is transformed
+    if input.config.error_policy() == ErrorPolicy::FailFirst {
+        if let Some(error) = finder.first_error() {
+            return Err(error.format());
+        }
+    }
+    let dump = input.config.debug_dump.then(|| {
+        debug_dump(&cm, &module, &metadata, &finder, &input.source_path, &input.config.path_mappings)
+    });
+
+    // If no GQL calls found and nothing was removed, return unchanged (but may have errors)
+    if !finder.has_transformations() && !flags_folded {
+        finder.report_orphan_import(&module, &graphql_system_aliases(&input.config, &input.source_path));
+        let errors = collapse_duplicates(finder.take_errors());
+        let result = TransformResult {
             output_code: input.source_code.clone(),
             transformed: false,
-            errors: finder.take_errors(),
+            severity_counts: SeverityCounts::of(&errors),
+            diagnostics: lsp_diagnostics(&input.config, &errors, &input.source_code, &input.source_path),
+            errors,
             source_map: None,
-        });
+            estree: input.config.emit_estree.then(|| estree_of(&cm, &module, &input.source_code)),
+            dependencies: TransformDependencies::default(),
+            sidecar: None,
+            debug_dump: dump,
+            matched_ids: Vec::new(),
+        };
+        return apply_error_policy(&input.config, &input.source_code, result);
     }
 
     // Build runtime calls and transform
-    let runtime_builder = RuntimeCallBuilder::new(input.config.is_cjs);
-    let mut transformer = GqlTransformer::new(&finder, &runtime_builder, &input.source_path);
+    let directive = input.config.effective_directive(module_directive(&module), &input.source_path);
+    let mut runtime_builder = runtime_call_builder(input, directive);
+    // Prebuilds carry no documents in trusted-documents mode, so there is nothing to share
+    if input.config.dedupe_prebuilds && input.config.trusted_documents.is_none() {
+        runtime_builder = runtime_builder.with_shared_definitions(finder.shared_definitions());
+    }
+    let mut transformer = GqlTransformer::new(&finder, &runtime_builder, &input.source_path)
+        .with_pure_annotations(input.config.pure_annotations().then_some(&comments));
     module.visit_mut_with(&mut transformer);
 
     // Manage imports
     let mut import_manager = ImportManager::new(
         transformer.needs_runtime_import(),
-        input.config.is_cjs,
-        &input.config.graphql_system_aliases,
-    );
+        input.config.emits_cjs(),
+        &graphql_system_aliases(&input.config, &input.source_path),
+    )
+    .with_runtime_module(input.config.runtime_module(&input.context, directive))
+    .with_output_profile(input.config.output_profile)
+    .with_graphql_tag_interop(input.config.graphql_tag_interop)
+    .with_keep_graphql_system(transformer.keeps_gql_calls());
     module.visit_mut_with(&mut import_manager);
+    let import_changes = import_manager.take_changes();
 
-    // Insert runtime calls after imports
+    // Insert shared definitions and runtime calls after imports
+    let mut runtime_calls = runtime_builder.build_shared_declarations();
     if !transformer.runtime_calls.is_empty() {
-        insert_runtime_calls(&mut module, std::mem::take(&mut transformer.runtime_calls));
+        runtime_calls.extend(runtime_builder.build_registration_guard_declaration());
     }
+    runtime_calls.append(&mut transformer.runtime_calls);
+    insert_runtime_calls(&mut module, runtime_calls);
 
     // Emit the transformed code with preserved comments and optional source map
-    let emit_output = emit_module(&cm, &module, &comments, input.config.source_map)?;
+    let sidecar = input.config.emit_sidecar.then_some(transformer.replaced.as_slice());
+    let emit_output = emit_module(&cm, &module, &comments, &input.config, sidecar)?;
 
     // Collect errors from both phases
-    // Take transformer errors first, then drop to release borrow of finder
     let transformer_errors = transformer.take_errors();
     drop(transformer);
-    // Now we can mutably borrow finder
     let mut errors = finder.take_errors();
     errors.extend(transformer_errors);
+    let errors = collapse_duplicates(errors);
 
-    Ok(TransformResult {
+    let result = TransformResult {
         output_code: emit_output.code,
         transformed: true,
+        severity_counts: SeverityCounts::of(&errors),
+        diagnostics: lsp_diagnostics(&input.config, &errors, &input.source_code, &input.source_path),
         errors,
         source_map: emit_output.source_map,
-    })
+        estree: input.config.emit_estree.then(|| estree_of(&cm, &module, &input.source_code)),
+        dependencies: TransformDependencies::new(
+            import_changes,
+            input.artifact_files,
+            input.config.graphql_system_path.as_deref(),
+        ),
+        sidecar: emit_output.sidecar,
+        debug_dump: dump,
+        matched_ids: finder.matched_ids(),
+    };
+    apply_error_policy(&input.config, &input.source_code, result)
+}
+
+/// Result of analyzing a source file without transforming it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyzeResult {
+    /// Operations referenced by the file, ordered by canonical ID.
+    pub operations: Vec<AnalyzedOperation>,
+
+    /// Errors encountered during analysis.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<PluginError>,
+}
+
+/// An operation referenced by an analyzed file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyzedOperation {
+    pub canonical_id: CanonicalId,
+
+    #[serde(flatten)]
+    pub stats: OperationStats,
 }
 
-/// Transform a source file with a pre-parsed artifact reference.
+/// Analyze a source file with an already loaded artifact.
 ///
-/// This is more efficient than `transform_source` when transforming multiple files
-/// with the same artifact, as it avoids repeated JSON parsing.
+/// Runs the same parsing and call detection as `transform_source_ref`, but
+/// reports metrics for the referenced operations instead of emitting code.
 ///
 /// # Arguments
-/// * `input` - The transformation input containing source, path, artifact reference, and config
+/// * `input` - The input containing source, path, artifact reference, and config
 ///
 /// # Returns
-/// Result containing the transformed code, or an error message
-pub fn transform_source_ref(input: &TransformInputRef<'_>) -> Result<TransformResult, String> {
-    // Check if this is an internal module (graphql-system or inject) - if so, stub it out
-    if is_internal_module_file(
-        &input.source_path,
-        &input.config.graphql_system_path,
-        &input.config.inject_paths,
-    ) {
-        return Ok(TransformResult {
-            output_code: "export {};".to_string(),
-            transformed: true,
+/// Result containing the referenced operations and analysis errors, or an error message
+pub fn analyze_source_ref(input: &TransformInputRef<'_>) -> Result<AnalyzeResult, String> {
+    if !is_included(&input.config, &input.source_path)
+        || !may_reference_gql(&input.config, &input.source_code, &input.source_path)
+    {
+        return Ok(AnalyzeResult {
+            operations: Vec::new(),
             errors: Vec::new(),
-            source_map: None,
         });
     }
 
-    // Create source map
     let cm: Lrc<SourceMap> = Default::default();
-    let fm = cm.new_source_file(
-        Lrc::new(FileName::Custom(input.source_path.clone())),
-        input.source_code.clone(),
-    );
+    let comments = SingleThreadedComments::default();
+    let mut module = parse_module_cached(input.module_cache, &cm, &input.source_path, &input.source_code, &comments)?;
 
-    // Determine if this is a TSX file
-    let is_tsx = input.source_path.ends_with(".tsx");
+    let metadata = MetadataCollector::collect(&module, &input.source_path);
+    let template_tags = template_tags(&input.config, &module);
+    fold_compile_flags(&mut module, &input.config.compile_flags);
+    let mut finder = call_finder(input, &cm, &comments, &module, &metadata, &template_tags);
+    module.visit_with(&mut finder);
 
-    // Create comments storage for preservation
-    let comments = SingleThreadedComments::default();
+    let mut operations: Vec<AnalyzedOperation> = finder
+        .replacements()
+        .filter_map(|replacement| {
+            Some(AnalyzedOperation {
+                canonical_id: replacement.canonical_id.clone(),
+                stats: OperationStats::from_element(&replacement.artifact)?,
+            })
+        })
+        .collect();
+    operations.sort_by(|a, b| a.canonical_id.cmp(&b.canonical_id));
+    finder.report_orphan_import(&module, &graphql_system_aliases(&input.config, &input.source_path));
+
+    Ok(AnalyzeResult {
+        operations,
+        errors: collapse_duplicates(finder.take_errors()),
+    })
+}
 
-    // Create parser with comments collection
-    let lexer = Lexer::new(
-        Syntax::Typescript(TsSyntax {
-            tsx: is_tsx,
-            ..Default::default()
-        }),
-        EsVersion::Es2022,
-        (&*fm).into(),
-        Some(&comments),
-    );
+/// Explain how the gql calls of a source file resolve against the artifact.
+///
+/// Runs the same parsing and call detection as `transform_source_ref`, but
+/// reports each call's canonical ID and artifact match instead of emitting code.
+///
+/// # Arguments
+/// * `input` - The input containing source, path, artifact reference, and config
+///
+/// # Returns
+/// Result containing the per-call report, or an error message
+pub fn explain_source_ref(input: &TransformInputRef<'_>) -> Result<ExplainReport, String> {
+    let path = canonical_path(&input.source_path, &input.config.path_mappings);
 
-    let mut parser = Parser::new_from(lexer);
-    let mut module = parser
-        .parse_module()
-        .map_err(|e| format!("Parse error: {:?}", e))?;
+    let cm: Lrc<SourceMap> = Default::default();
+    let comments = SingleThreadedComments::default();
+    let mut module = parse_module_cached(input.module_cache, &cm, &input.source_path, &input.source_code, &comments)?;
 
-    // Collect metadata about GQL definitions
     let metadata = MetadataCollector::collect(&module, &input.source_path);
-
-    // Find and analyze GQL calls (use pre-parsed artifact reference)
-    let mut finder = GqlCallFinder::new(input.artifact, &metadata, &input.source_path);
+    let template_tags = template_tags(&input.config, &module);
+    fold_compile_flags(&mut module, &input.config.compile_flags);
+    let mut finder = call_finder(input, &cm, &comments, &module, &metadata, &template_tags);
     module.visit_with(&mut finder);
 
-    // If no GQL calls found, return unchanged (but may have errors)
-    if !finder.has_transformations() {
-        return Ok(TransformResult {
-            output_code: input.source_code.clone(),
-            transformed: false,
-            errors: finder.take_errors(),
-            source_map: None,
-        });
-    }
+    let mut resolutions: Vec<_> = finder.resolutions().iter().collect();
+    resolutions.sort_by_key(|resolution| resolution.span.lo);
+    let calls = resolutions
+        .into_iter()
+        .map(|resolution| explain_call(&cm, input.artifact, &path, resolution))
+        .collect();
+
+    Ok(ExplainReport {
+        canonical_path: path,
+        calls,
+        errors: collapse_duplicates(finder.take_errors()),
+    })
+}
 
-    // Build runtime calls and transform
-    let runtime_builder = RuntimeCallBuilder::new(input.config.is_cjs);
-    let mut transformer = GqlTransformer::new(&finder, &runtime_builder, &input.source_path);
-    module.visit_mut_with(&mut transformer);
+/// Create the call finder for a parsed file, configured from the input.
+///
+/// Shared by transforming, analyzing, and explaining, so they resolve calls the same way.
+fn call_finder<'a>(
+    input: &'a TransformInputRef<'_>,
+    cm: &'a SourceMap,
+    comments: &SingleThreadedComments,
+    module: &Module,
+    metadata: &'a MetadataMap,
+    template_tags: &'a [String],
+) -> GqlCallFinder<'a> {
+    GqlCallFinder::new(input.artifact, metadata, &input.source_path, input.schema)
+        .with_path_mappings(&input.config.path_mappings)
+        .with_template_tags(template_tags)
+        .with_field_policy(&input.config.field_policy)
+        .with_trusted_documents(input.config.trusted_documents.as_ref())
+        .with_extract_prebuilds(input.config.extract_prebuilds)
+        .with_source_text(input.config.debug_payloads())
+        .with_source_map(cm)
+        .with_suppressions(Suppressions::collect(cm, comments))
+        .with_captures(collect_captures(module))
+        .with_logger(input.logger())
+}
 
-    // Manage imports
-    let mut import_manager = ImportManager::new(
-        transformer.needs_runtime_import(),
-        input.config.is_cjs,
-        &input.config.graphql_system_aliases,
-    );
-    module.visit_mut_with(&mut import_manager);
+/// Create the runtime call builder for a file with the given directive, configured from the input.
+fn runtime_call_builder<'a>(input: &'a TransformInputRef<'_>, directive: Option<ModuleDirective>) -> RuntimeCallBuilder<'a> {
+    RuntimeCallBuilder::new(input.config.emits_cjs())
+        .with_logger(input.logger())
+        .with_output_profile(input.config.output_profile)
+        .with_hashed_operation_names(input.config.hash_operation_names())
+        .with_trusted_documents(input.config.trusted_documents.is_some())
+        .with_subscription_transport(input.config.subscription_transport)
+        .with_react_refresh(input.config.react_refresh)
+        .with_debug_payloads(input.config.debug_payloads())
+        .with_runtime_variables(input.config.runtime_variables)
+        .with_mutation_invalidation(input.config.mutation_invalidation.as_ref(), input.schema)
+        .with_prebuild_cache(input.prebuild_cache)
+        .with_client_safe(input.config.is_client_module(directive))
+        .with_server_module(input.config.is_server_module(directive))
+}
 
-    // Insert runtime calls after imports
-    if !transformer.runtime_calls.is_empty() {
-        insert_runtime_calls(&mut module, std::mem::take(&mut transformer.runtime_calls));
+/// Parse a source file, reusing the module from `cache` if the source is unchanged.
+fn parse_module_cached(
+    cache: Option<&ModuleCache>,
+    cm: &Lrc<SourceMap>,
+    source_path: &str,
+    source_code: &str,
+    comments: &SingleThreadedComments,
+) -> Result<Module, String> {
+    let Some(cache) = cache else {
+        return parse_module(cm, source_path, source_code, comments);
+    };
+
+    if let Some(module) = cache.get(source_path, source_code, comments) {
+        // Register the file so the cached spans resolve for source maps and errors
+        cm.new_source_file(
+            Lrc::new(FileName::Custom(source_path.to_string())),
+            source_code.to_string(),
+        );
+        return Ok(module);
     }
 
-    // Emit the transformed code with preserved comments and optional source map
-    let emit_output = emit_module(&cm, &module, &comments, input.config.source_map)?;
+    let module = parse_module(cm, source_path, source_code, comments)?;
+    cache.insert(source_path, source_code, &module, comments);
+    Ok(module)
+}
 
-    // Collect errors from both phases
-    let transformer_errors = transformer.take_errors();
-    drop(transformer);
-    let mut errors = finder.take_errors();
-    errors.extend(transformer_errors);
+/// Parse a source file as TypeScript (TSX for `.tsx` files), collecting comments.
+pub fn parse_module(
+    cm: &Lrc<SourceMap>,
+    source_path: &str,
+    source_code: &str,
+    comments: &SingleThreadedComments,
+) -> Result<Module, String> {
+    let fm = cm.new_source_file(
+        Lrc::new(FileName::Custom(source_path.to_string())),
+        source_code.to_string(),
+    );
 
-    Ok(TransformResult {
-        output_code: emit_output.code,
-        transformed: true,
-        errors,
-        source_map: emit_output.source_map,
+    // Create parser with comments collection
+    let lexer = Lexer::new(
+        parser_syntax(source_path),
+        EsVersion::Es2022,
+        (&*fm).into(),
+        Some(comments),
+    );
+
+    let mut parser = Parser::new_from(lexer);
+    parser
+        .parse_module()
+        .map_err(|e| format!("Parse error: {:?}", e))
+}
+
+/// Parser syntax of a source file: TypeScript, with JSX in `.tsx` files.
+pub fn parser_syntax(source_path: &str) -> Syntax {
+    Syntax::Typescript(TsSyntax {
+        tsx: source_path.ends_with(".tsx"),
+        ..Default::default()
     })
 }
 
+/// Convert a module parsed into `cm` to an ESTree program.
+fn estree_of(cm: &Lrc<SourceMap>, module: &Module, source_code: &str) -> serde_json::Value {
+    let start_pos = cm.files().first().map_or(BytePos(1), |file| file.start_pos);
+    module_to_estree(module, source_code, start_pos)
+}
+
 /// Main AST transformer that replaces gql.default() calls with runtime calls.
 struct GqlTransformer<'a> {
     finder: &'a GqlCallFinder<'a>,
-    runtime_builder: &'a RuntimeCallBuilder,
+    runtime_builder: &'a RuntimeCallBuilder<'a>,
     needs_runtime: bool,
+    /// Whether some gql calls were left in place, so they still need the graphql-system import
+    kept_calls: bool,
     pub runtime_calls: Vec<Stmt>,
+    /// Original spans of the replaced expressions, with their replacements
+    replaced: Vec<(Span, &'a GqlReplacement<'a>)>,
+    /// Comments of the file, to mark replaced references `/*#__PURE__*/`, if enabled
+    pure_comments: Option<&'a SingleThreadedComments>,
     errors: Vec<PluginError>,
     source_path: String,
 }
 
 impl<'a> GqlTransformer<'a> {
-    fn new(finder: &'a GqlCallFinder<'a>, runtime_builder: &'a RuntimeCallBuilder, source_path: &str) -> Self {
+    fn new(finder: &'a GqlCallFinder<'a>, runtime_builder: &'a RuntimeCallBuilder<'a>, source_path: &str) -> Self {
         Self {
             finder,
             runtime_builder,
             needs_runtime: false,
+            kept_calls: false,
             runtime_calls: Vec::new(),
+            replaced: Vec::new(),
+            pure_comments: None,
             errors: Vec::new(),
             source_path: source_path.to_string(),
         }
     }
 
+    /// Mark replaced references `/*#__PURE__*/` by adding comments to `comments`.
+    fn with_pure_annotations(mut self, comments: Option<&'a SingleThreadedComments>) -> Self {
+        self.pure_comments = comments;
+        self
+    }
+
     fn needs_runtime_import(&self) -> bool {
         self.needs_runtime
     }
 
+    fn keeps_gql_calls(&self) -> bool {
+        self.kept_calls
+    }
+
     fn take_errors(&mut self) -> Vec<PluginError> {
         std::mem::take(&mut self.errors)
     }
@@ -298,52 +592,140 @@ impl VisitMut for GqlTransformer<'_> {
         // First visit children
         expr.visit_mut_children_with(self);
 
-        // Check if this is a GQL call that should be transformed
-        if let Expr::Call(call) = expr {
-            if let Some(replacement) = self.finder.get_replacement(call) {
-                // Mark that we need the runtime import
-                self.needs_runtime = true;
-
-                // Build the replacement expression
-                if let Some((reference_expr, runtime_stmt)) =
-                    self.runtime_builder.build_replacement(replacement)
-                {
-                    // Store the runtime statement to be inserted later
-                    if let Some(stmt) = runtime_stmt {
-                        self.runtime_calls.push(stmt);
-                    }
+        // Check if this is a GQL call or tagged template that should be transformed
+        let span = match expr {
+            Expr::Call(call) => call.span,
+            Expr::TaggedTpl(tagged) => tagged.span,
+            _ => return,
+        };
+        // A gql call returning an object or array becomes that object or array,
+        // with its builder calls already replaced while visiting the children
+        if self.finder.is_collection_definition(span) {
+            if let Some(collection) = match expr {
+                Expr::Call(call) => take_builder_collection(call),
+                _ => None,
+            } {
+                *expr = collection;
+            }
+            return;
+        }
+        if let Some(replacement) = self.finder.get_replacement(span) {
+            // Server modules are left as they are rather than pulling in browser-only runtime code
+            if let Some(helper) = self.runtime_builder.client_only_helper(&replacement.artifact) {
+                let error = PluginError::client_only_helper(&self.source_path, &replacement.canonical_id, helper)
+                    .with_span(self.finder.locate(span));
+                if !self.finder.is_suppressed(&error) {
+                    self.finder.logger().error(&error);
+                    self.errors.push(error);
+                }
+                self.kept_calls = true;
+                return;
+            }
+
+            // Mark that we need the runtime import
+            self.needs_runtime = true;
 
-                    // Replace the expression
-                    *expr = reference_expr;
-                } else {
-                    // Record structured error when replacement build fails
-                    let artifact_type = match &replacement.artifact {
-                        crate::types::BuilderArtifactElement::Fragment { .. } => "fragment",
-                        crate::types::BuilderArtifactElement::Operation { .. } => "operation",
-                    };
-                    let error = PluginError::missing_builder_arg(
-                        &self.source_path,
-                        artifact_type,
-                        "builder callback",
-                    );
-                    eprintln!("[swc] {}", error.format());
+            // Build the replacement expression
+            if let Some((reference_expr, runtime_stmt)) =
+                self.runtime_builder.build_replacement(replacement)
+            {
+                // Store the runtime statement to be inserted later
+                if let Some(stmt) = runtime_stmt {
+                    self.runtime_calls.push(stmt);
+                }
+
+                // Replace the expression, keeping the original span so source maps
+                // and sidecar positions point back at the definition
+                *expr = reference_expr;
+                if let Expr::Call(call) = expr {
+                    call.span = span;
+                    if let Some(comments) = self.pure_comments {
+                        comments.add_pure_comment(span.lo);
+                    }
+                }
+                self.replaced.push((span, replacement));
+            } else {
+                // Record structured error when replacement build fails
+                let artifact_type = match &*replacement.artifact {
+                    crate::types::BuilderArtifactElement::Fragment { .. } => "fragment",
+                    crate::types::BuilderArtifactElement::Operation { .. } => "operation",
+                };
+                let error = PluginError::missing_builder_arg(
+                    &self.source_path,
+                    artifact_type,
+                    "builder callback",
+                )
+                .with_span(self.finder.locate(span));
+                if !self.finder.is_suppressed(&error) {
+                    self.finder.logger().error(&error);
                     self.errors.push(error);
                 }
+                self.kept_calls = true;
             }
         }
     }
 }
 
-/// Insert runtime calls after the last import statement.
+/// Apply the configured error policy to a file's result.
+///
+/// `failFirst` turns the first error into a failure of the whole call (errors
+/// found during analysis already failed it before transforming);
+/// `failFile` discards the transformation so no partially transformed file
+/// with missing registrations is emitted. Warnings and informational
+/// diagnostics never trigger either.
+fn apply_error_policy(
+    config: &TransformConfig,
+    source_code: &str,
+    result: TransformResult,
+) -> Result<TransformResult, String> {
+    let first_error = result.errors.iter().find(|error| error.severity == Severity::Error);
+    let Some(first_error) = first_error else {
+        return Ok(result);
+    };
+
+    match config.error_policy() {
+        ErrorPolicy::Collect => Ok(result),
+        ErrorPolicy::FailFirst => Err(first_error.format()),
+        ErrorPolicy::FailFile => Ok(TransformResult {
+            output_code: source_code.to_string(),
+            transformed: false,
+            source_map: None,
+            estree: None,
+            dependencies: TransformDependencies::default(),
+            sidecar: None,
+            debug_dump: None,
+            matched_ids: Vec::new(),
+            ..result
+        }),
+    }
+}
+
+/// Convert errors to LSP diagnostics if the config asks for them.
+fn lsp_diagnostics(
+    config: &TransformConfig,
+    errors: &[PluginError],
+    source_code: &str,
+    source_path: &str,
+) -> Option<Vec<LspDiagnostic>> {
+    (config.diagnostic_format == DiagnosticFormat::Lsp).then(|| {
+        errors
+            .iter()
+            .map(|error| LspDiagnostic::from_error(error, source_code, source_path))
+            .collect()
+    })
+}
+
+/// Insert runtime calls after the last import statement or runtime require.
 fn insert_runtime_calls(module: &mut Module, calls: Vec<Stmt>) {
     if calls.is_empty() {
         return;
     }
 
-    // Find the position after the last import
+    // Find the position after the last import, so the runtime binding is
+    // initialized before the calls run
     let mut insert_pos = 0;
     for (i, item) in module.body.iter().enumerate() {
-        if matches!(item, ModuleItem::ModuleDecl(ModuleDecl::Import(_))) {
+        if matches!(item, ModuleItem::ModuleDecl(ModuleDecl::Import(_))) || is_runtime_require(item) {
             insert_pos = i + 1;
         }
     }
@@ -357,27 +739,39 @@ fn insert_runtime_calls(module: &mut Module, calls: Vec<Stmt>) {
 struct EmitOutput {
     code: String,
     source_map: Option<String>,
+    sidecar: Option<Vec<SidecarEntry>>,
 }
 
 /// Configuration for source map generation.
-struct SimpleSourceMapConfig;
+struct SimpleSourceMapConfig<'a> {
+    /// Original identifier names, recorded as the names of their mappings
+    names: &'a SourceNames,
+    /// Whether to embed the source in `sourcesContent`
+    sources_content: bool,
+    /// Directory `sources` are relative to when the source is not embedded
+    source_map_dir: Option<&'a str>,
+}
 
-impl SourceMapGenConfig for SimpleSourceMapConfig {
+impl SourceMapGenConfig for SimpleSourceMapConfig<'_> {
     fn file_name_to_source(&self, f: &FileName) -> String {
-        match f {
+        let source = match f {
             FileName::Real(path) => path.to_string_lossy().to_string(),
             FileName::Custom(name) => name.clone(),
             FileName::Url(url) => url.to_string(),
             _ => "unknown".to_string(),
+        };
+        match self.source_map_dir.filter(|_| !self.sources_content) {
+            Some(dir) => relative_path(dir, &source),
+            None => source,
         }
     }
 
-    fn name_for_bytepos(&self, _bpos: BytePos) -> Option<&str> {
-        None
+    fn name_for_bytepos(&self, bpos: BytePos) -> Option<&str> {
+        self.names.get(bpos)
     }
 
     fn inline_sources_content(&self, _f: &FileName) -> bool {
-        true // Include source content in the source map
+        self.sources_content
     }
 }
 
@@ -386,49 +780,205 @@ fn emit_module(
     cm: &Lrc<SourceMap>,
     module: &Module,
     comments: &SingleThreadedComments,
-    generate_source_map: bool,
+    config: &TransformConfig,
+    sidecar: Option<&[(Span, &GqlReplacement<'_>)]>,
 ) -> Result<EmitOutput, String> {
-    let mut buf = vec![];
-    let mut srcmap_buf = if generate_source_map {
-        Some(vec![])
-    } else {
-        None
-    };
+    let banner = take_legal_banner(module, comments);
+
+    with_emit_scratch(|scratch| {
+        {
+            // Output positions of the replacements are read from the mappings too
+            let collect_mappings = config.source_map || sidecar.is_some();
+            let writer = JsWriter::new(
+                cm.clone(),
+                "\n",
+                &mut scratch.code,
+                collect_mappings.then_some(&mut scratch.mappings),
+            );
+            let mut emitter = Emitter {
+                cfg: swc_core::ecma::codegen::Config::default().with_minify(false),
+                cm: cm.clone(),
+                comments: Some(comments),
+                wr: writer,
+            };
+
+            // Written through the emitter so source map lines account for them
+            for comment in &banner {
+                emitter
+                    .wr
+                    .write_comment(&format!("/*{}*/", comment.text))
+                    .and_then(|_| emitter.wr.write_line())
+                    .map_err(|e| format!("Emit error: {:?}", e))?;
+            }
+            emitter
+                .emit_module(module)
+                .map_err(|e| format!("Emit error: {:?}", e))?;
+        }
 
-    {
-        let writer = JsWriter::new(
-            cm.clone(),
-            "\n",
-            &mut buf,
-            srcmap_buf.as_mut(),
-        );
-        let mut emitter = Emitter {
-            cfg: swc_core::ecma::codegen::Config::default().with_minify(false),
-            cm: cm.clone(),
-            comments: Some(comments),
-            wr: writer,
+        // Copy out of the reused buffer, allocating the exact size once
+        let code = std::str::from_utf8(&scratch.code)
+            .map_err(|e| format!("UTF-8 error: {}", e))?
+            .to_string();
+
+        let source_map = if config.source_map {
+            // Build source map from collected entries
+            let names = SourceNames::collect(module);
+            let map_config = SimpleSourceMapConfig {
+                names: &names,
+                sources_content: config.sources_content,
+                source_map_dir: config.source_map_dir.as_deref(),
+            };
+            let map = cm.build_source_map(&scratch.mappings, None, map_config);
+            if config.index_source_map {
+                Some(to_index_map(map)?)
+            } else {
+                map.to_writer(&mut scratch.source_map)
+                    .map_err(|e| format!("Source map error: {:?}", e))?;
+                Some(
+                    std::str::from_utf8(&scratch.source_map)
+                        .map_err(|e| format!("Source map UTF-8 error: {}", e))?
+                        .to_string(),
+                )
+            }
+        } else {
+            None
         };
 
-        emitter
-            .emit_module(module)
-            .map_err(|e| format!("Emit error: {:?}", e))?;
+        let sidecar = sidecar.map(|replaced| sidecar_entries(replaced, &scratch.mappings));
+
+        Ok(EmitOutput {
+            code,
+            source_map,
+            sidecar,
+        })
+    })
+}
+
+/// Quick textual check run before parsing.
+///
+/// A file that mentions neither the `gql` identifier, a graphql-system alias,
+/// a configured template tag nor graphql-tag (with the interop) has nothing to
+/// transform, which is the common case in application code, so it can be
+/// returned unchanged without building an AST. Files still go through the
+/// parser when the AST itself is part of the output (`emitEstree`).
+fn may_reference_gql(config: &TransformConfig, source_code: &str, source_path: &str) -> bool {
+    if config.emit_estree || source_code.contains("gql") {
+        return true;
     }
 
-    let code = String::from_utf8(buf).map_err(|e| format!("UTF-8 error: {}", e))?;
+    if config.graphql_tag_interop && source_code.contains(GRAPHQL_TAG_MODULE) {
+        return true;
+    }
 
-    let source_map = if let Some(srcmap) = srcmap_buf {
-        // Build source map from collected entries
-        let config = SimpleSourceMapConfig;
-        let map = cm.build_source_map(&srcmap, None, config);
-        let mut map_buf = vec![];
-        map.to_writer(&mut map_buf)
-            .map_err(|e| format!("Source map error: {:?}", e))?;
-        Some(String::from_utf8(map_buf).map_err(|e| format!("Source map UTF-8 error: {}", e))?)
-    } else {
-        None
-    };
+    config.tagged_template_tags.iter().any(|tag| source_code.contains(tag.as_str()))
+        || graphql_system_aliases(config, source_path)
+            .iter()
+            .any(|alias| source_code.contains(alias.as_str()))
+}
+
+/// Result for a registry virtual module, listing the operations in `artifact`.
+///
+/// Depends on the artifact files, so bundlers regenerate it when operations change.
+fn registry_module(
+    config: &TransformConfig,
+    artifact: &dyn ArtifactSource,
+    artifact_files: &[String],
+) -> Result<TransformResult, String> {
+    Ok(TransformResult {
+        output_code: registry_module_code(config, artifact)?,
+        transformed: true,
+        errors: Vec::new(),
+        severity_counts: SeverityCounts::default(),
+        diagnostics: (config.diagnostic_format == DiagnosticFormat::Lsp).then(Vec::new),
+        source_map: None,
+        estree: None,
+        dependencies: TransformDependencies::new(ImportChanges::default(), artifact_files, None),
+        sidecar: None,
+        debug_dump: None,
+        matched_ids: Vec::new(),
+    })
+}
+
+/// Tags of templates resolved by operation name: the configured tags, plus the
+/// local names of graphql-tag imports with `graphqlTagInterop`.
+fn template_tags(config: &TransformConfig, module: &Module) -> Vec<String> {
+    let mut tags = config.tagged_template_tags.clone();
+    if config.graphql_tag_interop {
+        for binding in graphql_tag_bindings(module) {
+            if !tags.contains(&binding) {
+                tags.push(binding);
+            }
+        }
+    }
+    tags
+}
 
-    Ok(EmitOutput { code, source_map })
+/// Result for a file returned unchanged without being parsed.
+fn untransformed(config: &TransformConfig, source_code: &str) -> TransformResult {
+    TransformResult {
+        output_code: source_code.to_string(),
+        transformed: false,
+        errors: Vec::new(),
+        severity_counts: SeverityCounts::default(),
+        diagnostics: (config.diagnostic_format == DiagnosticFormat::Lsp).then(Vec::new),
+        source_map: None,
+        estree: None,
+        dependencies: TransformDependencies::default(),
+        sidecar: None,
+        debug_dump: None,
+        matched_ids: Vec::new(),
+    }
+}
+
+/// Result for a file that already imports the runtime, returned unchanged with a warning.
+fn already_transformed(
+    config: &TransformConfig,
+    cm: &Lrc<SourceMap>,
+    span: Span,
+    source_code: &str,
+    source_path: &str,
+) -> TransformResult {
+    let errors = vec![PluginError::already_transformed(source_path).with_span(ErrorSpan::resolve(cm, span))];
+    TransformResult {
+        output_code: source_code.to_string(),
+        transformed: false,
+        severity_counts: SeverityCounts::of(&errors),
+        diagnostics: lsp_diagnostics(config, &errors, source_code, source_path),
+        errors,
+        source_map: None,
+        estree: None,
+        dependencies: TransformDependencies::default(),
+        sidecar: None,
+        debug_dump: None,
+        matched_ids: Vec::new(),
+    }
+}
+
+/// Result for an internal module: a stub (`graphqlSystemStub` for the
+/// graphql-system module, if set), or the source untransformed when
+/// `stubGraphqlSystem` is off.
+fn internal_module(config: &TransformConfig, source_path: &str, source_code: &str) -> TransformResult {
+    if !config.stub_graphql_system {
+        return untransformed(config, source_code);
+    }
+
+    let output_code = match (&config.graphql_system_stub, &config.graphql_system_path) {
+        (Some(stub), Some(gql_path)) if is_same_path(source_path, gql_path) => stub.clone(),
+        _ => "export {};".to_string(),
+    };
+    TransformResult {
+        output_code,
+        transformed: true,
+        errors: Vec::new(),
+        severity_counts: SeverityCounts::default(),
+        diagnostics: (config.diagnostic_format == DiagnosticFormat::Lsp).then(Vec::new),
+        source_map: None,
+        estree: None,
+        dependencies: TransformDependencies::default(),
+        sidecar: None,
+        debug_dump: None,
+        matched_ids: Vec::new(),
+    }
 }
 
 /// Check if the source file is an internal module that should be stubbed.
@@ -439,23 +989,16 @@ fn is_internal_module_file(
     graphql_system_path: &Option<String>,
     inject_paths: &[String],
 ) -> bool {
-    let normalized_source = source_path.replace('\\', "/");
-
     // Check graphql-system path
-    if let Some(gql_path) = graphql_system_path {
-        let normalized_gql = gql_path.replace('\\', "/");
-        if normalized_source == normalized_gql {
-            return true;
-        }
+    if graphql_system_path.as_deref().is_some_and(|gql_path| is_same_path(source_path, gql_path)) {
+        return true;
     }
 
     // Check inject paths (scalars, adapter)
-    for inject_path in inject_paths {
-        let normalized_inject = inject_path.replace('\\', "/");
-        if normalized_source == normalized_inject {
-            return true;
-        }
-    }
+    inject_paths.iter().any(|inject_path| is_same_path(source_path, inject_path))
+}
 
-    false
+/// Compare two paths with normalized (forward slash, no verbatim prefix) spellings.
+fn is_same_path(a: &str, b: &str) -> bool {
+    normalize_path(a) == normalize_path(b)
 }
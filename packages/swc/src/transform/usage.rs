@@ -0,0 +1,40 @@
+//! Session-level artifact usage tracking.
+//!
+//! `SwcTransformer` records the artifact entries each transformed file
+//! resolved to. Entries never referenced by any file point at stale artifacts
+//! or at AST-path drift between the builder and the transformer.
+
+use std::collections::HashSet;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::artifact::ArtifactSource;
+use crate::types::CanonicalId;
+
+/// Canonical IDs matched across transform calls.
+#[derive(Default)]
+pub struct ArtifactUsage {
+    matched: Mutex<HashSet<CanonicalId>>,
+}
+
+impl ArtifactUsage {
+    /// Record the canonical IDs a file resolved to.
+    pub fn record(&self, canonical_ids: &[CanonicalId]) {
+        if canonical_ids.is_empty() {
+            return;
+        }
+
+        self.lock().extend(canonical_ids.iter().cloned());
+    }
+
+    /// Canonical IDs in `artifact` that no recorded file referenced, sorted.
+    pub fn unused(&self, artifact: &dyn ArtifactSource) -> Vec<CanonicalId> {
+        let matched = self.lock();
+        let mut unused: Vec<CanonicalId> = artifact.ids().filter(|id| !matched.contains(*id)).cloned().collect();
+        unused.sort();
+        unused
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashSet<CanonicalId>> {
+        self.matched.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,151 @@
+//! Golden-output verification.
+//!
+//! Compares transform output with a stored expected output, so downstream
+//! repositories can guard against upgrades of the native transformer changing
+//! emitted code. Mismatches come with a unified diff of the two outputs.
+
+use serde::{Deserialize, Serialize};
+
+/// Lines of unchanged context around each change in the diff.
+const CONTEXT_LINES: usize = 3;
+
+/// Result of verifying transform output against the expected output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyResult {
+    /// Whether the output matches the expected output.
+    pub matches: bool,
+
+    /// Unified diff from the expected output to the actual output, on mismatch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+/// A line of the diff between the expected and actual output.
+enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Compare the output of a file with its expected output.
+///
+/// Line endings are normalized first, so goldens checked out with CRLF still match.
+pub fn verify_output(source_path: &str, actual: &str, expected: &str) -> VerifyResult {
+    let actual = actual.replace("\r\n", "\n");
+    let expected = expected.replace("\r\n", "\n");
+    if actual == expected {
+        return VerifyResult {
+            matches: true,
+            diff: None,
+        };
+    }
+
+    let expected_lines: Vec<&str> = expected.split('\n').collect();
+    let actual_lines: Vec<&str> = actual.split('\n').collect();
+    let lines = diff_lines(&expected_lines, &actual_lines);
+
+    VerifyResult {
+        matches: false,
+        diff: Some(unified_diff(source_path, &lines)),
+    }
+}
+
+/// Diff two lists of lines by their longest common subsequence.
+///
+/// The common prefix and suffix are skipped first, so the quadratic part
+/// only covers the changed region.
+fn diff_lines<'a>(expected: &[&'a str], actual: &[&'a str]) -> Vec<DiffLine<'a>> {
+    let prefix = expected.iter().zip(actual).take_while(|(a, b)| a == b).count();
+    let suffix = expected[prefix..]
+        .iter()
+        .rev()
+        .zip(actual[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old = &expected[prefix..expected.len() - suffix];
+    let new = &actual[prefix..actual.len() - suffix];
+
+    // Lengths of the longest common subsequences of `old[i..]` and `new[j..]`
+    let mut lcs = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines: Vec<DiffLine<'a>> = expected[..prefix].iter().map(|line| DiffLine::Same(line)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    lines.extend(expected[expected.len() - suffix..].iter().map(|line| DiffLine::Same(line)));
+    lines
+}
+
+/// Format diff lines as a unified diff with `CONTEXT_LINES` of context.
+fn unified_diff(source_path: &str, lines: &[DiffLine<'_>]) -> String {
+    let mut diff = format!("--- {} (expected)\n+++ {} (actual)\n", source_path, source_path);
+
+    // Merge the context ranges of nearby changes into hunks
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (index, _) in lines.iter().enumerate().filter(|(_, line)| !matches!(line, DiffLine::Same(_))) {
+        let start = index.saturating_sub(CONTEXT_LINES);
+        let end = (index + CONTEXT_LINES + 1).min(lines.len());
+        match hunks.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    for (start, end) in hunks {
+        let old_before = lines[..start].iter().filter(|line| !matches!(line, DiffLine::Added(_))).count();
+        let new_before = lines[..start].iter().filter(|line| !matches!(line, DiffLine::Removed(_))).count();
+        let hunk = &lines[start..end];
+        let old_len = hunk.iter().filter(|line| !matches!(line, DiffLine::Added(_))).count();
+        let new_len = hunk.iter().filter(|line| !matches!(line, DiffLine::Removed(_))).count();
+
+        diff.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_before, old_len),
+            hunk_range(new_before, new_len)
+        ));
+        for line in hunk {
+            let (marker, text) = match line {
+                DiffLine::Same(text) => (' ', text),
+                DiffLine::Removed(text) => ('-', text),
+                DiffLine::Added(text) => ('+', text),
+            };
+            diff.push(marker);
+            diff.push_str(text);
+            diff.push('\n');
+        }
+    }
+
+    diff
+}
+
+/// Format the `start,length` range of a hunk, given the lines before it.
+///
+/// Empty ranges start at the line before the hunk, as in GNU diff.
+fn hunk_range(before: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", before),
+        1 => format!("{}", before + 1),
+        _ => format!("{},{}", before + 1, len),
+    }
+}
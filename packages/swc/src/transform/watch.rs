@@ -0,0 +1,187 @@
+//! Source file watching for `SwcTransformer::watch`.
+//!
+//! Watches the directories of a set of source globs and re-transforms files
+//! matching them when they are created or modified, so a dev workflow can run
+//! without a bundler driving the transformer. Each glob's directory is the
+//! part before its first wildcard segment; relative globs are resolved against
+//! the working directory. Events of a burst of writes (e.g., a branch switch)
+//! are coalesced, and every changed file is transformed once afterwards.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+
+use crate::artifact::ArtifactSlot;
+use crate::graphql::schema::SchemaIndex;
+use crate::types::config::{TransformConfig, TransformContext, TransformInputRef};
+
+use super::cache::{ModuleCache, PrebuildCache};
+use super::filter::glob_matches;
+use super::logger::LogSink;
+use super::paths::normalize_path;
+use super::realpath::realpath;
+use super::transformer::{transform_source_ref, TransformResult};
+
+/// Quiet period after the last event before changed files are transformed.
+const DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Watches source globs until dropped.
+pub struct SourceWatcher {
+    /// Dropping the watcher closes the event channel and ends the worker thread.
+    _watcher: RecommendedWatcher,
+}
+
+impl SourceWatcher {
+    /// Start watching files matching `patterns`, calling `on_change` on a
+    /// background thread with each changed file's path.
+    pub fn start(patterns: Vec<String>, mut on_change: impl FnMut(String) + Send + 'static) -> Result<Self, String> {
+        if patterns.is_empty() {
+            return Err("No paths to watch".to_string());
+        }
+
+        let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+        let mut watcher =
+            notify::recommended_watcher(tx).map_err(|e| format!("Failed to create source watcher: {}", e))?;
+        for pattern in &patterns {
+            let root = watch_root(pattern);
+            // A pattern without wildcards names a single file
+            let mode = if root.is_file() {
+                RecursiveMode::NonRecursive
+            } else {
+                RecursiveMode::Recursive
+            };
+            watcher
+                .watch(&root, mode)
+                .map_err(|e| format!("Failed to watch '{}': {}", root.display(), e))?;
+        }
+
+        thread::spawn(move || {
+            while let Ok(event) = rx.recv() {
+                let mut changed = BTreeSet::new();
+                collect_changes(&event, &patterns, &mut changed);
+
+                // Coalesce the remaining events of this burst
+                loop {
+                    match rx.recv_timeout(DEBOUNCE) {
+                        Ok(event) => collect_changes(&event, &patterns, &mut changed),
+                        Err(mpsc::RecvTimeoutError::Timeout) => break,
+                        Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+
+                changed.into_iter().for_each(&mut on_change);
+            }
+        });
+
+        Ok(Self { _watcher: watcher })
+    }
+}
+
+/// Directory to watch for a glob: its segments before the first wildcard.
+fn watch_root(pattern: &str) -> PathBuf {
+    let prefix: Vec<&str> = pattern
+        .split('/')
+        .take_while(|segment| !segment.contains(['*', '?', '{']))
+        .collect();
+    let prefix = match prefix.join("/") {
+        prefix if prefix.is_empty() && pattern.starts_with('/') => "/".to_string(),
+        prefix => prefix,
+    };
+
+    let root = Path::new(&prefix);
+    if root.is_absolute() {
+        return root.to_path_buf();
+    }
+    std::env::current_dir().map_or_else(|_| root.to_path_buf(), |cwd| cwd.join(root))
+}
+
+/// Add the files an event creates or modifies that match one of `patterns`.
+fn collect_changes(event: &notify::Result<Event>, patterns: &[String], changed: &mut BTreeSet<String>) {
+    let Ok(event) = event else {
+        return;
+    };
+    if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+        return;
+    }
+
+    for path in event.paths.iter().filter(|path| path.is_file()) {
+        let path = normalize_path(&path.to_string_lossy()).into_owned();
+        if patterns.iter().any(|pattern| glob_matches(pattern, &path)) {
+            changed.insert(path);
+        }
+    }
+}
+
+/// Transforms watched files with the transformer's artifact and config.
+///
+/// Holds its own parse caches, since it runs on the watcher thread. The
+/// artifact is shared, so reloads by `watchArtifact` apply to it as well.
+pub struct WatchTransformer {
+    pub artifact: Arc<ArtifactSlot>,
+    pub schema: Option<Arc<SchemaIndex>>,
+    pub artifact_files: Vec<String>,
+    pub log_sink: Option<LogSink>,
+    pub config: TransformConfig,
+    pub prebuild_cache: PrebuildCache,
+    pub module_cache: ModuleCache,
+}
+
+impl WatchTransformer {
+    /// Read and transform a changed file.
+    pub fn transform_file(&self, source_path: String) -> SourceWatchEvent {
+        match self.try_transform_file(&source_path) {
+            Ok(result) => SourceWatchEvent::Transformed {
+                source_path,
+                result: Box::new(result),
+            },
+            Err(message) => SourceWatchEvent::Error { source_path, message },
+        }
+    }
+
+    fn try_transform_file(&self, source_path: &str) -> Result<TransformResult, String> {
+        let source_code =
+            std::fs::read_to_string(source_path).map_err(|e| format!("Failed to read '{}': {}", source_path, e))?;
+        let source_path = if self.config.resolve_symlinks {
+            realpath(source_path)
+        } else {
+            source_path.to_string()
+        };
+
+        let (generation, artifact) = self.artifact.snapshot();
+        self.prebuild_cache.sync(generation);
+        let shares_prebuilds = !self.config.has_overrides_for(&source_path);
+        let input = TransformInputRef {
+            source_code,
+            config: self.config.for_path(&source_path)?,
+            source_path,
+            artifact: artifact.as_ref(),
+            schema: self.schema.as_deref(),
+            prebuild_cache: shares_prebuilds.then_some(&self.prebuild_cache),
+            module_cache: Some(&self.module_cache),
+            artifact_files: &self.artifact_files,
+            context: TransformContext::default(),
+            log_sink: self.log_sink.clone(),
+        };
+        transform_source_ref(&input)
+    }
+}
+
+/// Outcome of re-transforming a changed file, delivered to the JavaScript callback.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SourceWatchEvent {
+    /// The file was transformed.
+    #[serde(rename_all = "camelCase")]
+    Transformed {
+        source_path: String,
+        result: Box<TransformResult>,
+    },
+    /// The file could not be read or transformed.
+    #[serde(rename_all = "camelCase")]
+    Error { source_path: String, message: String },
+}
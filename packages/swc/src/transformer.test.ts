@@ -6,12 +6,15 @@
  */
 
 import { describe, expect, it } from "bun:test";
-import { mkdirSync, mkdtempSync, realpathSync, writeFileSync } from "node:fs";
+import { existsSync, mkdirSync, mkdtempSync, realpathSync, writeFileSync } from "node:fs";
+import { connect, type Socket } from "node:net";
 import { tmpdir } from "node:os";
 import { dirname, join } from "node:path";
-import type { BuilderArtifact } from "@soda-gql/builder";
+import type { BuilderArtifact, BuilderArtifactOperation } from "@soda-gql/builder";
+import type { CanonicalId } from "@soda-gql/common";
 import type { ResolvedSodaGqlConfig } from "@soda-gql/config";
 import { createTestConfig, loadTestCases, normalizeCode, type TransformTestCase } from "@soda-gql/tsc/test";
+import type { TextEdit } from "./index";
 
 // Check if native module is available before running tests
 // This needs to be evaluated synchronously at module load time
@@ -365,3 +368,1069 @@ describe("swc internal module stubbing", () => {
     expect(transformer.transform({ sourceCode: "export const a = {};", sourcePath: adapter2 }).sourceCode).toBe("export {};");
   });
 });
+
+/**
+ * Create a config without schemas, for tests of transform options.
+ */
+const createOptionsTestConfig = (baseDir: string): ResolvedSodaGqlConfig => ({
+  analyzer: "ts",
+  baseDir,
+  outdir: join(baseDir, "graphql-system"),
+  graphqlSystemAliases: ["@/graphql-system"],
+  include: [],
+  exclude: [],
+  schemas: {},
+  styles: { importExtension: false },
+  codegen: { chunkSize: 100 },
+  plugins: {},
+});
+
+/**
+ * Create an operation element selecting `fields` on the root type, followed by
+ * `definitions` (such as the fragments it spreads) in its document.
+ */
+const createOperationElement = (
+  id: string,
+  operationType: "query" | "mutation" | "subscription",
+  operationName: string,
+  fields: readonly string[] = ["id"],
+  definitions: readonly object[] = [],
+): BuilderArtifactOperation => ({
+  type: "operation",
+  id: id as CanonicalId,
+  metadata: { sourcePath: id.slice(0, id.indexOf("::")), contentHash: "hash" },
+  prebuild: {
+    operationType,
+    operationName,
+    variableNames: [],
+    document: {
+      kind: "Document",
+      definitions: [
+        {
+          kind: "OperationDefinition",
+          operation: operationType,
+          name: { kind: "Name", value: operationName },
+          selectionSet: {
+            kind: "SelectionSet",
+            selections: fields.map((field) => ({ kind: "Field", name: { kind: "Name", value: field } })),
+          },
+        },
+        ...definitions,
+      ],
+    },
+  } as unknown as BuilderArtifactOperation["prebuild"],
+});
+
+/**
+ * Create an artifact holding `elements`.
+ */
+const createArtifact = (elements: readonly BuilderArtifactOperation[]): BuilderArtifact => ({
+  elements: Object.fromEntries(elements.map((element) => [element.id, element])),
+  report: {
+    durationMs: 0,
+    warnings: [],
+    stats: { hits: 0, misses: 0, skips: 0 },
+  },
+});
+
+/**
+ * Source of a file defining one operation per entry of `definitions` with gql.default.
+ */
+const createOperationSource = (
+  definitions: readonly { exportName: string; operationType: "query" | "mutation" | "subscription"; operationName: string }[],
+): string =>
+  [
+    'import { gql } from "@/graphql-system";',
+    "",
+    ...definitions.map(
+      ({ exportName, operationType, operationName }) =>
+        `export const ${exportName} = gql.default(({ ${operationType} }) => ${operationType}.operation({ name: "${operationName}" }, () => ({})));`,
+    ),
+    "",
+  ].join("\n");
+
+describe("swc tagged templates", () => {
+  it.skipIf(!nativeModuleAvailable)("replaces templates naming an artifact operation defined in another file", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createArtifact([createOperationElement("src/user.ts::getUser", "query", "GetUser")]),
+      taggedTemplateTags: ["gql"],
+      logLevel: "silent",
+    });
+
+    const result = transformer.transform({
+      sourceCode: [
+        'import { gql } from "./tag";',
+        "",
+        "export const query = gql`query GetUser { id }`;",
+        "export const missing = gql`query Missing { id }`;",
+        "",
+      ].join("\n"),
+      sourcePath: join(tmpDir, "src/view.ts"),
+    });
+
+    expect(result.sourceCode).toContain('import { gqlRuntime } from "@soda-gql/core/runtime";');
+    expect(result.sourceCode).toContain('"operationName":"GetUser"');
+    expect(result.sourceCode).toContain('export const query = gqlRuntime.getOperation("GetUser");');
+    // Templates naming no artifact operation are left in place and reported
+    expect(result.sourceCode).toContain("export const missing = gql`query Missing { id }`;");
+    expect(result.errors.map((error) => error.code)).toEqual(["SODA_GQL_ANALYSIS_TEMPLATE_UNRESOLVED"]);
+  });
+});
+
+describe("swc error policies", () => {
+  const sourceCode = createOperationSource([
+    { exportName: "getUser", operationType: "query", operationName: "GetUser" },
+    { exportName: "getPost", operationType: "query", operationName: "GetPost" },
+  ]);
+
+  /**
+   * Create a transformer whose artifact lacks the entry of `getUser`.
+   */
+  const createPolicyTransformer = (tmpDir: string, errorPolicy: "collect" | "failFirst" | "failFile") =>
+    createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createArtifact([createOperationElement("src/user.ts::getPost", "query", "GetPost")]),
+      errorPolicy,
+      logLevel: "silent",
+    });
+
+  it.skipIf(!nativeModuleAvailable)("collect transforms the rest of the file and reports the error", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const transformer = await createPolicyTransformer(tmpDir, "collect");
+
+    const result = transformer.transform({ sourceCode, sourcePath: join(tmpDir, "src/user.ts") });
+
+    expect(result.transformed).toBe(true);
+    expect(result.sourceCode).toContain('export const getPost = gqlRuntime.getOperation("GetPost");');
+    expect(result.sourceCode).toContain("export const getUser = gql.default(");
+    expect(result.errors.map((error) => error.code)).toEqual(["SODA_GQL_ANALYSIS_ARTIFACT_NOT_FOUND"]);
+  });
+
+  it.skipIf(!nativeModuleAvailable)("failFile returns the file unchanged with its errors", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const transformer = await createPolicyTransformer(tmpDir, "failFile");
+
+    const result = transformer.transform({ sourceCode, sourcePath: join(tmpDir, "src/user.ts") });
+
+    expect(result.transformed).toBe(false);
+    expect(result.sourceCode).toBe(sourceCode);
+    expect(result.errors.map((error) => error.code)).toEqual(["SODA_GQL_ANALYSIS_ARTIFACT_NOT_FOUND"]);
+  });
+
+  it.skipIf(!nativeModuleAvailable)("failFirst throws the first error", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const transformer = await createPolicyTransformer(tmpDir, "failFirst");
+
+    expect(() => transformer.transform({ sourceCode, sourcePath: join(tmpDir, "src/user.ts") })).toThrow(
+      "[SODA_GQL_ANALYSIS_ARTIFACT_NOT_FOUND]",
+    );
+  });
+});
+
+describe("swc field policy", () => {
+  it.skipIf(!nativeModuleAvailable)("removes denied fields from prebuilds and warns about each", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createArtifact([createOperationElement("src/user.ts::getUser", "query", "GetUser", ["id", "email"])]),
+      schemaSdl: "type Query { id: ID! email: String! }",
+      fieldPolicy: { deny: { Query: ["email"] } },
+      logLevel: "silent",
+    });
+
+    const result = transformer.transform({
+      sourceCode: createOperationSource([{ exportName: "getUser", operationType: "query", operationName: "GetUser" }]),
+      sourcePath: join(tmpDir, "src/user.ts"),
+    });
+
+    expect(result.sourceCode).toContain(
+      '"selectionSet":{"kind":"SelectionSet","selections":[{"kind":"Field","name":{"kind":"Name","value":"id"}}]}',
+    );
+    expect(result.sourceCode).not.toContain("email");
+    expect(result.errors.map((error) => [error.code, error.severity])).toEqual([["SODA_GQL_POLICY_FIELD_PRUNED", "warning"]]);
+  });
+
+  it.skipIf(!nativeModuleAvailable)("throws for a schema that does not parse", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createArtifact([createOperationElement("src/user.ts::getUser", "query", "GetUser")]),
+      schemaSdl: "type Query {",
+      fieldPolicy: { deny: { Query: ["email"] } },
+    });
+
+    expect(() =>
+      transformer.transform({
+        sourceCode: createOperationSource([{ exportName: "getUser", operationType: "query", operationName: "GetUser" }]),
+        sourcePath: join(tmpDir, "src/user.ts"),
+      }),
+    ).toThrow("Failed to parse schema SDL");
+  });
+});
+
+describe("swc config overrides", () => {
+  it.skipIf(!nativeModuleAvailable)("applies an override only to the files it matches", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createArtifact([
+        createOperationElement("src/admin/user.ts::getUser", "query", "GetUser"),
+        createOperationElement("src/app/user.ts::getUser", "query", "GetUser"),
+      ]),
+      overrides: [{ files: "**/admin/**", config: { hashOperationNames: true } }],
+    });
+    const sourceCode = createOperationSource([{ exportName: "getUser", operationType: "query", operationName: "GetUser" }]);
+
+    const admin = transformer.transform({ sourceCode, sourcePath: join(tmpDir, "src/admin/user.ts") });
+    const app = transformer.transform({ sourceCode, sourcePath: join(tmpDir, "src/app/user.ts") });
+
+    expect(admin.sourceCode).toContain('export const getUser = gqlRuntime.getOperation("Op38e84acac90d");');
+    expect(admin.sourceCode).not.toContain("GetUser");
+    expect(app.sourceCode).toContain('export const getUser = gqlRuntime.getOperation("GetUser");');
+  });
+
+  it.skipIf(!nativeModuleAvailable)("rejects overrides of transformer-wide options and mistyped values", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const createOverrideTransformer = (config: Record<string, unknown>) =>
+      createTransformer({
+        config: createOptionsTestConfig(tmpDir),
+        artifact: createArtifact([createOperationElement("src/admin/user.ts::getUser", "query", "GetUser")]),
+        overrides: [{ files: "**/admin/**", config }],
+      });
+    const input = {
+      sourceCode: createOperationSource([{ exportName: "getUser", operationType: "query", operationName: "GetUser" }]),
+      sourcePath: join(tmpDir, "src/admin/user.ts"),
+    };
+
+    const schemaOverride = await createOverrideTransformer({ schemaSdl: "type Query { id: ID! }" });
+    const mistyped = await createOverrideTransformer({ hashOperationNames: "yes" });
+
+    expect(() => schemaOverride.transform(input)).toThrow(
+      "Config override for '**/admin/**' cannot set 'schemaSdl', which applies to the whole transformer",
+    );
+    expect(() => mistyped.transform(input)).toThrow(
+      "Invalid config override for '**/admin/**': invalid type: string \"yes\", expected a boolean",
+    );
+  });
+});
+
+describe("swc trusted documents", () => {
+  it.skipIf(!nativeModuleAvailable)("registers hashed operations without documents and reports unlisted ones", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createArtifact([
+        createOperationElement("src/user.ts::getUser", "query", "GetUser"),
+        createOperationElement("src/user.ts::getPost", "query", "GetPost"),
+      ]),
+      trustedDocuments: { manifestIds: ["Op38e84acac90d"] },
+      logLevel: "silent",
+    });
+
+    const result = transformer.transform({
+      sourceCode: createOperationSource([
+        { exportName: "getUser", operationType: "query", operationName: "GetUser" },
+        { exportName: "getPost", operationType: "query", operationName: "GetPost" },
+      ]),
+      sourcePath: join(tmpDir, "src/user.ts"),
+    });
+
+    expect(result.sourceCode).toContain(
+      'prebuild: JSON.parse(\'{"metadata":null,"operationName":"Op38e84acac90d","operationType":"query","variableNames":[]}\')',
+    );
+    expect(result.sourceCode).toContain('export const getPost = gqlRuntime.getOperation("Opead3de4c3a5a");');
+    expect(result.sourceCode).not.toContain('"document"');
+    expect(result.errors.map((error) => [error.code, error.canonicalId])).toEqual([
+      ["SODA_GQL_UNTRUSTED_DOCUMENT", join(tmpDir, "src/user.ts::getPost")],
+    ]);
+  });
+});
+
+describe("swc server/client split", () => {
+  it.skipIf(!nativeModuleAvailable)("omits operation metadata from client modules", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const element = createOperationElement("src/user.ts::getUser", "query", "GetUser");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createArtifact([
+        { ...element, prebuild: { ...element.prebuild, metadata: { headers: { "x-api-key": "secret" } } } },
+      ]),
+      splitServerClient: true,
+    });
+    const sourceCode = createOperationSource([{ exportName: "getUser", operationType: "query", operationName: "GetUser" }]);
+    const sourcePath = join(tmpDir, "src/user.ts");
+
+    const client = transformer.transform({ sourceCode: `"use client";\n${sourceCode}`, sourcePath });
+    const shared = transformer.transform({ sourceCode, sourcePath });
+
+    expect(client.sourceCode).toContain('"metadata":null');
+    expect(client.sourceCode).not.toContain("secret");
+    expect(shared.sourceCode).toContain('"metadata":{"headers":{"x-api-key":"secret"}}');
+  });
+
+  it.skipIf(!nativeModuleAvailable)("leaves calls with no artifact entry in client modules and reports them", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createArtifact([createOperationElement("src/user.ts::getUser", "query", "GetUser")]),
+      splitServerClient: true,
+    });
+    const sourceCode = createOperationSource([
+      { exportName: "getUser", operationType: "query", operationName: "GetUser" },
+      { exportName: "getPost", operationType: "query", operationName: "GetPost" },
+    ]);
+
+    const result = transformer.transform({ sourceCode: `"use client";\n${sourceCode}`, sourcePath: join(tmpDir, "src/user.ts") });
+
+    expect(result.sourceCode).toContain('export const getUser = gqlRuntime.getOperation("GetUser");');
+    expect(result.sourceCode).toContain("export const getPost = gql.default(");
+    expect(result.errors.map((error) => [error.code, error.canonicalId])).toEqual([
+      ["SODA_GQL_ANALYSIS_ARTIFACT_NOT_FOUND", join(tmpDir, "src/user.ts::getPost")],
+    ]);
+  });
+
+  it.skipIf(!nativeModuleAvailable)("uses the server runtime and keeps calls needing client-only helpers", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createArtifact([
+        createOperationElement("src/user.ts::getUser", "query", "GetUser"),
+        createOperationElement("src/user.ts::onUser", "subscription", "OnUser"),
+      ]),
+      splitServerClient: true,
+      subscriptionTransport: "websocket",
+      logLevel: "silent",
+    });
+    const sourceCode = createOperationSource([
+      { exportName: "getUser", operationType: "query", operationName: "GetUser" },
+      { exportName: "onUser", operationType: "subscription", operationName: "OnUser" },
+    ]);
+
+    const result = transformer.transform({ sourceCode: `"use server";\n${sourceCode}`, sourcePath: join(tmpDir, "src/user.ts") });
+
+    expect(result.sourceCode).toContain('import { gqlRuntime } from "@soda-gql/runtime/server";');
+    expect(result.sourceCode).toContain('export const getUser = gqlRuntime.getOperation("GetUser");');
+    // The subscription is left in place, so it still needs gql
+    expect(result.sourceCode).toContain("export const onUser = gql.default(");
+    expect(result.sourceCode).toContain('import { gql } from "@/graphql-system";');
+    expect(result.errors.map((error) => error.code)).toEqual(["SODA_GQL_TRANSFORM_CLIENT_ONLY_HELPER"]);
+  });
+});
+
+describe("swc shared prebuilds", () => {
+  it.skipIf(!nativeModuleAvailable)("emits a definition shared by several operations once", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const userFields = {
+      kind: "FragmentDefinition",
+      name: { kind: "Name", value: "UserFields" },
+      typeCondition: { kind: "NamedType", name: { kind: "Name", value: "User" } },
+      selectionSet: { kind: "SelectionSet", selections: [{ kind: "Field", name: { kind: "Name", value: "id" } }] },
+    };
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createArtifact([
+        createOperationElement("src/user.ts::getUser", "query", "GetUser", ["id"], [userFields]),
+        createOperationElement("src/user.ts::getUserAgain", "query", "GetUserAgain", ["id"], [userFields]),
+      ]),
+      dedupePrebuilds: true,
+    });
+
+    const result = transformer.transform({
+      sourceCode: createOperationSource([
+        { exportName: "getUser", operationType: "query", operationName: "GetUser" },
+        { exportName: "getUserAgain", operationType: "query", operationName: "GetUserAgain" },
+      ]),
+      sourcePath: join(tmpDir, "src/user.ts"),
+    });
+
+    expect(result.sourceCode).toContain("const __soda_gql_shared_0 = JSON.parse(");
+    expect(result.sourceCode.match(/"UserFields"/g)).toHaveLength(1);
+    // Declared once and referenced by both prebuilds
+    expect(result.sourceCode.match(/__soda_gql_shared_0/g)).toHaveLength(3);
+    expect(result.sourceCode).toContain('export const getUserAgain = gqlRuntime.getOperation("GetUserAgain");');
+  });
+
+  it.skipIf(!nativeModuleAvailable)("inlines definitions whose other users have no artifact entry", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const userFields = {
+      kind: "FragmentDefinition",
+      name: { kind: "Name", value: "UserFields" },
+      typeCondition: { kind: "NamedType", name: { kind: "Name", value: "User" } },
+      selectionSet: { kind: "SelectionSet", selections: [{ kind: "Field", name: { kind: "Name", value: "id" } }] },
+    };
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createArtifact([createOperationElement("src/user.ts::getUser", "query", "GetUser", ["id"], [userFields])]),
+      dedupePrebuilds: true,
+    });
+
+    const result = transformer.transform({
+      sourceCode: createOperationSource([
+        { exportName: "getUser", operationType: "query", operationName: "GetUser" },
+        { exportName: "getUserAgain", operationType: "query", operationName: "GetUserAgain" },
+      ]),
+      sourcePath: join(tmpDir, "src/user.ts"),
+    });
+
+    expect(result.sourceCode).not.toContain("__soda_gql_shared");
+    expect(result.sourceCode.match(/"UserFields"/g)).toHaveLength(1);
+    expect(result.sourceCode).toContain("export const getUserAgain = gql.default(");
+    expect(result.errors.map((error) => error.code)).toEqual(["SODA_GQL_ANALYSIS_ARTIFACT_NOT_FOUND"]);
+  });
+});
+
+describe("swc source maps", () => {
+  it.skipIf(!nativeModuleAvailable)("names sources relative to sourceMapDir without sourcesContent", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const sourceCode = createOperationSource([{ exportName: "getUser", operationType: "query", operationName: "GetUser" }]);
+    const sourcePath = join(tmpDir, "src/user.ts");
+    const createSourceMap = async (options: { sourcesContent: boolean; sourceMapDir?: string }) => {
+      const transformer = await createTransformer({
+        config: createOptionsTestConfig(tmpDir),
+        artifact: createArtifact([createOperationElement("src/user.ts::getUser", "query", "GetUser")]),
+        sourceMap: true,
+        ...options,
+      });
+      return JSON.parse(transformer.transform({ sourceCode, sourcePath }).sourceMap ?? "{}");
+    };
+
+    const embedded = await createSourceMap({ sourcesContent: true });
+    const referenced = await createSourceMap({ sourcesContent: false });
+    const served = await createSourceMap({ sourcesContent: false, sourceMapDir: join(tmpDir, "dist/assets") });
+
+    expect(embedded.sources).toEqual([sourcePath]);
+    expect(embedded.sourcesContent).toEqual([sourceCode]);
+    // Relative to baseDir by default, so no build-machine paths leak into the map
+    expect(referenced.sources).toEqual(["src/user.ts"]);
+    expect(referenced.sourcesContent).toBeUndefined();
+    expect(served.sources).toEqual(["../../src/user.ts"]);
+  });
+
+  it.skipIf(!nativeModuleAvailable)("wraps the map into an index map with one section", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const sourcePath = join(tmpDir, "src/user.ts");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createArtifact([createOperationElement("src/user.ts::getUser", "query", "GetUser")]),
+      sourceMap: true,
+      indexSourceMap: true,
+    });
+
+    const result = transformer.transform({
+      sourceCode: createOperationSource([{ exportName: "getUser", operationType: "query", operationName: "GetUser" }]),
+      sourcePath,
+    });
+    const indexMap = JSON.parse(result.sourceMap ?? "{}");
+
+    expect(indexMap.version).toBe(3);
+    expect(indexMap.sections).toHaveLength(1);
+    expect(indexMap.sections[0].offset).toEqual({ line: 0, column: 0 });
+    expect(indexMap.sections[0].map.sources).toEqual([sourcePath]);
+    expect(indexMap.sections[0].map.names).toEqual(["getUser"]);
+  });
+
+  it.skipIf(!nativeModuleAvailable)("throws for an input source map that does not parse", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createArtifact([createOperationElement("src/user.ts::getUser", "query", "GetUser")]),
+      sourceMap: true,
+    });
+
+    expect(() =>
+      transformer.transform({
+        sourceCode: createOperationSource([{ exportName: "getUser", operationType: "query", operationName: "GetUser" }]),
+        sourcePath: join(tmpDir, "src/user.ts"),
+        inputSourceMap: "{",
+      }),
+    ).toThrow("Invalid source map");
+  });
+});
+
+describe("swc incremental transform", () => {
+  const createIncrementalArtifact = () =>
+    createArtifact([
+      createOperationElement("src/user.ts::getUser", "query", "GetUser"),
+      createOperationElement("src/user.ts::getPost", "query", "GetPost"),
+    ]);
+
+  // Applies the edits with transformIncremental and to the source, and checks that
+  // the output matches a fresh transformer's full transform of the edited source
+  const expectSameAsFullTransform = async (sourceCode: string, edits: TextEdit[]) => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const sourcePath = join(tmpDir, "src/user.ts");
+    const options = { config: createOptionsTestConfig(tmpDir), artifact: createIncrementalArtifact() };
+    const transformer = await createTransformer({ ...options, incremental: true });
+    transformer.transform({ sourceCode, sourcePath });
+
+    const incremental = transformer.transformIncremental({ sourcePath, edits });
+    const editedSource = [...edits]
+      .sort((a, b) => b.start - a.start)
+      .reduce((source, { start, end, text }) => source.slice(0, start) + text + source.slice(end), sourceCode);
+    const full = (await createTransformer(options)).transform({ sourceCode: editedSource, sourcePath });
+
+    expect(incremental.sourceCode).toBe(full.sourceCode);
+    expect(incremental.errors).toEqual(full.errors);
+    return incremental;
+  };
+
+  const twoOperationSource = createOperationSource([
+    { exportName: "getUser", operationType: "query", operationName: "GetUser" },
+    { exportName: "getPost", operationType: "query", operationName: "GetPost" },
+  ]);
+
+  it.skipIf(!nativeModuleAvailable)("produces the same output as transforming the edited source", async () => {
+    const sourceCode = createOperationSource([{ exportName: "getUser", operationType: "query", operationName: "GetUser" }]);
+
+    const result = await expectSameAsFullTransform(sourceCode, [
+      { start: sourceCode.length, end: sourceCode.length, text: twoOperationSource.slice(sourceCode.length) },
+    ]);
+
+    expect(result.sourceCode).toContain('export const getPost = gqlRuntime.getOperation("GetPost");');
+  });
+
+  it.skipIf(!nativeModuleAvailable)("counts offsets after surrogate pairs in UTF-16 code units", async () => {
+    const sourceCode = `// 👋 users\n${createOperationSource([{ exportName: "getUser", operationType: "query", operationName: "GetUser" }])}`;
+
+    const result = await expectSameAsFullTransform(sourceCode, [
+      { start: sourceCode.length, end: sourceCode.length, text: twoOperationSource.split("\n")[3] ?? "" },
+    ]);
+
+    expect(result.sourceCode).toContain('export const getPost = gqlRuntime.getOperation("GetPost");');
+  });
+
+  it.skipIf(!nativeModuleAvailable)("throws for offsets inside a surrogate pair", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const sourcePath = join(tmpDir, "src/user.ts");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createIncrementalArtifact(),
+      incremental: true,
+    });
+    const sourceCode = `// 👋 users\n${twoOperationSource}`;
+    transformer.transform({ sourceCode, sourcePath });
+
+    const inside = sourceCode.indexOf("👋") + 1;
+    expect(() => transformer.transformIncremental({ sourcePath, edits: [{ start: inside, end: inside, text: "x" }] })).toThrow(
+      "is not within the previous source",
+    );
+  });
+
+  it.skipIf(!nativeModuleAvailable)("applies edits given out of order", async () => {
+    const getUserAt = twoOperationSource.indexOf("GetUser");
+    const getPostAt = twoOperationSource.indexOf("GetPost");
+
+    const result = await expectSameAsFullTransform(twoOperationSource, [
+      { start: getPostAt, end: getPostAt + "GetPost".length, text: "GetUser" },
+      { start: getUserAt, end: getUserAt + "GetUser".length, text: "GetPost" },
+    ]);
+
+    expect(result.sourceCode).toContain('export const getUser = gqlRuntime.getOperation("GetUser");');
+  });
+
+  it.skipIf(!nativeModuleAvailable)("throws for overlapping edits", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const sourcePath = join(tmpDir, "src/user.ts");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createIncrementalArtifact(),
+      incremental: true,
+    });
+    transformer.transform({ sourceCode: twoOperationSource, sourcePath });
+
+    const getUserAt = twoOperationSource.indexOf("getUser");
+    expect(() =>
+      transformer.transformIncremental({
+        sourcePath,
+        edits: [
+          { start: getUserAt, end: getUserAt + 7, text: "a" },
+          { start: getUserAt + 3, end: getUserAt + 10, text: "b" },
+        ],
+      }),
+    ).toThrow("Edits overlap");
+  });
+
+  it.skipIf(!nativeModuleAvailable)("handles edits in the leading comments of the file", async () => {
+    const sourceCode = `/* header */\n${twoOperationSource}`;
+
+    const result = await expectSameAsFullTransform(sourceCode, [{ start: 3, end: 9, text: "banner" }]);
+
+    expect(result.sourceCode).toContain("/* banner */");
+  });
+
+  it.skipIf(!nativeModuleAvailable)("joins statements when an edit removes a semicolon", async () => {
+    const sourceCode = `${twoOperationSource}const log = console.log;\n(0);\n`;
+    const semicolon = sourceCode.indexOf("console.log;") + "console.log".length;
+
+    const result = await expectSameAsFullTransform(sourceCode, [{ start: semicolon, end: semicolon + 1, text: "" }]);
+
+    expect(result.sourceCode).toContain("const log = console.log(0);");
+  });
+
+  it.skipIf(!nativeModuleAvailable)("splits statements when an edit adds a semicolon", async () => {
+    const sourceCode = `${twoOperationSource}const log = console.log\n(0);\n`;
+    const lineEnd = sourceCode.indexOf("console.log\n") + "console.log".length;
+
+    const result = await expectSameAsFullTransform(sourceCode, [{ start: lineEnd, end: lineEnd, text: ";" }]);
+
+    expect(result.sourceCode).toContain("const log = console.log;\n(0);");
+  });
+
+  it.skipIf(!nativeModuleAvailable)("parses the whole file when the edited statements do not parse on their own", async () => {
+    // The opened comment runs to the end of a later statement's comment
+    const sourceCode = `${twoOperationSource}const a = 1;\nconst b = 2;\nconst c = 3; /* end */\n`;
+    const getPostAt = sourceCode.indexOf("export const getPost");
+
+    const result = await expectSameAsFullTransform(sourceCode, [{ start: getPostAt, end: getPostAt, text: "/* " }]);
+
+    expect(result.sourceCode).toContain("/* export const getPost");
+  });
+
+  it.skipIf(!nativeModuleAvailable)("throws for files not transformed before", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createEmptyArtifact(),
+      incremental: true,
+    });
+
+    expect(() => transformer.transformIncremental({ sourcePath: join(tmpDir, "src/user.ts"), edits: [] })).toThrow(
+      "No previous transform",
+    );
+  });
+
+  it.skipIf(!nativeModuleAvailable)("throws without the incremental option", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const sourcePath = join(tmpDir, "src/user.ts");
+    const transformer = await createTransformer({ config: createOptionsTestConfig(tmpDir), artifact: createIncrementalArtifact() });
+    transformer.transform({ sourceCode: twoOperationSource, sourcePath });
+
+    expect(() => transformer.transformIncremental({ sourcePath, edits: [] })).toThrow("requires the incremental option");
+  });
+
+  it.skipIf(!nativeModuleAvailable)("drops files that are forgotten or beyond incrementalMaxFiles", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const userPath = join(tmpDir, "src/user.ts");
+    const postPath = join(tmpDir, "src/post.ts");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createArtifact([
+        createOperationElement("src/user.ts::getUser", "query", "GetUser"),
+        createOperationElement("src/post.ts::getPost", "query", "GetPost"),
+      ]),
+      incremental: true,
+      incrementalMaxFiles: 1,
+    });
+    transformer.transform({
+      sourceCode: createOperationSource([{ exportName: "getUser", operationType: "query", operationName: "GetUser" }]),
+      sourcePath: userPath,
+    });
+    transformer.transform({
+      sourceCode: createOperationSource([{ exportName: "getPost", operationType: "query", operationName: "GetPost" }]),
+      sourcePath: postPath,
+    });
+
+    expect(() => transformer.transformIncremental({ sourcePath: userPath, edits: [] })).toThrow("No previous transform");
+    expect(transformer.transformIncremental({ sourcePath: postPath, edits: [] }).sourceCode).toContain(
+      'export const getPost = gqlRuntime.getOperation("GetPost");',
+    );
+
+    transformer.forget(postPath);
+    expect(() => transformer.transformIncremental({ sourcePath: postPath, edits: [] })).toThrow("No previous transform");
+  });
+});
+
+describe("swc cache keys", () => {
+  const sourceCode = createOperationSource([{ exportName: "getUser", operationType: "query", operationName: "GetUser" }]);
+  const getUser = createOperationElement("src/user.ts::getUser", "query", "GetUser");
+  const getPost = createOperationElement("src/post.ts::getPost", "query", "GetPost");
+
+  it.skipIf(!nativeModuleAvailable)("getCacheKey changes with the source and the full artifact", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const sourcePath = join(tmpDir, "src/user.ts");
+    const getCacheKey = async (elements: readonly BuilderArtifactOperation[], source = sourceCode) =>
+      (await createTransformer({ config: createOptionsTestConfig(tmpDir), artifact: createArtifact(elements) })).getCacheKey({
+        sourceCode: source,
+        sourcePath,
+      });
+
+    const key = await getCacheKey([getUser]);
+
+    expect(await getCacheKey([getUser])).toBe(key);
+    expect(await getCacheKey([getUser], `${sourceCode}\n`)).not.toBe(key);
+    expect(await getCacheKey([getUser, getPost])).not.toBe(key);
+  });
+
+  it.skipIf(!nativeModuleAvailable)("computeCacheKey covers only the entries of the file", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const sourcePath = join(tmpDir, "src/user.ts");
+    const computeCacheKey = async (elements: readonly BuilderArtifactOperation[], sourceHash = "source-hash") =>
+      (await createTransformer({ config: createOptionsTestConfig(tmpDir), artifact: createArtifact(elements) })).computeCacheKey(
+        sourcePath,
+        sourceHash,
+      );
+
+    const key = await computeCacheKey([getUser]);
+
+    expect(await computeCacheKey([getUser, getPost])).toBe(key);
+    expect(await computeCacheKey([{ ...getUser, metadata: { ...getUser.metadata, contentHash: "changed" } }])).not.toBe(key);
+    expect(await computeCacheKey([getUser], "changed-hash")).not.toBe(key);
+  });
+});
+
+describe("swc ESTree output", () => {
+  // Drops location fields, to compare node shapes
+  const withoutLocations = (node: unknown): unknown =>
+    JSON.parse(JSON.stringify(node, (key, value) => (["start", "end", "range", "loc"].includes(key) ? undefined : value)));
+
+  it.skipIf(!nativeModuleAvailable)("converts the transformed program with locations in the original source", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createArtifact([createOperationElement("src/user.ts::getUser", "query", "GetUser")]),
+      emitEstree: true,
+    });
+    const sourceCode = [
+      'import { gql } from "@/graphql-system";',
+      'export const label = "👋 users";',
+      'export const getUser = gql.default(({ query }) => query.operation({ name: "GetUser" }, () => ({})));',
+      "",
+    ].join("\n");
+
+    const { estree } = transformer.transform({ sourceCode, sourcePath: join(tmpDir, "src/user.ts") });
+    const body = (estree?.body ?? []) as Record<string, unknown>[];
+
+    expect(estree?.sourceType).toBe("module");
+    expect(body.map((node) => node.type)).toEqual([
+      "ImportDeclaration",
+      "ExpressionStatement",
+      "ExportNamedDeclaration",
+      "ExportNamedDeclaration",
+    ]);
+
+    // Offsets are UTF-16 offsets, and columns are 0-based on 1-based lines
+    const labelStart = sourceCode.indexOf("export const label");
+    const labelEnd = sourceCode.indexOf("\n", labelStart);
+    expect(body[2]).toMatchObject({
+      start: labelStart,
+      end: labelEnd,
+      range: [labelStart, labelEnd],
+      loc: { start: { line: 2, column: 0 }, end: { line: 2, column: labelEnd - labelStart } },
+    });
+
+    // Nodes generated by the transformer carry no location
+    expect(body[0]).toMatchObject({ start: null, end: null, range: null, loc: null });
+    expect(withoutLocations(body[3])).toMatchObject({
+      declaration: {
+        declarations: [
+          {
+            id: { type: "Identifier", name: "getUser" },
+            init: {
+              type: "CallExpression",
+              callee: { type: "MemberExpression", property: { type: "Identifier", name: "getOperation" } },
+              arguments: [{ type: "Literal", value: "GetUser" }],
+            },
+          },
+        ],
+      },
+    });
+  });
+
+  it.skipIf(!nativeModuleAvailable)("erases TypeScript-only syntax", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createEmptyArtifact(),
+      emitEstree: true,
+    });
+    const sourceCode = [
+      'import { gql } from "@/graphql-system";',
+      'import type { User } from "./types";',
+      "interface Props { user: User }",
+      'export const label: string = "users";',
+      "export const size = (label as string).length!;",
+      "",
+    ].join("\n");
+
+    const { estree } = transformer.transform({ sourceCode, sourcePath: join(tmpDir, "src/user.ts") });
+    const body = (estree?.body ?? []) as Record<string, unknown>[];
+
+    expect(JSON.stringify(estree)).not.toContain("typeAnnotation");
+    expect(JSON.stringify(estree)).not.toContain('"type":"TS');
+    // The graphql-system import is kept, since no definition was replaced
+    expect(body.map((node) => node.type)).toEqual(["ImportDeclaration", "ExportNamedDeclaration", "ExportNamedDeclaration"]);
+    expect(withoutLocations(body[2])).toMatchObject({
+      declaration: {
+        declarations: [
+          {
+            init: {
+              type: "MemberExpression",
+              object: { type: "Identifier", name: "label" },
+              property: { type: "Identifier", name: "length" },
+            },
+          },
+        ],
+      },
+    });
+  });
+
+  it.skipIf(!nativeModuleAvailable)("converts JSX to the ESTree JSX extension", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createEmptyArtifact(),
+      emitEstree: true,
+    });
+
+    const { estree } = transformer.transform({
+      sourceCode: ['import { gql } from "@/graphql-system";', 'export const view = <div title="user">{label}</div>;', ""].join("\n"),
+      sourcePath: join(tmpDir, "src/view.tsx"),
+    });
+
+    expect(withoutLocations(estree?.body[1])).toMatchObject({
+      declaration: {
+        declarations: [
+          {
+            init: {
+              type: "JSXElement",
+              openingElement: {
+                type: "JSXOpeningElement",
+                name: { type: "JSXIdentifier", name: "div" },
+                attributes: [{ type: "JSXAttribute", name: { name: "title" }, value: { type: "Literal", value: "user" } }],
+              },
+              children: [{ type: "JSXExpressionContainer", expression: { type: "Identifier", name: "label" } }],
+              closingElement: { type: "JSXClosingElement" },
+            },
+          },
+        ],
+      },
+    });
+  });
+
+  it.skipIf(!nativeModuleAvailable)("throws for sources that do not parse", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createEmptyArtifact(),
+      emitEstree: true,
+    });
+
+    expect(() =>
+      transformer.transform({
+        sourceCode: 'import { gql } from "@/graphql-system";\nexport const = ;\n',
+        sourcePath: join(tmpDir, "src/user.ts"),
+      }),
+    ).toThrow("Parse error");
+  });
+});
+
+describe("swc diagnostics server", () => {
+  // Frames a JSON-RPC message with a Content-Length header, as in the Language Server Protocol
+  const frame = (message: Record<string, unknown>): string => {
+    const payload = JSON.stringify({ jsonrpc: "2.0", ...message });
+    return `Content-Length: ${Buffer.byteLength(payload)}\r\n\r\n${payload}`;
+  };
+
+  // The server binds its socket on the thread pool, so the first attempts may find no socket
+  const connectWhenListening = async (socketPath: string): Promise<Socket> => {
+    for (;;) {
+      try {
+        return await new Promise<Socket>((resolve, reject) => {
+          const socket = connect(socketPath, () => resolve(socket));
+          socket.once("error", reject);
+        });
+      } catch {
+        await new Promise((resolve) => setTimeout(resolve, 10));
+      }
+    }
+  };
+
+  const collectResponses = (socket: Socket): Record<string, unknown>[] => {
+    const responses: Record<string, unknown>[] = [];
+    let buffer = Buffer.alloc(0);
+    socket.on("data", (chunk: Buffer) => {
+      buffer = Buffer.concat([buffer, chunk]);
+      for (;;) {
+        const headerEnd = buffer.indexOf("\r\n\r\n");
+        const length = Number(/Content-Length: (\d+)/.exec(buffer.subarray(0, headerEnd).toString())?.[1]);
+        if (headerEnd < 0 || buffer.length < headerEnd + 4 + length) {
+          break;
+        }
+        responses.push(JSON.parse(buffer.subarray(headerEnd + 4, headerEnd + 4 + length).toString()));
+        buffer = buffer.subarray(headerEnd + 4 + length);
+      }
+    });
+    return responses;
+  };
+
+  it.skipIf(!nativeModuleAvailable)("answers requests over a socket and stops on shutdown", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const socketPath = join(tmpDir, "diagnostics.sock");
+    const sourcePath = join(tmpDir, "src/user.ts");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createArtifact([createOperationElement("src/user.ts::getUser", "query", "GetUser")]),
+    });
+
+    let server: Promise<void>;
+    try {
+      server = transformer.startDiagnosticsServer({ socketPath });
+    } catch (error) {
+      // Native modules built without the diagnostics-server feature refuse to start
+      expect(String(error)).toContain("diagnostics-server");
+      return;
+    }
+
+    const socket = await connectWhenListening(socketPath);
+    const responses = collectResponses(socket);
+    socket.write(frame({ id: 1, method: "initialize" }));
+    socket.write(
+      frame({
+        id: 2,
+        method: "analyze",
+        params: {
+          sourcePath,
+          sourceCode: createOperationSource([{ exportName: "getPost", operationType: "query", operationName: "GetPost" }]),
+        },
+      }),
+    );
+    socket.write(frame({ id: 3, method: "analyze", params: { sourcePath } }));
+    socket.write(frame({ id: 4, method: "format" }));
+    socket.write(frame({ id: 5, method: "shutdown" }));
+    // The server closes the connection once it has answered shutdown
+    await Promise.all([server, new Promise((resolve) => socket.once("close", resolve))]);
+
+    expect(responses).toMatchObject([
+      { id: 1, result: { serverInfo: { name: "soda-gql-swc" } } },
+      { id: 2, result: { sourcePath, diagnostics: [{ code: "SODA_GQL_ANALYSIS_ARTIFACT_NOT_FOUND", severity: "error" }] } },
+      { id: 3, error: { code: -32602, message: "Missing string parameter 'sourceCode'" } },
+      { id: 4, error: { code: -32601, message: "Unknown method 'format'" } },
+      { id: 5, result: null },
+    ]);
+    expect(existsSync(socketPath)).toBe(false);
+  });
+});
+
+describe("swc suppression comments", () => {
+  it.skipIf(!nativeModuleAvailable)("drops only the diagnostics a suppression comment covers", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const sourcePath = join(tmpDir, "src/user.ts");
+    const transformer = await createTransformer({ config: createOptionsTestConfig(tmpDir), artifact: createEmptyArtifact() });
+    const definition = (exportName: string) =>
+      `export const ${exportName} = gql.default(({ query }) => query.operation({ name: "${exportName}" }, () => ({})));`;
+
+    const result = transformer.transform({
+      sourceCode: [
+        'import { gql } from "@/graphql-system";',
+        "// @soda-gql-ignore-next-line",
+        definition("ignored"),
+        "// @soda-gql-ignore-next-line SODA_GQL_UNKNOWN_CODE",
+        definition("ignoredOtherCode"),
+        "/* @soda-gql-disable SODA_GQL_ANALYSIS_ARTIFACT_NOT_FOUND */",
+        definition("disabled"),
+        "/* @soda-gql-enable */",
+        definition("enabled"),
+        "",
+      ].join("\n"),
+      sourcePath,
+    });
+
+    // Every definition misses its artifact entry; suppressions listing other codes do not apply
+    expect(result.errors.map((error) => [error.code, error.canonicalId])).toEqual([
+      ["SODA_GQL_ANALYSIS_ARTIFACT_NOT_FOUND", `${sourcePath}::ignoredOtherCode`],
+      ["SODA_GQL_ANALYSIS_ARTIFACT_NOT_FOUND", `${sourcePath}::enabled`],
+    ]);
+  });
+});
+
+describe("swc compile flags", () => {
+  const sourceCode = [
+    'import { gql } from "@/graphql-system";',
+    "if (__DEV__) {",
+    '  const debugQuery = gql.default(({ query }) => query.operation({ name: "DebugQuery" }, () => ({})));',
+    "  console.log(debugQuery);",
+    "}",
+    'export const getUser = gql.default(({ query }) => query.operation({ name: "GetUser" }, () => ({})));',
+    "",
+  ].join("\n");
+
+  it.skipIf(!nativeModuleAvailable)("removes definitions guarded by a flag that is off before later ones", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createArtifact([createOperationElement("src/user.ts::getUser", "query", "GetUser")]),
+      compileFlags: { __DEV__: false },
+    });
+
+    const result = transformer.transform({ sourceCode, sourcePath: join(tmpDir, "src/user.ts") });
+
+    expect(result.sourceCode).not.toContain("__DEV__");
+    expect(result.sourceCode).not.toContain("DebugQuery");
+    expect(result.sourceCode).toContain('export const getUser = gqlRuntime.getOperation("GetUser");');
+    expect(result.errors).toEqual([]);
+  });
+
+  it.skipIf(!nativeModuleAvailable)("keeps guarded definitions when the flag is on", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const sourcePath = join(tmpDir, "src/user.ts");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createArtifact([createOperationElement("src/user.ts::getUser", "query", "GetUser")]),
+      compileFlags: { __DEV__: true },
+    });
+
+    const result = transformer.transform({ sourceCode, sourcePath });
+
+    expect(result.sourceCode).toContain("if (__DEV__) {");
+    expect(result.sourceCode).toContain('export const getUser = gqlRuntime.getOperation("GetUser");');
+    expect(result.errors.map((error) => [error.code, error.canonicalId])).toEqual([
+      ["SODA_GQL_ANALYSIS_ARTIFACT_NOT_FOUND", `${sourcePath}::debugQuery`],
+    ]);
+  });
+});
+
+describe("swc builder callback captures", () => {
+  it.skipIf(!nativeModuleAvailable)("reports callbacks capturing runtime bindings and leaves them untransformed", async () => {
+    const tmpDir = createCanonicalTempDir("swc-options-test-");
+    const transformer = await createTransformer({
+      config: createOptionsTestConfig(tmpDir),
+      artifact: createArtifact([createOperationElement("src/user.ts::getUser", "query", "GetUser")]),
+    });
+
+    const result = transformer.transform({
+      sourceCode: [
+        'import { gql } from "@/graphql-system";',
+        "const pageSize = 10;",
+        // Module-level bindings, globals and the callback's own bindings are available to the builder
+        "export const getUser = gql.default(({ query }) => {",
+        "  const fields = () => ({ size: pageSize, max: Math.max(1, 2) });",
+        '  return query.operation({ name: "GetUser" }, fields);',
+        "});",
+        "export function makeQuery(limit: number) {",
+        '  const label = "users";',
+        "  return gql.default(({ query }) => query.operation({ name: label }, () => ({ limit, again: limit })));",
+        "}",
+        "export const makeShadowed = (pageSize: number) =>",
+        '  gql.default(({ query }) => query.operation({ name: "Shadowed" }, () => ({ pageSize })));',
+        "try {",
+        "} catch (error) {",
+        '  gql.default(({ query }) => query.operation({ name: "Caught" }, () => ({ error })));',
+        "}",
+        "",
+      ].join("\n"),
+      sourcePath: join(tmpDir, "src/user.ts"),
+    });
+
+    expect(result.sourceCode).toContain('export const getUser = gqlRuntime.getOperation("GetUser");');
+    expect(result.sourceCode).toContain('name: "Shadowed"');
+    // Each captured binding is reported once per definition, at its first reference
+    expect(result.errors.map((error) => [error.code, /captures '(\w+)'/.exec(error.message)?.[1], error.span?.line])).toEqual([
+      ["SODA_GQL_NON_STATIC_BUILDER", "label", 9],
+      ["SODA_GQL_NON_STATIC_BUILDER", "limit", 9],
+      ["SODA_GQL_NON_STATIC_BUILDER", "pageSize", 12],
+      ["SODA_GQL_NON_STATIC_BUILDER", "error", 15],
+    ]);
+  });
+});
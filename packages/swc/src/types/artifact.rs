@@ -4,17 +4,21 @@
 //! service during static analysis.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::{Map, Value};
+use swc_core::ecma::atoms::Atom;
+use std::sync::Arc;
 
 /// Canonical identifier for a GQL definition.
 /// Format: "filepath:scope.path"
-pub type CanonicalId = String;
+///
+/// Interned, since the same IDs key the artifact index, per-file maps, and caches.
+pub type CanonicalId = Atom;
 
 /// Metadata about the source of an artifact element.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BuilderArtifactElementMetadata {
-    pub source_path: String,
+    pub source_path: Atom,
     pub content_hash: String,
 }
 
@@ -30,14 +34,35 @@ pub struct FragmentPrebuild {
 #[serde(rename_all = "camelCase")]
 pub struct OperationPrebuild {
     pub operation_type: String,
-    pub operation_name: String,
+    pub operation_name: Atom,
     pub variable_names: Vec<String>,
     /// GraphQL document AST (complex object, not a string)
-    pub document: serde_json::Value,
+    pub document: PrebuildDocument,
     #[serde(default)]
     pub metadata: Option<serde_json::Value>,
 }
 
+/// A graphql-js `DocumentNode` whose top-level definitions can be shared.
+///
+/// Operations that embed the same fragment definition point at a single
+/// interned copy (see `crate::artifact::dedup`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrebuildDocument {
+    /// Remaining document fields (`kind`, `loc`, ...), kept verbatim.
+    #[serde(flatten)]
+    pub rest: Map<String, Value>,
+    /// Top-level definitions.
+    #[serde(default)]
+    pub definitions: Vec<Arc<Value>>,
+}
+
+impl PrebuildDocument {
+    /// Get the `kind` of the document node.
+    pub fn kind(&self) -> Option<&str> {
+        self.rest.get("kind")?.as_str()
+    }
+}
+
 /// A single artifact element from the builder.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
@@ -56,7 +81,6 @@ pub enum BuilderArtifactElement {
     },
 }
 
-#[allow(dead_code)]
 impl BuilderArtifactElement {
     /// Get the canonical ID of this element.
     pub fn id(&self) -> &str {
@@ -66,6 +90,14 @@ impl BuilderArtifactElement {
         }
     }
 
+    /// Get the source metadata of this element.
+    pub fn metadata(&self) -> &BuilderArtifactElementMetadata {
+        match self {
+            Self::Fragment { metadata, .. } => metadata,
+            Self::Operation { metadata, .. } => metadata,
+        }
+    }
+
     /// Get the element type as a string.
     pub fn element_type(&self) -> &'static str {
         match self {
@@ -75,33 +107,3 @@ impl BuilderArtifactElement {
     }
 }
 
-/// Build statistics from the builder service.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BuilderArtifactStats {
-    pub hits: usize,
-    pub misses: usize,
-    pub skips: usize,
-}
-
-/// Build report from the builder service.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct BuilderArtifactReport {
-    pub duration_ms: u64,
-    pub warnings: Vec<String>,
-    pub stats: BuilderArtifactStats,
-}
-
-/// The complete artifact generated by the builder service.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BuilderArtifact {
-    pub elements: HashMap<CanonicalId, BuilderArtifactElement>,
-    pub report: BuilderArtifactReport,
-}
-
-impl BuilderArtifact {
-    /// Look up an element by its canonical ID.
-    pub fn get(&self, id: &str) -> Option<&BuilderArtifactElement> {
-        self.elements.get(id)
-    }
-}
@@ -0,0 +1,43 @@
+//! Capabilities of the native binary.
+//!
+//! The JS wrapper can be paired with an older or differently built native
+//! module, so it asks which optional features and config options the binary
+//! has before relying on them.
+
+use serde::Serialize;
+
+use super::config_validation::config_options;
+
+/// Features, syntaxes, and config options supported by this build.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    /// Version of the native crate.
+    pub version: &'static str,
+    /// Optional cargo features the binary was compiled with.
+    pub features: Vec<&'static str>,
+    /// Source syntaxes the parser accepts.
+    pub syntaxes: Vec<&'static str>,
+    /// Config options the transformer understands.
+    pub config_options: Vec<String>,
+}
+
+/// Collect the capabilities of this build.
+pub fn capabilities() -> Capabilities {
+    let features = [
+        ("schema-validation", cfg!(feature = "schema-validation")),
+        ("watch", cfg!(feature = "watch")),
+        ("artifact-free", cfg!(feature = "artifact-free")),
+        ("diagnostics-server", cfg!(feature = "diagnostics-server")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect();
+
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION"),
+        features,
+        syntaxes: vec!["typescript", "tsx"],
+        config_options: config_options(),
+    }
+}
@@ -1,6 +1,19 @@
 //! Configuration types for the transformer.
 
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use swc_core::ecma::ast::VarDeclKind;
+
+use crate::graphql::schema::SchemaIndex;
+use crate::transform::filter::glob_matches;
+use crate::transform::imports::{RUNTIME_MODULE, SERVER_RUNTIME_MODULE};
+use crate::transform::logger::{LogSink, Logger};
+use crate::transform::paths::normalize_path;
+
+use super::lsp::DiagnosticFormat;
 
 /// Configuration for the transformer.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +28,10 @@ pub struct TransformConfig {
     #[serde(default)]
     pub is_cjs: bool,
 
+    /// Syntax profile for generated code.
+    #[serde(default)]
+    pub output_profile: OutputProfile,
+
     /// The canonical path to the graphql-system file.
     /// When the source file matches this path, it will be stubbed out.
     /// This is resolved by the TypeScript wrapper and passed to Rust.
@@ -27,10 +44,270 @@ pub struct TransformConfig {
     #[serde(default)]
     pub inject_paths: Vec<String>,
 
+    /// Whether to stub out the graphql-system and inject modules.
+    /// When false, they pass through untransformed, for builds that still
+    /// need their runtime exports (e.g., while adoption is in progress).
+    #[serde(default = "default_stub_graphql_system")]
+    pub stub_graphql_system: bool,
+
+    /// Source emitted for the stubbed graphql-system module instead of
+    /// `export {};` (e.g., re-exports of runtime helpers, or dev-mode warnings
+    /// on access). Inject modules are still stubbed with `export {};`.
+    #[serde(default)]
+    pub graphql_system_stub: Option<String>,
+
+    /// Whether to resolve symlinks in source paths (and the graphql-system and
+    /// inject paths) before matching internal modules and resolving canonical
+    /// IDs, for pnpm's virtual store and linked workspace packages.
+    #[serde(default)]
+    pub resolve_symlinks: bool,
+
     /// Whether to generate source maps.
     /// If true, a source map will be included in the output.
     #[serde(default)]
     pub source_map: bool,
+
+    /// Whether to embed the original source in the source map's
+    /// `sourcesContent`. When false, `sources` are relative to
+    /// `source_map_dir`, for setups serving original sources separately.
+    #[serde(default = "default_sources_content")]
+    pub sources_content: bool,
+
+    /// Whether to keep the source of each transformed file, so
+    /// `transformIncremental` can apply edits to it. Off by default, since
+    /// the sources stay in memory for the lifetime of the transformer.
+    #[serde(default)]
+    pub incremental: bool,
+
+    /// Directory the source map is written to or served from. Without
+    /// `sourcesContent`, `sources` are relative to it, so maps carry no
+    /// build-machine paths; when unset they keep the source path as given.
+    #[serde(default)]
+    pub source_map_dir: Option<String>,
+
+    /// Whether to emit the source map as a sectioned "index map" with one
+    /// section covering the whole output, for pipelines that concatenate
+    /// per-file maps into chunk-level index maps.
+    #[serde(default)]
+    pub index_source_map: bool,
+
+    /// Whether to also return the transformed program as ESTree JSON.
+    #[serde(default)]
+    pub emit_estree: bool,
+
+    /// Source path prefixes to rewrite when resolving canonical IDs.
+    /// Lets artifacts built under a different root (e.g., in CI) match local sources.
+    #[serde(default)]
+    pub path_mappings: Vec<PathMapping>,
+
+    /// Import map (Deno/browser style) used to resolve import specifiers.
+    /// Specifiers that map to the graphql-system module or one of the aliases
+    /// are treated as graphql-system imports.
+    #[serde(default)]
+    pub import_map: Option<ImportMap>,
+
+    /// Subpath `imports` (`#graphql-system`) and `exports` of package.json
+    /// files. Specifiers that map to the graphql-system module are treated
+    /// as graphql-system imports, without listing them in the aliases.
+    #[serde(default)]
+    pub package_manifests: Vec<PackageManifest>,
+
+    /// Tag names of graphql-tag style templates (e.g., `["gql", "graphql"]`)
+    /// to replace with registered operations, resolved by operation name.
+    /// Lets projects migrating from graphql-tag adopt the runtime incrementally.
+    #[serde(default)]
+    pub tagged_template_tags: Vec<String>,
+
+    /// Also resolve templates tagged with the `graphql-tag` import
+    /// (`import gql from "graphql-tag"`) and drop the import once unused,
+    /// so Apollo codebases can migrate without a codemod.
+    #[serde(default)]
+    pub graphql_tag_interop: bool,
+
+    /// Virtual module specifiers (e.g., `"soda-gql:registry"`) that are
+    /// generated as a list of all operations in the artifact instead of
+    /// being transformed.
+    #[serde(default)]
+    pub registry_modules: Vec<String>,
+
+    /// Whether to list the replaced definitions and their output positions
+    /// in `TransformResult::sidecar`.
+    #[serde(default)]
+    pub emit_sidecar: bool,
+
+    /// Whether to include the collected definitions, export bindings,
+    /// resolved canonical IDs, and replacement table in
+    /// `TransformResult::debug_dump`, for diagnosing artifact mismatches.
+    #[serde(default)]
+    pub debug_dump: bool,
+
+    /// Compile-time flags (e.g., `{ "__DEV__": false }`). gql definitions
+    /// guarded by a flag that is off (`if (__DEV__)`, `devOnly(...)`) are
+    /// removed along with their registrations. Ordered, so the serialized
+    /// config used in cache keys is deterministic.
+    #[serde(default)]
+    pub compile_flags: BTreeMap<String, bool>,
+
+    /// What to do with a file whose transformation reported errors
+    /// (diagnostics with `error` severity). Defaults to the `mode`'s policy.
+    #[serde(default)]
+    pub error_policy: Option<ErrorPolicy>,
+
+    /// Verbosity of the transformer's own stderr output.
+    /// Errors are returned in the result either way.
+    #[serde(default)]
+    pub log_level: LogLevel,
+
+    /// Format of diagnostics returned alongside `errors`.
+    /// "lsp" adds LSP `Diagnostic`s for IDE plugins and problem matchers.
+    #[serde(default)]
+    pub diagnostic_format: DiagnosticFormat,
+
+    /// Runtime module imported by transformed files rendered for SSR.
+    /// Defaults to the regular runtime module.
+    #[serde(default)]
+    pub ssr_runtime_module: Option<String>,
+
+    /// Runtime module per bundler environment name (e.g., Vite environments).
+    /// Takes precedence over `ssrRuntimeModule` when the environment matches.
+    #[serde(default)]
+    pub environment_runtime_modules: HashMap<String, String>,
+
+    /// Whether to emit per-module output for React Server Components (e.g.,
+    /// Next.js App Router). Modules marked `"use client"` get client-safe
+    /// registrations without server-only fields, imported from
+    /// `clientRuntimeModule`. Server modules (marked `"use server"` or matching
+    /// `serverOnly`) register against `serverRuntimeModule`, without client-only
+    /// helpers. All other modules get full registrations.
+    #[serde(default)]
+    pub split_server_client: bool,
+
+    /// Runtime module imported by `"use client"` modules when
+    /// `splitServerClient` is enabled. Defaults to the regular runtime module.
+    #[serde(default)]
+    pub client_runtime_module: Option<String>,
+
+    /// Runtime module imported by server modules when `splitServerClient` is
+    /// enabled. Defaults to `@soda-gql/runtime/server`.
+    #[serde(default)]
+    pub server_runtime_module: Option<String>,
+
+    /// Glob patterns of server-only modules (e.g., `src/server/**`), treated as
+    /// marked `"use server"` unless marked `"use client"`.
+    #[serde(default)]
+    pub server_only: Vec<String>,
+
+    /// Expected content hash of the artifact (XXH64, hex).
+    /// When set, constructing a transformer with a different artifact fails,
+    /// so stale or mismatched artifacts are caught before emitting code.
+    #[serde(default)]
+    pub expected_artifact_hash: Option<String>,
+
+    /// Whether to load and validate every artifact element when the transformer is created.
+    /// Elements are otherwise validated lazily, the first time a file references them.
+    #[serde(default)]
+    pub validate_artifact: bool,
+
+    /// Whether to keep transform results in memory and return them for files
+    /// whose source, artifact entries, and transform context are unchanged.
+    /// Speeds up watch-mode rebuilds that re-request unchanged files.
+    #[serde(default)]
+    pub cache_output: bool,
+
+    /// Whether to emit document definitions shared by several operations in a
+    /// file once, as a module-level constant referenced by each prebuild.
+    #[serde(default)]
+    pub dedupe_prebuilds: bool,
+
+    /// GraphQL SDL of the project schema.
+    /// When set, prebuilds are validated against it and fields or types that no
+    /// longer exist are reported as errors (artifact drifted from the schema).
+    #[serde(default)]
+    pub schema_sdl: Option<String>,
+
+    /// Fields removed from operation prebuilds (e.g., PII), by parent type.
+    /// Requires `schemaSdl`, which resolves the types of nested selections;
+    /// each removed selection is reported as a warning.
+    #[serde(default)]
+    pub field_policy: FieldPolicy,
+
+    /// Whether to replace operation names in registrations and `getOperation`
+    /// references with short content hashes, for production builds.
+    /// Defaults to on in production `mode`.
+    #[serde(default)]
+    pub hash_operation_names: Option<bool>,
+
+    /// Trusted-documents mode, for production builds served by a GraphQL
+    /// server that only accepts pre-registered documents. Operations are
+    /// registered under their hashed name without their document, which
+    /// implies `hashOperationNames`.
+    #[serde(default)]
+    pub trusted_documents: Option<TrustedDocuments>,
+
+    /// Experimental: derive prebuilds from the builder call itself for
+    /// definitions the artifact has no entry for, so simple projects can
+    /// transform without running the builder. Only tagged-template builders
+    /// without interpolations or fragment spreads are supported (see
+    /// `transform::extract`); requires the `artifact-free` feature.
+    #[serde(default)]
+    pub extract_prebuilds: bool,
+
+    /// Transport hint for subscription operations.
+    /// When set, subscriptions are registered with `gqlRuntime.subscription`
+    /// and referenced with `getSubscription` instead of the operation calls.
+    #[serde(default)]
+    pub subscription_transport: Option<SubscriptionTransport>,
+
+    /// Cache-invalidation hints for mutation registrations.
+    /// When set, each mutation is registered with the typenames it affects.
+    #[serde(default)]
+    pub mutation_invalidation: Option<MutationInvalidation>,
+
+    /// Whether to structure output for React Fast Refresh: registrations are
+    /// skipped when a module re-runs with unchanged definitions, and generated
+    /// identifiers stay stable across edits.
+    #[serde(default)]
+    pub react_refresh: bool,
+
+    /// Whether to add debug payloads (canonical ID, source path, original
+    /// definition text) to registrations, guarded by
+    /// `process.env.NODE_ENV !== "production"` so production bundles strip them.
+    /// Defaults to on in development `mode`.
+    #[serde(default)]
+    pub debug_payloads: Option<bool>,
+
+    /// Whether to pass operation variable definitions to the runtime, as
+    /// `runtime: { variables: { id: "ID!" } }`, so required variables can be
+    /// checked without re-deriving them from documents.
+    #[serde(default)]
+    pub runtime_variables: bool,
+
+    /// Glob patterns of files to transform; every file when empty.
+    /// Patterns not starting with `/` match from any directory (e.g., `src/**`).
+    #[serde(default)]
+    pub include: Vec<String>,
+
+    /// Glob patterns of files returned unchanged without being parsed, even
+    /// when included (e.g., `node_modules/**`, `__fixtures__/**`).
+    #[serde(default)]
+    pub exclude: Vec<String>,
+
+    /// Partial configs applied to files matching a glob, in order, e.g.
+    /// `[{ "files": "src/legacy/**", "config": { "isCjs": true } }]`.
+    /// Options that apply to the whole instance cannot be overridden.
+    #[serde(default)]
+    pub overrides: Vec<ConfigOverride>,
+
+    /// Build mode setting the defaults of `debugPayloads`, `pureAnnotations`,
+    /// `hashOperationNames`, and `errorPolicy` together; options set
+    /// explicitly take precedence.
+    #[serde(default)]
+    pub mode: Option<TransformMode>,
+
+    /// Whether to mark references to registered definitions `/*#__PURE__*/`,
+    /// so minifiers can drop unused ones. Defaults to on in production `mode`.
+    #[serde(default)]
+    pub pure_annotations: Option<bool>,
 }
 
 impl Default for TransformConfig {
@@ -38,13 +315,480 @@ impl Default for TransformConfig {
         Self {
             graphql_system_aliases: vec!["@/graphql-system".to_string()],
             is_cjs: false,
+            output_profile: OutputProfile::Default,
             graphql_system_path: None,
             inject_paths: Vec::new(),
+            stub_graphql_system: true,
+            graphql_system_stub: None,
+            resolve_symlinks: false,
             source_map: false,
+            sources_content: true,
+            incremental: false,
+            source_map_dir: None,
+            index_source_map: false,
+            emit_estree: false,
+            path_mappings: Vec::new(),
+            import_map: None,
+            package_manifests: Vec::new(),
+            tagged_template_tags: Vec::new(),
+            graphql_tag_interop: false,
+            registry_modules: Vec::new(),
+            emit_sidecar: false,
+            debug_dump: false,
+            compile_flags: BTreeMap::new(),
+            error_policy: None,
+            log_level: LogLevel::Warn,
+            diagnostic_format: DiagnosticFormat::Plugin,
+            ssr_runtime_module: None,
+            environment_runtime_modules: HashMap::new(),
+            split_server_client: false,
+            client_runtime_module: None,
+            server_runtime_module: None,
+            server_only: Vec::new(),
+            expected_artifact_hash: None,
+            validate_artifact: false,
+            cache_output: false,
+            dedupe_prebuilds: false,
+            schema_sdl: None,
+            field_policy: FieldPolicy::default(),
+            hash_operation_names: None,
+            trusted_documents: None,
+            extract_prebuilds: false,
+            subscription_transport: None,
+            mutation_invalidation: None,
+            react_refresh: false,
+            debug_payloads: None,
+            runtime_variables: false,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            overrides: Vec::new(),
+            mode: None,
+            pure_annotations: None,
         }
     }
 }
 
+impl TransformConfig {
+    /// Whether to add debug payloads: `debugPayloads`, or on in development mode.
+    pub fn debug_payloads(&self) -> bool {
+        self.debug_payloads.unwrap_or(self.mode == Some(TransformMode::Development))
+    }
+
+    /// Whether to hash operation names: `hashOperationNames`, or on in production
+    /// mode; always on in trusted-documents mode, where the hash is the document ID.
+    pub fn hash_operation_names(&self) -> bool {
+        self.trusted_documents.is_some()
+            || self.hash_operation_names.unwrap_or(self.mode == Some(TransformMode::Production))
+    }
+
+    /// Whether to add PURE annotations: `pureAnnotations`, or on in production mode.
+    pub fn pure_annotations(&self) -> bool {
+        self.pure_annotations.unwrap_or(self.mode == Some(TransformMode::Production))
+    }
+
+    /// Error policy: `errorPolicy`, or `failFirst` in production mode so a
+    /// broken definition fails the build instead of shipping, `collect` otherwise.
+    pub fn error_policy(&self) -> ErrorPolicy {
+        self.error_policy.unwrap_or(match self.mode {
+            Some(TransformMode::Production) => ErrorPolicy::FailFirst,
+            Some(TransformMode::Development) | None => ErrorPolicy::Collect,
+        })
+    }
+
+    /// Whether the runtime is loaded with `require` instead of an ESM import.
+    pub fn emits_cjs(&self) -> bool {
+        self.is_cjs || self.output_profile == OutputProfile::Hermes
+    }
+
+    /// Resolve the runtime module to import for a transform context.
+    ///
+    /// With `splitServerClient`, `"use client"` modules use `clientRuntimeModule`
+    /// regardless of the context, since they are bundled for the browser too, and
+    /// server modules use `serverRuntimeModule`.
+    pub fn runtime_module(&self, context: &TransformContext, directive: Option<ModuleDirective>) -> &str {
+        if self.is_client_module(directive) {
+            if let Some(client_module) = &self.client_runtime_module {
+                return client_module;
+            }
+        }
+        if self.is_server_module(directive) {
+            return self.server_runtime_module.as_deref().unwrap_or(SERVER_RUNTIME_MODULE);
+        }
+
+        let environment_module = context
+            .environment
+            .as_ref()
+            .and_then(|environment| self.environment_runtime_modules.get(environment));
+        let ssr_module = self.ssr_runtime_module.as_ref().filter(|_| context.ssr);
+
+        environment_module.or(ssr_module).map_or(RUNTIME_MODULE, String::as_str)
+    }
+
+    /// Every runtime module a transform may import, for any context.
+    pub fn runtime_modules(&self) -> Vec<&str> {
+        let mut modules = vec![RUNTIME_MODULE];
+        modules.extend(self.client_runtime_module.as_deref());
+        if self.split_server_client {
+            modules.push(self.server_runtime_module.as_deref().unwrap_or(SERVER_RUNTIME_MODULE));
+        }
+        modules.extend(self.ssr_runtime_module.as_deref());
+        modules.extend(self.environment_runtime_modules.values().map(String::as_str));
+        modules
+    }
+
+    /// Whether a module with `directive` gets client-safe output.
+    pub fn is_client_module(&self, directive: Option<ModuleDirective>) -> bool {
+        self.split_server_client && directive == Some(ModuleDirective::Client)
+    }
+
+    /// Whether a module with `directive` gets server output, without client-only helpers.
+    pub fn is_server_module(&self, directive: Option<ModuleDirective>) -> bool {
+        self.split_server_client && directive == Some(ModuleDirective::Server)
+    }
+
+    /// Resolve the directive a module is handled with: its own directive, or
+    /// `"use server"` when it matches `serverOnly` and has none.
+    pub fn effective_directive(&self, directive: Option<ModuleDirective>, source_path: &str) -> Option<ModuleDirective> {
+        if directive.is_none() && self.server_only.iter().any(|pattern| glob_matches(pattern, source_path)) {
+            return Some(ModuleDirective::Server);
+        }
+        directive
+    }
+
+    /// Parse `schemaSdl`, if configured.
+    ///
+    /// Fails if a field policy is set without a schema to resolve it against.
+    pub fn load_schema(&self) -> Result<Option<SchemaIndex>, String> {
+        if self.schema_sdl.is_none() && !self.field_policy.is_empty() {
+            return Err("fieldPolicy requires schemaSdl to resolve the types of nested selections".to_string());
+        }
+        self.schema_sdl.as_deref().map(SchemaIndex::parse).transpose()
+    }
+
+    /// Whether any of `overrides` applies to `source_path`.
+    pub fn has_overrides_for(&self, source_path: &str) -> bool {
+        self.overrides.iter().any(|entry| glob_matches(&entry.files, source_path))
+    }
+
+    /// Get the config for `source_path`: this config with the partial configs of
+    /// matching `overrides` applied in order.
+    pub fn for_path(&self, source_path: &str) -> Result<TransformConfig, String> {
+        let matching: Vec<&ConfigOverride> =
+            self.overrides.iter().filter(|entry| glob_matches(&entry.files, source_path)).collect();
+        if matching.is_empty() {
+            return Ok(self.clone());
+        }
+        self.merge_overrides(&matching)
+            .map_err(|e| format!("Failed to apply config overrides for '{}': {}", source_path, e))
+    }
+
+    /// Check that every override only sets per-file options and applies cleanly,
+    /// so misconfigured overrides fail at startup instead of on the first matching file.
+    pub fn check_overrides(&self) -> Result<(), String> {
+        for entry in &self.overrides {
+            if let Some(key) = entry.config.keys().find(|key| INSTANCE_OPTIONS.contains(&key.as_str())) {
+                return Err(format!(
+                    "Config override for '{}' cannot set '{}', which applies to the whole transformer",
+                    entry.files, key
+                ));
+            }
+
+            let merged = self
+                .merge_overrides(&[entry])
+                .map_err(|e| format!("Invalid config override for '{}': {}", entry.files, e))?;
+            if merged.schema_sdl.is_none() && !merged.field_policy.is_empty() {
+                return Err(format!(
+                    "Config override for '{}' sets fieldPolicy, which requires schemaSdl",
+                    entry.files
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace this config's options with those set by `overrides`, in order.
+    fn merge_overrides(&self, overrides: &[&ConfigOverride]) -> Result<TransformConfig, String> {
+        let Value::Object(mut merged) = serde_json::to_value(self).map_err(|e| e.to_string())? else {
+            return Err("config is not an object".to_string());
+        };
+        for entry in overrides {
+            merged.extend(entry.config.clone());
+        }
+        merged.remove("overrides");
+
+        serde_json::from_value(Value::Object(merged)).map_err(|e| e.to_string())
+    }
+}
+
+fn default_stub_graphql_system() -> bool {
+    true
+}
+
+fn default_sources_content() -> bool {
+    true
+}
+
+/// Options that apply to the whole transformer instance, which overrides cannot set.
+pub(crate) const INSTANCE_OPTIONS: &[&str] = &[
+    "overrides",
+    "schemaSdl",
+    "expectedArtifactHash",
+    "validateArtifact",
+    "cacheOutput",
+    "resolveSymlinks",
+    "incremental",
+];
+
+/// A partial config applied to the files matching `files`.
+///
+/// e.g., `{ "files": "src/legacy/**", "config": { "isCjs": true, "graphqlSystemAliases": ["~/gql"] } }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigOverride {
+    /// Glob pattern of the files the override applies to, matched like `include`.
+    pub files: String,
+    /// Options replacing the base config's, keyed like the top-level config.
+    pub config: Map<String, Value>,
+}
+
+/// Fields removed from operation prebuilds.
+///
+/// e.g., `{ "deny": { "User": ["email", "phone"] }, "allow": { "Payment": ["id", "status"] } }`.
+/// A type listed in `allow` keeps only the listed fields; `deny` applies on top.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldPolicy {
+    /// Type name to fields removed from its selections.
+    #[serde(default)]
+    pub deny: BTreeMap<String, Vec<String>>,
+    /// Type name to the only fields kept in its selections.
+    #[serde(default)]
+    pub allow: BTreeMap<String, Vec<String>>,
+}
+
+impl FieldPolicy {
+    /// Whether the policy removes nothing.
+    pub fn is_empty(&self) -> bool {
+        self.deny.is_empty() && self.allow.is_empty()
+    }
+
+    /// Check if `field_name` may be selected on `type_name`.
+    pub fn permits(&self, type_name: &str, field_name: &str) -> bool {
+        let denied = self
+            .deny
+            .get(type_name)
+            .is_some_and(|fields| fields.iter().any(|field| field == field_name));
+        let allowed = self
+            .allow
+            .get(type_name)
+            .is_none_or(|fields| fields.iter().any(|field| field == field_name));
+        allowed && !denied
+    }
+}
+
+/// Cache-invalidation hints added to mutation registrations.
+///
+/// Typenames are derived from the prebuild's selections (resolved through
+/// `schemaSdl` if configured) and extended with the configured ones.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MutationInvalidation {
+    /// Operation name to additional typenames the mutation invalidates.
+    #[serde(default)]
+    pub typenames: BTreeMap<String, Vec<String>>,
+}
+
+/// Trusted-documents mode settings.
+///
+/// e.g., `{ "manifestIds": ["Op3f2a9c1d0b7e", ...] }`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustedDocuments {
+    /// Document IDs listed in the generated manifest. When set, operations
+    /// whose ID it does not list are reported as errors, since the server
+    /// would reject them.
+    #[serde(default)]
+    pub manifest_ids: Option<Arc<BTreeSet<String>>>,
+}
+
+/// Transport the runtime wires subscription operations to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SubscriptionTransport {
+    #[serde(rename = "websocket")]
+    WebSocket,
+    /// Server-sent events.
+    #[serde(rename = "sse")]
+    Sse,
+}
+
+impl SubscriptionTransport {
+    /// Name of the transport as passed to the runtime.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SubscriptionTransport::WebSocket => "websocket",
+            SubscriptionTransport::Sse => "sse",
+        }
+    }
+}
+
+/// Syntax profile for code generated by the transformer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OutputProfile {
+    /// Modern syntax (`const`, object spread) for bundlers that lower output themselves.
+    #[default]
+    Default,
+    /// ES5-compatible generated code for Hermes, with the runtime loaded via
+    /// a top-level `require` that Metro resolves without a Babel pass.
+    Hermes,
+}
+
+impl OutputProfile {
+    /// Declaration kind for generated module-level bindings.
+    pub fn declaration_kind(self) -> VarDeclKind {
+        match self {
+            OutputProfile::Default => VarDeclKind::Const,
+            OutputProfile::Hermes => VarDeclKind::Var,
+        }
+    }
+
+    /// Whether generated objects may use spread syntax (ES2018).
+    pub fn supports_object_spread(self) -> bool {
+        self == OutputProfile::Default
+    }
+}
+
+/// Build mode, bundling the defaults of development- and production-specific options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransformMode {
+    /// Debug payloads on; readable names; errors collected so the dev server keeps running.
+    Development,
+    /// PURE annotations and hashed operation names on; the first error fails the build.
+    Production,
+}
+
+/// Handling of files whose transformation reported errors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ErrorPolicy {
+    /// Transform what can be transformed and return all errors.
+    #[default]
+    Collect,
+    /// Fail the transform call with the first error, without analyzing or
+    /// transforming the rest of the file.
+    FailFirst,
+    /// Return the original source untransformed, with all errors.
+    FailFile,
+}
+
+/// Verbosity of stderr output, ordered from quietest to most verbose.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LogLevel {
+    /// No output.
+    Silent,
+    /// Errors and warnings.
+    #[default]
+    Warn,
+    /// Everything, including informational diagnostics and resolution details.
+    Debug,
+}
+
+/// Per-call hints from the bundler about where the output will run.
+///
+/// Lets one transformer serve several bundler environments (e.g., Vite's
+/// client and SSR environments) with different output.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransformContext {
+    /// Whether the output is rendered on the server.
+    #[serde(default)]
+    pub ssr: bool,
+
+    /// Name of the bundler environment (e.g., "client", "ssr", "edge").
+    #[serde(default)]
+    pub environment: Option<String>,
+}
+
+/// React Server Components directive at the top of a module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleDirective {
+    /// `"use client"`: the module is bundled for the browser.
+    Client,
+    /// `"use server"`: the module only runs on the server.
+    Server,
+}
+
+/// A source path prefix rewrite applied when resolving canonical IDs.
+///
+/// e.g., `{ "from": "/home/me/repo", "to": "/build/repo" }` resolves
+/// `/home/me/repo/src/a.ts` against artifact entries for `/build/repo/src/a.ts`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathMapping {
+    /// Prefix of the local source path.
+    pub from: String,
+    /// Prefix used in the artifact's canonical IDs.
+    pub to: String,
+}
+
+impl PathMapping {
+    /// Rewrite `path` if it starts with `from` at a path segment boundary.
+    /// Both `path` and the mapping are compared with forward slashes.
+    pub fn apply(&self, path: &str) -> Option<String> {
+        let from = normalize_path(&self.from);
+        let from = from.trim_end_matches('/');
+        let rest = path.strip_prefix(from)?;
+        if !rest.is_empty() && !rest.starts_with('/') {
+            return None;
+        }
+
+        let to = normalize_path(&self.to);
+        Some(format!("{}{}", to.trim_end_matches('/'), rest))
+    }
+}
+
+/// An import map in the format used by Deno and browsers.
+///
+/// Relative addresses and scope prefixes are resolved against `baseDir`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportMap {
+    /// Top-level specifier mappings.
+    #[serde(default)]
+    pub imports: HashMap<String, String>,
+
+    /// Specifier mappings that apply to importers under a path prefix.
+    #[serde(default)]
+    pub scopes: HashMap<String, HashMap<String, String>>,
+
+    /// Directory containing the import map (usually the project root).
+    #[serde(default)]
+    pub base_dir: Option<String>,
+}
+
+/// The module resolution fields of a package.json.
+///
+/// Relative targets are resolved against `dir`. Conditional targets may use
+/// any condition, since the bundler's conditions are not known here.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageManifest {
+    /// Directory containing the package.json.
+    pub dir: String,
+
+    /// Package name, used as the prefix of `exports` subpaths.
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Private subpath imports, applying to files within `dir`.
+    #[serde(default)]
+    pub imports: HashMap<String, Value>,
+
+    /// Public subpath exports, applying to every file.
+    #[serde(default)]
+    pub exports: Option<Value>,
+}
+
 /// Input for a single file transformation (JSON-based, for one-shot transform).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -60,6 +804,10 @@ pub struct TransformInput {
 
     /// Transformation configuration.
     pub config: TransformConfig,
+
+    /// Bundler context hints for this call.
+    #[serde(default)]
+    pub context: TransformContext,
 }
 
 /// Input for a single file transformation with pre-parsed artifact.
@@ -71,9 +819,34 @@ pub struct TransformInputRef<'a> {
     /// The file path of the source.
     pub source_path: String,
 
-    /// Pre-parsed BuilderArtifact reference.
-    pub artifact: &'a super::BuilderArtifact,
+    /// Artifact to resolve canonical IDs against.
+    pub artifact: &'a dyn crate::artifact::ArtifactSource,
+
+    /// Pre-parsed schema for prebuild validation, if configured.
+    pub schema: Option<&'a SchemaIndex>,
+
+    /// Prebuild expressions shared across calls, synced to the artifact generation.
+    pub prebuild_cache: Option<&'a crate::transform::cache::PrebuildCache>,
+
+    /// Parsed modules shared across calls, reused while a file is unchanged.
+    pub module_cache: Option<&'a crate::transform::cache::ModuleCache>,
+
+    /// Artifact files the transformer was loaded from, reported as dependencies.
+    pub artifact_files: &'a [String],
 
     /// Transformation configuration.
     pub config: TransformConfig,
+
+    /// Bundler context hints for this call.
+    pub context: TransformContext,
+
+    /// Host logger to forward log messages to, instead of stderr.
+    pub log_sink: Option<LogSink>,
+}
+
+impl TransformInputRef<'_> {
+    /// Logger for this call, at the configured level.
+    pub fn logger(&self) -> Logger {
+        Logger::new(self.config.log_level).with_sink(self.log_sink.clone())
+    }
 }
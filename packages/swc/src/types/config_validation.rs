@@ -0,0 +1,191 @@
+//! Config validation for plugin startup.
+//!
+//! Reports every problem in a config at once: malformed values, unknown keys
+//! (ignored by the transformer, so usually typos), and option combinations
+//! that do not work together. Plugins call this before creating a transformer
+//! so misconfiguration fails with clear messages instead of odd output.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::graphql::schema::SchemaIndex;
+
+use super::config::{OutputProfile, TransformConfig, INSTANCE_OPTIONS};
+use super::error::Severity;
+
+/// A problem found in a config.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigIssue {
+    pub severity: Severity,
+    /// Path of the offending option (e.g., `overrides[0].config.isCjs`); empty for the whole config.
+    pub path: String,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    fn new(severity: Severity, path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Validate a JSON-serialized config, returning its issues (empty when valid).
+pub fn validate_config(config_json: &str) -> Vec<ConfigIssue> {
+    let options = match serde_json::from_str::<Value>(config_json) {
+        Ok(Value::Object(options)) => options,
+        Ok(_) => return vec![ConfigIssue::new(Severity::Error, "", "Config must be a JSON object")],
+        Err(e) => return vec![ConfigIssue::new(Severity::Error, "", format!("Failed to parse config: {}", e))],
+    };
+
+    let defaults = default_options();
+    let mut issues = Vec::new();
+    check_options(&options, "", &defaults, &mut issues);
+
+    if let Some(Value::Array(overrides)) = options.get("overrides") {
+        for (index, entry) in overrides.iter().enumerate() {
+            let Some(Value::Object(config)) = entry.get("config") else {
+                continue;
+            };
+            let prefix = format!("overrides[{}].config.", index);
+            for key in config.keys().filter(|key| INSTANCE_OPTIONS.contains(&key.as_str())) {
+                issues.push(ConfigIssue::new(
+                    Severity::Error,
+                    format!("{}{}", prefix, key),
+                    format!("'{}' applies to the whole transformer and cannot be overridden", key),
+                ));
+            }
+            check_options(config, &prefix, &defaults, &mut issues);
+        }
+    }
+
+    if issues.iter().any(|issue| issue.severity == Severity::Error) {
+        return issues;
+    }
+    match serde_json::from_value::<TransformConfig>(Value::Object(options)) {
+        Ok(config) => check_combinations(&config, &mut issues),
+        Err(e) => issues.push(ConfigIssue::new(Severity::Error, "", format!("Invalid config: {}", e))),
+    }
+    issues
+}
+
+/// Names of every known option.
+pub fn config_options() -> Vec<String> {
+    default_options().keys().cloned().collect()
+}
+
+/// Every option with its default value.
+fn default_options() -> Map<String, Value> {
+    match serde_json::to_value(TransformConfig::default()) {
+        Ok(Value::Object(defaults)) => defaults,
+        _ => Map::new(),
+    }
+}
+
+/// Check that each option is known and has a valid value on its own.
+fn check_options(options: &Map<String, Value>, prefix: &str, defaults: &Map<String, Value>, issues: &mut Vec<ConfigIssue>) {
+    for (key, value) in options {
+        let path = format!("{}{}", prefix, key);
+        if !defaults.contains_key(key) {
+            let message = match suggest_key(key, defaults) {
+                Some(known) => format!("Unknown option '{}' is ignored; did you mean '{}'?", key, known),
+                None => format!("Unknown option '{}' is ignored", key),
+            };
+            issues.push(ConfigIssue::new(Severity::Warning, path, message));
+            continue;
+        }
+
+        // Deserialize the value alone on top of the defaults, so each bad value is reported
+        let mut probe = defaults.clone();
+        probe.insert(key.clone(), value.clone());
+        if let Err(e) = serde_json::from_value::<TransformConfig>(Value::Object(probe)) {
+            issues.push(ConfigIssue::new(Severity::Error, path, format!("Invalid value: {}", e)));
+        }
+    }
+}
+
+/// Check options that are valid on their own but not together.
+fn check_combinations(config: &TransformConfig, issues: &mut Vec<ConfigIssue>) {
+    if config.is_cjs && config.split_server_client {
+        issues.push(ConfigIssue::new(
+            Severity::Error,
+            "splitServerClient",
+            "splitServerClient requires ES module output; React Server Components cannot load CommonJS registrations",
+        ));
+    }
+    if config.is_cjs && config.output_profile == OutputProfile::Hermes {
+        issues.push(ConfigIssue::new(
+            Severity::Info,
+            "isCjs",
+            "isCjs is redundant with outputProfile \"hermes\", which always loads the runtime with require",
+        ));
+    }
+    if config.client_runtime_module.is_some() && !config.split_server_client {
+        issues.push(ConfigIssue::new(
+            Severity::Warning,
+            "clientRuntimeModule",
+            "clientRuntimeModule is ignored unless splitServerClient is enabled",
+        ));
+    }
+    if config.server_runtime_module.is_some() && !config.split_server_client {
+        issues.push(ConfigIssue::new(
+            Severity::Warning,
+            "serverRuntimeModule",
+            "serverRuntimeModule is ignored unless splitServerClient is enabled",
+        ));
+    }
+    if !config.server_only.is_empty() && !config.split_server_client {
+        issues.push(ConfigIssue::new(
+            Severity::Warning,
+            "serverOnly",
+            "serverOnly is ignored unless splitServerClient is enabled",
+        ));
+    }
+    if config.graphql_system_stub.is_some() && !config.stub_graphql_system {
+        issues.push(ConfigIssue::new(
+            Severity::Warning,
+            "graphqlSystemStub",
+            "graphqlSystemStub is ignored when stubGraphqlSystem is disabled",
+        ));
+    }
+    if !config.field_policy.is_empty() && config.schema_sdl.is_none() {
+        issues.push(ConfigIssue::new(
+            Severity::Error,
+            "fieldPolicy",
+            "fieldPolicy requires schemaSdl to resolve the types of nested selections",
+        ));
+    }
+    if let Some(Err(e)) = config.schema_sdl.as_deref().map(SchemaIndex::parse) {
+        issues.push(ConfigIssue::new(Severity::Error, "schemaSdl", e));
+    }
+    if let Err(e) = config.check_overrides() {
+        issues.push(ConfigIssue::new(Severity::Error, "overrides", e));
+    }
+}
+
+/// Suggest the known option closest to an unknown `key`, for typos.
+fn suggest_key<'a>(key: &str, defaults: &'a Map<String, Value>) -> Option<&'a str> {
+    defaults
+        .keys()
+        .map(|known| (edit_distance(&key.to_lowercase(), &known.to_lowercase()), known))
+        .filter(|(distance, _)| *distance <= 2)
+        .min()
+        .map(|(_, known)| known.as_str())
+}
+
+/// Levenshtein distance between two strings, by character.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
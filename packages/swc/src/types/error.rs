@@ -4,6 +4,7 @@
 //! for consistent error reporting across TypeScript and Rust implementations.
 
 use serde::{Deserialize, Serialize};
+use swc_core::common::{SourceMap, Span};
 
 /// Stage where the error occurred.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -13,6 +14,37 @@ pub enum ErrorStage {
     Transform,
 }
 
+/// Error codes reported by the transformer.
+pub mod codes {
+    pub const METADATA_NOT_FOUND: &str = "SODA_GQL_METADATA_NOT_FOUND";
+    pub const ARTIFACT_NOT_FOUND: &str = "SODA_GQL_ANALYSIS_ARTIFACT_NOT_FOUND";
+    pub const ARTIFACT_INVALID: &str = "SODA_GQL_ANALYSIS_ARTIFACT_INVALID";
+    pub const ARTIFACT_LAYER_MATCHED: &str = "SODA_GQL_ANALYSIS_ARTIFACT_LAYER_MATCHED";
+    pub const MISSING_BUILDER_ARG: &str = "SODA_GQL_TRANSFORM_MISSING_BUILDER_ARG";
+    pub const SCHEMA_UNKNOWN_TYPE: &str = "SODA_GQL_SCHEMA_UNKNOWN_TYPE";
+    pub const SCHEMA_UNKNOWN_FIELD: &str = "SODA_GQL_SCHEMA_UNKNOWN_FIELD";
+    pub const TEMPLATE_UNRESOLVED: &str = "SODA_GQL_ANALYSIS_TEMPLATE_UNRESOLVED";
+    pub const POLICY_FIELD_PRUNED: &str = "SODA_GQL_POLICY_FIELD_PRUNED";
+    pub const NON_STATIC_BUILDER: &str = "SODA_GQL_NON_STATIC_BUILDER";
+    pub const ALREADY_TRANSFORMED: &str = "SODA_GQL_TRANSFORM_ALREADY_TRANSFORMED";
+    pub const UNTRUSTED_DOCUMENT: &str = "SODA_GQL_UNTRUSTED_DOCUMENT";
+    pub const ORPHAN_IMPORT: &str = "SODA_GQL_ANALYSIS_ORPHAN_IMPORT";
+    pub const CLIENT_ONLY_HELPER: &str = "SODA_GQL_TRANSFORM_CLIENT_ONLY_HELPER";
+}
+
+/// How serious a reported problem is.
+///
+/// Only `Error` indicates output that will not work at runtime; callers decide
+/// whether warnings fail the build.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
+    Info,
+}
+
 /// Base structure for all plugin errors.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -30,6 +62,10 @@ pub struct PluginError {
     /// Stage where the error occurred.
     pub stage: ErrorStage,
 
+    /// How serious the problem is.
+    #[serde(default)]
+    pub severity: Severity,
+
     /// Additional context about the error.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub filename: Option<String>,
@@ -49,6 +85,53 @@ pub struct PluginError {
     /// Argument name if applicable.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub arg_name: Option<String>,
+
+    /// Location of the offending gql call, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<ErrorSpan>,
+
+    /// Number of identical errors collapsed into this one.
+    #[serde(default = "single_occurrence")]
+    pub occurrences: usize,
+}
+
+fn single_occurrence() -> usize {
+    1
+}
+
+/// Location of a problem in the source file.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ErrorSpan {
+    /// Byte offset of the start, from the beginning of the file.
+    pub start: u32,
+    /// Byte offset of the end (exclusive).
+    pub end: u32,
+    /// 1-based line of the start.
+    pub line: usize,
+    /// 0-based column of the start, in UTF-16 code units (as used by editors
+    /// and JavaScript source maps).
+    pub column: usize,
+}
+
+impl ErrorSpan {
+    /// Resolve a span of a file registered in `cm`, or `None` for generated nodes.
+    pub fn resolve(cm: &SourceMap, span: Span) -> Option<Self> {
+        if span.is_dummy() {
+            return None;
+        }
+
+        let start = cm.lookup_byte_offset(span.lo);
+        let end = cm.lookup_byte_offset(span.hi);
+        let before = start.sf.src.get(..start.pos.0 as usize)?;
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+        Some(Self {
+            start: start.pos.0,
+            end: end.pos.0,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].encode_utf16().count(),
+        })
+    }
 }
 
 impl PluginError {
@@ -56,14 +139,17 @@ impl PluginError {
     pub fn metadata_not_found(filename: &str) -> Self {
         Self {
             error_type: "PluginError".to_string(),
-            code: "SODA_GQL_METADATA_NOT_FOUND".to_string(),
+            code: codes::METADATA_NOT_FOUND.to_string(),
             message: format!("No metadata found for gql call in '{}'", filename),
             stage: ErrorStage::Analysis,
+            severity: Severity::Warning,
             filename: Some(filename.to_string()),
             canonical_id: None,
             artifact_type: None,
             builder_type: None,
             arg_name: None,
+            span: None,
+            occurrences: 1,
         }
     }
 
@@ -71,17 +157,62 @@ impl PluginError {
     pub fn artifact_not_found(filename: &str, canonical_id: &str) -> Self {
         Self {
             error_type: "PluginError".to_string(),
-            code: "SODA_GQL_ANALYSIS_ARTIFACT_NOT_FOUND".to_string(),
+            code: codes::ARTIFACT_NOT_FOUND.to_string(),
             message: format!(
                 "No artifact found for canonical ID '{}' in '{}'",
                 canonical_id, filename
             ),
             stage: ErrorStage::Analysis,
+            severity: Severity::Error,
+            filename: Some(filename.to_string()),
+            canonical_id: Some(canonical_id.to_string()),
+            artifact_type: None,
+            builder_type: None,
+            arg_name: None,
+            span: None,
+            occurrences: 1,
+        }
+    }
+
+    /// Create an "invalid artifact element" error.
+    pub fn artifact_invalid(filename: &str, canonical_id: &str, reason: &str) -> Self {
+        Self {
+            error_type: "PluginError".to_string(),
+            code: codes::ARTIFACT_INVALID.to_string(),
+            message: format!(
+                "Artifact element '{}' referenced in '{}' is invalid: {}",
+                canonical_id, filename, reason
+            ),
+            stage: ErrorStage::Analysis,
+            severity: Severity::Error,
+            filename: Some(filename.to_string()),
+            canonical_id: Some(canonical_id.to_string()),
+            artifact_type: None,
+            builder_type: None,
+            arg_name: None,
+            span: None,
+            occurrences: 1,
+        }
+    }
+
+    /// Create an "artifact layer matched" diagnostic for layered artifacts.
+    pub fn artifact_layer_matched(filename: &str, canonical_id: &str, layer_index: usize, layer_label: &str) -> Self {
+        Self {
+            error_type: "PluginError".to_string(),
+            code: codes::ARTIFACT_LAYER_MATCHED.to_string(),
+            message: format!(
+                "Canonical ID '{}' in '{}' resolved from artifact layer {} ('{}')",
+                canonical_id, filename, layer_index, layer_label
+            ),
+            stage: ErrorStage::Analysis,
+            severity: Severity::Info,
             filename: Some(filename.to_string()),
             canonical_id: Some(canonical_id.to_string()),
             artifact_type: None,
             builder_type: None,
             arg_name: None,
+            span: None,
+            occurrences: 1,
         }
     }
 
@@ -89,32 +220,320 @@ impl PluginError {
     pub fn missing_builder_arg(filename: &str, builder_type: &str, arg_name: &str) -> Self {
         Self {
             error_type: "PluginError".to_string(),
-            code: "SODA_GQL_TRANSFORM_MISSING_BUILDER_ARG".to_string(),
+            code: codes::MISSING_BUILDER_ARG.to_string(),
             message: format!(
                 "Missing required builder argument '{}' for {} in '{}'",
                 arg_name, builder_type, filename
             ),
             stage: ErrorStage::Transform,
+            severity: Severity::Error,
+            filename: Some(filename.to_string()),
+            canonical_id: None,
+            artifact_type: None,
+            builder_type: Some(builder_type.to_string()),
+            arg_name: Some(arg_name.to_string()),
+            span: None,
+            occurrences: 1,
+        }
+    }
+
+    /// Create a "schema unknown type" error.
+    pub fn schema_unknown_type(filename: &str, canonical_id: &str, artifact_type: &str, type_name: &str) -> Self {
+        Self {
+            error_type: "PluginError".to_string(),
+            code: codes::SCHEMA_UNKNOWN_TYPE.to_string(),
+            message: format!(
+                "Type '{}' referenced by '{}' does not exist in the schema",
+                type_name, canonical_id
+            ),
+            stage: ErrorStage::Analysis,
+            severity: Severity::Error,
+            filename: Some(filename.to_string()),
+            canonical_id: Some(canonical_id.to_string()),
+            artifact_type: Some(artifact_type.to_string()),
+            builder_type: None,
+            arg_name: None,
+            span: None,
+            occurrences: 1,
+        }
+    }
+
+    /// Create a "schema unknown field" error.
+    pub fn schema_unknown_field(filename: &str, canonical_id: &str, type_name: &str, field_name: &str) -> Self {
+        Self {
+            error_type: "PluginError".to_string(),
+            code: codes::SCHEMA_UNKNOWN_FIELD.to_string(),
+            message: format!(
+                "Field '{}.{}' selected by '{}' does not exist in the schema",
+                type_name, field_name, canonical_id
+            ),
+            stage: ErrorStage::Analysis,
+            severity: Severity::Error,
+            filename: Some(filename.to_string()),
+            canonical_id: Some(canonical_id.to_string()),
+            artifact_type: Some("operation".to_string()),
+            builder_type: None,
+            arg_name: None,
+            span: None,
+            occurrences: 1,
+        }
+    }
+
+    /// Create an error for a tagged template that could not be resolved to an operation.
+    ///
+    /// A warning, since the template is left as is and keeps working with its original tag.
+    pub fn template_unresolved(filename: &str, reason: &str) -> Self {
+        Self {
+            error_type: "PluginError".to_string(),
+            code: codes::TEMPLATE_UNRESOLVED.to_string(),
+            message: format!("Tagged template in '{}' left untransformed: {}", filename, reason),
+            stage: ErrorStage::Analysis,
+            severity: Severity::Warning,
+            filename: Some(filename.to_string()),
+            canonical_id: None,
+            artifact_type: Some("operation".to_string()),
+            builder_type: None,
+            arg_name: None,
+            span: None,
+            occurrences: 1,
+        }
+    }
+
+    /// Create an error for a selection removed from a prebuild by `fieldPolicy`.
+    ///
+    /// A warning, since the output is valid; the selecting code will not receive the field.
+    pub fn policy_field_pruned(filename: &str, canonical_id: &str, type_name: &str, field_name: &str) -> Self {
+        Self {
+            error_type: "PluginError".to_string(),
+            code: codes::POLICY_FIELD_PRUNED.to_string(),
+            message: format!(
+                "Field '{}.{}' selected by '{}' was removed by the field policy",
+                type_name, field_name, canonical_id
+            ),
+            stage: ErrorStage::Analysis,
+            severity: Severity::Warning,
+            filename: Some(filename.to_string()),
+            canonical_id: Some(canonical_id.to_string()),
+            artifact_type: Some("operation".to_string()),
+            builder_type: None,
+            arg_name: None,
+            span: None,
+            occurrences: 1,
+        }
+    }
+
+    /// Create an error for a builder callback referencing a binding that only exists at runtime.
+    ///
+    /// The definition is left untransformed, since the artifact cannot represent it.
+    pub fn non_static_builder(filename: &str, identifier: &str) -> Self {
+        Self {
+            error_type: "PluginError".to_string(),
+            code: codes::NON_STATIC_BUILDER.to_string(),
+            message: format!(
+                "gql definition in '{}' captures '{}', which is not available when the builder evaluates the module",
+                filename, identifier
+            ),
+            stage: ErrorStage::Analysis,
+            severity: Severity::Error,
+            filename: Some(filename.to_string()),
+            canonical_id: None,
+            artifact_type: None,
+            builder_type: None,
+            arg_name: None,
+            span: None,
+            occurrences: 1,
+        }
+    }
+
+    /// Create an error for a file that already imports the runtime, as left by a previous transform.
+    ///
+    /// A warning, since the file is returned unchanged rather than registering its definitions twice.
+    pub fn already_transformed(filename: &str) -> Self {
+        Self {
+            error_type: "PluginError".to_string(),
+            code: codes::ALREADY_TRANSFORMED.to_string(),
+            message: format!(
+                "'{}' already imports the soda-gql runtime and was left unchanged; it may have been transformed twice",
+                filename
+            ),
+            stage: ErrorStage::Transform,
+            severity: Severity::Warning,
             filename: Some(filename.to_string()),
             canonical_id: None,
             artifact_type: None,
+            builder_type: None,
+            arg_name: None,
+            span: None,
+            occurrences: 1,
+        }
+    }
+
+    /// Create an error for an operation whose document ID is missing from the trusted-documents manifest.
+    pub fn untrusted_document(filename: &str, canonical_id: &str, document_id: &str) -> Self {
+        Self {
+            error_type: "PluginError".to_string(),
+            code: codes::UNTRUSTED_DOCUMENT.to_string(),
+            message: format!(
+                "Document ID '{}' of '{}' is not in the trusted-documents manifest, so the server will reject it",
+                document_id, canonical_id
+            ),
+            stage: ErrorStage::Analysis,
+            severity: Severity::Error,
+            filename: Some(filename.to_string()),
+            canonical_id: Some(canonical_id.to_string()),
+            artifact_type: Some("operation".to_string()),
+            builder_type: None,
+            arg_name: None,
+            span: None,
+            occurrences: 1,
+        }
+    }
+
+    /// Create a "missing builder arg" error for a positional argument found missing during analysis.
+    ///
+    /// `position` is 1-based; `arg_name` may name a property of the argument
+    /// (e.g., `options.fields`).
+    pub fn missing_positional_arg(
+        filename: &str,
+        canonical_id: &str,
+        builder_type: &str,
+        arg_name: &str,
+        position: usize,
+    ) -> Self {
+        let ordinal = match position {
+            1 => "1st".to_string(),
+            2 => "2nd".to_string(),
+            3 => "3rd".to_string(),
+            n => format!("{}th", n),
+        };
+        Self {
+            error_type: "PluginError".to_string(),
+            code: codes::MISSING_BUILDER_ARG.to_string(),
+            message: format!(
+                "Builder call {}() of '{}' is missing '{}' ({} argument)",
+                builder_type, canonical_id, arg_name, ordinal
+            ),
+            stage: ErrorStage::Analysis,
+            severity: Severity::Error,
+            filename: Some(filename.to_string()),
+            canonical_id: Some(canonical_id.to_string()),
+            artifact_type: None,
             builder_type: Some(builder_type.to_string()),
             arg_name: Some(arg_name.to_string()),
+            span: None,
+            occurrences: 1,
         }
     }
 
+    /// Create an informational diagnostic for a graphql-system import in a file
+    /// with no gql definitions the transformer recognizes.
+    pub fn orphan_import(filename: &str, specifier: &str) -> Self {
+        Self {
+            error_type: "PluginError".to_string(),
+            code: codes::ORPHAN_IMPORT.to_string(),
+            message: format!(
+                "'{}' imports '{}' but contains no gql definitions the transformer recognizes",
+                filename, specifier
+            ),
+            stage: ErrorStage::Analysis,
+            severity: Severity::Info,
+            filename: Some(filename.to_string()),
+            canonical_id: None,
+            artifact_type: None,
+            builder_type: None,
+            arg_name: None,
+            span: None,
+            occurrences: 1,
+        }
+    }
+
+    /// Create a "client-only helper" error for a definition in a server module.
+    pub fn client_only_helper(filename: &str, canonical_id: &str, helper: &str) -> Self {
+        Self {
+            error_type: "PluginError".to_string(),
+            code: codes::CLIENT_ONLY_HELPER.to_string(),
+            message: format!(
+                "'{}' in server module '{}' needs the client-only runtime helper '{}'",
+                canonical_id, filename, helper
+            ),
+            stage: ErrorStage::Transform,
+            severity: Severity::Error,
+            filename: Some(filename.to_string()),
+            canonical_id: Some(canonical_id.to_string()),
+            artifact_type: Some("operation".to_string()),
+            builder_type: None,
+            arg_name: None,
+            span: None,
+            occurrences: 1,
+        }
+    }
+
+    /// Attach the location of the offending call.
+    pub fn with_span(mut self, span: Option<ErrorSpan>) -> Self {
+        self.span = span;
+        self
+    }
+
     /// Format the error into a human-readable message.
     pub fn format(&self) -> String {
         format!("[{}] ({:?}) {}", self.code, self.stage, self.message)
     }
 }
 
+/// Collapse identical errors into one, counting their occurrences.
+///
+/// Errors are identical when they share code, file, and canonical ID; errors
+/// without a canonical ID must also share a location, since they may concern
+/// different calls. The first occurrence is kept, in order.
+pub fn collapse_duplicates(errors: Vec<PluginError>) -> Vec<PluginError> {
+    let mut collapsed: Vec<PluginError> = Vec::with_capacity(errors.len());
+    for error in errors {
+        let duplicate = collapsed.iter_mut().find(|existing| {
+            existing.code == error.code
+                && existing.filename == error.filename
+                && existing.canonical_id == error.canonical_id
+                && (existing.canonical_id.is_some() || existing.span == error.span)
+        });
+
+        match duplicate {
+            Some(existing) => existing.occurrences += error.occurrences,
+            None => collapsed.push(error),
+        }
+    }
+    collapsed
+}
+
+/// Number of reported problems per severity.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SeverityCounts {
+    pub error: usize,
+    pub warning: usize,
+    pub info: usize,
+}
+
+impl SeverityCounts {
+    /// Count `errors` by severity.
+    pub fn of(errors: &[PluginError]) -> Self {
+        let mut counts = Self::default();
+        for error in errors {
+            match error.severity {
+                Severity::Error => counts.error += 1,
+                Severity::Warning => counts.warning += 1,
+                Severity::Info => counts.info += 1,
+            }
+        }
+        counts
+    }
+}
+
 /// Collection of errors from a transformation.
+#[allow(dead_code)]
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TransformErrors {
     pub errors: Vec<PluginError>,
 }
 
+#[allow(dead_code)]
 impl TransformErrors {
     pub fn new() -> Self {
         Self { errors: Vec::new() }
@@ -0,0 +1,195 @@
+//! Explanations for transformer error codes.
+//!
+//! CLI and plugin output use these to print actionable guidance next to a
+//! reported error code.
+
+use serde::Serialize;
+
+use super::error::codes;
+
+/// Description, likely causes, and suggested fixes for an error code.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorExplanation {
+    pub code: &'static str,
+    pub description: &'static str,
+    pub likely_causes: &'static [&'static str],
+    pub suggested_fixes: &'static [&'static str],
+}
+
+const CATALOGUE: &[ErrorExplanation] = &[
+    ErrorExplanation {
+        code: codes::METADATA_NOT_FOUND,
+        description: "A gql call was found, but its location in the file could not be identified, so it was left untransformed.",
+        likely_causes: &[
+            "The gql call is not assigned to an exported or module-level binding the builder tracks",
+            "The call is built dynamically (e.g., inside a function or conditional expression)",
+        ],
+        suggested_fixes: &[
+            "Assign each gql call directly to a top-level `const` declaration",
+            "Move gql calls out of functions and conditionals",
+        ],
+    },
+    ErrorExplanation {
+        code: codes::ARTIFACT_NOT_FOUND,
+        description: "The artifact has no entry for the canonical ID of a gql call, so the call cannot be replaced with its prebuilt document.",
+        likely_causes: &[
+            "The artifact is stale: the file was added, moved, or edited after the builder last ran",
+            "The source path does not match the paths recorded in the artifact (different root, symlinks, or `baseDir`)",
+            "The file is not covered by the builder's `include` patterns",
+        ],
+        suggested_fixes: &[
+            "Rebuild the artifact (e.g., run the soda-gql builder or codegen) and retry",
+            "Check that `baseDir` and `pathMappings` map local paths to the paths used when the artifact was built",
+            "Add the file to the builder's `include` patterns",
+        ],
+    },
+    ErrorExplanation {
+        code: codes::ARTIFACT_INVALID,
+        description: "The artifact has an entry for the canonical ID, but it could not be loaded.",
+        likely_causes: &[
+            "The artifact file was truncated or partially written",
+            "The artifact was produced by an incompatible builder version",
+        ],
+        suggested_fixes: &[
+            "Rebuild the artifact and make sure the write completes before transforming",
+            "Use matching versions of @soda-gql/builder and @soda-gql/swc",
+        ],
+    },
+    ErrorExplanation {
+        code: codes::ARTIFACT_LAYER_MATCHED,
+        description: "Informational: with layered artifacts, reports which layer an element was resolved from.",
+        likely_causes: &["The transformer was created from an artifact chain"],
+        suggested_fixes: &["No action needed; filter on severity to hide informational diagnostics"],
+    },
+    ErrorExplanation {
+        code: codes::MISSING_BUILDER_ARG,
+        description: "The builder call of a gql definition is missing a required argument, so no replacement can be built for it.",
+        likely_causes: &[
+            "A positional argument of the builder is missing (e.g., the type name of `fragment(\"Name\", \"Type\")`)",
+            "The options object of `query(\"Name\")({ ... })` has no `fields`",
+            "The gql callback does not return a builder call (e.g., `fragment(...)` or `query(...)`)",
+            "The builder call is wrapped in another expression the transformer does not recognize",
+        ],
+        suggested_fixes: &[
+            "Return the builder call directly from the gql callback",
+            "Compare the call with the documented `gql.default(({ query }) => query(...))` form",
+        ],
+    },
+    ErrorExplanation {
+        code: codes::SCHEMA_UNKNOWN_TYPE,
+        description: "A prebuilt document references a type that does not exist in the configured schema.",
+        likely_causes: &[
+            "The schema changed after the artifact was built (the artifact drifted from the schema)",
+            "`schemaSdl` points at a different schema than the one used by the builder",
+        ],
+        suggested_fixes: &[
+            "Rebuild the artifact against the current schema",
+            "Update the query or fragment to use a type that exists in the schema",
+        ],
+    },
+    ErrorExplanation {
+        code: codes::SCHEMA_UNKNOWN_FIELD,
+        description: "A prebuilt document selects a field that does not exist on its type in the configured schema.",
+        likely_causes: &[
+            "The field was removed or renamed in the schema after the artifact was built",
+            "`schemaSdl` points at a different schema than the one used by the builder",
+        ],
+        suggested_fixes: &[
+            "Rebuild the artifact against the current schema",
+            "Update the selection to use a field that exists in the schema",
+        ],
+    },
+    ErrorExplanation {
+        code: codes::TEMPLATE_UNRESOLVED,
+        description: "A tagged template matching `taggedTemplateTags` could not be resolved to an operation in the artifact, so it was left for its original tag to handle at runtime.",
+        likely_causes: &[
+            "No operation with the template's operation name exists in the artifact",
+            "The template defines an anonymous operation or only fragments",
+            "The template interpolates other documents (e.g., `${UserFields}`)",
+        ],
+        suggested_fixes: &[
+            "Define the operation with soda-gql and rebuild the artifact",
+            "Give the operation a name that matches the soda-gql operation",
+            "Leave the template on graphql-tag until its fragments are migrated",
+        ],
+    },
+    ErrorExplanation {
+        code: codes::POLICY_FIELD_PRUNED,
+        description: "An operation selects a field that `fieldPolicy` does not permit, so the selection was removed from its prebuilt document.",
+        likely_causes: &[
+            "The field is listed under `deny` for its type (e.g., a PII field)",
+            "The field's type is listed under `allow` and the field is not",
+        ],
+        suggested_fixes: &[
+            "Remove the field from the operation",
+            "Update `fieldPolicy` if the field may be selected",
+        ],
+    },
+    ErrorExplanation {
+        code: codes::NON_STATIC_BUILDER,
+        description: "A gql builder callback references a parameter or local variable of an enclosing function, which the build-time artifact cannot represent.",
+        likely_causes: &[
+            "The gql definition is declared inside a function or component",
+            "The callback reads a runtime value (e.g., a prop or argument) to build the selection",
+        ],
+        suggested_fixes: &[
+            "Move the definition to module level",
+            "Pass runtime values as operation variables instead of capturing them",
+        ],
+    },
+    ErrorExplanation {
+        code: codes::ALREADY_TRANSFORMED,
+        description: "The file already imports the soda-gql runtime, so it was returned unchanged instead of being transformed a second time.",
+        likely_causes: &[
+            "The soda-gql loader or plugin is registered twice (e.g., in both a framework preset and the bundler config)",
+            "The file was transformed output from a previous build (e.g., a prebuilt package) passed through the transformer again",
+        ],
+        suggested_fixes: &[
+            "Register the soda-gql plugin once per build pipeline",
+            "Exclude prebuilt output from the transformer with `exclude`",
+        ],
+    },
+    ErrorExplanation {
+        code: codes::UNTRUSTED_DOCUMENT,
+        description: "In trusted-documents mode, an operation's document ID is not listed in the manifest, so a server that only accepts pre-registered documents will reject it.",
+        likely_causes: &[
+            "The manifest was generated before the operation was added or changed",
+            "The manifest was generated from a different artifact than the build uses",
+            "`fieldPolicy` removed selections, which changes the document and its ID",
+        ],
+        suggested_fixes: &[
+            "Regenerate the manifest from the current artifact and register it with the server",
+            "Pass the IDs of the regenerated manifest as `trustedDocuments.manifestIds`",
+        ],
+    },
+    ErrorExplanation {
+        code: codes::ORPHAN_IMPORT,
+        description: "Informational: a file imports the graphql-system but has no gql definitions the transformer recognizes, so the import is left for the bundler to resolve.",
+        likely_causes: &[
+            "The import is left over after its definitions were moved or removed",
+            "A definition is written in a shape the transformer does not recognize (e.g., the builder callback is stored in a variable first)",
+        ],
+        suggested_fixes: &[
+            "Remove the unused import",
+            "Write definitions as `gql.default(({ query }) => query(...))`, returning the builder call from an inline callback",
+        ],
+    },
+    ErrorExplanation {
+        code: codes::CLIENT_ONLY_HELPER,
+        description: "With `splitServerClient`, a definition in a server module would be registered with a runtime helper only the browser runtime provides, so it is left untransformed.",
+        likely_causes: &[
+            "A subscription is defined in a `\"use server\"` module or one matching `serverOnly` while `subscriptionTransport` is set",
+            "A `serverOnly` pattern matches a module that is also bundled for the browser",
+        ],
+        suggested_fixes: &[
+            "Move the definition to a module without the directive (or a `\"use client\"` module)",
+            "Narrow the `serverOnly` patterns",
+        ],
+    },
+];
+
+/// Look up the explanation for an error code.
+pub fn explain(code: &str) -> Option<&'static ErrorExplanation> {
+    CATALOGUE.iter().find(|explanation| explanation.code == code)
+}
@@ -0,0 +1,129 @@
+//! LSP-compatible diagnostics.
+//!
+//! Mirrors the Language Server Protocol `Diagnostic` shape, so IDE plugins and
+//! problem matchers can ingest transformer output without converting it.
+
+use serde::{Deserialize, Serialize};
+
+use super::error::{PluginError, Severity};
+
+/// Source name reported on every diagnostic.
+const DIAGNOSTIC_SOURCE: &str = "soda-gql";
+
+/// Format of the `diagnostics` returned alongside `errors`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiagnosticFormat {
+    /// Only `errors` (PluginError) are returned.
+    #[default]
+    Plugin,
+    /// Errors are also returned as LSP `Diagnostic`s.
+    Lsp,
+}
+
+/// A zero-based line and UTF-16 character offset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    pub line: usize,
+    pub character: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Location {
+    pub uri: String,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticRelatedInformation {
+    pub location: Location,
+    pub message: String,
+}
+
+/// An LSP `Diagnostic`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LspDiagnostic {
+    pub range: Range,
+    /// 1 = Error, 2 = Warning, 3 = Information.
+    pub severity: u8,
+    pub code: String,
+    pub source: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related_information: Vec<DiagnosticRelatedInformation>,
+}
+
+impl LspDiagnostic {
+    /// Convert a plugin error reported for `source_code` at `source_path`.
+    ///
+    /// Errors without a location are placed at the start of the file.
+    pub fn from_error(error: &PluginError, source_code: &str, source_path: &str) -> Self {
+        let range = error.span.map_or_else(Range::default, |span| Range {
+            start: position_at(source_code, span.start as usize),
+            end: position_at(source_code, span.end as usize),
+        });
+
+        let related_information = error
+            .canonical_id
+            .iter()
+            .map(|canonical_id| DiagnosticRelatedInformation {
+                location: Location {
+                    uri: file_uri(source_path),
+                    range,
+                },
+                message: format!("Canonical ID: {}", canonical_id),
+            })
+            .collect();
+
+        Self {
+            range,
+            severity: match error.severity {
+                Severity::Error => 1,
+                Severity::Warning => 2,
+                Severity::Info => 3,
+            },
+            code: error.code.clone(),
+            source: DIAGNOSTIC_SOURCE.to_string(),
+            message: error.message.clone(),
+            related_information,
+        }
+    }
+}
+
+/// Position of a byte offset, with the character counted in UTF-16 code units.
+fn position_at(source_code: &str, offset: usize) -> Position {
+    let before = source_code.get(..offset.min(source_code.len())).unwrap_or(source_code);
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+    Position {
+        line: before.matches('\n').count(),
+        character: before[line_start..].encode_utf16().count(),
+    }
+}
+
+/// Build a `file://` URI for a path, percent-encoding reserved characters.
+fn file_uri(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut uri = String::from("file://");
+    if !path.starts_with('/') {
+        // Windows drive paths (C:/...)
+        uri.push('/');
+    }
+
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
@@ -4,8 +4,12 @@
 //! and @soda-gql/config packages.
 
 pub mod artifact;
+pub mod capabilities;
 pub mod config;
+pub mod config_validation;
 pub mod error;
+pub mod error_catalogue;
+pub mod lsp;
 
 pub use artifact::*;
 pub use config::*;
@@ -0,0 +1,240 @@
+/**
+ * Tests for native transformer APIs that createTransformer does not wrap.
+ *
+ * These tests drive the napi bindings directly, with canonical IDs built from
+ * absolute paths, since the native config below sets no baseDir.
+ */
+
+import { describe, expect, it } from "bun:test";
+import { mkdirSync, mkdtempSync, realpathSync, renameSync, writeFileSync } from "node:fs";
+import { createRequire } from "node:module";
+import { tmpdir } from "node:os";
+import { join } from "node:path";
+
+type Native = typeof import("../../src/native/index");
+
+// Load the bindings the way src/index.ts does, including its glibc fallback under Bun
+const loadNative = (): Native => {
+  const require = createRequire(import.meta.url);
+  try {
+    return require("../../src/native/index.js") as Native;
+  } catch (error) {
+    if (!process.versions.bun || process.platform !== "linux") {
+      throw error;
+    }
+    return require(`../../src/native/swc.linux-${process.arch}-gnu.node`) as Native;
+  }
+};
+
+// Check if native module is available before running tests
+let nativeModuleAvailable = false;
+let native: Native;
+
+try {
+  native = loadNative();
+  nativeModuleAvailable = true;
+} catch (e) {
+  console.warn("[swc] Native module not available - tests will be skipped:", e instanceof Error ? e.message : String(e));
+}
+
+const configJson = JSON.stringify({ graphqlSystemAliases: ["@/graphql-system"] });
+
+const createTempDir = (): string => realpathSync(mkdtempSync(join(tmpdir(), "swc-native-test-")));
+
+// Elements given as a string are operations registered under that name; others are used as is
+const createArtifactJson = (elements: Record<string, unknown>): string =>
+  JSON.stringify({
+    elements: Object.fromEntries(
+      Object.entries(elements).map(([id, operationName]) => [
+        id,
+        typeof operationName === "string"
+          ? {
+              type: "operation",
+              id,
+              metadata: { sourcePath: id.split("::")[0], contentHash: operationName },
+              prebuild: {
+                operationType: "query",
+                operationName,
+                variableNames: [],
+                document: {
+                  kind: "Document",
+                  definitions: [
+                    {
+                      kind: "OperationDefinition",
+                      operation: "query",
+                      name: { kind: "Name", value: operationName },
+                      selectionSet: { kind: "SelectionSet", selections: [{ kind: "Field", name: { kind: "Name", value: "id" } }] },
+                    },
+                  ],
+                },
+              },
+            }
+          : operationName,
+      ]),
+    ),
+    report: { durationMs: 0, warnings: [], stats: { hits: 0, misses: 0, skips: 0 } },
+  });
+
+const createSource = (exportNames: readonly string[]): string =>
+  [
+    'import { gql } from "@/graphql-system";',
+    ...exportNames.map(
+      (exportName) =>
+        `export const ${exportName} = gql.default(({ query }) => query.operation({ name: "${exportName}" }, () => ({})));`,
+    ),
+    "",
+  ].join("\n");
+
+// Operation names each export was registered under
+const operationNames = (outputCode: string): string[] =>
+  [...outputCode.matchAll(/getOperation\("([^"]+)"\)/g)].map((match) => match[1] ?? "");
+
+// Replace a file the way the builder does, so readers never see it half-written
+const replaceFile = (path: string, content: string) => {
+  writeFileSync(`${path}.tmp`, content);
+  renameSync(`${path}.tmp`, path);
+};
+
+// Watchers report on a background thread, so poll until the expected event arrives
+const waitFor = async (condition: () => boolean) => {
+  const deadline = Date.now() + 5000;
+  while (!condition()) {
+    if (Date.now() > deadline) {
+      throw new Error("Timed out waiting for a watch event");
+    }
+    await new Promise((resolve) => setTimeout(resolve, 20));
+  }
+};
+
+describe("artifact watcher", () => {
+  it.skipIf(!nativeModuleAvailable)("swaps in a rewritten artifact and keeps it when a rewrite fails to load", async () => {
+    const tmpDir = createTempDir();
+    const sourcePath = join(tmpDir, "src/user.ts");
+    const artifactPath = join(tmpDir, "artifact.json");
+    replaceFile(artifactPath, createArtifactJson({ [`${sourcePath}::getUser`]: "GetUserV1" }));
+    const transformer = native.SwcTransformer.fromArtifactFile(artifactPath, configJson);
+    const events: { type: string; artifactHash?: string; message?: string }[] = [];
+    transformer.watchArtifact((json: string) => events.push(JSON.parse(json)));
+    const transform = () => operationNames(JSON.parse(transformer.transform(createSource(["getUser"]), sourcePath)).outputCode);
+
+    try {
+      const initialHash = transformer.artifactHash();
+      expect(transform()).toEqual(["GetUserV1"]);
+
+      replaceFile(artifactPath, createArtifactJson({ [`${sourcePath}::getUser`]: "GetUserV2" }));
+      await waitFor(() => events.some((event) => event.type === "reloaded"));
+
+      const reloadedHash = transformer.artifactHash();
+      expect(reloadedHash).not.toBe(initialHash);
+      expect(events.at(-1)).toEqual({ type: "reloaded", artifactHash: reloadedHash });
+      expect(transform()).toEqual(["GetUserV2"]);
+
+      replaceFile(artifactPath, '{ "elements": ');
+      await waitFor(() => events.some((event) => event.type === "error"));
+
+      expect(events.find((event) => event.type === "error")?.message).toContain("Failed to parse artifact");
+      expect(transformer.artifactHash()).toBe(reloadedHash);
+      expect(transform()).toEqual(["GetUserV2"]);
+    } finally {
+      transformer.unwatchArtifact();
+    }
+  });
+
+  it.skipIf(!nativeModuleAvailable)("requires a transformer created from an artifact file", () => {
+    const transformer = new native.SwcTransformer(createArtifactJson({}), configJson);
+
+    expect(() => transformer.watchArtifact(() => {})).toThrow("requires a transformer created with fromArtifactFile");
+  });
+});
+
+describe("artifact chain", () => {
+  const sourcePath = "/project/src/user.ts";
+  const base = createArtifactJson({
+    [`${sourcePath}::getUser`]: "GetUserBase",
+    [`${sourcePath}::getPost`]: "GetPostBase",
+  });
+
+  it.skipIf(!nativeModuleAvailable)("resolves each canonical ID from the first layer that has it", () => {
+    const override = createArtifactJson({ [`${sourcePath}::getUser`]: "GetUserOverride" });
+    const transformer = native.SwcTransformer.fromArtifactChain([override, base], configJson);
+
+    const result = JSON.parse(transformer.transform(createSource(["getUser", "getPost"]), sourcePath));
+
+    expect(operationNames(result.outputCode)).toEqual(["GetUserOverride", "GetPostBase"]);
+    expect(result.errors).toMatchObject([
+      {
+        code: "SODA_GQL_ANALYSIS_ARTIFACT_LAYER_MATCHED",
+        canonicalId: `${sourcePath}::getUser`,
+        message: expect.stringContaining("layer 0"),
+      },
+      {
+        code: "SODA_GQL_ANALYSIS_ARTIFACT_LAYER_MATCHED",
+        canonicalId: `${sourcePath}::getPost`,
+        message: expect.stringContaining("layer 1"),
+      },
+    ]);
+  });
+
+  it.skipIf(!nativeModuleAvailable)("does not fall back to a later layer when the owning element is invalid", () => {
+    const brokenOverride = createArtifactJson({
+      [`${sourcePath}::getUser`]: {
+        type: "operation",
+        id: `${sourcePath}::getUser`,
+        metadata: { sourcePath, contentHash: "broken" },
+        prebuild: 42,
+      },
+    });
+    const transformer = native.SwcTransformer.fromArtifactChain([brokenOverride, base], configJson);
+
+    const result = JSON.parse(transformer.transform(createSource(["getUser", "getPost"]), sourcePath));
+
+    expect(operationNames(result.outputCode)).toEqual(["GetPostBase"]);
+    expect(result.errors[0]).toMatchObject({ code: "SODA_GQL_ANALYSIS_ARTIFACT_INVALID", canonicalId: `${sourcePath}::getUser` });
+  });
+
+  it.skipIf(!nativeModuleAvailable)("rejects empty chains and malformed layers", () => {
+    expect(() => native.SwcTransformer.fromArtifactChain([], configJson)).toThrow("at least one artifact");
+    expect(() => native.SwcTransformer.fromArtifactChain([base, "{"], configJson)).toThrow("Failed to parse artifact");
+  });
+});
+
+describe("source watcher", () => {
+  it.skipIf(!nativeModuleAvailable)("re-transforms changed files with the current artifact", async () => {
+    const tmpDir = createTempDir();
+    mkdirSync(join(tmpDir, "src"));
+    const sourcePath = join(tmpDir, "src/user.ts");
+    const artifactPath = join(tmpDir, "artifact.json");
+    replaceFile(artifactPath, createArtifactJson({ [`${sourcePath}::getUser`]: "GetUserV1" }));
+    const transformer = native.SwcTransformer.fromArtifactFile(artifactPath, configJson);
+    const artifactEvents: { type: string }[] = [];
+    const events: { type: string; sourcePath: string; result?: { outputCode: string }; message?: string }[] = [];
+    transformer.watchArtifact((json: string) => artifactEvents.push(JSON.parse(json)));
+    transformer.watch([join(tmpDir, "src/**/*.ts")], (json: string) => events.push(JSON.parse(json)));
+    const nextEvent = async (type: string) => {
+      events.length = 0;
+      await waitFor(() => events.some((event) => event.type === type));
+      return events.find((event) => event.type === type);
+    };
+
+    try {
+      const transformed = nextEvent("transformed");
+      writeFileSync(sourcePath, createSource(["getUser"]));
+      expect(await transformed).toMatchObject({ sourcePath });
+      expect(operationNames((await transformed)?.result?.outputCode ?? "")).toEqual(["GetUserV1"]);
+
+      const failed = nextEvent("error");
+      writeFileSync(sourcePath, 'import { gql } from "@/graphql-system";\nexport const = ;\n');
+      expect((await failed)?.message).toContain("Parse error");
+
+      // Files written after an artifact reload are transformed with the reloaded artifact
+      replaceFile(artifactPath, createArtifactJson({ [`${sourcePath}::getUser`]: "GetUserV2" }));
+      await waitFor(() => artifactEvents.some((event) => event.type === "reloaded"));
+      const retransformed = nextEvent("transformed");
+      writeFileSync(sourcePath, createSource(["getUser"]));
+      expect(operationNames((await retransformed)?.result?.outputCode ?? "")).toEqual(["GetUserV2"]);
+    } finally {
+      transformer.unwatch();
+      transformer.unwatchArtifact();
+    }
+  });
+});
@@ -0,0 +1,109 @@
+/**
+ * Stress tests for the parallel loader.
+ *
+ * These tests run many concurrent transforms against one shared native handle
+ * and verify that every result matches the same file transformed on its own,
+ * so no state leaks between calls running on different threads.
+ */
+
+import { describe, expect, it } from "bun:test";
+import type { BuilderArtifact } from "@soda-gql/builder";
+import type { ResolvedSodaGqlConfig } from "@soda-gql/config";
+
+const baseDir = "/tmp/soda-gql-parallel-loader";
+const fileCount = 64;
+const rounds = 8;
+
+const config = {
+  analyzer: "ts",
+  baseDir,
+  outdir: `${baseDir}/graphql-system`,
+  graphqlSystemAliases: ["@/graphql-system"],
+  include: [],
+  exclude: [],
+  schemas: {},
+  styles: { importExtension: false },
+  codegen: { chunkSize: 100 },
+  plugins: {},
+} as unknown as ResolvedSodaGqlConfig;
+
+// One fragment per file, with canonical IDs relative to baseDir like the builder writes them
+const files = Array.from({ length: fileCount }, (_, index) => ({
+  sourcePath: `${baseDir}/src/file${index}.ts`,
+  sourceCode: [
+    'import { gql } from "@/graphql-system";',
+    `export const userFragment${index} = gql.default(({ fragment }) => fragment.User({}, () => ({})));`,
+    "",
+  ].join("\n"),
+}));
+
+const artifact = {
+  elements: Object.fromEntries(
+    files.map((_, index) => {
+      const id = `src/file${index}.ts::userFragment${index}`;
+      return [
+        id,
+        {
+          type: "fragment",
+          id,
+          metadata: { sourcePath: `src/file${index}.ts`, contentHash: `hash${index}` },
+          prebuild: { typename: `User${index}` },
+        },
+      ];
+    }),
+  ),
+  report: { durationMs: 0, warnings: [], stats: { hits: 0, misses: 0, skips: 0 } },
+} as unknown as BuilderArtifact;
+
+// Check if native module is available before running tests
+let nativeModuleAvailable = false;
+let createParallelLoader: typeof import("../../src/index").createParallelLoader;
+let initError: string | null = null;
+
+try {
+  const mod = await import("../../src/index");
+  createParallelLoader = mod.createParallelLoader;
+  createParallelLoader({ config, artifact });
+  nativeModuleAvailable = true;
+} catch (e) {
+  initError = e instanceof Error ? e.message : String(e);
+  console.warn("[swc] Native module not available - parallel loader tests will be skipped:", initError);
+}
+
+describe("SWC parallel loader", () => {
+  it.skipIf(!nativeModuleAvailable)("should resolve relative canonical IDs against baseDir", () => {
+    const loader = createParallelLoader({ config, artifact });
+    const output = loader.transformSync(files[0]!);
+
+    expect(output.transformed).toBe(true);
+    expect(output.errors).toEqual([]);
+    expect(output.sourceCode).toContain('typename: "User0"');
+  });
+
+  it.skipIf(!nativeModuleAvailable)("should match isolated results under concurrent calls", async () => {
+    const loader = createParallelLoader({ config, artifact, sourceMap: true });
+    const expected = files.map((file) => loader.transformSync(file));
+
+    const inputs = Array.from({ length: rounds }, () => files).flat();
+    const outputs = await Promise.all(inputs.map((file) => loader.transform(file)));
+
+    outputs.forEach((output, index) => {
+      expect(output).toEqual(expected[index % fileCount]!);
+    });
+  });
+
+  it.skipIf(!nativeModuleAvailable)("should keep loaders created for different configs independent", async () => {
+    const esmLoader = createParallelLoader({ config, artifact });
+    const cjsLoader = createParallelLoader({ config, artifact, compilerOptions: { module: "CommonJS" } });
+
+    const outputs = await Promise.all(
+      files.flatMap((file) => [esmLoader.transform(file), cjsLoader.transform(file)]),
+    );
+
+    outputs.forEach((output, index) => {
+      const isCjs = index % 2 === 1;
+      expect(output.sourceCode.includes("require(")).toBe(isCjs);
+      expect(output.sourceCode).toContain(`typename: "User${Math.floor(index / 2)}"`);
+    });
+  });
+});
@@ -1,4 +1,4 @@
-import { mkdir, writeFile } from "node:fs/promises";
+import { mkdir, rename, rm, writeFile } from "node:fs/promises";
 import { dirname, resolve } from "node:path";
 import type { BuilderArtifact, BuilderArtifactMeta } from "@soda-gql/builder";
 import { createBuilderService } from "@soda-gql/builder";
@@ -147,7 +147,20 @@ export const buildCommand = async (argv: readonly string[]): Promise<BuildComman
   const outputDir = dirname(outputPath);
   try {
     await mkdir(outputDir, { recursive: true });
-    await writeFile(outputPath, JSON.stringify(artifactWithMeta, null, 2));
+    // Write to a temporary file and rename it into place, so transformers that
+    // memory-map the artifact keep reading the previous file until they reload
+    const tempPath = `${outputPath}.${process.pid}.tmp`;
+    let renamed = false;
+    try {
+      await writeFile(tempPath, JSON.stringify(artifactWithMeta, null, 2));
+      await rename(tempPath, outputPath);
+      renamed = true;
+    } finally {
+      // Don't leave a partial temporary file behind when writing or renaming fails
+      if (!renamed) {
+        await rm(tempPath, { force: true });
+      }
+    }
   } catch (error) {
     const message = error instanceof Error ? error.message : String(error);
     return err(cliErrors.writeFailed(outputPath, `Failed to write artifact: ${message}`, error));